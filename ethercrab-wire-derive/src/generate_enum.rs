@@ -1,5 +1,5 @@
 use crate::parse_enum::EnumMeta;
-use quote::quote;
+use quote::{quote, quote_spanned};
 use std::str::FromStr;
 use syn::DeriveInput;
 
@@ -43,7 +43,17 @@ pub fn generate_enum_write(
             buf.copy_from_slice(&value.to_le_bytes());
         }
     } else {
+        // Without this, a non-`Copy` enum fails to compile with an error inside the generated code
+        // instead of at the enum itself.
+        let assert_copy = quote_spanned! {name.span()=>
+            fn assert_copy<T: ::core::marker::Copy>() {}
+
+            let _ = assert_copy::<#name>;
+        };
+
         quote! {
+            #assert_copy
+
             buf.copy_from_slice(&(*self as #repr_type).to_le_bytes());
         }
     };
@@ -47,8 +47,18 @@ pub fn generate_struct_write(parsed: &StructMeta, input: &DeriveInput) -> proc_m
         else {
             let byte_end = field.bytes.end;
 
+            let swap = if field.big_endian {
+                quote! {
+                    buf[#byte_start..#byte_end].reverse();
+                }
+            } else {
+                quote! {}
+            };
+
             quote! {
                 <#field_ty as ::ethercrab_wire::EtherCrabWireWrite>::pack_to_slice_unchecked(&self.#name, &mut buf[#byte_start..#byte_end]);
+
+                #swap
             }
         }
     });
@@ -137,6 +147,21 @@ pub fn generate_struct_read(parsed: &StructMeta, input: &DeriveInput) -> proc_ma
         else {
             let start_byte = field.bytes.start;
             let end_byte = field.bytes.end;
+            let len = field.bytes.len();
+
+            if field.big_endian {
+                return quote! {
+                    #name: {
+                        let mut swapped = [0u8; #len];
+
+                        swapped.copy_from_slice(buf.get(#start_byte..#end_byte).ok_or(::ethercrab_wire::WireError::ReadBufferTooShort)?);
+
+                        swapped.reverse();
+
+                        <#ty as ::ethercrab_wire::EtherCrabWireRead>::unpack_from_slice(&swapped)?
+                    }
+                };
+            }
 
             quote! {
                 #name: <#ty as ::ethercrab_wire::EtherCrabWireRead>::unpack_from_slice(buf.get(#start_byte..#end_byte).ok_or(::ethercrab_wire::WireError::ReadBufferTooShort)?)?
@@ -144,14 +169,24 @@ pub fn generate_struct_read(parsed: &StructMeta, input: &DeriveInput) -> proc_ma
         }
     });
 
+    let validate = parsed.validate.as_ref().map(|validate| {
+        quote! {
+            #validate(&value).map_err(::ethercrab_wire::WireError::from)?;
+        }
+    });
+
     quote! {
         impl ::ethercrab_wire::EtherCrabWireRead for #name {
             fn unpack_from_slice(buf: &[u8]) -> Result<Self, ::ethercrab_wire::WireError> {
                 let buf = buf.get(0..#size_bytes).ok_or(::ethercrab_wire::WireError::ReadBufferTooShort)?;
 
-                Ok(Self {
+                let value = Self {
                     #(#fields_unpack),*
-                })
+                };
+
+                #validate
+
+                Ok(value)
             }
         }
     }
@@ -173,3 +208,33 @@ pub fn generate_sized_impl(parsed: &StructMeta, input: &DeriveInput) -> proc_mac
         }
     }
 }
+
+pub fn generate_wire_default(parsed: &StructMeta, input: &DeriveInput) -> proc_macro2::TokenStream {
+    if !parsed.has_wire_default() {
+        return quote! {};
+    }
+
+    let name = input.ident.clone();
+    let vis = input.vis.clone();
+
+    let fields_default = parsed.fields.iter().map(|field| {
+        let name = &field.name;
+
+        match &field.default {
+            Some(value) => quote! { #name: #value },
+            None => quote! { #name: Default::default() },
+        }
+    });
+
+    quote! {
+        impl #name {
+            /// Create a new instance with all fields set to their default value, respecting any
+            /// `#[wire(default = ...)]` constants.
+            #vis fn wire_default() -> Self {
+                Self {
+                    #(#fields_default),*
+                }
+            }
+        }
+    }
+}
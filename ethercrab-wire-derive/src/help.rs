@@ -1,8 +1,8 @@
 use proc_macro2::Span;
 use std::collections::HashSet;
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, Expr, ExprArray, ExprLit, Ident, Lit, Meta, Token,
-    Type,
+    punctuated::Punctuated, spanned::Spanned, DeriveInput, Expr, ExprArray, ExprLit, Ident, Lit,
+    Meta, Path, Token, Type,
 };
 
 pub const MY_ATTRIBUTE: &str = "wire";
@@ -53,6 +53,61 @@ pub fn usize_attr(attrs: &[syn::Attribute], search: &str) -> Result<Option<usize
     Ok(None)
 }
 
+/// Find an attribute of the form `search = <expr>`, returning the expression if present.
+///
+/// Unlike the other attribute helpers, an attribute that fails to parse is an error, as the
+/// expression would otherwise be silently ignored.
+pub fn expr_attr(attrs: &[syn::Attribute], search: &str) -> Result<Option<Expr>, syn::Error> {
+    for attr in my_attributes(attrs) {
+        let nested = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+
+        for meta in nested {
+            match meta {
+                Meta::NameValue(nv) if nv.path.is_ident(search) => return Ok(Some(nv.value)),
+                _ => (),
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Find an attribute of the form `search = "path::to::item"`, returning the parsed path if present.
+pub fn path_attr(attrs: &[syn::Attribute], search: &str) -> Result<Option<Path>, syn::Error> {
+    for attr in my_attributes(attrs) {
+        let Ok(nested) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        else {
+            continue;
+        };
+
+        for meta in nested {
+            match meta {
+                Meta::NameValue(nv) if nv.path.is_ident(search) => {
+                    let Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit), ..
+                    }) = &nv.value
+                    else {
+                        return Err(syn::Error::new(
+                            nv.value.span(),
+                            format!(
+                                "'{}' must be a string holding a path, e.g. \"my_fn\"",
+                                search
+                            ),
+                        ));
+                    };
+
+                    // Parsed tokens take the span of the string so unresolved paths are reported
+                    // at the attribute.
+                    return lit.parse::<Path>().map(Some);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    Ok(None)
+}
+
 /// Check that all attributes are supported
 pub fn all_valid_attrs(attrs: &[syn::Attribute], allowed: &[&str]) -> Result<(), syn::Error> {
     let allowed = allowed
@@ -140,19 +195,30 @@ pub fn attr_exists(attrs: &[syn::Attribute], search: &str) -> bool {
 //     Ok(false)
 // }
 
+/// Error for a union passed to any of the derives, pointing at the union's name.
+pub fn unsupported_union(input: &DeriveInput) -> syn::Error {
+    syn::Error::new(
+        input.ident.span(),
+        "Unions are not supported. Use a struct with named fields or an enum with a #[repr()] instead.",
+    )
+}
+
 pub fn enum_repr_ty(attrs: &[syn::Attribute], ident: &Ident) -> Result<Ident, syn::Error> {
     for attr in attrs {
         match attr.meta.clone() {
             Meta::List(l) if l.path.is_ident("repr") => {
                 let ty = l.parse_args::<Type>()?;
 
-                if let Type::Path(ty) = ty {
-                    return ty
-                        .path
-                        .get_ident()
-                        .cloned()
-                        .ok_or_else(|| syn::Error::new(ident.span(), "Repr is not a valid type"));
+                return match &ty {
+                    Type::Path(path) => path.path.get_ident().cloned(),
+                    _ => None,
                 }
+                .ok_or_else(|| {
+                    syn::Error::new(
+                        ty.span(),
+                        "Enum repr must be a primitive integer type, e.g. #[repr(u8)]",
+                    )
+                });
             }
             _ => (),
         }
@@ -160,7 +226,7 @@ pub fn enum_repr_ty(attrs: &[syn::Attribute], ident: &Ident) -> Result<Ident, sy
 
     Err(syn::Error::new(
         ident.span(),
-        "Enums must have a #[repr()] attribute",
+        "Enums must have a #[repr()] attribute with a primitive integer type, e.g. #[repr(u8)]",
     ))
 }
 
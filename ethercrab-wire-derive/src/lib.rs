@@ -18,6 +18,13 @@
 //!   The size of this struct when packed on the wire. These attributes may not be present at the
 //!   same time.
 //!
+//! - `#[wire(validate = "path::to::fn")]`
+//!
+//!   A function called with the unpacked struct at the end of `unpack_from_slice`, to reject data
+//!   with invariants the field layout can't express, e.g. reserved bits that must be zero. It must
+//!   have the signature `fn(&Self) -> Result<(), E>` where `E` is `WireError` or converts into it.
+//!   Packing is not affected.
+//!
 //! ## Struct fields
 //!
 //! - `#[wire(bits = N)]` OR `#[wire(bytes = N)]`
@@ -35,6 +42,23 @@
 //!
 //!   These attributes are only applicable to fields that are less than 8 bits wide.
 //!
+//! - `#[wire(big_endian)]`
+//!
+//!   Multi-byte fields are little-endian by default, as per the EtherCAT specification. This
+//!   attribute reverses the byte order of a multi-byte field when packing and unpacking, which is
+//!   useful for process data from devices that use big-endian values. It should only be used on
+//!   primitive numeric fields.
+//!
+//! - `#[wire(default = <expr>)]`
+//!
+//!   The value this field takes in the generated `wire_default()` method. This is useful for
+//!   reserved fields that must hold a fixed constant on the wire, where `Default::default()` would
+//!   produce zero instead. A `wire_default()` method is only generated if at least one field has
+//!   this attribute. All other fields are initialised with `Default::default()`.
+//!
+//!   The method is only generated by `EtherCrabWireWrite` and `EtherCrabWireReadWrite`, as the
+//!   constants only matter when packing. This attribute has no effect on read-only structs.
+//!
 //! ## Enums
 //!
 //! Enums must have a `#[repr()]` attribute, as well as implement the `Copy` trait.
@@ -110,6 +134,59 @@
 //! assert_eq!(OneByte::unpack_from_slice(&[0xaa]), Ok(OneByte::Unknown(0xaa)));
 //! ```
 //!
+//! ## A struct with a reserved constant
+//!
+//! ```rust
+//! use ethercrab_wire::EtherCrabWireWriteSized;
+//!
+//! #[derive(ethercrab_wire::EtherCrabWireReadWrite)]
+//! #[wire(bytes = 2)]
+//! struct Reserved {
+//!     #[wire(bytes = 1)]
+//!     value: u8,
+//!     /// Must always be `0xa5`.
+//!     #[wire(bytes = 1, default = 0xa5)]
+//!     reserved: u8,
+//! }
+//!
+//! assert_eq!(Reserved::wire_default().pack(), [0x00, 0xa5]);
+//! ```
+//!
+//! ## A struct with a validator
+//!
+//! ```rust
+//! use ethercrab_wire::{EtherCrabWireRead, WireError};
+//!
+//! #[derive(Debug, PartialEq, ethercrab_wire::EtherCrabWireRead)]
+//! #[wire(bytes = 1, validate = "reserved_is_zero")]
+//! struct Status {
+//!     #[wire(bits = 4)]
+//!     state: u8,
+//!     #[wire(bits = 4)]
+//!     reserved: u8,
+//! }
+//!
+//! fn reserved_is_zero(status: &Status) -> Result<(), WireError> {
+//!     if status.reserved == 0 {
+//!         Ok(())
+//!     } else {
+//!         Err(WireError::Validation)
+//!     }
+//! }
+//!
+//! assert_eq!(
+//!     Status::unpack_from_slice(&[0x02]),
+//!     Ok(Status {
+//!         state: 2,
+//!         reserved: 0
+//!     })
+//! );
+//! assert_eq!(
+//!     Status::unpack_from_slice(&[0x12]),
+//!     Err(WireError::Validation)
+//! );
+//! ```
+//!
 //! # Struct field alignment
 //!
 //! Struct fields of 1 byte or more MUST be byte-aligned. For example, the following struct will be
@@ -173,7 +250,10 @@ mod parse_enum;
 mod parse_struct;
 
 use generate_enum::{generate_enum_read, generate_enum_write};
-use generate_struct::{generate_sized_impl, generate_struct_read, generate_struct_write};
+use generate_struct::{
+    generate_sized_impl, generate_struct_read, generate_struct_write, generate_wire_default,
+};
+use help::unsupported_union;
 use parse_enum::parse_enum;
 use parse_struct::parse_struct;
 use proc_macro::TokenStream;
@@ -205,12 +285,11 @@ pub fn ether_crab_wire(input: TokenStream) -> TokenStream {
 
             tokens.extend(generate_sized_impl(&parsed, &input));
 
+            tokens.extend(generate_wire_default(&parsed, &input));
+
             tokens
         }),
-        Data::Union(_) => Err(syn::Error::new(
-            input.ident.span(),
-            "Unions are not supported",
-        )),
+        Data::Union(_) => Err(unsupported_union(&input)),
     };
 
     let res = match res {
@@ -242,10 +321,7 @@ pub fn ether_crab_wire_read(input: TokenStream) -> TokenStream {
 
             tokens
         }),
-        Data::Union(_) => Err(syn::Error::new(
-            input.ident.span(),
-            "Unions are not supported",
-        )),
+        Data::Union(_) => Err(unsupported_union(&input)),
     };
 
     let res = match res {
@@ -275,12 +351,11 @@ pub fn ether_crab_wire_write(input: TokenStream) -> TokenStream {
 
             tokens.extend(generate_sized_impl(&parsed, &input));
 
+            tokens.extend(generate_wire_default(&parsed, &input));
+
             tokens
         }),
-        Data::Union(_) => Err(syn::Error::new(
-            input.ident.span(),
-            "Unions are not supported",
-        )),
+        Data::Union(_) => Err(unsupported_union(&input)),
     };
 
     let res = match res {
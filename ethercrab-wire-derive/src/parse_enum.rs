@@ -1,7 +1,12 @@
 use crate::help::{
     all_valid_attrs, attr_exists, enum_repr_ty, variant_alternatives, variant_is_default,
 };
-use syn::{DataEnum, DeriveInput, Expr, ExprLit, ExprUnary, Ident, Lit, UnOp};
+use syn::{
+    spanned::Spanned, DataEnum, DeriveInput, Expr, ExprLit, ExprUnary, Fields, Ident, Lit, UnOp,
+};
+
+const INVALID_DISCRIMINANT: &str =
+    "Invalid discriminant format. Use an integer literal, e.g. Foo = 0x01";
 
 #[derive(Clone)]
 pub struct EnumMeta {
@@ -43,6 +48,16 @@ pub fn parse_enum(
         ));
     }
 
+    if !["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"]
+        .iter()
+        .any(|good| repr == good)
+    {
+        return Err(syn::Error::new(
+            repr.span(),
+            "Enum repr must be one of u8, u16, u32, u64, i8, i16, i32 or i64",
+        ));
+    }
+
     // --- Variants
 
     let mut discriminant_accum = 0;
@@ -55,6 +70,39 @@ pub fn parse_enum(
 
         let ident = variant.ident;
 
+        let is_catch_all = attr_exists(&variant.attrs, "catch_all");
+
+        match (&variant.fields, is_catch_all) {
+            (Fields::Unit, false) => (),
+            (Fields::Unnamed(fields), true) if fields.unnamed.len() == 1 => (),
+            (fields, true) => {
+                // A unit variant has no fields to point at
+                let span = match fields {
+                    Fields::Unit => ident.span(),
+                    fields => fields.span(),
+                };
+
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "Catch all variant must hold a single unnamed field of the repr type, e.g. {}({})",
+                        ident, repr
+                    ),
+                ));
+            }
+            (fields, false) => {
+                return Err(syn::Error::new(
+                    fields.span(),
+                    "Enum variants may not have fields. Only a #[wire(catch_all)] variant may hold a value.",
+                ))
+            }
+        }
+
+        let discriminant_span = variant
+            .discriminant
+            .as_ref()
+            .map_or(ident.span(), |(_, expr)| expr.span());
+
         let variant_discriminant = match variant.discriminant {
             Some((
                 _,
@@ -85,16 +133,14 @@ pub fn parse_enum(
                             // Negate value because we matched on `UnOp::Neg` above.
                             .map(|value| -value)?
                     }
-                    _ => return Err(syn::Error::new(repr.span(), "Invalid discriminant format")),
+                    _ => return Err(syn::Error::new(discriminant_span, INVALID_DISCRIMINANT)),
                 }
             }
             None => discriminant_accum + 1,
-            _ => return Err(syn::Error::new(repr.span(), "Invalid discriminant format")),
+            _ => return Err(syn::Error::new(discriminant_span, INVALID_DISCRIMINANT)),
         };
 
         let is_default = variant_is_default(&variant.attrs);
-        let is_catch_all = attr_exists(&variant.attrs, "catch_all");
-
         let alternatives = variant_alternatives(&variant.attrs)?;
 
         if is_catch_all && !alternatives.is_empty() {
@@ -1,6 +1,9 @@
-use crate::help::{all_valid_attrs, attr_exists, bit_width_attr, usize_attr};
+use crate::help::{all_valid_attrs, attr_exists, bit_width_attr, expr_attr, path_attr, usize_attr};
 use std::ops::Range;
-use syn::{DataStruct, DeriveInput, Fields, FieldsNamed, Ident, Type, Visibility};
+use syn::{
+    spanned::Spanned, DataStruct, DeriveInput, Expr, Fields, FieldsNamed, Ident, Path, Type,
+    Visibility,
+};
 
 #[derive(Clone)]
 pub struct StructMeta {
@@ -8,6 +11,9 @@ pub struct StructMeta {
     pub width_bits: usize,
 
     pub fields: Vec<FieldMeta>,
+
+    /// Function called with the unpacked struct to check invariants the layout can't express.
+    pub validate: Option<Path>,
 }
 
 #[derive(Clone)]
@@ -38,18 +44,51 @@ pub struct FieldMeta {
     pub post_skip: Option<usize>,
 
     pub skip: bool,
+
+    /// Value to use for this field in the generated `wire_default()` method, e.g. a reserved
+    /// constant.
+    pub default: Option<Expr>,
+
+    /// Whether this multi-byte field is stored big-endian on the wire.
+    pub big_endian: bool,
+}
+
+impl StructMeta {
+    /// Whether a `wire_default()` method should be generated for this struct.
+    pub fn has_wire_default(&self) -> bool {
+        self.fields.iter().any(|field| field.default.is_some())
+    }
 }
 
 pub fn parse_struct(
     s: DataStruct,
     DeriveInput { attrs, ident, .. }: DeriveInput,
 ) -> syn::Result<StructMeta> {
+    // Check the struct's shape first as attributes are irrelevant if it can't be derived at all
+    let fields = match s.fields {
+        Fields::Named(FieldsNamed { named, .. }) => named,
+        Fields::Unnamed(fields) => {
+            return Err(syn::Error::new(
+                fields.span(),
+                "Tuple structs are not supported. Give each field a name, e.g. `struct Foo { bar: u8 }`",
+            ))
+        }
+        Fields::Unit => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Unit structs are not supported as they have no fields to pack",
+            ))
+        }
+    };
+
     // --- Struct attributes
 
-    all_valid_attrs(&attrs, &["bits", "bytes"])?;
+    all_valid_attrs(&attrs, &["bits", "bytes", "validate"])?;
 
     let width = bit_width_attr(&attrs)?;
 
+    let validate = path_attr(&attrs, "validate")?;
+
     let Some(width) = width else {
         return Err(syn::Error::new(
             ident.span(),
@@ -59,13 +98,6 @@ pub fn parse_struct(
 
     // --- Fields
 
-    let Fields::Named(FieldsNamed { named: fields, .. }) = s.fields else {
-        return Err(syn::Error::new(
-            ident.span(),
-            "Only structs with named fields can be derived.",
-        ));
-    };
-
     let mut total_field_width = 0;
 
     let mut field_meta = Vec::new();
@@ -81,6 +113,8 @@ pub fn parse_struct(
                 "pre_skip_bytes",
                 "post_skip",
                 "post_skip_bytes",
+                "default",
+                "big_endian",
             ],
         )?;
 
@@ -111,6 +145,10 @@ pub fn parse_struct(
         let bit_offset = bit_start % 8;
         let bits = bit_start..bit_end;
 
+        let default = expr_attr(&field.attrs, "default")?;
+
+        let big_endian = attr_exists(&field.attrs, "big_endian");
+
         let ty_name = match field.ty.clone() {
             Type::Path(path) => path.path.get_ident().cloned(),
             _ => None,
@@ -136,6 +174,10 @@ pub fn parse_struct(
             post_skip,
 
             skip,
+
+            default,
+
+            big_endian,
         };
 
         // Validation if we're not skipping this field
@@ -154,6 +196,13 @@ pub fn parse_struct(
                 ));
             }
 
+            if meta.big_endian && field_width <= 8 {
+                return Err(syn::Error::new(
+                    meta.name.span(),
+                    "big_endian may only be used on multi-byte fields",
+                ));
+            }
+
             if meta.bits.len() < 8 && meta.bytes.len() > 1 {
                 return Err(syn::Error::new(
                     meta.name.span(),
@@ -184,5 +233,6 @@ pub fn parse_struct(
     Ok(StructMeta {
         width_bits: width,
         fields: field_meta,
+        validate,
     })
 }
@@ -0,0 +1,8 @@
+#[derive(ethercrab_wire::EtherCrabWireReadWrite)]
+#[wire(bytes = 1)]
+struct InvalidDefault {
+    #[wire(bytes = 1, default = )]
+    foo: u8,
+}
+
+fn main() {}
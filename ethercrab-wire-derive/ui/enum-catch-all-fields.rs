@@ -0,0 +1,9 @@
+#[derive(Copy, Clone, ethercrab_wire::EtherCrabWireRead)]
+#[repr(u8)]
+enum BadCatchAll {
+    Foo = 0x01,
+    #[wire(catch_all)]
+    Unknown(u8, u8),
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(Copy, Clone, ethercrab_wire::EtherCrabWireWrite)]
+enum NoRepr {
+    Foo,
+    Bar,
+}
+
+fn main() {}
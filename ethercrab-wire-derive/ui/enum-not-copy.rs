@@ -0,0 +1,8 @@
+#[derive(ethercrab_wire::EtherCrabWireWrite)]
+#[repr(u8)]
+enum NotCopy {
+    Foo,
+    Bar,
+}
+
+fn main() {}
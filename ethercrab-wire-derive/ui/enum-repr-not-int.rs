@@ -0,0 +1,8 @@
+#[derive(Copy, Clone, ethercrab_wire::EtherCrabWireWrite)]
+#[repr(C)]
+enum ReprC {
+    Foo,
+    Bar,
+}
+
+fn main() {}
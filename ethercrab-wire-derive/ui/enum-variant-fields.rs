@@ -0,0 +1,8 @@
+#[derive(Copy, Clone, ethercrab_wire::EtherCrabWireRead)]
+#[repr(u8)]
+enum WithFields {
+    Foo = 0x01,
+    Bar { value: u8 } = 0x02,
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+#[derive(ethercrab_wire::EtherCrabWireRead)]
+#[wire(bytes = 3)]
+struct Tuple(u8, u16);
+
+fn main() {}
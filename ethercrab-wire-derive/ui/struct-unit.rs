@@ -0,0 +1,5 @@
+#[derive(ethercrab_wire::EtherCrabWireRead)]
+#[wire(bytes = 0)]
+struct Unit;
+
+fn main() {}
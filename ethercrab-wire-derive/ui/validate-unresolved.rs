@@ -0,0 +1,8 @@
+#[derive(ethercrab_wire::EtherCrabWireRead)]
+#[wire(bytes = 1, validate = "does_not_exist")]
+struct Unresolved {
+    #[wire(bytes = 1)]
+    foo: u8,
+}
+
+fn main() {}
@@ -17,6 +17,8 @@ pub enum WireError {
     ArrayLength,
     /// Valid UTF8 input data is required to decode to a string.
     InvalidUtf8,
+    /// The data was unpacked but rejected by the type's `#[wire(validate = "...")]` function.
+    Validation,
 }
 
 #[cfg(feature = "std")]
@@ -34,6 +36,7 @@ impl core::fmt::Display for WireError {
             WireError::InvalidValue => f.write_str("Invalid decoded value"),
             WireError::ArrayLength => f.write_str("Incorrect array length"),
             WireError::InvalidUtf8 => f.write_str("Invalid UTF8"),
+            WireError::Validation => f.write_str("Validation failed"),
         }
     }
 }
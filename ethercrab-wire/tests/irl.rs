@@ -2,6 +2,8 @@ use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireReadWrite};
 
 #[test]
 fn sync_manager_channel() {
+    // Only checks that the derive compiles
+    #[allow(unused)]
     #[derive(Default, Copy, Clone, Debug, PartialEq, Eq, EtherCrabWireReadWrite)]
     #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
     #[wire(bits = 8)]
@@ -39,6 +41,8 @@ fn sync_manager_channel() {
 
 #[test]
 fn pdo() {
+    // Only checks that the derive compiles
+    #[allow(unused)]
     #[derive(Clone, PartialEq, ethercrab_wire::EtherCrabWireReadWrite)]
     #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
     #[wire(bytes = 6)]
@@ -106,6 +110,8 @@ fn bare_enum() {
 
 #[test]
 fn arr() {
+    // Only checks that the derive compiles
+    #[allow(unused)]
     #[derive(Default, Copy, Clone, Debug, PartialEq, Eq, EtherCrabWireReadWrite)]
     #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
     #[wire(bytes = 4)]
@@ -1,7 +1,9 @@
-use ethercrab_wire::{EtherCrabWireReadWrite, EtherCrabWireWrite};
+use ethercrab_wire::{EtherCrabWireReadWrite, EtherCrabWireWrite, EtherCrabWireWriteSized};
 
 #[test]
 fn one_bit() {
+    // Only checks that the derive compiles
+    #[allow(unused)]
     #[derive(Debug, EtherCrabWireReadWrite)]
     #[wire(bits = 1)]
     struct Bit {
@@ -12,6 +14,8 @@ fn one_bit() {
 
 #[test]
 fn one_byte() {
+    // Only checks that the derive compiles
+    #[allow(unused)]
     #[derive(Debug, EtherCrabWireReadWrite)]
     #[wire(bits = 8)]
     struct Byte {
@@ -213,6 +217,23 @@ fn nested_structs() {
     assert_eq!(out, &expected);
 }
 
+#[test]
+fn wire_default_reserved_constant() {
+    #[derive(Debug, Default, EtherCrabWireReadWrite)]
+    #[wire(bytes = 3)]
+    struct Check {
+        #[wire(bits = 1)]
+        enable: bool,
+        #[wire(bits = 3, default = 0b101, post_skip = 4)]
+        reserved_bits: u8,
+        #[wire(bytes = 2, default = 0xc00c)]
+        reserved: u16,
+    }
+
+    assert_eq!(Check::default().pack(), [0x00, 0x00, 0x00]);
+    assert_eq!(Check::wire_default().pack(), [0b1010, 0x0c, 0xc0]);
+}
+
 // // If I don't need this I won't implement it because it makes things a bunch more complex.
 // #[test]
 // fn u16_across_bytes() {
@@ -1,4 +1,4 @@
-use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireReadWrite, EtherCrabWireWrite};
+use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireReadWrite, EtherCrabWireWrite, WireError};
 
 #[test]
 fn basic_struct() {
@@ -116,3 +116,133 @@ fn nested_structs() {
 
     assert_eq!(out, &expected);
 }
+
+#[test]
+fn big_endian_fields() {
+    #[derive(Debug, EtherCrabWireReadWrite, PartialEq)]
+    #[wire(bytes = 7)]
+    struct Check {
+        #[wire(bits = 1, post_skip = 7)]
+        valid: bool,
+        #[wire(bytes = 2, big_endian)]
+        analog: i16,
+        #[wire(bytes = 4, big_endian)]
+        counter: u32,
+    }
+
+    let input = [0x01u8, 0x12, 0x34, 0xaa, 0xbb, 0xcc, 0xdd];
+
+    let check = Check {
+        valid: true,
+        analog: 0x1234,
+        counter: 0xaabbccdd,
+    };
+
+    assert_eq!(Check::unpack_from_slice(&input), Ok(check));
+
+    let mut buf = [0u8; 7];
+
+    let check = Check {
+        valid: true,
+        analog: -2,
+        counter: 0xaabbccdd,
+    };
+
+    assert_eq!(
+        check.pack_to_slice(&mut buf).unwrap(),
+        &[0x01u8, 0xff, 0xfe, 0xaa, 0xbb, 0xcc, 0xdd]
+    );
+}
+
+#[test]
+fn validate_reserved_bits() {
+    #[derive(Debug, EtherCrabWireReadWrite, PartialEq)]
+    #[wire(bytes = 2, validate = "reserved_is_zero")]
+    struct Check {
+        #[wire(bits = 4)]
+        state: u8,
+        /// Must be zero.
+        #[wire(bits = 4)]
+        reserved: u8,
+        #[wire(bytes = 1)]
+        value: u8,
+    }
+
+    fn reserved_is_zero(check: &Check) -> Result<(), WireError> {
+        if check.reserved == 0 {
+            Ok(())
+        } else {
+            Err(WireError::Validation)
+        }
+    }
+
+    assert_eq!(
+        Check::unpack_from_slice(&[0x04, 0xaa]),
+        Ok(Check {
+            state: 4,
+            reserved: 0,
+            value: 0xaa
+        })
+    );
+    assert_eq!(
+        Check::unpack_from_slice(&[0x84, 0xaa]),
+        Err(WireError::Validation)
+    );
+
+    // Buffer length is still checked before validation
+    assert_eq!(
+        Check::unpack_from_slice(&[0x04]),
+        Err(WireError::ReadBufferTooShort)
+    );
+
+    // Packing is not validated
+    let mut buf = [0u8; 2];
+
+    assert_eq!(
+        Check {
+            state: 4,
+            reserved: 1,
+            value: 0xaa
+        }
+        .pack_to_slice(&mut buf)
+        .unwrap(),
+        &[0x14, 0xaa]
+    );
+}
+
+#[test]
+fn validate_custom_error() {
+    struct TooLong;
+
+    impl From<TooLong> for WireError {
+        fn from(_: TooLong) -> Self {
+            WireError::Validation
+        }
+    }
+
+    #[derive(Debug, EtherCrabWireRead, PartialEq)]
+    #[wire(bytes = 2, validate = "Header::length_fits")]
+    struct Header {
+        #[wire(bytes = 2)]
+        len: u16,
+    }
+
+    impl Header {
+        fn length_fits(&self) -> Result<(), TooLong> {
+            if self.len <= 16 {
+                Ok(())
+            } else {
+                Err(TooLong)
+            }
+        }
+    }
+
+    assert_eq!(
+        Header::unpack_from_slice(&[0x10, 0x00]),
+        Ok(Header { len: 16 })
+    );
+    assert_eq!(
+        Header::unpack_from_slice(&[0x11, 0x00]),
+        Err(WireError::Validation)
+    );
+}
@@ -0,0 +1,160 @@
+//! Read-only compatibility probe for an EtherCAT network.
+//!
+//! Enumerates every SubDevice on the network and reports its identity, EEPROM checksum, topology,
+//! Distributed Clocks support and mailbox protocols without resetting, reconfiguring or changing
+//! the state of any SubDevice. This makes it safe to run against a machine that is in operation.
+//!
+//! Run with e.g.
+//!
+//! ```bash
+//! cargo run --example compat_probe -- eth0
+//! ```
+//!
+//! Pass `--json` as the second argument to print the report as JSON. This requires the `serde`
+//! feature:
+//!
+//! ```bash
+//! cargo run --example compat_probe --features serde -- eth0 --json
+//! ```
+
+use env_logger::Env;
+use ethercrab::{
+    DcSupport, DiscoveredSubDevice, MainDevice, MainDeviceConfig, PduStorage, Timeouts,
+    error::Error,
+};
+
+/// Maximum number of SubDevices that can be stored.
+const MAX_SUBDEVICES: usize = 128;
+/// Discovery only sends small PDUs.
+const MAX_PDU_DATA: usize = PduStorage::element_size(64);
+/// Maximum number of EtherCAT frames that can be in flight at any one time.
+const MAX_FRAMES: usize = 16;
+
+static PDU_STORAGE: PduStorage<MAX_FRAMES, MAX_PDU_DATA> = PduStorage::new();
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("warn")).init();
+
+    let interface = std::env::args()
+        .nth(1)
+        .expect("Provide network interface as first argument.");
+
+    let json = std::env::args().nth(2).as_deref() == Some("--json");
+
+    let (tx, rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+
+    let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+    #[cfg(target_os = "windows")]
+    std::thread::spawn(move || {
+        ethercrab::std::tx_rx_task_blocking(
+            &interface,
+            tx,
+            rx,
+            ethercrab::std::TxRxTaskConfig::default(),
+        )
+        .expect("TX/RX task")
+    });
+    #[cfg(not(target_os = "windows"))]
+    tokio::spawn(ethercrab::std::tx_rx_task(&interface, tx, rx).expect("spawn TX/RX task"));
+
+    let subdevices = maindevice.discover::<MAX_SUBDEVICES>().await?;
+
+    if json {
+        print_json(&subdevices);
+    } else {
+        print_report(&subdevices);
+    }
+
+    Ok(())
+}
+
+fn print_report(subdevices: &[DiscoveredSubDevice]) {
+    println!("Found {} SubDevice(s)", subdevices.len());
+
+    for sd in subdevices {
+        println!();
+        println!(
+            "[{}] {}",
+            sd.position,
+            sd.name.as_deref().unwrap_or("<EEPROM not accessible>")
+        );
+
+        match sd.identity {
+            Some(identity) => println!("    Identity:       {}", identity),
+            None => println!("    Identity:       unknown"),
+        }
+
+        println!(
+            "    Address:        configured {:#06x}, alias {:#06x}",
+            sd.configured_address, sd.alias_address
+        );
+        println!(
+            "    State:          {}{}",
+            sd.state,
+            if sd.state_error { " (error)" } else { "" }
+        );
+
+        let ports = sd
+            .link_ports
+            .iter()
+            .enumerate()
+            .filter(|(_, link)| **link)
+            .map(|(port, _)| port.to_string())
+            .collect::<Vec<_>>();
+
+        println!("    Ports with link: {}", ports.join(", "));
+
+        println!(
+            "    DC:             {}",
+            match sd.dc_support {
+                DcSupport::None => "not supported",
+                DcSupport::RefOnly => "reference clock only",
+                DcSupport::Bits32 => "32 bit",
+                DcSupport::Bits64 => "64 bit",
+            }
+        );
+
+        println!(
+            "    EEPROM:         {}",
+            match sd.eeprom_checksum_valid {
+                Some(true) => "checksum OK",
+                Some(false) => "CHECKSUM INVALID",
+                None => "assigned to PDI, not read",
+            }
+        );
+
+        match sd.mailbox_protocols {
+            Some(protocols) if protocols.is_empty() => println!("    Mailbox:        none"),
+            Some(protocols) => println!("    Mailbox:        {:?}", protocols),
+            None => println!("    Mailbox:        unknown"),
+        }
+    }
+
+    let warnings = subdevices
+        .iter()
+        .filter(|sd| sd.eeprom_checksum_valid == Some(false) || sd.state_error)
+        .count();
+
+    println!();
+    println!(
+        "{} SubDevice(s) need attention. SubDevice state was not changed.",
+        warnings
+    );
+}
+
+#[cfg(feature = "serde")]
+fn print_json(subdevices: &[DiscoveredSubDevice]) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(subdevices).expect("serialise report")
+    );
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_json(_subdevices: &[DiscoveredSubDevice]) {
+    eprintln!("JSON output requires the `serde` feature");
+
+    std::process::exit(1);
+}
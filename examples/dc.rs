@@ -88,7 +88,7 @@ fn main() -> Result<(), Error> {
             &interface,
             tx,
             rx,
-            ethercrab::std::TxRxTaskConfig { spinloop: false },
+            ethercrab::std::TxRxTaskConfig::default(),
         )
         .expect("TX/RX task")
     });
@@ -42,7 +42,7 @@ fn main() {
                 &interface,
                 tx,
                 rx,
-                ethercrab::std::TxRxTaskConfig { spinloop: false },
+                ethercrab::std::TxRxTaskConfig::default(),
             )
             .expect("TX/RX task")
         });
@@ -56,11 +56,10 @@ async fn main() -> Result<(), Error> {
 
     let maindevice = Arc::new(MainDevice::new(
         pdu_loop,
-        Timeouts {
-            wait_loop_delay: Duration::from_millis(2),
-            mailbox_response: Duration::from_millis(1000),
-            ..Default::default()
-        },
+        Timeouts::builder()
+            .wait_loop_delay(Duration::from_millis(2))
+            .mailbox_response(Duration::from_millis(1000))
+            .build()?,
         MainDeviceConfig::default(),
     ));
 
@@ -70,7 +69,7 @@ async fn main() -> Result<(), Error> {
             &interface,
             tx,
             rx,
-            ethercrab::std::TxRxTaskConfig { spinloop: false },
+            ethercrab::std::TxRxTaskConfig::default(),
         )
         .expect("TX/RX task")
     });
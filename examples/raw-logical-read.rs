@@ -0,0 +1,115 @@
+//! Read a group's inputs with raw `LRD` commands and compare them with the group's PDI.
+//!
+//! This demonstrates logical addressing without the group machinery. The logical address of each
+//! SubDevice's inputs is read back from its FMMU configuration.
+//!
+//! Run with e.g.
+//!
+//! ```bash
+//! RUST_LOG=info cargo run --example raw-logical-read --release -- eth0
+//! ```
+
+use env_logger::Env;
+use ethercrab::{
+    Command, MainDevice, MainDeviceConfig, PduStorage, Timeouts, error::Error, std::ethercat_now,
+};
+use std::time::Duration;
+
+/// Maximum number of SubDevices that can be stored. This must be a power of 2 greater than 1.
+const MAX_SUBDEVICES: usize = 16;
+/// Maximum PDU data payload size - set this to the max PDI size or higher.
+const MAX_PDU_DATA: usize = PduStorage::element_size(1100);
+/// Maximum number of EtherCAT frames that can be in flight at any one time.
+const MAX_FRAMES: usize = 16;
+/// Maximum total PDI length.
+const PDI_LEN: usize = 64;
+
+static PDU_STORAGE: PduStorage<MAX_FRAMES, MAX_PDU_DATA> = PduStorage::new();
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let interface = std::env::args()
+        .nth(1)
+        .expect("Provide network interface as first argument.");
+
+    let (tx, rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+
+    let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+    #[cfg(target_os = "windows")]
+    std::thread::spawn(move || {
+        ethercrab::std::tx_rx_task_blocking(
+            &interface,
+            tx,
+            rx,
+            ethercrab::std::TxRxTaskConfig::default(),
+        )
+        .expect("TX/RX task")
+    });
+    #[cfg(not(target_os = "windows"))]
+    tokio::spawn(ethercrab::std::tx_rx_task(&interface, tx, rx).expect("spawn TX/RX task"));
+
+    let group = maindevice
+        .init_single_group::<MAX_SUBDEVICES, PDI_LEN>(ethercat_now)
+        .await
+        .expect("Init");
+
+    let group = group.into_op(&maindevice).await.expect("PRE-OP -> OP");
+
+    // Inputs are only updated by the group, so make sure they're current.
+    group.tx_rx(&maindevice).await?;
+
+    for subdevice in group.iter(&maindevice) {
+        let inputs = subdevice.io_raw().inputs().to_vec();
+
+        if inputs.is_empty() {
+            continue;
+        }
+
+        let snapshot = subdevice.config_snapshot().await?;
+
+        let Some(fmmu) = snapshot.fmmus.iter().find(|fmmu| fmmu.read_enable) else {
+            log::warn!("{} has inputs but no read FMMU", subdevice.name());
+
+            continue;
+        };
+
+        // Only this SubDevice maps this range, so the working counter should be 1.
+        let raw = Command::lrd(fmmu.logical_start_address)
+            .with_wkc(1)
+            .receive_slice(&maindevice, inputs.len() as u16)
+            .await?;
+
+        log::info!(
+            "{:#06x} {}: logical address {:#010x}, group inputs {:02x?}, LRD {:02x?}{}",
+            subdevice.configured_address(),
+            subdevice.name(),
+            fmmu.logical_start_address,
+            inputs,
+            &*raw,
+            if *raw == inputs[..] {
+                ""
+            } else {
+                " (inputs changed between reads)"
+            }
+        );
+    }
+
+    let group = group
+        .into_safe_op(&maindevice)
+        .await
+        .expect("OP -> SAFE-OP");
+
+    let group = group
+        .into_pre_op(&maindevice)
+        .await
+        .expect("SAFE-OP -> PRE-OP");
+
+    let _group = group.into_init(&maindevice).await.expect("PRE-OP -> INIT");
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    Ok(())
+}
@@ -95,8 +95,7 @@ async fn main() -> Result<(), ethercrab::error::Error> {
                 .then_some(())
                 .expect("Set TX/RX thread core");
 
-            tx_rx_task_blocking(&interface, tx, rx, TxRxTaskConfig { spinloop: false })
-                .expect("TX/RX task");
+            tx_rx_task_blocking(&interface, tx, rx, TxRxTaskConfig::default()).expect("TX/RX task");
         })
         .unwrap();
 
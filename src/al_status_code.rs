@@ -118,14 +118,24 @@ pub enum AlStatusCode {
     Unknown(u16),
 }
 
-impl core::fmt::Display for AlStatusCode {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let num = u16::from(*self);
-
-        f.write_fmt(format_args!("{:#06x}", num))?;
-        f.write_str(": ")?;
-
-        let s = match self {
+impl AlStatusCode {
+    /// The human readable description of this code as given in ETG1000.6 Table 11.
+    ///
+    /// Codes from `0x8000` upwards are defined by the SubDevice vendor and are described as
+    /// `"Vendor specific"`. Any other unrecognised code is described as `"(unknown)"`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ethercrab::AlStatusCode;
+    ///
+    /// assert_eq!(
+    ///     AlStatusCode::InvalidRequestedStateChange.description(),
+    ///     "Invalid requested state change"
+    /// );
+    /// ```
+    pub const fn description(&self) -> &'static str {
+        match self {
             AlStatusCode::NoError => "No error",
             AlStatusCode::UnspecifiedError => "Unspecified error",
             AlStatusCode::NoMemory => "No Memory",
@@ -179,9 +189,58 @@ impl core::fmt::Display for AlStatusCode {
             AlStatusCode::SubDeviceRestartedLocally => "SubDevice restarted locally",
             AlStatusCode::DeviceIdentificationValueUpdated => "Device Identification value updated",
             AlStatusCode::ApplicationControllerAvailable => "Application controller available",
+            AlStatusCode::Unknown(0x8000..) => "Vendor specific",
             AlStatusCode::Unknown(_) => "(unknown)",
-        };
+        }
+    }
+}
+
+impl core::fmt::Display for AlStatusCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let num = u16::from(*self);
+
+        f.write_fmt(format_args!("{:#06x}", num))?;
+        f.write_str(": ")?;
+
+        f.write_str(self.description())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptions() {
+        assert_eq!(AlStatusCode::NoError.description(), "No error");
+        assert_eq!(
+            AlStatusCode::InvalidRequestedStateChange.description(),
+            "Invalid requested state change"
+        );
+        assert_eq!(
+            AlStatusCode::SyncManagerWatchdog.description(),
+            "Sync manager watchdog"
+        );
+        assert_eq!(
+            AlStatusCode::InvalidDcSyncConfiguration.description(),
+            "Invalid DC SYNC Configuration"
+        );
+        assert_eq!(AlStatusCode::Unknown(0x0004).description(), "(unknown)");
+        assert_eq!(
+            AlStatusCode::Unknown(0x8001).description(),
+            "Vendor specific"
+        );
+    }
 
-        f.write_str(s)
+    #[test]
+    fn display() {
+        assert_eq!(
+            AlStatusCode::InvalidOutputConfiguration.to_string(),
+            "0x001d: Invalid Output Configuration"
+        );
+        assert_eq!(
+            AlStatusCode::Unknown(0x8123).to_string(),
+            "0x8123: Vendor specific"
+        );
     }
 }
@@ -1,4 +1,4 @@
-use super::{CoeService, InitSdoHeader, SegmentSdoHeader, SubIndex};
+use super::{CoeService, InitSdoHeader, SegmentSdoHeader, SubIndex, abort_code::CoeAbortCode};
 use crate::mailbox::{MailboxHeader, MailboxType, Priority};
 use core::fmt::Display;
 
@@ -81,6 +81,20 @@ impl Display for SdoSegmented {
     }
 }
 
+/// An SDO abort transfer request, sent to cancel a transfer that is in progress.
+///
+/// This request is unconfirmed, so the SubDevice does not send a response.
+///
+/// See ETG1000.6 Section 5.6.2.7 Abort SDO Transfer.
+#[derive(Debug, Copy, Clone, PartialEq, ethercrab_wire::EtherCrabWireReadWrite)]
+#[wire(bytes = 16)]
+pub struct SdoAbort {
+    #[wire(bytes = 12)]
+    pub headers: SdoNormal,
+    #[wire(bytes = 4)]
+    pub code: CoeAbortCode,
+}
+
 /// Must be implemented for any type used to send a CoE service.
 pub trait CoeServiceRequest:
     ethercrab_wire::EtherCrabWireReadWrite + ethercrab_wire::EtherCrabWireWriteSized
@@ -181,11 +195,38 @@ pub fn upload(counter: u8, index: u16, access: SubIndex) -> SdoNormal {
     }
 }
 
+pub fn abort(counter: u8, index: u16, sub_index: u8, code: CoeAbortCode) -> SdoAbort {
+    SdoAbort {
+        headers: SdoNormal {
+            header: MailboxHeader {
+                length: 0x0a,
+                // address: 0x0000,
+                priority: Priority::Lowest,
+                mailbox_type: MailboxType::Coe,
+                counter,
+                service: CoeService::SdoRequest,
+            },
+            sdo_header: InitSdoHeader {
+                size_indicator: false,
+                expedited_transfer: false,
+                size: 0,
+                complete_access: false,
+                command: super::CoeCommand::Abort,
+                index,
+                sub_index,
+            },
+        },
+        code,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::error::CoeAbortCode;
-    use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireSized, EtherCrabWireWrite};
+    use ethercrab_wire::{
+        EtherCrabWireRead, EtherCrabWireSized, EtherCrabWireWrite, EtherCrabWireWriteSized,
+    };
 
     #[test]
     fn decode_sdo_response_normal() {
@@ -346,6 +387,19 @@ mod tests {
         assert_eq!(&raw[(12 + u32::PACKED_LEN)..][..4], &[69, 75, 49, 57]);
     }
 
+    #[test]
+    fn encode_abort_request() {
+        let request = abort(6, 0x1001, 0, CoeAbortCode::General);
+
+        assert_eq!(
+            request.pack(),
+            [
+                0x0a, 0x00, 0x00, 0x00, 0x00, 0x63, 0x00, 0x20, 0x80, 0x01, 0x10, 0x00, 0x00, 0x00,
+                0x00, 0x08,
+            ]
+        );
+    }
+
     #[test]
     fn error_not_found() {
         // Copypasta'd from Wireshark
@@ -3,22 +3,23 @@
 mod reads;
 mod writes;
 
+use crate::error::PduError;
 use ethercrab_wire::{EtherCrabWireSized, EtherCrabWireWriteSized};
 
 pub use reads::{Reads, WrappedRead};
 pub use writes::{WrappedWrite, Writes};
 
-const NOP: u8 = 0x00;
-const APRD: u8 = 0x01;
-const FPRD: u8 = 0x04;
-const BRD: u8 = 0x07;
-const LRD: u8 = 0x0A;
-const BWR: u8 = 0x08;
-const APWR: u8 = 0x02;
-const FPWR: u8 = 0x05;
-const FRMW: u8 = 0x0E;
-const LWR: u8 = 0x0B;
-const LRW: u8 = 0x0c;
+pub(crate) const NOP: u8 = 0x00;
+pub(crate) const APRD: u8 = 0x01;
+pub(crate) const FPRD: u8 = 0x04;
+pub(crate) const BRD: u8 = 0x07;
+pub(crate) const LRD: u8 = 0x0A;
+pub(crate) const BWR: u8 = 0x08;
+pub(crate) const APWR: u8 = 0x02;
+pub(crate) const FPWR: u8 = 0x05;
+pub(crate) const FRMW: u8 = 0x0E;
+pub(crate) const LWR: u8 = 0x0B;
+pub(crate) const LRW: u8 = 0x0c;
 
 /// PDU command.
 ///
@@ -77,6 +78,45 @@ const LRW: u8 = 0x0c;
 /// # Result::<(), ethercrab::error::Error>::Ok(())
 /// # };
 /// ```
+///
+/// # Logical addressing
+///
+/// [`lrd`](Command::lrd), [`lwr`](Command::lwr) and [`lrw`](Command::lrw) access the 4GiB logical
+/// address space mapped to SubDevice memory by FMMUs. This is normally managed by
+/// [`SubDeviceGroup`](crate::SubDeviceGroup), but these commands can be used directly for
+/// diagnostics or custom mappings.
+///
+/// EtherCrab can't know how many SubDevices map an arbitrary logical address range, so the
+/// expected working counter should be set with `with_wkc`. Each SubDevice with a matching FMMU
+/// increments the working counter by 1 for a read, 1 for a write and, for `LRW`, 1 for a read and
+/// 2 for a write. Accesses that would run past the end of the logical address space return
+/// [`PduError::LogicalAddressOverflow`].
+///
+/// <div class="warning">
+///
+/// Writing to a logical address range that overlaps a group's PDI will corrupt that group's
+/// process data, as SubDevice outputs are updated with the written values instead of the group's.
+/// Ranges used by a running group should only ever be read with `LRD`.
+///
+/// </div>
+///
+/// ```rust
+/// # use ethercrab::{ std::tx_rx_task, MainDevice, MainDeviceConfig, PduStorage, Timeouts };
+/// use ethercrab::Command;
+/// # static PDU_STORAGE: PduStorage<16, 1100> = PduStorage::new();
+/// # let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+/// let maindevice = /* ... */
+/// # MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+///
+/// # async {
+/// // Read 4 bytes of process data mapped by two SubDevices
+/// let data = Command::lrd(0x0000_1000)
+///     .with_wkc(2)
+///     .receive_slice(&maindevice, 4)
+///     .await?;
+/// # Result::<(), ethercrab::error::Error>::Ok(())
+/// # };
+/// ```
 #[derive(Default, PartialEq, Eq, Debug, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
@@ -220,15 +260,50 @@ impl Command {
     }
 
     /// Logical Read Write (LRW), used mainly for sending and receiving PDI.
+    ///
+    /// See [Logical addressing](Command#logical-addressing) for working counter semantics and
+    /// caveats.
     pub fn lrw(address: u32) -> WrappedWrite {
         WrappedWrite::new(Writes::Lrw { address })
     }
 
+    /// Logical Read (LRD).
+    ///
+    /// See [Logical addressing](Command#logical-addressing) for working counter semantics and
+    /// caveats.
+    pub fn lrd(address: u32) -> WrappedRead {
+        WrappedRead::new(Reads::Lrd { address })
+    }
+
     /// Logical Write (LWR).
+    ///
+    /// See [Logical addressing](Command#logical-addressing) for working counter semantics and
+    /// caveats.
     pub fn lwr(address: u32) -> WrappedWrite {
         WrappedWrite::new(Writes::Lwr { address })
     }
 
+    /// Check that `len` bytes accessed by this command fit in the 32 bit logical address space.
+    ///
+    /// Always succeeds for non-logical commands.
+    pub(crate) fn check_logical_range(&self, len: usize) -> Result<(), PduError> {
+        let address = match self {
+            Command::Read(Reads::Lrd { address })
+            | Command::Write(Writes::Lwr { address })
+            | Command::Write(Writes::Lrw { address }) => *address,
+            _ => return Ok(()),
+        };
+
+        if u64::from(address) + len as u64 > 1 << 32 {
+            return Err(PduError::LogicalAddressOverflow {
+                address,
+                len: u16::try_from(len).unwrap_or(u16::MAX),
+            });
+        }
+
+        Ok(())
+    }
+
     /// Get just the command code for a command.
     pub(crate) const fn code(&self) -> u8 {
         match self {
@@ -294,4 +369,24 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn logical_range() {
+        let lrd = Command::from(Command::lrd(0xffff_fff0));
+
+        assert_eq!(lrd.check_logical_range(16), Ok(()));
+        assert_eq!(
+            lrd.check_logical_range(17),
+            Err(PduError::LogicalAddressOverflow {
+                address: 0xffff_fff0,
+                len: 17
+            })
+        );
+
+        // Physical addresses wrap in the ESC, so aren't checked
+        assert_eq!(
+            Command::from(Command::fprd(0x1000, 0xfff0)).check_logical_range(32),
+            Ok(())
+        );
+    }
 }
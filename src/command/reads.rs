@@ -1,4 +1,4 @@
-use crate::{MainDevice, error::Error, pdu_loop::ReceivedPdu};
+use crate::{MainDevice, error::Error, pdu_loop::HeldPdu};
 use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireSized};
 
 /// Read commands that send no data.
@@ -59,6 +59,8 @@ pub struct WrappedRead {
     pub command: Reads,
     /// Expected working counter.
     wkc: Option<u16>,
+    /// Zero the frame buffer once the response has been consumed.
+    scrub: bool,
 }
 
 impl WrappedRead {
@@ -66,6 +68,7 @@ impl WrappedRead {
         Self {
             command,
             wkc: Some(1),
+            scrub: false,
         }
     }
 
@@ -84,6 +87,15 @@ impl WrappedRead {
         }
     }
 
+    /// Zero the frame buffer used for this PDU once it's released, if enabled by
+    /// [`MainDeviceConfig::scrub_mailbox_frames`](crate::MainDeviceConfig::scrub_mailbox_frames).
+    pub(crate) fn scrub(self) -> Self {
+        Self {
+            scrub: true,
+            ..self
+        }
+    }
+
     /// Receive data and decode into a `T`.
     pub async fn receive<'maindevice, T>(
         self,
@@ -103,7 +115,7 @@ impl WrappedRead {
         self,
         maindevice: &'maindevice MainDevice<'maindevice>,
         len: u16,
-    ) -> Result<ReceivedPdu<'maindevice>, Error> {
+    ) -> Result<HeldPdu<'maindevice>, Error> {
         self.common(maindevice, len).await?.maybe_wkc(self.wkc)
     }
 
@@ -123,7 +135,7 @@ impl WrappedRead {
     {
         self.common(maindevice, T::PACKED_LEN as u16)
             .await
-            .map(|res| res.working_counter)
+            .map(|res| res.working_counter())
     }
 
     // Some manual monomorphisation
@@ -131,7 +143,7 @@ impl WrappedRead {
         &self,
         maindevice: &'maindevice MainDevice<'maindevice>,
         len: u16,
-    ) -> impl core::future::Future<Output = Result<ReceivedPdu<'maindevice>, Error>> {
-        maindevice.single_pdu(self.command.into(), (), Some(len))
+    ) -> impl core::future::Future<Output = Result<HeldPdu<'maindevice>, Error>> {
+        maindevice.single_pdu(self.command.into(), (), Some(len), self.scrub)
     }
 }
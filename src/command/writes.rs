@@ -1,4 +1,4 @@
-use crate::{MainDevice, error::Error, pdu_loop::ReceivedPdu};
+use crate::{MainDevice, error::Error, pdu_loop::HeldPdu};
 use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireWrite};
 
 /// Write commands.
@@ -51,6 +51,8 @@ pub struct WrappedWrite {
     pub command: Writes,
     /// Expected working counter.
     wkc: Option<u16>,
+    /// Zero the frame buffer once the response has been consumed.
+    scrub: bool,
     len_override: Option<u16>,
 }
 
@@ -59,6 +61,7 @@ impl WrappedWrite {
         Self {
             command,
             wkc: Some(1),
+            scrub: false,
             len_override: None,
         }
     }
@@ -88,6 +91,15 @@ impl WrappedWrite {
         }
     }
 
+    /// Zero the frame buffer used for this PDU once it's released, if enabled by
+    /// [`MainDeviceConfig::scrub_mailbox_frames`](crate::MainDeviceConfig::scrub_mailbox_frames).
+    pub(crate) fn scrub(self) -> Self {
+        Self {
+            scrub: true,
+            ..self
+        }
+    }
+
     /// Send a payload with a length set by [`with_len`](WrappedWrite::with_len), ignoring the
     /// response.
     pub async fn send<'maindevice>(
@@ -120,7 +132,7 @@ impl WrappedWrite {
         self,
         maindevice: &'maindevice MainDevice<'maindevice>,
         value: impl EtherCrabWireWrite,
-    ) -> Result<ReceivedPdu<'maindevice>, Error> {
+    ) -> Result<HeldPdu<'maindevice>, Error> {
         self.common(maindevice, value, None)
             .await?
             .maybe_wkc(self.wkc)
@@ -132,7 +144,7 @@ impl WrappedWrite {
         maindevice: &'maindevice MainDevice<'maindevice>,
         value: impl EtherCrabWireWrite,
         len_override: Option<u16>,
-    ) -> impl core::future::Future<Output = Result<ReceivedPdu<'maindevice>, Error>> {
-        maindevice.single_pdu(self.command.into(), value, len_override)
+    ) -> impl core::future::Future<Output = Result<HeldPdu<'maindevice>, Error>> {
+        maindevice.single_pdu(self.command.into(), value, len_override, self.scrub)
     }
 }
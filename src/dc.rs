@@ -310,7 +310,7 @@ fn configure_subdevice_offsets(
 
 /// Assign parent/child relationships and compute propagation delays for all SubDevices.
 #[deny(clippy::arithmetic_side_effects)]
-fn assign_parent_relationships(subdevices: &mut [SubDevice]) -> Result<(), Error> {
+pub(crate) fn assign_parent_relationships(subdevices: &mut [SubDevice]) -> Result<(), Error> {
     let mut delay_accum = 0;
 
     for i in 0..subdevices.len() {
@@ -438,6 +438,20 @@ pub(crate) async fn configure_dc<'subdevices>(
         let now_nanos = now();
 
         for subdevice in subdevices.iter().filter(|sl| sl.dc_support().any()) {
+            if maindevice
+                .config
+                .dc_exclude
+                .contains(&subdevice.configured_address())
+            {
+                fmt::debug!(
+                    "Skipping DC offset for SubDevice {:#06x} {}: excluded by config",
+                    subdevice.configured_address(),
+                    subdevice.name
+                );
+
+                continue;
+            }
+
             write_dc_parameters(
                 maindevice,
                 subdevice,
@@ -468,22 +482,18 @@ pub(crate) async fn configure_dc<'subdevices>(
 /// alignment at this stage isn't critical.
 pub(crate) async fn run_dc_static_sync(
     maindevice: &MainDevice<'_>,
-    dc_reference_subdevice: &SubDevice,
+    dc_reference_address: u16,
     iterations: u32,
 ) -> Result<(), Error> {
     fmt::debug!(
-        "Performing static drift compensation using SubDevice {:#06x} {} as reference. This can take some time...",
-        dc_reference_subdevice.configured_address(),
-        dc_reference_subdevice.name
+        "Performing static drift compensation using SubDevice {:#06x} as reference. This can take some time...",
+        dc_reference_address,
     );
 
     for _ in 0..iterations {
-        Command::frmw(
-            dc_reference_subdevice.configured_address(),
-            RegisterAddress::DcSystemTime.into(),
-        )
-        .receive_wkc::<u64>(maindevice)
-        .await?;
+        Command::frmw(dc_reference_address, RegisterAddress::DcSystemTime.into())
+            .receive_wkc::<u64>(maindevice)
+            .await?;
     }
 
     fmt::debug!("Static drift compensation complete");
@@ -491,6 +501,21 @@ pub(crate) async fn run_dc_static_sync(
     Ok(())
 }
 
+/// Compute the first cycle boundary that lies strictly after `reference_time + margin`.
+///
+/// All values are in nanoseconds. Cycle boundaries are whole multiples of `cycle_time` counted from
+/// the DC epoch, so SYNC0 pulses started at the returned time line up with every other SubDevice
+/// started the same way.
+///
+/// # Panics
+///
+/// Panics if `cycle_time` is zero.
+pub(crate) fn next_cycle_boundary(reference_time: u64, cycle_time: u64, margin: u64) -> u64 {
+    let earliest = reference_time.saturating_add(margin);
+
+    (earliest / cycle_time + 1).saturating_mul(cycle_time)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -499,6 +524,26 @@ mod tests {
         subdevice::ports::{Port, Ports, tests::make_ports},
     };
 
+    #[test]
+    fn next_boundary_after_reference_time() {
+        const CYCLE: u64 = 1_000_000;
+
+        // Mid-cycle
+        assert_eq!(next_cycle_boundary(1_234_567, CYCLE, 0), 2_000_000);
+        // Exactly on a boundary must move to the next one, as that time has already passed
+        assert_eq!(next_cycle_boundary(3_000_000, CYCLE, 0), 4_000_000);
+        // Margin pushes start past the next boundary
+        assert_eq!(next_cycle_boundary(1_234_567, CYCLE, 1_500_000), 3_000_000);
+
+        for reference_time in [0, 1, 999_999, 1_000_000, 123_456_789_012] {
+            let start = next_cycle_boundary(reference_time, CYCLE, 0);
+
+            assert_eq!(start % CYCLE, 0);
+            assert!(start > reference_time);
+            assert!(start - reference_time <= CYCLE);
+        }
+    }
+
     // A SubDevice in the middle of the chain
     fn ports_passthrough() -> Ports {
         make_ports(true, true, false, false)
@@ -1014,4 +1059,560 @@ mod tests {
 
         assert_eq!(assign_parent_relationships(&mut subdevices), Ok(()));
     }
+
+    #[test]
+    fn latch_stores_receive_time() {
+        use crate::{
+            MainDeviceConfig, PduStorage, Timeouts,
+            command::{BWR, FPRD},
+            fake_network::FakeNetwork,
+        };
+
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut subdevices = [SubDevice {
+            configured_address: 0x1000,
+            dc_support: DcSupport::Bits64,
+            ports: ports_eol(),
+            ..SubDevice::default()
+        }];
+
+        let receive_time = 0x0123_4567_89ab_cdefu64;
+
+        let result = FakeNetwork::new(&mut tx, &mut rx).run(
+            latch_dc_times(&maindevice, &mut subdevices),
+            |pdu| {
+                match (pdu.command, pdu.ado) {
+                    (BWR, 0x0900) => (),
+                    (FPRD, 0x0918) => pdu.data.copy_from_slice(&receive_time.to_le_bytes()),
+                    (FPRD, 0x0900) => pdu.data.fill(0),
+                    other => panic!("unexpected command {:02x?}", other),
+                }
+
+                1
+            },
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subdevices[0].dc_receive_time(), receive_time);
+    }
+
+    #[test]
+    fn excluded_subdevice_offset_not_written() {
+        use crate::{
+            MainDeviceConfig, PduStorage, Timeouts,
+            command::{BWR, FPRD, FPWR},
+            fake_network::FakeNetwork,
+        };
+
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts::default(),
+            MainDeviceConfig {
+                dc_exclude: &[0x1001],
+                ..MainDeviceConfig::default()
+            },
+        );
+
+        let mut subdevices = [
+            SubDevice {
+                index: 0,
+                configured_address: 0x1000,
+                dc_support: DcSupport::Bits64,
+                ports: ports_passthrough(),
+                ..SubDevice::default()
+            },
+            SubDevice {
+                index: 1,
+                configured_address: 0x1001,
+                dc_support: DcSupport::Bits64,
+                ports: ports_eol(),
+                ..SubDevice::default()
+            },
+        ];
+
+        let mut writes = Vec::new();
+
+        let result = FakeNetwork::new(&mut tx, &mut rx)
+            .run(configure_dc(&maindevice, &mut subdevices, || 0), |pdu| {
+                match (pdu.command, pdu.ado) {
+                    (BWR, 0x0900) => (),
+                    (FPRD, 0x0918) | (FPRD, 0x0900) => pdu.data.fill(0),
+                    (FPWR, _) => writes.push((pdu.adp, pdu.ado)),
+                    other => panic!("unexpected command {:02x?}", other),
+                }
+
+                1
+            })
+            .map(|reference| reference.map(|sd| sd.configured_address()));
+
+        // Excluded SubDevices are still part of the topology
+        assert_eq!(result, Ok(Some(0x1000)));
+        assert_eq!(subdevices[1].parent_index, Some(0));
+
+        assert_eq!(
+            writes,
+            [
+                (0x1000, u16::from(RegisterAddress::DcSystemTimeOffset)),
+                (
+                    0x1000,
+                    u16::from(RegisterAddress::DcSystemTimeTransmissionDelay)
+                ),
+            ]
+        );
+    }
+
+    /// Virtual time model of EtherCAT SubDevices for exercising DC configuration and static drift
+    /// compensation without hardware.
+    mod sim {
+        use super::*;
+        use crate::{
+            DcSupport, PduRx, PduTx,
+            command::{BWR, FPRD, FPWR, FRMW},
+            fake_network::{FakeNetwork, SentFrame},
+            subdevice::ports::Ports,
+        };
+        use core::cell::Cell;
+
+        /// Virtual time between two frames sent by the MainDevice, in ns.
+        const FRAME_INTERVAL: u64 = 10_000;
+
+        /// Register address of each port's receive time latch, in frame processing order.
+        const PORT_LATCH_ORDER: [usize; 4] = [0, 3, 1, 2];
+
+        /// An ESC with a free running local clock.
+        pub struct SimulatedSubDevice {
+            pub configured_address: u16,
+            pub dc_support: DcSupport,
+            /// Active ports in frame processing order, i.e. 0, 3, 1, 2.
+            pub ports: [bool; 4],
+            /// One way delay from the parent's port to this SubDevice, in ns.
+            pub hop_delay: u64,
+            /// Local time at virtual time zero.
+            pub initial_offset: u64,
+            /// Local clock speed error in parts per million.
+            pub drift_ppm: i64,
+            system_time_offset: u64,
+            transmission_delay: u32,
+            /// Adjustment made by the control loop when receiving the reference time.
+            correction: i64,
+            port_latches: [u32; 4],
+            receive_time: u64,
+            time_difference: u32,
+        }
+
+        impl SimulatedSubDevice {
+            pub fn new(configured_address: u16, dc_support: DcSupport, ports: [bool; 4]) -> Self {
+                Self {
+                    configured_address,
+                    dc_support,
+                    ports,
+                    hop_delay: 100,
+                    initial_offset: 0,
+                    drift_ppm: 0,
+                    system_time_offset: 0,
+                    transmission_delay: 0,
+                    correction: 0,
+                    port_latches: [0; 4],
+                    receive_time: 0,
+                    time_difference: 0,
+                }
+            }
+
+            pub fn hop_delay(self, hop_delay: u64) -> Self {
+                Self { hop_delay, ..self }
+            }
+
+            pub fn clock(self, initial_offset: u64, drift_ppm: i64) -> Self {
+                Self {
+                    initial_offset,
+                    drift_ppm,
+                    ..self
+                }
+            }
+
+            fn mask(&self, time: u64) -> u64 {
+                if self.dc_support == DcSupport::Bits32 {
+                    time & u64::from(u32::MAX)
+                } else {
+                    time
+                }
+            }
+
+            fn local_time(&self, t: u64) -> u64 {
+                let drift = t as i64 * self.drift_ppm / 1_000_000;
+
+                self.mask(
+                    self.initial_offset
+                        .wrapping_add(t)
+                        .wrapping_add_signed(drift),
+                )
+            }
+
+            pub fn system_time(&self, t: u64) -> u64 {
+                self.mask(
+                    self.local_time(t)
+                        .wrapping_add(self.system_time_offset)
+                        .wrapping_add_signed(self.correction),
+                )
+            }
+
+            /// Signed difference between two times, wrapping at the width of this ESC's clock.
+            pub fn diff(&self, a: u64, b: u64) -> i64 {
+                if self.dc_support == DcSupport::Bits32 {
+                    i64::from((a as u32).wrapping_sub(b as u32) as i32)
+                } else {
+                    a.wrapping_sub(b) as i64
+                }
+            }
+
+            /// Decoded value of the system time difference register.
+            pub fn time_difference(&self) -> i64 {
+                let magnitude = i64::from(self.time_difference & 0x7fff_ffff);
+
+                if self.time_difference & 0x8000_0000 != 0 {
+                    -magnitude
+                } else {
+                    magnitude
+                }
+            }
+
+            fn read(&self, register: u16, t: u64, data: &mut [u8]) {
+                let value = match register {
+                    0x0900 => {
+                        for (latch, chunk) in self.port_latches.iter().zip(data.chunks_mut(4)) {
+                            chunk.copy_from_slice(&latch.to_le_bytes());
+                        }
+
+                        return;
+                    }
+                    0x0910 => self.system_time(t),
+                    0x0918 => self.receive_time,
+                    0x0920 => self.system_time_offset,
+                    0x0928 => u64::from(self.transmission_delay),
+                    0x092c => u64::from(self.time_difference),
+                    other => panic!("unsupported DC register read {:#06x}", other),
+                };
+
+                let len = data.len();
+
+                data.copy_from_slice(&value.to_le_bytes()[0..len]);
+            }
+
+            fn write(&mut self, register: u16, data: &[u8]) {
+                let mut bytes = [0u8; 8];
+                bytes[0..data.len()].copy_from_slice(data);
+                let value = u64::from_le_bytes(bytes);
+
+                match register {
+                    0x0920 => self.system_time_offset = self.mask(value),
+                    0x0928 => self.transmission_delay = value as u32,
+                    other => panic!("unsupported DC register write {:#06x}", other),
+                }
+            }
+
+            /// Compare the distributed reference time against the local copy of system time and
+            /// nudge the local clock towards it.
+            fn receive_reference_time(&mut self, t: u64, reference: u64) {
+                let received = reference.wrapping_add(u64::from(self.transmission_delay));
+                let diff = self.diff(self.system_time(t), received);
+
+                let magnitude = diff.unsigned_abs().min(0x7fff_ffff) as u32;
+
+                self.time_difference = if diff < 0 {
+                    0x8000_0000 | magnitude
+                } else {
+                    magnitude
+                };
+
+                self.correction -= diff / 4;
+            }
+        }
+
+        /// A network of [`SimulatedSubDevice`]s, connected in discovery order.
+        pub struct SimulatedNetwork<'clock> {
+            pub subdevices: Vec<SimulatedSubDevice>,
+            /// Virtual time in ns.
+            clock: &'clock Cell<u64>,
+        }
+
+        impl<'clock> SimulatedNetwork<'clock> {
+            pub fn new(clock: &'clock Cell<u64>, subdevices: Vec<SimulatedSubDevice>) -> Self {
+                Self { subdevices, clock }
+            }
+
+            /// EtherCrab's view of the network before DC configuration.
+            pub fn discovered(&self) -> Vec<SubDevice> {
+                self.subdevices
+                    .iter()
+                    .enumerate()
+                    .map(|(index, sd)| {
+                        let [p0, p3, p1, p2] = sd.ports;
+
+                        SubDevice {
+                            index: index as u16,
+                            configured_address: sd.configured_address,
+                            dc_support: sd.dc_support,
+                            ports: Ports::new(p0, p3, p1, p2),
+                            ..SubDevice::default()
+                        }
+                    })
+                    .collect()
+            }
+
+            pub fn get(&self, configured_address: u16) -> &SimulatedSubDevice {
+                self.subdevices
+                    .iter()
+                    .find(|sd| sd.configured_address == configured_address)
+                    .unwrap()
+            }
+
+            /// Walk the frame through the SubDevice at `index` and everything connected to it,
+            /// recording the arrival time at each port. Returns the time the frame leaves back out
+            /// of port 0 and the index of the next SubDevice not in this subtree.
+            fn visit(
+                &self,
+                index: usize,
+                arrival: u64,
+                port_times: &mut [[u64; 4]],
+            ) -> (u64, usize) {
+                let mut t = arrival;
+                let mut next = index + 1;
+
+                port_times[index][0] = arrival;
+
+                let ports = self.subdevices[index].ports;
+
+                for (slot, _) in ports
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .filter(|(_, active)| **active)
+                {
+                    let hop = self.subdevices[next].hop_delay;
+
+                    let (back, after) = self.visit(next, t + hop, port_times);
+
+                    t = back + hop;
+                    port_times[index][slot] = t;
+                    next = after;
+                }
+
+                (t, next)
+            }
+
+            fn process(&mut self, frame: &mut SentFrame) {
+                let now = self.clock.get();
+                self.clock.set(now + FRAME_INTERVAL);
+
+                // Virtual time the frame passes through each port of each SubDevice
+                let mut port_times = vec![[0u64; 4]; self.subdevices.len()];
+
+                let mut index = 0;
+                let mut t = now;
+
+                while index < self.subdevices.len() {
+                    let hop = self.subdevices[index].hop_delay;
+
+                    let (back, next) = self.visit(index, t + hop, &mut port_times);
+
+                    t = back;
+                    index = next;
+                }
+
+                frame.respond(|pdu| {
+                    let ado = pdu.ado;
+                    let mut wkc = 0u16;
+
+                    for (subdevice, times) in self.subdevices.iter_mut().zip(&port_times) {
+                        if !subdevice.dc_support.any() {
+                            continue;
+                        }
+
+                        let arrival = times[0];
+                        let addressed = subdevice.configured_address == pdu.adp;
+
+                        match pdu.command {
+                            BWR if ado == 0x0900 => {
+                                for (slot, time) in times.iter().enumerate() {
+                                    subdevice.port_latches[PORT_LATCH_ORDER[slot]] =
+                                        subdevice.local_time(*time) as u32;
+                                }
+
+                                subdevice.receive_time = subdevice.local_time(arrival);
+                            }
+                            FPRD if addressed => subdevice.read(ado, arrival, pdu.data),
+                            FPWR if addressed => subdevice.write(ado, pdu.data),
+                            FRMW if ado == 0x0910 && addressed => {
+                                subdevice.read(ado, arrival, pdu.data)
+                            }
+                            FRMW if ado == 0x0910 => {
+                                let reference = u64::from_le_bytes(
+                                    (&*pdu.data).try_into().expect("8 byte system time"),
+                                );
+
+                                subdevice.receive_reference_time(arrival, reference)
+                            }
+                            FPRD | FPWR => continue,
+                            other => panic!("unsupported command {:#04x}", other),
+                        }
+
+                        wkc += 1;
+                    }
+
+                    wkc
+                });
+            }
+
+            /// Poll `fut` to completion, passing every frame it sends through the network.
+            pub fn run<'sto, F: Future>(
+                &mut self,
+                tx: &mut PduTx<'sto>,
+                rx: &mut PduRx<'sto>,
+                fut: F,
+            ) -> F::Output {
+                FakeNetwork::new(tx, rx).run_frames(fut, |frame| {
+                    self.process(frame);
+
+                    true
+                })
+            }
+
+            /// Difference between each DC SubDevice's system time and the reference's system
+            /// time at the current virtual time.
+            pub fn sync_errors(&self, reference: u16) -> Vec<(u16, i64)> {
+                let t = self.clock.get();
+                let reference = self.get(reference).system_time(t);
+
+                self.subdevices
+                    .iter()
+                    .filter(|sd| sd.dc_support.any())
+                    .map(|sd| (sd.configured_address, sd.diff(sd.system_time(t), reference)))
+                    .collect()
+            }
+        }
+    }
+
+    /// Run DC configuration and static drift compensation against a simulated network, then check
+    /// every SubDevice is synchronised to the reference clock.
+    fn check_dc_sync(epoch: u64, subdevices: Vec<sim::SimulatedSubDevice>) {
+        use crate::{MainDeviceConfig, PduStorage, Timeouts};
+        use core::cell::Cell;
+
+        /// Maximum acceptable clock error in ns.
+        const THRESHOLD: i64 = 10;
+
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let clock = Cell::new(0);
+        let mut network = sim::SimulatedNetwork::new(&clock, subdevices);
+        let mut discovered = network.discovered();
+
+        let now = || epoch + clock.get();
+
+        let reference = network.run(&mut tx, &mut rx, async {
+            let reference = configure_dc(&maindevice, &mut discovered, now)
+                .await?
+                .expect("DC reference");
+
+            run_dc_static_sync(&maindevice, reference.configured_address(), 2_000).await?;
+
+            Ok::<_, Error>(reference.configured_address())
+        });
+
+        let reference = reference.expect("DC sync");
+
+        for (configured_address, error) in network.sync_errors(reference) {
+            assert!(
+                error.abs() <= THRESHOLD,
+                "SubDevice {:#06x} is {} ns from reference",
+                configured_address,
+                error
+            );
+
+            let difference = network.get(configured_address).time_difference();
+
+            assert!(
+                difference.abs() <= THRESHOLD,
+                "SubDevice {:#06x} time difference register is {} ns",
+                configured_address,
+                difference
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn sim_dc_line() {
+        use sim::SimulatedSubDevice;
+
+        check_dc_sync(
+            1_000_000_000,
+            vec![
+                SimulatedSubDevice::new(0x1000, DcSupport::Bits64, [true, true, false, false])
+                    .clock(5_000_000, 0),
+                SimulatedSubDevice::new(0x1001, DcSupport::Bits64, [true, true, false, false])
+                    .hop_delay(150)
+                    .clock(123_456_789, 40),
+                SimulatedSubDevice::new(0x1002, DcSupport::Bits64, [true, false, false, false])
+                    .hop_delay(320)
+                    .clock(42, -25),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn sim_dc_32_bit_wrap() {
+        use sim::SimulatedSubDevice;
+
+        // The lower 32 bits of system time wrap around a few ms into static sync
+        check_dc_sync(
+            u64::from(u32::MAX) - 5_000_000,
+            vec![
+                SimulatedSubDevice::new(0x1000, DcSupport::Bits64, [true, true, false, false])
+                    .clock(0x0000_0001_0000_0000, 10),
+                SimulatedSubDevice::new(0x1001, DcSupport::Bits32, [true, false, false, false])
+                    .hop_delay(200)
+                    .clock(0x1234_5678, -30),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn sim_dc_asymmetric_branches() {
+        use sim::SimulatedSubDevice;
+
+        check_dc_sync(
+            1_000_000_000,
+            vec![
+                // EK1100 with a long cable to a branch on port 3 and a short one on port 1
+                SimulatedSubDevice::new(0x1000, DcSupport::Bits64, [true, true, true, false]),
+                SimulatedSubDevice::new(0x1001, DcSupport::Bits64, [true, true, false, false])
+                    .hop_delay(900)
+                    .clock(1_000, 15),
+                SimulatedSubDevice::new(0x1002, DcSupport::Bits64, [true, false, false, false])
+                    .hop_delay(450)
+                    .clock(2_000, -15),
+                SimulatedSubDevice::new(0x1003, DcSupport::Bits64, [true, false, false, false])
+                    .hop_delay(120)
+                    .clock(3_000, 20),
+            ],
+        );
+    }
 }
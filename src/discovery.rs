@@ -0,0 +1,486 @@
+//! Read-only discovery of SubDevices on a network.
+
+use crate::{
+    Command, DcSupport, MainDevice, SubDevice, SubDeviceIdentity, SubDeviceState,
+    al_control::AlControl,
+    dl_status::DlStatus,
+    eeprom::{device_provider::DeviceEeprom, types::MailboxProtocols},
+    error::Error,
+    fmt,
+    register::{RegisterAddress, SupportFlags},
+    subdevice::eeprom::SubDeviceEeprom,
+};
+
+/// SII configuration register bit set when the EEPROM is assigned to the PDI.
+const SII_ASSIGNED_TO_PDI: u16 = 0x0001;
+/// SII configuration register bit set while the PDI is accessing the EEPROM.
+const SII_PDI_ACCESS: u16 = 0x0100;
+
+/// Information about a SubDevice gathered by [`MainDevice::discover`].
+///
+/// Fields read from the SubDevice EEPROM are `None` if the EEPROM is currently assigned to the
+/// SubDevice's PDI, as the MainDevice would need to take ownership of it to read it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct DiscoveredSubDevice {
+    /// Position of the SubDevice in the network, starting at `0`.
+    pub position: u16,
+
+    /// The configured station address currently held by the SubDevice.
+    ///
+    /// This is `0` if the SubDevice has not been configured since power on.
+    pub configured_address: u16,
+
+    /// The configured station alias address.
+    pub alias_address: u16,
+
+    /// The current AL state of the SubDevice.
+    pub state: SubDeviceState,
+
+    /// The AL status error flag is set.
+    pub state_error: bool,
+
+    /// Link detected on ports 0 to 3, in port number order.
+    pub link_ports: [bool; 4],
+
+    /// Distributed Clocks support.
+    pub dc_support: DcSupport,
+
+    /// Vendor, product, revision and serial number read from the EEPROM.
+    pub identity: Option<SubDeviceIdentity>,
+
+    /// The SubDevice name read from the EEPROM. This is `Some("")` if the EEPROM holds no name.
+    pub name: Option<heapless::String<64>>,
+
+    /// Whether the checksum over the first 7 words of the EEPROM is valid.
+    pub eeprom_checksum_valid: Option<bool>,
+
+    /// Mailbox protocols supported by the SubDevice, read from the EEPROM.
+    pub mailbox_protocols: Option<MailboxProtocols>,
+}
+
+/// A summary of the network produced by [`MainDevice::scan`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct NetworkReport<const MAX_SUBDEVICES: usize> {
+    /// Every SubDevice found on the network, in network order.
+    pub subdevices: heapless::Vec<SubDeviceDescriptor, MAX_SUBDEVICES>,
+}
+
+/// A single SubDevice found by [`MainDevice::scan`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct SubDeviceDescriptor {
+    /// Position of the SubDevice in the network, starting at `0`.
+    pub index: u16,
+
+    /// The configured station address assigned to the SubDevice by the scan.
+    pub configured_address: u16,
+
+    /// The configured station alias address.
+    pub alias_address: u16,
+
+    /// Vendor, product, revision and serial number read from the EEPROM.
+    pub identity: SubDeviceIdentity,
+
+    /// The SubDevice name read from the EEPROM.
+    pub name: heapless::String<64>,
+
+    /// Distributed Clocks support.
+    pub dc_support: DcSupport,
+
+    /// The index of the SubDevice this one is connected to, or `None` for the first SubDevice in
+    /// the network.
+    pub parent_index: Option<u16>,
+}
+
+impl From<&SubDevice> for SubDeviceDescriptor {
+    fn from(subdevice: &SubDevice) -> Self {
+        Self {
+            index: subdevice.index,
+            configured_address: subdevice.configured_address(),
+            alias_address: subdevice.alias_address(),
+            identity: subdevice.identity(),
+            name: subdevice.name.clone(),
+            dc_support: subdevice.dc_support(),
+            parent_index: subdevice.parent_index,
+        }
+    }
+}
+
+/// Read everything that can be found out about the SubDevice at the given position without
+/// changing its configuration or state.
+pub(crate) async fn discover_subdevice(
+    maindevice: &MainDevice<'_>,
+    position: u16,
+) -> Result<DiscoveredSubDevice, Error> {
+    let read = |register: RegisterAddress| Command::aprd(position, register.into());
+
+    let configured_address = read(RegisterAddress::ConfiguredStationAddress)
+        .receive::<u16>(maindevice)
+        .await?;
+
+    let alias_address = read(RegisterAddress::ConfiguredStationAlias)
+        .receive::<u16>(maindevice)
+        .await?;
+
+    let status = read(RegisterAddress::AlStatus)
+        .receive::<AlControl>(maindevice)
+        .await?;
+
+    let dl_status = read(RegisterAddress::DlStatus)
+        .receive::<DlStatus>(maindevice)
+        .await?;
+
+    let flags = read(RegisterAddress::SupportFlags)
+        .receive::<SupportFlags>(maindevice)
+        .await?;
+
+    let sii_config = read(RegisterAddress::SiiConfig)
+        .receive::<u16>(maindevice)
+        .await?;
+
+    let mut discovered = DiscoveredSubDevice {
+        position,
+        configured_address,
+        alias_address,
+        state: status.state,
+        state_error: status.error,
+        link_ports: [
+            dl_status.link_port0,
+            dl_status.link_port1,
+            dl_status.link_port2,
+            dl_status.link_port3,
+        ],
+        dc_support: flags.dc_support(),
+        identity: None,
+        name: None,
+        eeprom_checksum_valid: None,
+        mailbox_protocols: None,
+    };
+
+    if sii_config & (SII_ASSIGNED_TO_PDI | SII_PDI_ACCESS) != 0 {
+        fmt::warn!(
+            "SubDevice at position {} has its EEPROM assigned to the PDI, skipping EEPROM reads",
+            position
+        );
+
+        return Ok(discovered);
+    }
+
+    let eeprom = SubDeviceEeprom::new(DeviceEeprom::positional(maindevice, position));
+
+    discovered.identity = Some(eeprom.identity().await?);
+    discovered.name = Some(eeprom.device_name().await?.unwrap_or_default());
+    discovered.eeprom_checksum_valid = Some(eeprom.checksum_valid().await?);
+    discovered.mailbox_protocols = Some(eeprom.mailbox_config().await?.supported_protocols);
+
+    fmt::debug!(
+        "Discovered SubDevice at position {}, configured address {:#06x}",
+        position,
+        configured_address
+    );
+
+    Ok(discovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        EepromErrorBehaviour, MainDeviceConfig, PduRx, PduStorage, PduTx, Timeouts,
+        command::{APRD, APWR, BRD, FPRD, FPWR},
+        fake_network::{FakeEsc, FakeNetwork, FakePdu},
+    };
+    use core::time::Duration;
+
+    /// Pass a PDU through every SubDevice in turn, recording the command and register of any
+    /// writes. Returns the working counter.
+    fn process(
+        subdevices: &mut [FakeEsc],
+        pdu: &mut FakePdu<'_>,
+        writes: &mut Vec<(u8, u16)>,
+    ) -> u16 {
+        assert!(
+            matches!(pdu.command, APRD | APWR | BRD),
+            "Discovery must only use positional addressing, got {:#04x}",
+            pdu.command
+        );
+
+        if pdu.command == APWR {
+            writes.push((pdu.command, pdu.ado));
+        }
+
+        FakeEsc::process(subdevices, pdu)
+    }
+
+    /// Poll `fut` to completion, answering every frame it sends with `subdevices`.
+    fn run<'sto, F: Future>(
+        fut: F,
+        tx: &mut PduTx<'sto>,
+        rx: &mut PduRx<'sto>,
+        subdevices: &mut [FakeEsc],
+        writes: &mut Vec<(u8, u16)>,
+    ) -> F::Output {
+        FakeNetwork::new(tx, rx).run(fut, |pdu| process(subdevices, pdu, writes))
+    }
+
+    #[test]
+    fn discover_read_only() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut subdevices = [
+            // Already configured by another MainDevice and in OP, with DC support and links on
+            // ports 0 and 1.
+            FakeEsc::new(include_bytes!("../dumps/eeprom/akd.hex"))
+                .with_register(RegisterAddress::ConfiguredStationAddress, &[0x00, 0x10])
+                .with_register(RegisterAddress::AlStatus, &[0x08, 0x00])
+                .with_register(RegisterAddress::DlStatus, &[0b0011_0000, 0x00])
+                .with_register(RegisterAddress::SupportFlags, &[0x0c, 0x01]),
+            // Fresh from power on
+            FakeEsc::new(include_bytes!("../dumps/eeprom/el2889.hex"))
+                .with_register(RegisterAddress::AlStatus, &[0x01, 0x00])
+                .with_register(RegisterAddress::DlStatus, &[0b0001_0000, 0x00]),
+            // EEPROM held by the PDI
+            FakeEsc::new(include_bytes!("../dumps/eeprom/ek1100.hex"))
+                .with_register(RegisterAddress::AlStatus, &[0x02, 0x00])
+                .with_register(RegisterAddress::SiiConfig, &[0x01, 0x00]),
+        ];
+
+        let before = subdevices
+            .iter()
+            .map(|sd| sd.registers.clone())
+            .collect::<Vec<_>>();
+
+        let mut writes = Vec::new();
+
+        let discovered = run(
+            maindevice.discover::<4>(),
+            &mut tx,
+            &mut rx,
+            &mut subdevices,
+            &mut writes,
+        )
+        .expect("discover");
+
+        assert_eq!(discovered.len(), 3);
+
+        assert_eq!(
+            discovered[0],
+            DiscoveredSubDevice {
+                position: 0,
+                configured_address: 0x1000,
+                alias_address: 0,
+                state: SubDeviceState::Op,
+                state_error: false,
+                link_ports: [true, true, false, false],
+                dc_support: DcSupport::Bits64,
+                identity: Some(SubDeviceIdentity {
+                    vendor_id: 0x0000006a,
+                    product_id: 0x00414b44,
+                    revision: 2,
+                    serial: 2575499411,
+                }),
+                name: Some("AKD".try_into().unwrap()),
+                eeprom_checksum_valid: Some(true),
+                mailbox_protocols: Some(
+                    MailboxProtocols::EOE | MailboxProtocols::COE | MailboxProtocols::FOE
+                ),
+            }
+        );
+
+        assert_eq!(
+            discovered[1],
+            DiscoveredSubDevice {
+                position: 1,
+                configured_address: 0,
+                alias_address: 0,
+                state: SubDeviceState::Init,
+                state_error: false,
+                link_ports: [true, false, false, false],
+                dc_support: DcSupport::None,
+                identity: Some(SubDeviceIdentity {
+                    vendor_id: 2,
+                    product_id: 0x0b493052,
+                    revision: 0x00110000,
+                    serial: 0,
+                }),
+                name: Some("EL2889".try_into().unwrap()),
+                eeprom_checksum_valid: Some(true),
+                mailbox_protocols: Some(MailboxProtocols::empty()),
+            }
+        );
+
+        assert_eq!(discovered[2].state, SubDeviceState::PreOp);
+        assert_eq!(discovered[2].identity, None);
+        assert_eq!(discovered[2].name, None);
+        assert_eq!(discovered[2].eeprom_checksum_valid, None);
+        assert_eq!(discovered[2].mailbox_protocols, None);
+
+        // The only writes are EEPROM read requests
+        assert!(!writes.is_empty());
+        assert!(
+            writes
+                .iter()
+                .all(|write| *write == (APWR, u16::from(RegisterAddress::SiiControl))),
+            "unexpected writes {:x?}",
+            writes
+        );
+
+        // Apart from the SII registers used to read the EEPROM, no register has changed
+        for (before, after) in before.iter().zip(subdevices.iter()) {
+            assert_eq!(before[..0x0502], after.registers[..0x0502]);
+            assert_eq!(before[0x0510..], after.registers[0x0510..]);
+        }
+    }
+
+    #[test]
+    fn scan_no_reset_or_dc() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut subdevices = [
+            // Links on ports 0 and 1 so the next SubDevice is its child
+            FakeEsc::new(include_bytes!("../dumps/eeprom/akd.hex"))
+                .with_register(RegisterAddress::AlStatus, &[0x01, 0x00])
+                .with_register(RegisterAddress::DlStatus, &[0b0011_0000, 0x00])
+                .with_register(RegisterAddress::SupportFlags, &[0x0c, 0x01]),
+            FakeEsc::new(include_bytes!("../dumps/eeprom/el2889.hex"))
+                .with_register(RegisterAddress::AlStatus, &[0x01, 0x00])
+                .with_register(RegisterAddress::DlStatus, &[0b0001_0000, 0x00]),
+        ];
+
+        // Every PDU sent, as (command, register)
+        let mut sent = Vec::new();
+
+        let report = FakeNetwork::new(&mut tx, &mut rx)
+            .run(maindevice.scan::<4>(), |pdu| {
+                sent.push((pdu.command, pdu.ado));
+
+                FakeEsc::process(&mut subdevices, pdu)
+            })
+            .expect("scan");
+
+        assert_eq!(
+            report.subdevices.as_slice(),
+            &[
+                SubDeviceDescriptor {
+                    index: 0,
+                    configured_address: 0x1000,
+                    alias_address: 0,
+                    identity: SubDeviceIdentity {
+                        vendor_id: 0x0000006a,
+                        product_id: 0x00414b44,
+                        revision: 2,
+                        serial: 2575499411,
+                    },
+                    name: "AKD".try_into().unwrap(),
+                    dc_support: DcSupport::Bits64,
+                    parent_index: None,
+                },
+                SubDeviceDescriptor {
+                    index: 1,
+                    configured_address: 0x1001,
+                    alias_address: 0,
+                    identity: SubDeviceIdentity {
+                        vendor_id: 2,
+                        product_id: 0x0b493052,
+                        revision: 0x00110000,
+                        serial: 0,
+                    },
+                    name: "EL2889".try_into().unwrap(),
+                    dc_support: DcSupport::None,
+                    parent_index: Some(0),
+                },
+            ]
+        );
+
+        assert_eq!(maindevice.configured_address_for_position(1), Some(0x1001));
+
+        // No broadcast writes, so no reset or DC latching, and no AL control or DC register
+        // accesses.
+        let dc_registers = u16::from(RegisterAddress::DcTimePort0)..0x0a00;
+        let al_control = u16::from(RegisterAddress::AlControl);
+
+        for (command, register) in sent {
+            assert!(
+                matches!(command, BRD | APWR | FPRD | FPWR),
+                "unexpected command {:#04x} to {:#06x}",
+                command,
+                register
+            );
+            assert!(
+                !dc_registers.contains(&register) && register != al_control,
+                "unexpected access to {:#06x}",
+                register
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn scan_eeprom_error() {
+        crate::test_logger();
+
+        let scan = |eeprom_error: EepromErrorBehaviour| {
+            let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+            let maindevice = MainDevice::new(
+                pdu_loop,
+                Timeouts {
+                    eeprom: Duration::from_millis(10),
+                    ..Timeouts::default()
+                },
+                MainDeviceConfig {
+                    eeprom_error,
+                    ..MainDeviceConfig::default()
+                },
+            );
+
+            let mut subdevices = [
+                FakeEsc::new(include_bytes!("../dumps/eeprom/el2889.hex"))
+                    .with_register(RegisterAddress::DlStatus, &[0b0011_0000, 0x00]),
+                FakeEsc::new(include_bytes!("../dumps/eeprom/el2889.hex"))
+                    .with_register(RegisterAddress::DlStatus, &[0b0001_0000, 0x00]),
+            ];
+
+            // The second SubDevice's EEPROM never finishes a read
+            subdevices[1].eeprom_busy = true;
+
+            FakeNetwork::new(&mut tx, &mut rx).run(maindevice.scan::<4>(), |pdu| {
+                FakeEsc::process(&mut subdevices, pdu)
+            })
+        };
+
+        assert_eq!(scan(EepromErrorBehaviour::Fail), Err(Error::Timeout));
+
+        let report = scan(EepromErrorBehaviour::Placeholder).expect("scan");
+
+        assert_eq!(report.subdevices.len(), 2);
+        assert_eq!(report.subdevices[0].name, "EL2889");
+        assert_eq!(
+            report.subdevices[1],
+            SubDeviceDescriptor {
+                index: 1,
+                configured_address: 0x1001,
+                alias_address: 0,
+                identity: SubDeviceIdentity::default(),
+                name: heapless::String::new(),
+                dc_support: DcSupport::None,
+                parent_index: Some(0),
+            }
+        );
+    }
+}
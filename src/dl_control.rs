@@ -0,0 +1,222 @@
+use crate::dl_status::DlStatus;
+
+/// DL control register (`0x0100`).
+///
+/// Controls frame forwarding, per-port loop behaviour and the RX FIFO size of a SubDevice.
+///
+/// Defined in ETG1000.4 Table 33 - DL control.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, ethercrab_wire::EtherCrabWireReadWrite)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[wire(bytes = 4)]
+pub struct DlControl {
+    /// If `true`, non-EtherCAT frames are destroyed. If `false`, they are forwarded.
+    #[wire(bits = 1)]
+    pub destroy_non_ecat_frames: bool,
+    /// Loop control settings are temporary and are reset after a timeout of approximately one
+    /// second.
+    #[wire(bits = 1, post_skip = 6)]
+    pub temporary_loop_control: bool,
+
+    /// Loop control for port 0.
+    #[wire(bits = 2)]
+    pub loop_port0: LoopControl,
+    /// Loop control for port 1.
+    #[wire(bits = 2)]
+    pub loop_port1: LoopControl,
+    /// Loop control for port 2.
+    #[wire(bits = 2)]
+    pub loop_port2: LoopControl,
+    /// Loop control for port 3.
+    #[wire(bits = 2)]
+    pub loop_port3: LoopControl,
+
+    /// RX FIFO size, in units of 8 octets. The default value of `7` gives the largest FIFO.
+    #[wire(bits = 3)]
+    pub rx_fifo_size: u8,
+    /// Reduce jitter on EBUS ports.
+    #[wire(bits = 1, post_skip = 2)]
+    pub ebus_low_jitter: bool,
+    /// Shorten the time an EBUS port signals a remote link down from around 660 ms to around
+    /// 80 µs.
+    #[wire(bits = 1, post_skip = 1)]
+    pub ebus_fast_link_down: bool,
+
+    /// Use the configured station alias for addressing.
+    #[wire(bits = 1, post_skip = 7)]
+    pub station_alias_enabled: bool,
+}
+
+impl DlControl {
+    /// Get the loop control setting for the given port number (`0` - `3`).
+    pub fn loop_control(&self, port: u8) -> Option<LoopControl> {
+        match port {
+            0 => Some(self.loop_port0),
+            1 => Some(self.loop_port1),
+            2 => Some(self.loop_port2),
+            3 => Some(self.loop_port3),
+            _ => None,
+        }
+    }
+
+    /// Set the loop control setting for the given port number (`0` - `3`).
+    ///
+    /// Invalid port numbers are ignored.
+    pub fn with_loop_control(self, port: u8, loop_control: LoopControl) -> Self {
+        match port {
+            0 => Self {
+                loop_port0: loop_control,
+                ..self
+            },
+            1 => Self {
+                loop_port1: loop_control,
+                ..self
+            },
+            2 => Self {
+                loop_port2: loop_control,
+                ..self
+            },
+            3 => Self {
+                loop_port3: loop_control,
+                ..self
+            },
+            _ => self,
+        }
+    }
+}
+
+/// Port loop control setting.
+///
+/// A closed port loops frames back instead of forwarding them to the next SubDevice.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, ethercrab_wire::EtherCrabWireReadWrite)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[wire(bits = 2)]
+#[repr(u8)]
+pub enum LoopControl {
+    /// Close the port if there is no link, open it if there is.
+    #[default]
+    Auto = 0x00,
+    /// Close the port if the link is lost. It must be opened again explicitly.
+    AutoClose = 0x01,
+    /// Always open the port, regardless of link state.
+    Open = 0x02,
+    /// Always close the port.
+    Closed = 0x03,
+}
+
+/// The loop state of a single SubDevice port.
+///
+/// Read with [`SubDeviceRef::loop_status`](crate::SubDeviceRef::loop_status).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PortLoopState {
+    /// The port is forced open with [`LoopControl::Open`], so frames are forwarded through it.
+    Open,
+    /// The port is forced closed with [`LoopControl::Closed`], so frames are looped back instead of
+    /// being forwarded through it.
+    Closed,
+    /// The port is opened and closed by the SubDevice depending on its link state, with
+    /// [`LoopControl::Auto`] or [`LoopControl::AutoClose`].
+    Auto {
+        /// Whether the port is currently closed, e.g. because its link was lost.
+        closed: bool,
+    },
+}
+
+impl PortLoopState {
+    /// Decode the loop state of every port from the DL control and DL status registers.
+    pub(crate) fn from_registers(control: &DlControl, status: &DlStatus) -> [Self; 4] {
+        let controls = [
+            control.loop_port0,
+            control.loop_port1,
+            control.loop_port2,
+            control.loop_port3,
+        ];
+
+        let closed = [
+            status.loopback_port0,
+            status.loopback_port1,
+            status.loopback_port2,
+            status.loopback_port3,
+        ];
+
+        core::array::from_fn(|port| match controls[port] {
+            LoopControl::Open => Self::Open,
+            LoopControl::Closed => Self::Closed,
+            LoopControl::Auto | LoopControl::AutoClose => Self::Auto {
+                closed: closed[port],
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireWriteSized};
+
+    #[test]
+    fn close_port1() {
+        // Default ESC value with port 1 forced closed
+        let raw = [0x01u8, 0b0000_1100, 0x07, 0x00];
+
+        let control = DlControl::unpack_from_slice(&raw).expect("Unpack");
+
+        pretty_assertions::assert_eq!(
+            control,
+            DlControl {
+                destroy_non_ecat_frames: true,
+                temporary_loop_control: false,
+                loop_port0: LoopControl::Auto,
+                loop_port1: LoopControl::Closed,
+                loop_port2: LoopControl::Auto,
+                loop_port3: LoopControl::Auto,
+                rx_fifo_size: 7,
+                ebus_low_jitter: false,
+                ebus_fast_link_down: false,
+                station_alias_enabled: false,
+            }
+        );
+
+        let opened = DlControl::unpack_from_slice(&[0x01u8, 0x00, 0x07, 0x00]).expect("Unpack");
+
+        assert_eq!(opened.with_loop_control(1, LoopControl::Closed).pack(), raw);
+        assert_eq!(control.loop_control(1), Some(LoopControl::Closed));
+        assert_eq!(control.loop_control(4), None);
+    }
+
+    #[test]
+    fn ebus_bits_survive_loop_change() {
+        // EBUS low jitter (bit 19) and fast link down (bit 22) set
+        let raw = [0x01u8, 0x00, 0b0100_1111, 0x00];
+
+        let control = DlControl::unpack_from_slice(&raw).expect("Unpack");
+
+        assert!(control.ebus_low_jitter);
+        assert!(control.ebus_fast_link_down);
+
+        assert_eq!(
+            control.with_loop_control(1, LoopControl::Closed).pack(),
+            [0x01, 0b0000_1100, 0b0100_1111, 0x00]
+        );
+    }
+
+    #[test]
+    fn port_loop_state_after_break() {
+        // Port 3 forced closed, all other ports in auto mode
+        let control = DlControl::unpack_from_slice(&[0x01u8, 0b1100_0000, 0x07, 0x00]).unwrap();
+
+        // Link on port 0 only. The cable on port 1 was pulled so the port has looped back, and the
+        // unused ports 2 and 3 are closed.
+        let status = DlStatus::unpack_from_slice(&[0x10, 0b0101_0110]).unwrap();
+
+        assert_eq!(
+            PortLoopState::from_registers(&control, &status),
+            [
+                PortLoopState::Auto { closed: false },
+                PortLoopState::Auto { closed: true },
+                PortLoopState::Auto { closed: true },
+                PortLoopState::Closed,
+            ]
+        );
+    }
+}
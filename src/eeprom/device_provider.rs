@@ -1,12 +1,13 @@
 use crate::{
-    Command, MainDevice,
+    Command, MainDevice, WrappedRead, WrappedWrite,
     eeprom::{
         EepromDataProvider,
-        types::{SiiControl, SiiRequest},
+        types::{DefaultMailbox, SiiControl, SiiRequest},
     },
     error::{EepromError, Error},
     fmt,
     register::RegisterAddress,
+    subdevice::eeprom::SubDeviceEeprom,
     timer_factory::IntoTimeout,
 };
 
@@ -16,11 +17,36 @@ use crate::{
 /// SII EEPROM is WORD-addressed.
 pub(crate) const SII_FIRST_CATEGORY_START: u16 = 0x0040u16;
 
+/// How the SubDevice whose EEPROM is being accessed is addressed.
+#[derive(Copy, Clone)]
+enum Addressing {
+    /// `FPRD`/`FPWR` using the SubDevice's configured station address.
+    Configured(u16),
+    /// `APRD`/`APWR` using the SubDevice's position in the network.
+    Position(u16),
+}
+
+impl Addressing {
+    fn read(self, register: RegisterAddress) -> WrappedRead {
+        match self {
+            Addressing::Configured(address) => Command::fprd(address, register.into()),
+            Addressing::Position(position) => Command::aprd(position, register.into()),
+        }
+    }
+
+    fn write(self, register: RegisterAddress) -> WrappedWrite {
+        match self {
+            Addressing::Configured(address) => Command::fpwr(address, register.into()),
+            Addressing::Position(position) => Command::apwr(position, register.into()),
+        }
+    }
+}
+
 /// EEPROM data provider that communicates with a physical sub device.
 #[derive(Clone)]
 pub struct DeviceEeprom<'subdevice> {
     maindevice: &'subdevice MainDevice<'subdevice>,
-    configured_address: u16,
+    addressing: Addressing,
 }
 
 impl<'subdevice> DeviceEeprom<'subdevice> {
@@ -28,17 +54,39 @@ impl<'subdevice> DeviceEeprom<'subdevice> {
     pub fn new(maindevice: &'subdevice MainDevice<'subdevice>, configured_address: u16) -> Self {
         Self {
             maindevice,
-            configured_address,
+            addressing: Addressing::Configured(configured_address),
         }
     }
 
+    /// Create a new EEPROM reader instance that addresses the SubDevice by its position in the
+    /// network.
+    ///
+    /// This can be used before configured station addresses have been assigned.
+    pub fn positional(maindevice: &'subdevice MainDevice<'subdevice>, position: u16) -> Self {
+        Self {
+            maindevice,
+            addressing: Addressing::Position(position),
+        }
+    }
+
+    /// Read the standard mailbox offsets, sizes and supported protocols from the fixed SII area
+    /// (ETG2010 Table 2).
+    ///
+    /// These are stored at a fixed address before the first category so can be read without
+    /// searching the EEPROM. They describe the mailbox used in PRE-OP and above, not the bootstrap
+    /// mailbox used for firmware updates.
+    pub(crate) async fn standard_mailbox_config(&self) -> Result<DefaultMailbox, Error> {
+        SubDeviceEeprom::new(self.clone()).mailbox_config().await
+    }
+
     async fn wait_while_busy(&self) -> Result<SiiControl, Error> {
         let res = async {
             loop {
-                let control: SiiControl =
-                    Command::fprd(self.configured_address, RegisterAddress::SiiControl.into())
-                        .receive::<SiiControl>(self.maindevice)
-                        .await?;
+                let control: SiiControl = self
+                    .addressing
+                    .read(RegisterAddress::SiiControl)
+                    .receive::<SiiControl>(self.maindevice)
+                    .await?;
 
                 if !control.busy {
                     break Ok(control);
@@ -55,17 +103,25 @@ impl<'subdevice> DeviceEeprom<'subdevice> {
 }
 
 impl EepromDataProvider for DeviceEeprom<'_> {
+    /// Read a chunk of 4 or 8 bytes from the SubDevice EEPROM.
+    ///
+    /// The chunk size is fixed by the ESC and is reported in the read only
+    /// `SiiControl::read_size` field, so it can't be requested. The size reported in the status
+    /// returned after the read request is used for the data read, so ESCs that support 8 byte reads
+    /// need half the round trips of ones that don't.
     async fn read_chunk(
         &mut self,
         start_word: u16,
     ) -> Result<impl core::ops::Deref<Target = [u8]>, Error> {
-        Command::fpwr(self.configured_address, RegisterAddress::SiiControl.into())
+        self.addressing
+            .write(RegisterAddress::SiiControl)
             .send(self.maindevice, SiiRequest::read(start_word))
             .await?;
 
         let status = self.wait_while_busy().await?;
 
-        Command::fprd(self.configured_address, RegisterAddress::SiiData.into())
+        self.addressing
+            .read(RegisterAddress::SiiData)
             .receive_slice(self.maindevice, status.read_size.chunk_len())
             .await
             .inspect(|data| {
@@ -84,13 +140,15 @@ impl EepromDataProvider for DeviceEeprom<'_> {
 
         loop {
             // Set data to write
-            Command::fpwr(self.configured_address, RegisterAddress::SiiData.into())
+            self.addressing
+                .write(RegisterAddress::SiiData)
                 .send(self.maindevice, data)
                 .await?;
 
             // Send control and address registers. A rising edge on the write flag will store whatever
             // is in `SiiAddress` into the EEPROM at the given address.
-            Command::fpwr(self.configured_address, RegisterAddress::SiiControl.into())
+            self.addressing
+                .write(RegisterAddress::SiiControl)
                 .send(self.maindevice, SiiRequest::write(start_word))
                 .await?;
 
@@ -110,7 +168,9 @@ impl EepromDataProvider for DeviceEeprom<'_> {
     }
 
     async fn clear_errors(&self) -> Result<(), Error> {
-        let status = Command::fprd(self.configured_address, RegisterAddress::SiiControl.into())
+        let status = self
+            .addressing
+            .read(RegisterAddress::SiiControl)
             .receive::<SiiControl>(self.maindevice)
             .await?;
 
@@ -118,7 +178,8 @@ impl EepromDataProvider for DeviceEeprom<'_> {
         let status = if status.has_error() {
             fmt::trace!("Resetting EEPROM error flags");
 
-            Command::fpwr(self.configured_address, RegisterAddress::SiiControl.into())
+            self.addressing
+                .write(RegisterAddress::SiiControl)
                 .send_receive(self.maindevice, status.error_reset())
                 .await?
         } else {
@@ -132,3 +193,124 @@ impl EepromDataProvider for DeviceEeprom<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        MainDeviceConfig, PduStorage, Timeouts,
+        command::FPRD,
+        eeprom::types::MailboxProtocols,
+        fake_network::{FakeEsc, FakeNetwork},
+    };
+
+    #[test]
+    fn read_chunk_uses_read_size() {
+        crate::test_logger();
+
+        let eeprom = include_bytes!("../../dumps/eeprom/el2828.hex");
+
+        for (esc, expected_len) in [
+            (FakeEsc::new(eeprom), 8),
+            (FakeEsc::new(eeprom).with_short_eeprom_reads(), 4),
+        ] {
+            let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+            let maindevice =
+                MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+            let mut escs = [esc.with_configured_address(0x1000)];
+            let mut data_read_lens = Vec::new();
+
+            let chunk = FakeNetwork::new(&mut tx, &mut rx)
+                .run(
+                    async {
+                        let mut provider = DeviceEeprom::new(&maindevice, 0x1000);
+
+                        provider
+                            .read_chunk(0x0008)
+                            .await
+                            .map(|chunk| chunk.to_vec())
+                    },
+                    |pdu| {
+                        if pdu.command == FPRD && pdu.ado == u16::from(RegisterAddress::SiiData) {
+                            data_read_lens.push(pdu.data.len());
+                        }
+
+                        FakeEsc::process(&mut escs, pdu)
+                    },
+                )
+                .expect("read chunk");
+
+            // Word 8 onwards
+            assert_eq!(chunk, eeprom[0x10..0x10 + expected_len]);
+            assert_eq!(data_read_lens, [expected_len]);
+        }
+    }
+
+    #[test]
+    fn standard_mailbox_config_dumps() {
+        crate::test_logger();
+
+        let no_mailbox = DefaultMailbox::default();
+
+        let cases: [(&'static [u8], DefaultMailbox); 7] = [
+            (
+                include_bytes!("../../dumps/eeprom/akd.hex"),
+                DefaultMailbox {
+                    subdevice_receive_offset: 0x1800,
+                    subdevice_receive_size: 0x0400,
+                    subdevice_send_offset: 0x1c00,
+                    subdevice_send_size: 0x0400,
+                    supported_protocols: MailboxProtocols::EOE
+                        | MailboxProtocols::COE
+                        | MailboxProtocols::FOE,
+                },
+            ),
+            (
+                include_bytes!("../../dumps/eeprom/akd_null_strings.hex"),
+                DefaultMailbox {
+                    subdevice_receive_offset: 0x1800,
+                    subdevice_receive_size: 0x0400,
+                    subdevice_send_offset: 0x1c00,
+                    subdevice_send_size: 0x0400,
+                    supported_protocols: MailboxProtocols::EOE
+                        | MailboxProtocols::COE
+                        | MailboxProtocols::FOE,
+                },
+            ),
+            (
+                include_bytes!("../../dumps/eeprom/hbm_clipx_eeprom_dump.bin"),
+                DefaultMailbox {
+                    subdevice_receive_offset: 0x1000,
+                    subdevice_receive_size: 0x0080,
+                    subdevice_send_offset: 0x1080,
+                    subdevice_send_size: 0x0080,
+                    supported_protocols: MailboxProtocols::COE | MailboxProtocols::FOE,
+                },
+            ),
+            // Simple IO terminals without a mailbox
+            (include_bytes!("../../dumps/eeprom/ek1100.hex"), no_mailbox),
+            (include_bytes!("../../dumps/eeprom/el2262.bin"), no_mailbox),
+            (include_bytes!("../../dumps/eeprom/el2828.hex"), no_mailbox),
+            (include_bytes!("../../dumps/eeprom/el2889.hex"), no_mailbox),
+        ];
+
+        for (image, expected) in cases {
+            let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+            let maindevice =
+                MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+            let mut escs = [FakeEsc::new(image).with_configured_address(0x1000)];
+
+            let mbox = FakeNetwork::new(&mut tx, &mut rx).run(
+                DeviceEeprom::new(&maindevice, 0x1000).standard_mailbox_config(),
+                |pdu| FakeEsc::process(&mut escs, pdu),
+            );
+
+            assert_eq!(mbox, Ok(expected));
+            assert_eq!(mbox.unwrap().has_mailbox(), expected != no_mailbox);
+        }
+    }
+}
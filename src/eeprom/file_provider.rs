@@ -93,3 +93,77 @@ impl<const CHUNK: usize> EepromDataProvider for EepromFile<CHUNK> {
         Ok(())
     }
 }
+
+/// Words written through a [`CountingProvider`], as `(word address, data)` pairs.
+#[cfg(test)]
+type WrittenWords = Vec<(u16, [u8; 2])>;
+
+/// Wraps another provider, counting the number of chunk reads and error clears issued to it and
+/// recording every word written.
+///
+/// The counts are shared between clones, so they include accesses made through every `EepromRange`
+/// created from the same provider.
+#[cfg(test)]
+#[derive(Clone)]
+pub struct CountingProvider<P> {
+    inner: P,
+    reads: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    clears: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    writes: std::sync::Arc<std::sync::Mutex<WrittenWords>>,
+}
+
+#[cfg(test)]
+impl<P> CountingProvider<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            reads: Default::default(),
+            clears: Default::default(),
+            writes: Default::default(),
+        }
+    }
+
+    /// The number of chunk reads made so far.
+    pub fn reads(&self) -> usize {
+        self.reads.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// The number of times errors were cleared so far.
+    pub fn clears(&self) -> usize {
+        self.clears.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// The word address and data of every write made so far, oldest first.
+    pub fn writes(&self) -> WrittenWords {
+        self.writes.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl<P> EepromDataProvider for CountingProvider<P>
+where
+    P: EepromDataProvider,
+{
+    async fn read_chunk(
+        &mut self,
+        start_word: u16,
+    ) -> Result<impl core::ops::Deref<Target = [u8]>, Error> {
+        self.reads
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        self.inner.read_chunk(start_word).await
+    }
+
+    async fn write_word(&mut self, start_word: u16, data: [u8; 2]) -> Result<(), Error> {
+        self.writes.lock().unwrap().push((start_word, data));
+
+        self.inner.write_word(start_word, data).await
+    }
+
+    async fn clear_errors(&self) -> Result<(), Error> {
+        self.clears
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        self.inner.clear_errors().await
+    }
+}
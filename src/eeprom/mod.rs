@@ -1,21 +1,35 @@
+//! SubDevice EEPROM (SII) access and utilities.
+//!
+//! [`EepromRange`] reads or writes a section of an EEPROM through any [`EepromDataProvider`].
+//!
+//! The checksum and header helpers are also useful for tools that inspect or patch EEPROM images
+//! outside of a running network.
+
 use core::ops::Deref;
 
 use crate::{
-    error::{EepromError, Error},
+    SubDeviceIdentity,
+    error::{DistributedClockError, EepromError, Error, MailboxError, PduError},
     fmt,
 };
-use embedded_io_async::{ErrorType, ReadExactError};
+use embedded_io_async::{ErrorType, Read, ReadExactError};
+use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireSized, WireError};
 
-pub mod device_provider;
-pub mod types;
+pub(crate) mod device_provider;
+pub(crate) mod types;
 
 #[cfg(feature = "std")]
-pub mod file_provider;
+pub(crate) mod file_provider;
 
-pub const STATION_ALIAS_POSITION: core::ops::Range<usize> = 8..10;
-pub const CHECKSUM_POSITION: core::ops::Range<usize> = 14..16;
+pub(crate) const STATION_ALIAS_POSITION: core::ops::Range<usize> = 8..10;
+pub(crate) const CHECKSUM_POSITION: core::ops::Range<usize> = 14..16;
+/// Word address of the vendor ID, followed by the product code, revision and serial number.
+pub(crate) const IDENTITY_WORD: u16 = 0x0008;
 
-const ECAT_CRC_ALGORITHM: crc::Algorithm<u8> = crc::Algorithm {
+/// The CRC-8 algorithm used for the checksum over the first 7 words of a SubDevice EEPROM.
+///
+/// Defined in ETG2010 Table 2.
+pub const ECAT_CRC_ALGORITHM: crc::Algorithm<u8> = crc::Algorithm {
     width: 8,
     poly: 0x07,
     init: 0xff,
@@ -26,9 +40,169 @@ const ECAT_CRC_ALGORITHM: crc::Algorithm<u8> = crc::Algorithm {
     residue: 0x00,
 };
 
-pub const STATION_ALIAS_CRC: crc::Crc<u8> = crc::Crc::<u8>::new(&ECAT_CRC_ALGORITHM);
+const ECAT_CRC: crc::Crc<u8> = crc::Crc::<u8>::new(&ECAT_CRC_ALGORITHM);
+
+/// Compute the checksum of the first 7 words (14 bytes) of a SubDevice EEPROM.
+///
+/// The result is stored in word 7 of the EEPROM. Only the lower byte is used, so the upper byte of
+/// the returned value is always zero.
+///
+/// # Examples
+///
+/// ```rust
+/// use ethercrab::eeprom::sii_checksum;
+///
+/// // Start of the EEPROM of an EL2828
+/// let header = [
+///     0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+/// ];
+///
+/// assert_eq!(sii_checksum(&header), 0x00e2);
+/// ```
+pub fn sii_checksum(header: &[u8; 14]) -> u16 {
+    u16::from(ECAT_CRC.checksum(header))
+}
+
+/// The fixed fields at the start of a SubDevice EEPROM.
+///
+/// Defined in ETG2010 Table 2.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SiiHeader {
+    /// Initialisation value for the PDI control register `0x0140`.
+    pub pdi_control: u16,
+    /// Initialisation value for the PDI configuration register `0x0150`.
+    pub pdi_config: u16,
+    /// Initialisation value for the SYNC impulse length register `0x0982`, in units of 10ns.
+    pub sync_impulse_len: u16,
+    /// Initialisation value for the extended PDI configuration register `0x0152`.
+    pub pdi_config2: u16,
+    /// Configured station alias.
+    pub station_alias: u16,
+    /// The checksum stored in the EEPROM.
+    pub checksum: u16,
+}
+
+/// Parse the first 8 words of a SubDevice EEPROM and check the stored checksum.
+///
+/// Only the first 16 bytes of `bytes` are used, so a whole EEPROM image can be passed.
+///
+/// # Errors
+///
+/// Returns [`EepromError::SectionUnderrun`] if `bytes` is shorter than 16 bytes, or
+/// [`EepromError::InvalidChecksum`] if the stored checksum doesn't match the one computed with
+/// [`sii_checksum`].
+pub fn validate_sii_header(bytes: &[u8]) -> Result<SiiHeader, EepromError> {
+    let header = bytes
+        .first_chunk::<{ CHECKSUM_POSITION.end }>()
+        .ok_or(EepromError::SectionUnderrun)?;
+
+    let word = |idx: usize| u16::from_le_bytes([header[idx * 2], header[idx * 2 + 1]]);
+
+    let parsed = SiiHeader {
+        pdi_control: word(0),
+        pdi_config: word(1),
+        sync_impulse_len: word(2),
+        pdi_config2: word(3),
+        station_alias: word(STATION_ALIAS_POSITION.start / 2),
+        checksum: word(CHECKSUM_POSITION.start / 2),
+    };
+
+    let checksummed = header
+        .first_chunk::<{ CHECKSUM_POSITION.start }>()
+        .ok_or(EepromError::SectionUnderrun)?;
+
+    let computed = sii_checksum(checksummed);
+
+    if computed != parsed.checksum {
+        fmt::warn!(
+            "EEPROM checksum mismatch: expected {:#06x}, computed {:#06x}",
+            parsed.checksum,
+            computed
+        );
+
+        return Err(EepromError::InvalidChecksum);
+    }
+
+    Ok(parsed)
+}
+
+/// Read the vendor ID, product code, revision and serial number from the fixed fields of a
+/// SubDevice EEPROM.
+///
+/// Only the 8 identity words starting at word 8 (ETG2010 Table 2) are read, without walking the
+/// EEPROM categories, so this is much faster than fully initialising a SubDevice when only the
+/// identity is needed, e.g. when scanning for a particular device.
+///
+/// # Examples
+///
+/// ```rust
+/// use core::ops::Deref;
+/// use ethercrab::{eeprom::{EepromDataProvider, read_identity}, error::Error};
+///
+/// /// An in-memory EEPROM image.
+/// #[derive(Clone)]
+/// struct Image([u8; 32]);
+///
+/// impl EepromDataProvider for Image {
+///     async fn read_chunk(&mut self, start_word: u16) -> Result<impl Deref<Target = [u8]>, Error> {
+///         let start = usize::from(start_word) * 2;
+///
+///         Ok(self.0[start..start + 8].to_vec())
+///     }
+///
+///     async fn write_word(&mut self, start_word: u16, data: [u8; 2]) -> Result<(), Error> {
+///         let start = usize::from(start_word) * 2;
+///
+///         self.0[start..start + 2].copy_from_slice(&data);
+///
+///         Ok(())
+///     }
+///
+///     async fn clear_errors(&self) -> Result<(), Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// let mut image = [0u8; 32];
+///
+/// // EK1100 vendor ID and product code
+/// image[16..20].copy_from_slice(&0x0000_0002u32.to_le_bytes());
+/// image[20..24].copy_from_slice(&0x044c_2c52u32.to_le_bytes());
+///
+/// # smol::block_on(async {
+/// let identity = read_identity(Image(image)).await?;
+///
+/// assert_eq!(identity.vendor_id, 0x0000_0002);
+/// assert_eq!(identity.product_id, 0x044c_2c52);
+/// # Ok::<(), Error>(())
+/// # }).unwrap();
+/// ```
+pub async fn read_identity<P>(provider: P) -> Result<SubDeviceIdentity, Error>
+where
+    P: EepromDataProvider,
+{
+    let mut reader = EepromRange::new(
+        provider,
+        IDENTITY_WORD,
+        SubDeviceIdentity::PACKED_LEN as u16 / 2,
+    );
+
+    fmt::trace!("Get identity");
+
+    let mut buf = SubDeviceIdentity::buffer();
+
+    reader.read_exact(&mut buf).await?;
+
+    Ok(SubDeviceIdentity::unpack_from_slice(&buf)?)
+}
 
 /// A data source for EEPROM reads.
+///
+/// Implement this to read an EEPROM image from somewhere other than a SubDevice, e.g. a file, and
+/// wrap it in an [`EepromRange`] to read or write a section of it.
+// Implementors don't need to be `Send`, so the `Send` bound the lint asks for isn't wanted.
+#[allow(async_fn_in_trait)]
 pub trait EepromDataProvider: Clone {
     /// Read a chunk of either 4 or 8 bytes from the backing store.
     async fn read_chunk(&mut self, start_word: u16) -> Result<impl Deref<Target = [u8]>, Error>;
@@ -40,10 +214,96 @@ pub trait EepromDataProvider: Clone {
     async fn clear_errors(&self) -> Result<(), Error>;
 }
 
+/// Map EtherCrab errors onto the closest [`ErrorKind`](embedded_io_async::ErrorKind) so generic
+/// `embedded-io` consumers of e.g. [`EepromRange`] can tell failures apart.
+///
+/// The mapping is as follows:
+///
+/// - `TimedOut`: [`Error::Timeout`].
+/// - `InvalidInput`: reading or skipping past the end of a range ([`EepromError::SectionOverrun`])
+///   or past the end of the EEPROM itself ([`EepromError::PastEnd`]),
+///   PDU data too long to fit in a frame or past the end of the logical address space, write
+///   buffers that are too short, [`Error::IntegerTypeConversion`] and [`Error::InvalidTimeouts`].
+/// - `OutOfMemory`: fixed size storage is too small, i.e. [`Error::Capacity`],
+///   [`Error::StringTooLong`], [`Error::PdiTooLong`], mailbox data too long for the given type, or
+///   no free frame slots ([`PduError::SwapState`]).
+/// - `InvalidData`: decode, validation and protocol errors, including unexpected working counters,
+///   invalid checksums, unexpected SubDevice states and topology problems.
+/// - `NotFound`: [`Error::NotFound`], [`Error::UnknownSubDevice`], missing EEPROM categories and
+///   no DC reference SubDevice.
+/// - `Unsupported`: a mailbox is required but the SubDevice doesn't have one.
+/// - `ConnectionAborted`: a mailbox transfer was aborted by the SubDevice.
+/// - `BrokenPipe`: sending a frame failed or the frame was only partially sent.
+/// - `ConnectionReset`: receiving a frame failed.
+/// - `NotConnected`: the network interface has no link ([`Error::LinkDown`]).
+/// - `Other`: errors reported by the SubDevice itself ([`Error::SubDevice`],
+///   [`Error::StateTransition`] and mailbox emergencies), failure to clear EEPROM errors, and
+///   internal errors.
 impl embedded_io_async::Error for Error {
     fn kind(&self) -> embedded_io_async::ErrorKind {
-        // TODO: match()?
-        embedded_io_async::ErrorKind::Other
+        use embedded_io_async::ErrorKind;
+
+        match self {
+            Error::Timeout => ErrorKind::TimedOut,
+            Error::Pdu(e) => match e {
+                PduError::TooLong | PduError::LogicalAddressOverflow { .. } => {
+                    ErrorKind::InvalidInput
+                }
+                PduError::SwapState => ErrorKind::OutOfMemory,
+                PduError::Decode
+                | PduError::Ethernet
+                | PduError::InvalidIndex(_)
+                | PduError::Validation(_)
+                | PduError::PayloadLengthMismatch { .. } => ErrorKind::InvalidData,
+                PduError::CreateFrame | PduError::InvalidFrameState => ErrorKind::Other,
+            },
+            Error::Eeprom(e) => match e {
+                EepromError::SectionOverrun | EepromError::PastEnd => ErrorKind::InvalidInput,
+                EepromError::NoCategory => ErrorKind::NotFound,
+                EepromError::Decode
+                | EepromError::SectionUnderrun
+                | EepromError::InvalidChecksum => ErrorKind::InvalidData,
+                EepromError::ClearErrors => ErrorKind::Other,
+            },
+            Error::Mailbox(e) => match e {
+                MailboxError::Aborted { .. } => ErrorKind::ConnectionAborted,
+                MailboxError::TooLong { .. } => ErrorKind::OutOfMemory,
+                MailboxError::NoMailbox => ErrorKind::Unsupported,
+                MailboxError::RequestTooShort => ErrorKind::InvalidInput,
+                MailboxError::SdoResponseInvalid { .. } | MailboxError::InvalidCount => {
+                    ErrorKind::InvalidData
+                }
+                MailboxError::Emergency { .. } => ErrorKind::Other,
+            },
+            Error::Wire(e) => match e {
+                WireError::WriteBufferTooShort => ErrorKind::InvalidInput,
+                WireError::ReadBufferTooShort
+                | WireError::InvalidValue
+                | WireError::ArrayLength
+                | WireError::InvalidUtf8
+                | WireError::Validation => ErrorKind::InvalidData,
+            },
+            Error::Capacity(_) | Error::StringTooLong { .. } | Error::PdiTooLong { .. } => {
+                ErrorKind::OutOfMemory
+            }
+            Error::IntegerTypeConversion => ErrorKind::InvalidInput,
+            Error::WorkingCounter { .. }
+            | Error::Topology
+            | Error::InvalidState { .. }
+            | Error::StateMismatch { .. } => ErrorKind::InvalidData,
+            Error::NotFound { .. }
+            | Error::UnknownSubDevice
+            | Error::DistributedClock(DistributedClockError::NoReference) => ErrorKind::NotFound,
+            Error::InvalidTimeouts(_)
+            | Error::OutputOffsetOverlap { .. }
+            | Error::DistributedClock(DistributedClockError::InvalidCycleTime) => {
+                ErrorKind::InvalidInput
+            }
+            Error::SendFrame | Error::PartialSend { .. } => ErrorKind::BrokenPipe,
+            Error::ReceiveFrame => ErrorKind::ConnectionReset,
+            Error::LinkDown => ErrorKind::NotConnected,
+            Error::SubDevice(_) | Error::StateTransition | Error::Internal => ErrorKind::Other,
+        }
     }
 }
 
@@ -80,7 +340,7 @@ impl<P> EepromRange<P>
 where
     P: EepromDataProvider,
 {
-    /// Create a new `ChunkReader`.
+    /// Create a range of `len_words` words starting at `start_word`, read from `reader`.
     pub fn new(reader: P, start_word: u16, len_words: u16) -> Self {
         Self {
             reader,
@@ -123,8 +383,58 @@ where
         res.get(skip).copied().ok_or(Error::Internal)
     }
 
-    #[allow(unused)]
-    pub(crate) fn into_inner(self) -> P {
+    /// Consume this range, returning the underlying provider.
+    ///
+    /// Useful for inspecting the state of a custom provider after a series of reads or writes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use core::ops::Deref;
+    /// use ethercrab::{
+    ///     eeprom::{EepromDataProvider, EepromRange},
+    ///     error::Error,
+    /// };
+    ///
+    /// /// An in-memory EEPROM image that counts how many chunks were read from it.
+    /// #[derive(Clone)]
+    /// struct Image {
+    ///     bytes: [u8; 16],
+    ///     reads: usize,
+    /// }
+    ///
+    /// impl EepromDataProvider for Image {
+    ///     async fn read_chunk(&mut self, start_word: u16) -> Result<impl Deref<Target = [u8]>, Error> {
+    ///         let start = usize::from(start_word) * 2;
+    ///
+    ///         self.reads += 1;
+    ///
+    ///         Ok(self.bytes[start..start + 4].to_vec())
+    ///     }
+    ///
+    ///     async fn write_word(&mut self, start_word: u16, data: [u8; 2]) -> Result<(), Error> {
+    ///         let start = usize::from(start_word) * 2;
+    ///
+    ///         self.bytes[start..start + 2].copy_from_slice(&data);
+    ///
+    ///         Ok(())
+    ///     }
+    ///
+    ///     async fn clear_errors(&self) -> Result<(), Error> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # smol::block_on(async {
+    /// let mut range = EepromRange::new(Image { bytes: [0xab; 16], reads: 0 }, 0, 8);
+    ///
+    /// assert_eq!(range.read_byte().await, Ok(0xab));
+    /// assert_eq!(range.read_byte().await, Ok(0xab));
+    ///
+    /// assert_eq!(range.into_inner().reads, 2);
+    /// # })
+    /// ```
+    pub fn into_inner(self) -> P {
         self.reader
     }
 }
@@ -261,8 +571,30 @@ impl<P> ErrorType for EepromRange<P> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::eeprom::file_provider::EepromFile;
-    use embedded_io_async::{Read, Write};
+    use crate::eeprom::file_provider::{CountingProvider, EepromFile};
+    use embedded_io_async::Write;
+
+    #[tokio::test]
+    async fn read_identity_akd() {
+        crate::test_logger();
+
+        let provider = CountingProvider::new(EepromFile::new(include_bytes!(
+            "../../dumps/eeprom/akd.hex"
+        )));
+
+        assert_eq!(
+            read_identity(provider.clone()).await,
+            Ok(SubDeviceIdentity {
+                vendor_id: 0x0000006a,
+                product_id: 0x00414b44,
+                revision: 2,
+                serial: 2575499411,
+            })
+        );
+
+        // Two 8 byte chunks, and nothing outside the identity words
+        assert_eq!(provider.reads(), 2);
+    }
 
     #[tokio::test]
     async fn skip_past_end() {
@@ -388,20 +720,7 @@ mod tests {
 
         assert_eq!(checksum, expected_checksum);
 
-        const ECAT_CRC: crc::Algorithm<u8> = crc::Algorithm {
-            width: 8,
-            poly: 0x07,
-            init: 0xff,
-            refin: false,
-            refout: false,
-            xorout: 0x00,
-            check: 0x80,
-            residue: 0x00,
-        };
-
-        const EEPROM_CRC: crc::Crc<u8> = crc::Crc::<u8>::new(&ECAT_CRC);
-
-        let cs = u16::from(EEPROM_CRC.checksum(rest));
+        let cs = sii_checksum(rest.try_into().unwrap());
 
         assert_eq!(
             cs, expected_checksum,
@@ -436,20 +755,7 @@ mod tests {
 
         assert_eq!(checksum, expected_checksum);
 
-        const ECAT_CRC: crc::Algorithm<u8> = crc::Algorithm {
-            width: 8,
-            poly: 0x07,
-            init: 0xff,
-            refin: false,
-            refout: false,
-            xorout: 0x00,
-            check: 0x80,
-            residue: 0x00,
-        };
-
-        const EEPROM_CRC: crc::Crc<u8> = crc::Crc::<u8>::new(&ECAT_CRC);
-
-        let cs = u16::from(EEPROM_CRC.checksum(rest));
+        let cs = sii_checksum(rest.try_into().unwrap());
 
         assert_eq!(
             cs, expected_checksum,
@@ -458,6 +764,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_bundled_headers() {
+        for (name, image, checksum) in [
+            (
+                "el2828",
+                &include_bytes!("../../dumps/eeprom/el2828.hex")[..],
+                0x00e2,
+            ),
+            (
+                "akd",
+                &include_bytes!("../../dumps/eeprom/akd.hex")[..],
+                0x0010,
+            ),
+            (
+                "ek1100",
+                &include_bytes!("../../dumps/eeprom/ek1100.hex")[..],
+                0x0046,
+            ),
+            (
+                "el2889",
+                &include_bytes!("../../dumps/eeprom/el2889.hex")[..],
+                0x00e2,
+            ),
+            (
+                "el2262",
+                &include_bytes!("../../dumps/eeprom/el2262.bin")[..],
+                0x002b,
+            ),
+            (
+                "clipx",
+                &include_bytes!("../../dumps/eeprom/hbm_clipx_eeprom_dump.bin")[..],
+                0x00b7,
+            ),
+        ] {
+            let header = validate_sii_header(image).expect(name);
+
+            assert_eq!(header.checksum, checksum, "{}", name);
+            assert_eq!(
+                sii_checksum(image.first_chunk().unwrap()),
+                checksum,
+                "{}",
+                name
+            );
+        }
+
+        let el2828 = validate_sii_header(include_bytes!("../../dumps/eeprom/el2828.hex")).unwrap();
+
+        assert_eq!(
+            el2828,
+            SiiHeader {
+                pdi_control: 0x0104,
+                pdi_config: 0x0000,
+                sync_impulse_len: 0x0000,
+                pdi_config2: 0x00ff,
+                station_alias: 0,
+                checksum: 0x00e2
+            }
+        );
+    }
+
+    #[test]
+    fn validate_header_too_short() {
+        assert_eq!(
+            validate_sii_header(&[0u8; 15]),
+            Err(EepromError::SectionUnderrun)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn fuzz_sii_checksum() {
+        heckcheck::check(|(header, flip_byte, flip_bit): ([u8; 14], u8, u8)| {
+            let mut image = [0u8; 16];
+
+            image[0..14].copy_from_slice(&header);
+            image[CHECKSUM_POSITION].copy_from_slice(&sii_checksum(&header).to_le_bytes());
+
+            assert_eq!(
+                validate_sii_header(&image).map(|h| h.checksum),
+                Ok(sii_checksum(&header))
+            );
+
+            // A CRC-8 detects every single bit error
+            image[usize::from(flip_byte) % 14] ^= 1 << (flip_bit % 8);
+
+            assert_eq!(
+                validate_sii_header(&image),
+                Err(EepromError::InvalidChecksum)
+            );
+
+            Ok(())
+        });
+    }
+
     #[tokio::test]
     async fn write_station_alias() {
         let mut r = EepromRange::new(
@@ -483,7 +883,7 @@ mod tests {
         all[STATION_ALIAS_POSITION].copy_from_slice(&new_alias.to_le_bytes());
 
         // Don't checksum the checksum
-        let checksum = u16::from(STATION_ALIAS_CRC.checksum(&all[0..CHECKSUM_POSITION.start]));
+        let checksum = sii_checksum(all.first_chunk().unwrap());
 
         // Update checksum ready to write back into EEPROM
         all[CHECKSUM_POSITION].copy_from_slice(&checksum.to_le_bytes());
@@ -512,4 +912,183 @@ mod tests {
         // Check what we wrote is correct
         assert_eq!(w.into_inner().write_cache[0..16], expected);
     }
+
+    #[tokio::test]
+    async fn inspect_provider_after_read() {
+        crate::test_logger();
+
+        let mut r = EepromRange::new(
+            CountingProvider::new(EepromFile::new(include_bytes!(
+                "../../dumps/eeprom/el2828.hex"
+            ))),
+            0,
+            // 8 words, 16 bytes
+            8,
+        );
+
+        let mut all = [0u8; 16];
+
+        r.read_exact(&mut all).await.expect("Read");
+
+        let provider = r.into_inner();
+
+        // 16 bytes read in 8 byte chunks
+        assert_eq!(provider.reads(), 2);
+    }
+
+    #[tokio::test]
+    async fn eight_byte_chunks_halve_reads() {
+        crate::test_logger();
+
+        // 64 words, 128 bytes
+        const LEN_WORDS: u16 = 64;
+
+        let mut short = EepromRange::new(
+            CountingProvider::new(EepromFile::new_short(include_bytes!(
+                "../../dumps/eeprom/el2828.hex"
+            ))),
+            0,
+            LEN_WORDS,
+        );
+
+        let mut long = EepromRange::new(
+            CountingProvider::new(EepromFile::new(include_bytes!(
+                "../../dumps/eeprom/el2828.hex"
+            ))),
+            0,
+            LEN_WORDS,
+        );
+
+        let mut short_buf = [0u8; LEN_WORDS as usize * 2];
+        let mut long_buf = [0u8; LEN_WORDS as usize * 2];
+
+        short.read_exact(&mut short_buf).await.expect("Short read");
+        long.read_exact(&mut long_buf).await.expect("Long read");
+
+        assert_eq!(short_buf, long_buf);
+
+        assert_eq!(short.into_inner().reads(), 32);
+        assert_eq!(long.into_inner().reads(), 16);
+    }
+
+    #[test]
+    fn error_kinds() {
+        use crate::{
+            AlStatusCode, SubDeviceState,
+            error::{CoeAbortCode, Item, PduValidationError, TimeoutField, TimeoutsError},
+        };
+        use embedded_io_async::{Error as _, ErrorKind};
+
+        let cases = [
+            (Error::Timeout, ErrorKind::TimedOut),
+            (
+                Error::Eeprom(EepromError::SectionOverrun),
+                ErrorKind::InvalidInput,
+            ),
+            (Error::Eeprom(EepromError::PastEnd), ErrorKind::InvalidInput),
+            (Error::Pdu(PduError::TooLong), ErrorKind::InvalidInput),
+            (Error::IntegerTypeConversion, ErrorKind::InvalidInput),
+            (
+                Error::InvalidTimeouts(TimeoutsError::Zero(TimeoutField::Pdu)),
+                ErrorKind::InvalidInput,
+            ),
+            (Error::Capacity(Item::Pdo), ErrorKind::OutOfMemory),
+            (
+                Error::StringTooLong {
+                    max_length: 8,
+                    string_length: 9,
+                },
+                ErrorKind::OutOfMemory,
+            ),
+            (Error::Pdu(PduError::SwapState), ErrorKind::OutOfMemory),
+            (Error::Pdu(PduError::InvalidFrameState), ErrorKind::Other),
+            (Error::Eeprom(EepromError::Decode), ErrorKind::InvalidData),
+            (
+                Error::Eeprom(EepromError::InvalidChecksum),
+                ErrorKind::InvalidData,
+            ),
+            (
+                Error::Pdu(PduError::Validation(PduValidationError::IndexMismatch {
+                    sent: 1,
+                    received: 2,
+                })),
+                ErrorKind::InvalidData,
+            ),
+            (
+                Error::Wire(WireError::ReadBufferTooShort),
+                ErrorKind::InvalidData,
+            ),
+            (
+                Error::WorkingCounter {
+                    expected: 1,
+                    received: 0,
+                },
+                ErrorKind::InvalidData,
+            ),
+            (
+                Error::InvalidState {
+                    expected: SubDeviceState::Op,
+                    actual: SubDeviceState::SafeOp,
+                    configured_address: 0x1000,
+                },
+                ErrorKind::InvalidData,
+            ),
+            (
+                Error::StateMismatch {
+                    expected: SubDeviceState::Op,
+                    count: 2,
+                    configured_address: 0x1001,
+                    actual: SubDeviceState::SafeOp,
+                },
+                ErrorKind::InvalidData,
+            ),
+            (Error::Eeprom(EepromError::NoCategory), ErrorKind::NotFound),
+            (Error::UnknownSubDevice, ErrorKind::NotFound),
+            (
+                Error::Mailbox(MailboxError::NoMailbox),
+                ErrorKind::Unsupported,
+            ),
+            (
+                Error::Mailbox(MailboxError::Aborted {
+                    code: CoeAbortCode::NotFound,
+                    address: 0x1c12,
+                    sub_index: 0,
+                }),
+                ErrorKind::ConnectionAborted,
+            ),
+            (Error::SendFrame, ErrorKind::BrokenPipe),
+            (
+                Error::PartialSend { len: 60, sent: 14 },
+                ErrorKind::BrokenPipe,
+            ),
+            (Error::ReceiveFrame, ErrorKind::ConnectionReset),
+            (Error::LinkDown, ErrorKind::NotConnected),
+            (
+                Error::SubDevice(AlStatusCode::InvalidDeviceSetup),
+                ErrorKind::Other,
+            ),
+            (Error::Internal, ErrorKind::Other),
+        ];
+
+        for (error, expected) in cases {
+            assert_eq!(error.kind(), expected, "{:?}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn read_past_end_kind() {
+        use embedded_io_async::{Error as _, ErrorKind};
+
+        let mut r = EepromRange::new(
+            EepromFile::new(include_bytes!("../../dumps/eeprom/akd.hex")),
+            0,
+            2,
+        );
+
+        let mut buf = [0u8; 8];
+
+        let e = Error::from(r.read_exact(&mut buf).await.unwrap_err());
+
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    }
 }
@@ -722,6 +722,7 @@ bitflags::bitflags! {
     /// Defined in ETG1000.6 Table 18 or ETG2010 Table 4.
     // NOTE: Is actually a u16, but only the lower byte has any data in it
     #[derive(Copy, Clone, Default, Debug, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     pub struct MailboxProtocols: u8 {
         /// ADS over EtherCAT (routing and parallel services).
         const AOE = 0x01;
@@ -909,6 +910,25 @@ mod tests {
         assert_eq!(SiiGeneral::unpack_from_slice(&raw), Ok(expected))
     }
 
+    #[test]
+    fn mailbox_protocols() {
+        // CoE and FoE. The upper byte is unused.
+        assert_eq!(
+            MailboxProtocols::unpack_from_slice(&[0x0c, 0x00]),
+            Ok(MailboxProtocols::COE | MailboxProtocols::FOE)
+        );
+
+        assert_eq!(
+            MailboxProtocols::unpack_from_slice(&[0x3f, 0x00]),
+            Ok(MailboxProtocols::all())
+        );
+
+        assert_eq!(
+            MailboxProtocols::unpack_from_slice(&[0x00, 0x00]),
+            Ok(MailboxProtocols::empty())
+        );
+    }
+
     #[test]
     fn fmmu_ex() {
         let data = [0xaa, 0xbb, 0xcc];
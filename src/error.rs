@@ -51,12 +51,14 @@ pub enum Error {
     IntegerTypeConversion,
     /// The allotted storage for a group's PDI is too small for the calculated length read from all
     /// SubDevices in the group.
+    ///
+    /// Increase the `MAX_PDI` const parameter of the group to at least `required` to fix this.
     PdiTooLong {
-        /// Maximum PDI length.
-        max_length: usize,
+        /// The number of PDI bytes required by all SubDevices in the group.
+        required: usize,
 
-        /// Actual PDI length.
-        desired_length: usize,
+        /// The number of PDI bytes available, i.e. the group's `MAX_PDI`.
+        available: usize,
     },
     /// An item in a list could not be found.
     NotFound {
@@ -86,6 +88,22 @@ pub enum Error {
         /// SubDevice address.
         configured_address: u16,
     },
+    /// One or more SubDevices in a group are not in the state the group expects them to be in.
+    ///
+    /// Each of these SubDevices is also logged at `error` level.
+    StateMismatch {
+        /// The state the group expects.
+        expected: SubDeviceState,
+
+        /// The number of SubDevices not in the expected state.
+        count: u16,
+
+        /// Address of the first SubDevice not in the expected state.
+        configured_address: u16,
+
+        /// The state of the first SubDevice not in the expected state.
+        actual: SubDeviceState,
+    },
 
     /// An error occurred encoding or decoding an item.
     Wire(ethercrab_wire::WireError),
@@ -95,6 +113,30 @@ pub enum Error {
 
     /// A distributed clock error occurred.
     DistributedClock(DistributedClockError),
+
+    /// The network interface has no link, e.g. because no cable is plugged in.
+    LinkDown,
+
+    /// A [`Timeouts`](crate::Timeouts) configuration is invalid.
+    InvalidTimeouts(TimeoutsError),
+
+    /// A group's output offset, set with
+    /// [`SubDeviceGroup::set_output_offset`](crate::SubDeviceGroup::set_output_offset), would map
+    /// its outputs over its inputs.
+    OutputOffsetOverlap {
+        /// The requested output offset in bytes.
+        offset: u16,
+        /// The length of the group's inputs in bytes.
+        input_len: usize,
+    },
+}
+
+impl Error {
+    /// Whether this error may go away if the operation that caused it is retried, e.g. because a
+    /// frame was dropped or a SubDevice was not yet ready.
+    pub(crate) fn is_transient(&self) -> bool {
+        matches!(self, Error::Timeout | Error::WorkingCounter { .. })
+    }
 }
 
 #[cfg(feature = "std")]
@@ -126,12 +168,12 @@ impl core::fmt::Display for Error {
             }
             Error::IntegerTypeConversion => write!(f, "failed to convert between integer types"),
             Error::PdiTooLong {
-                max_length,
-                desired_length,
+                required,
+                available,
             } => write!(
                 f,
-                "Process Data Image is too long ({} bytes), max length is {}",
-                desired_length, max_length
+                "Process Data Image requires {} bytes but only {} are available",
+                required, available
             ),
             Error::NotFound { item, index } => {
                 write!(f, "item kind {:?} not found (index: {:?})", item, index)
@@ -151,9 +193,26 @@ impl core::fmt::Display for Error {
                 "SubDevice {:#06x} state is invalid: {}, expected {}",
                 configured_address, actual, expected
             ),
+            Error::StateMismatch {
+                expected,
+                count,
+                configured_address,
+                actual,
+            } => write!(
+                f,
+                "{} SubDevice(s) not in {}, first is {:#06x} in {}",
+                count, expected, configured_address, actual
+            ),
             Error::Wire(e) => write!(f, "wire encode/decode error: {}", e),
             Error::SubDevice(e) => write!(f, "subdevice error: {}", e),
             Error::DistributedClock(e) => write!(f, "distributed clock: {}", e),
+            Error::LinkDown => f.write_str("network interface has no link"),
+            Error::InvalidTimeouts(e) => write!(f, "invalid timeouts: {}", e),
+            Error::OutputOffsetOverlap { offset, input_len } => write!(
+                f,
+                "group output offset {} overlaps {} input bytes",
+                offset, input_len
+            ),
         }
     }
 }
@@ -179,6 +238,10 @@ pub enum Item {
     Group,
     /// A SDO sub-index.
     SdoSubIndex,
+    /// A network interface.
+    Interface,
+    /// A named signal in a `std::ProcessImageMap`.
+    Signal,
 }
 
 /// Low-level PDU (Process Data Unit) error.
@@ -208,6 +271,23 @@ pub enum PduError {
     /// This is an internal error and should not appear in user code. Please [open an
     /// issue](https://github.com/ethercrab-rs/ethercrab/issues/new) if this is encountered.
     SwapState,
+    /// A received frame's EtherCAT header declares a longer payload than was actually received.
+    ///
+    /// This is usually caused by a truncated frame.
+    PayloadLengthMismatch {
+        /// Payload length in bytes declared in the EtherCAT frame header.
+        declared: u16,
+        /// Number of payload bytes present in the received frame.
+        actual: u16,
+    },
+    /// A logical command (`LRD`, `LWR` or `LRW`) would access data past the end of the 32 bit
+    /// logical address space.
+    LogicalAddressOverflow {
+        /// Logical start address.
+        address: u32,
+        /// Length of the access in bytes.
+        len: u16,
+    },
 }
 
 impl core::fmt::Display for PduError {
@@ -221,6 +301,16 @@ impl core::fmt::Display for PduError {
             PduError::Validation(e) => write!(f, "received PDU validation failed: {}", e),
             PduError::InvalidFrameState => f.write_str("invalid PDU frame state"),
             PduError::SwapState => f.write_str("failed to swap frame state"),
+            PduError::PayloadLengthMismatch { declared, actual } => write!(
+                f,
+                "frame header declares {} payload bytes but only {} were received",
+                declared, actual
+            ),
+            PduError::LogicalAddressOverflow { address, len } => write!(
+                f,
+                "{} bytes at logical address {:#010x} overflow the logical address space",
+                len, address
+            ),
         }
     }
 }
@@ -232,12 +322,15 @@ impl core::fmt::Display for PduError {
 pub enum DistributedClockError {
     /// No DC System Time reference SubDevice was found.
     NoReference,
+    /// A cycle time of zero was given.
+    InvalidCycleTime,
 }
 
 impl core::fmt::Display for DistributedClockError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::NoReference => f.write_str("No DC reference SubDevice found"),
+            Self::InvalidCycleTime => f.write_str("Cycle time must be greater than zero"),
         }
     }
 }
@@ -276,6 +369,8 @@ pub enum MailboxError {
     ///
     /// Slowing down mailbox reads may help mitigate this error.
     InvalidCount,
+    /// A raw mailbox request is too short to hold a mailbox header.
+    RequestTooShort,
     /// SubDevice sent an emergency message.
     Emergency {
         /// Error code.
@@ -305,6 +400,9 @@ impl core::fmt::Display for MailboxError {
                 address, sub_index
             ),
             MailboxError::InvalidCount => f.write_str("incorrect mailbox count value"),
+            MailboxError::RequestTooShort => {
+                f.write_str("request is too short to hold a mailbox header")
+            }
             MailboxError::Emergency {
                 error_code,
                 error_register,
@@ -317,6 +415,58 @@ impl core::fmt::Display for MailboxError {
     }
 }
 
+/// A timeout in [`Timeouts`](crate::Timeouts).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TimeoutField {
+    /// [`Timeouts::state_transition`](crate::Timeouts::state_transition).
+    StateTransition,
+    /// [`Timeouts::pdu`](crate::Timeouts::pdu).
+    Pdu,
+    /// [`Timeouts::eeprom`](crate::Timeouts::eeprom).
+    Eeprom,
+    /// [`Timeouts::mailbox_echo`](crate::Timeouts::mailbox_echo).
+    MailboxEcho,
+    /// [`Timeouts::mailbox_response`](crate::Timeouts::mailbox_response).
+    MailboxResponse,
+}
+
+impl core::fmt::Display for TimeoutField {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            TimeoutField::StateTransition => "state_transition",
+            TimeoutField::Pdu => "pdu",
+            TimeoutField::Eeprom => "eeprom",
+            TimeoutField::MailboxEcho => "mailbox_echo",
+            TimeoutField::MailboxResponse => "mailbox_response",
+        })
+    }
+}
+
+/// A [`Timeouts`](crate::Timeouts) validation error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TimeoutsError {
+    /// The timeout is zero, so any operation using it would fail immediately.
+    Zero(TimeoutField),
+    /// The PDU timeout is longer than the given timeout, so the operation it covers would time out
+    /// before a single PDU could.
+    PduExceeds(TimeoutField),
+}
+
+impl core::fmt::Display for TimeoutsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TimeoutsError::Zero(field) => write!(f, "{} timeout is zero", field),
+            TimeoutsError::PduExceeds(field) => {
+                write!(f, "pdu timeout is longer than {} timeout", field)
+            }
+        }
+    }
+}
+
 /// EEPROM (SII) error.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -332,6 +482,10 @@ pub enum EepromError {
     SectionUnderrun,
     /// An attempt to clear errors on the device failed.
     ClearErrors,
+    /// The checksum stored in the EEPROM does not match its contents.
+    InvalidChecksum,
+    /// An access would read or write past the physical end of the EEPROM.
+    PastEnd,
 }
 
 impl core::fmt::Display for EepromError {
@@ -342,6 +496,8 @@ impl core::fmt::Display for EepromError {
             EepromError::NoCategory => f.write_str("category not found"),
             EepromError::SectionUnderrun => f.write_str("section too short to fill buffer"),
             EepromError::ClearErrors => f.write_str("clear device errors failed"),
+            EepromError::InvalidChecksum => f.write_str("invalid checksum"),
+            EepromError::PastEnd => f.write_str("access past the end of the EEPROM"),
         }
     }
 }
@@ -60,6 +60,43 @@ impl defmt::Format for EthernetAddress {
     }
 }
 
+/// The EtherType identifying an IEEE 802.1Q VLAN tag.
+pub(crate) const VLAN_ETHERTYPE: u16 = 0x8100;
+
+/// The number of bytes an 802.1Q VLAN tag adds to an Ethernet frame.
+pub(crate) const VLAN_TAG_LEN: usize = 4;
+
+/// An IEEE 802.1Q VLAN tag.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VlanTag {
+    /// Priority code point (PCP), from 0 to 7.
+    pub priority: u8,
+
+    /// Drop eligible indicator (DEI).
+    pub drop_eligible: bool,
+
+    /// VLAN identifier (VID), from 0 to 4095.
+    pub vlan_id: u16,
+}
+
+impl VlanTag {
+    /// Pack into the 16 bit Tag Control Information field. Out of range values are truncated.
+    pub(crate) const fn tci(&self) -> u16 {
+        ((self.priority as u16 & 0b111) << 13)
+            | ((self.drop_eligible as u16) << 12)
+            | (self.vlan_id & 0x0fff)
+    }
+
+    pub(crate) const fn from_tci(tci: u16) -> Self {
+        Self {
+            priority: (tci >> 13) as u8,
+            drop_eligible: tci & (1 << 12) != 0,
+            vlan_id: tci & 0x0fff,
+        }
+    }
+}
+
 /// A read/write wrapper around an Ethernet II frame buffer.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -74,6 +111,11 @@ mod field {
     pub const SOURCE: Range<usize> = 6..12;
     pub const ETHERTYPE: Range<usize> = 12..14;
     pub const PAYLOAD: RangeFrom<usize> = 14..;
+
+    // Fields following an 802.1Q tag, which takes the place of the EtherType.
+    pub const VLAN_TCI: Range<usize> = 14..16;
+    pub const VLAN_ETHERTYPE: Range<usize> = 16..18;
+    pub const VLAN_PAYLOAD: RangeFrom<usize> = 18..;
 }
 
 /// The Ethernet header length
@@ -151,11 +193,40 @@ impl<T: AsRef<[u8]>> EthernetFrame<T> {
 }
 
 impl<'a, T: AsRef<[u8]> + ?Sized> EthernetFrame<&'a T> {
-    /// Return a pointer to the payload, without checking for 802.1Q.
-    #[inline]
-    pub fn payload(&self) -> &'a [u8] {
+    /// Return the 802.1Q VLAN tag, if the frame has one.
+    pub fn vlan_tag(&self) -> Option<VlanTag> {
+        if self.ethertype() != VLAN_ETHERTYPE {
+            return None;
+        }
+
+        self.buffer
+            .as_ref()
+            .get(field::VLAN_TCI)
+            .map(|tci| VlanTag::from_tci(u16::from_be_bytes([tci[0], tci[1]])))
+    }
+
+    /// Return the EtherType of the payload, skipping over an 802.1Q VLAN tag if present.
+    pub fn inner_ethertype(&self) -> u16 {
+        if self.ethertype() != VLAN_ETHERTYPE {
+            return self.ethertype();
+        }
+
+        self.buffer
+            .as_ref()
+            .get(field::VLAN_ETHERTYPE)
+            .map(|res| u16::from_be_bytes([res[0], res[1]]))
+            .unwrap_or(0)
+    }
+
+    /// Return the payload, skipping over an 802.1Q VLAN tag if present.
+    pub fn inner_payload(&self) -> &'a [u8] {
         let data = self.buffer.as_ref();
-        &data[field::PAYLOAD]
+
+        if self.ethertype() != VLAN_ETHERTYPE {
+            return &data[field::PAYLOAD];
+        }
+
+        data.get(field::VLAN_PAYLOAD).unwrap_or(&[])
     }
 }
 
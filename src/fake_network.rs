@@ -0,0 +1,511 @@
+//! A fake network to answer frames sent by a [`MainDevice`](crate::MainDevice) in tests, without
+//! a real network interface.
+//!
+//! Tests poll the future under test with a no-op waker, then use [`FakeNetwork`] to take each
+//! frame it queues, fill in responses PDU by PDU, and feed the frame back into the PDU loop.
+//! [`FakeEsc`] models the registers and EEPROM of a SubDevice to answer those PDUs with.
+
+use crate::{
+    LEN_MASK, PduRx, PduTx, ReceiveAction,
+    command::{APRD, APWR, BRD, BWR, FPRD, FPWR},
+    ethernet::{EthernetAddress, EthernetFrame},
+    register::RegisterAddress,
+};
+use core::{
+    future::Future,
+    pin::{Pin, pin},
+    task::{Context, Poll, Waker},
+};
+
+/// Source MAC address used for responses. Anything other than the MainDevice's own address will
+/// do, as a frame with the MainDevice's address is filtered out as one of our own sent frames.
+const RESPONSE_MAC: EthernetAddress = EthernetAddress([0x12, 0x10, 0x10, 0x10, 0x10, 0x10]);
+
+/// Length of the Ethernet and EtherCAT frame headers before the first PDU.
+const FIRST_PDU: usize = 16;
+
+/// PDU header length.
+const PDU_HEADER: usize = 10;
+
+/// More follows flag in the PDU header flags field.
+const MORE_FOLLOWS: u16 = 0x8000;
+
+/// A single PDU in a frame sent by the MainDevice.
+#[derive(Debug)]
+pub(crate) struct FakePdu<'a> {
+    /// Command code, e.g. [`FPRD`](crate::command::FPRD).
+    pub command: u8,
+    /// Position or configured station address, or the low half of a logical address.
+    pub adp: u16,
+    /// Register, or the high half of a logical address.
+    pub ado: u16,
+    /// PDU payload, to be overwritten with the response data.
+    pub data: &'a mut [u8],
+    wkc: &'a mut [u8],
+}
+
+impl<'a> FakePdu<'a> {
+    /// Split the PDU at the start of `buf`, returning it and whatever follows it in the frame.
+    fn split(buf: &'a mut [u8]) -> (Self, bool, &'a mut [u8]) {
+        let (header, rest) = buf.split_at_mut(PDU_HEADER);
+
+        let flags = u16::from_le_bytes([header[6], header[7]]);
+        let len = usize::from(flags & LEN_MASK);
+
+        let (data, rest) = rest.split_at_mut(len);
+        let (wkc, rest) = rest.split_at_mut(2);
+
+        let pdu = Self {
+            command: header[0],
+            adp: u16::from_le_bytes([header[2], header[3]]),
+            ado: u16::from_le_bytes([header[4], header[5]]),
+            data,
+            wkc,
+        };
+
+        (pdu, flags & MORE_FOLLOWS > 0, rest)
+    }
+
+    /// Register address as an index into a register image.
+    pub fn register(&self) -> usize {
+        usize::from(self.ado)
+    }
+
+    /// Logical address for `LRD`, `LWR` and `LRW` PDUs.
+    pub fn logical_address(&self) -> u32 {
+        u32::from(self.adp) | (u32::from(self.ado) << 16)
+    }
+
+    /// Answer a read by copying the addressed registers into the payload.
+    pub fn read_from(&mut self, registers: &[u8]) {
+        let start = self.register();
+
+        self.data
+            .copy_from_slice(&registers[start..start + self.data.len()]);
+    }
+
+    /// Apply a write by copying the payload into the addressed registers.
+    pub fn write_to(&self, registers: &mut [u8]) {
+        let start = self.register();
+
+        registers[start..start + self.data.len()].copy_from_slice(self.data);
+    }
+
+    /// Set the working counter returned to the MainDevice.
+    pub fn set_wkc(&mut self, wkc: u16) {
+        self.wkc.copy_from_slice(&wkc.to_le_bytes());
+    }
+}
+
+/// The length of a frame as sent by the MainDevice, given its length up to the end of the last
+/// PDU.
+///
+/// If the `software-fcs` feature is enabled, this includes padding to the minimum Ethernet frame
+/// length and the FCS.
+pub(crate) fn wire_len(frame_len: usize) -> usize {
+    if cfg!(feature = "software-fcs") {
+        frame_len.max(crate::pdu_loop::MIN_ETHERNET_FRAME_LEN) + crate::pdu_loop::FCS_RESERVED_LEN
+    } else {
+        frame_len
+    }
+}
+
+/// Append an FCS to a hand written received frame if the `software-fcs` feature is enabled, so
+/// it's accepted by [`PduRx::receive_frame`].
+pub(crate) fn with_fcs(frame: &[u8]) -> Vec<u8> {
+    #[allow(unused_mut)]
+    let mut frame = frame.to_vec();
+
+    #[cfg(feature = "software-fcs")]
+    {
+        let fcs = crate::pdu_loop::fcs::compute(&frame);
+
+        frame.extend_from_slice(&fcs);
+    }
+
+    frame
+}
+
+/// An Ethernet frame sent by the MainDevice, modified in place into a response.
+#[derive(Debug, Clone)]
+pub(crate) struct SentFrame(Vec<u8>);
+
+impl SentFrame {
+    /// Wrap a frame sent through something other than [`FakeNetwork`], e.g. a mock socket.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Turn the frame into a response, with its source MAC changed as it would be by a
+    /// SubDevice.
+    pub fn into_reply(self) -> Vec<u8> {
+        let mut frame = EthernetFrame::new_checked(self.0).expect("sent frame");
+        frame.set_src_addr(RESPONSE_MAC);
+
+        #[allow(unused_mut)]
+        let mut frame = frame.into_inner();
+
+        // The response differs from what was sent, so its FCS must be computed again
+        #[cfg(feature = "software-fcs")]
+        {
+            let len = frame.len() - crate::pdu_loop::FCS_RESERVED_LEN;
+            let fcs = crate::pdu_loop::fcs::compute(&frame[..len]);
+
+            frame[len..].copy_from_slice(&fcs);
+        }
+
+        frame
+    }
+
+    /// Call `f` with each PDU in the frame in turn.
+    pub fn for_each_pdu(&mut self, mut f: impl FnMut(FakePdu<'_>)) {
+        let mut rest = &mut self.0[FIRST_PDU..];
+
+        loop {
+            let (pdu, more_follows, after) = FakePdu::split(rest);
+
+            f(pdu);
+
+            if !more_follows {
+                break;
+            }
+
+            rest = after;
+        }
+    }
+
+    /// The number of PDUs in the frame.
+    pub fn pdu_count(&mut self) -> usize {
+        let mut count = 0;
+
+        self.for_each_pdu(|_| count += 1);
+
+        count
+    }
+
+    /// Answer every PDU in the frame with `respond`, which returns that PDU's working counter.
+    pub fn respond(&mut self, mut respond: impl FnMut(&mut FakePdu<'_>) -> u16) {
+        self.for_each_pdu(|mut pdu| {
+            let wkc = respond(&mut pdu);
+
+            pdu.set_wkc(wkc);
+        });
+    }
+
+    /// Set the working counter of each PDU in turn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frame doesn't contain exactly one PDU per working counter.
+    pub fn set_wkcs(&mut self, wkcs: &[u16]) {
+        let mut wkcs = wkcs.iter();
+
+        self.for_each_pdu(|mut pdu| pdu.set_wkc(*wkcs.next().expect("more PDUs than WKCs")));
+
+        assert_eq!(wkcs.len(), 0, "fewer PDUs than WKCs");
+    }
+}
+
+/// A minimal ESC: register memory and an SII EEPROM image.
+///
+/// Requested AL states are reached immediately, and SII read requests are served from the EEPROM
+/// image straight away.
+pub(crate) struct FakeEsc {
+    pub registers: Vec<u8>,
+    pub eeprom: &'static [u8],
+    /// Every state requested through the AL control register, in order.
+    pub requested_states: Vec<u8>,
+    /// Whether the ESC latches DC receive times and responds to reads of them.
+    pub dc_latch: bool,
+    /// If set, an SII read request never completes and the EEPROM stays busy.
+    pub eeprom_busy: bool,
+    /// Number of bytes returned by each SII read, either 4 or 8.
+    eeprom_read_len: usize,
+}
+
+impl FakeEsc {
+    /// An ESC in INIT with 8 byte EEPROM reads.
+    pub fn new(eeprom: &'static [u8]) -> Self {
+        let mut registers = vec![0u8; 0x1000];
+
+        // INIT
+        registers[0x0130] = 0x01;
+        // 8 byte EEPROM reads
+        registers[0x0502] = 0x40;
+
+        Self {
+            registers,
+            eeprom,
+            requested_states: Vec::new(),
+            dc_latch: true,
+            eeprom_busy: false,
+            eeprom_read_len: 8,
+        }
+    }
+
+    pub fn with_register(mut self, register: RegisterAddress, value: &[u8]) -> Self {
+        let start = usize::from(u16::from(register));
+
+        self.registers[start..start + value.len()].copy_from_slice(value);
+
+        self
+    }
+
+    pub fn with_configured_address(self, configured_address: u16) -> Self {
+        self.with_register(
+            RegisterAddress::ConfiguredStationAddress,
+            &configured_address.to_le_bytes(),
+        )
+    }
+
+    /// Only support 4 byte EEPROM reads.
+    pub fn with_short_eeprom_reads(mut self) -> Self {
+        self.eeprom_read_len = 4;
+        self.registers[0x0502] &= !0x40;
+
+        self
+    }
+
+    pub fn configured_address(&self) -> u16 {
+        u16::from_le_bytes([self.registers[0x0010], self.registers[0x0011]])
+    }
+
+    pub fn write(&mut self, register: u16, data: &[u8]) {
+        let start = usize::from(register);
+
+        self.registers[start..start + data.len()].copy_from_slice(data);
+
+        // Keep read only `read_size` bit as supported by the ESC
+        if self.eeprom_read_len == 8 {
+            self.registers[0x0502] |= 0x40;
+        } else {
+            self.registers[0x0502] &= !0x40;
+        }
+
+        // AL control: go straight to the requested state
+        if register == u16::from(RegisterAddress::AlControl) {
+            let state = data[0] & 0x0f;
+
+            self.requested_states.push(state);
+            self.registers[0x0130] = state;
+        }
+
+        // SII read request
+        if register == u16::from(RegisterAddress::SiiControl) && self.registers[0x0503] & 0x01 != 0
+        {
+            if self.eeprom_busy {
+                self.registers[0x0503] |= 0x80;
+
+                return;
+            }
+
+            let word = u16::from_le_bytes([self.registers[0x0504], self.registers[0x0505]]);
+            let start = usize::from(word) * 2;
+
+            for (i, byte) in self.registers[0x0508..0x0508 + self.eeprom_read_len]
+                .iter_mut()
+                .enumerate()
+            {
+                *byte = self.eeprom.get(start + i).copied().unwrap_or(0xff);
+            }
+
+            // Read complete
+            self.registers[0x0503] &= !0x01;
+        }
+    }
+
+    /// Pass a PDU through each ESC in network order, returning the working counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics on any command other than `APRD`, `APWR`, `FPRD`, `FPWR`, `BRD` or `BWR`.
+    pub fn process(escs: &mut [FakeEsc], pdu: &mut FakePdu<'_>) -> u16 {
+        let mut wkc = 0;
+
+        for (position, esc) in escs.iter_mut().enumerate() {
+            let start = pdu.register();
+            let positional = pdu.adp.wrapping_add(position as u16) == 0;
+            let configured = pdu.adp == esc.configured_address();
+
+            match pdu.command {
+                BWR | FPRD
+                    if pdu.ado == u16::from(RegisterAddress::DcTimePort0) && !esc.dc_latch =>
+                {
+                    continue;
+                }
+                APRD if positional => pdu.read_from(&esc.registers),
+                FPRD if configured => pdu.read_from(&esc.registers),
+                APWR if positional => esc.write(pdu.ado, pdu.data),
+                FPWR if configured => esc.write(pdu.ado, pdu.data),
+                BRD => {
+                    for (out, reg) in pdu.data.iter_mut().zip(&esc.registers[start..]) {
+                        *out |= *reg;
+                    }
+                }
+                BWR => esc.write(pdu.ado, pdu.data),
+                APRD | APWR | FPRD | FPWR => continue,
+                other => panic!("unexpected command {:#04x}", other),
+            }
+
+            wkc += 1;
+        }
+
+        wkc
+    }
+}
+
+/// Answers frames sent through a split [`PduStorage`](crate::PduStorage).
+pub(crate) struct FakeNetwork<'a, 'sto> {
+    tx: &'a mut PduTx<'sto>,
+    rx: &'a mut PduRx<'sto>,
+}
+
+impl<'a, 'sto> FakeNetwork<'a, 'sto> {
+    pub fn new(tx: &'a mut PduTx<'sto>, rx: &'a mut PduRx<'sto>) -> Self {
+        Self { tx, rx }
+    }
+
+    /// Send the next queued frame, if there is one.
+    ///
+    /// Dropping the returned frame loses it on the wire.
+    pub fn next_frame(&mut self) -> Option<SentFrame> {
+        let mut sent = Vec::new();
+
+        self.tx
+            .next_sendable_frame()?
+            .send_blocking(|bytes| {
+                sent.extend_from_slice(bytes);
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        Some(SentFrame(sent))
+    }
+
+    /// Pass a response back to the MainDevice, with its source MAC changed as it would be by a
+    /// SubDevice.
+    pub fn reply(&mut self, frame: SentFrame) -> ReceiveAction {
+        self.rx.receive_frame(&frame.into_reply()).expect("receive")
+    }
+
+    /// Send the next queued frame and answer it with `respond`, which is called for every PDU and
+    /// returns that PDU's working counter.
+    pub fn exchange(&mut self, respond: impl FnMut(&mut FakePdu<'_>) -> u16) -> ReceiveAction {
+        let mut frame = self.next_frame().expect("need a frame");
+
+        frame.respond(respond);
+
+        self.reply(frame)
+    }
+
+    /// Poll `fut` once, then if it didn't complete, answer the frame it sent with
+    /// [`exchange`](FakeNetwork::exchange).
+    pub fn step<F: Future + ?Sized>(
+        &mut self,
+        fut: Pin<&mut F>,
+        respond: impl FnMut(&mut FakePdu<'_>) -> u16,
+    ) -> Option<F::Output> {
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        if let Poll::Ready(result) = fut.poll(&mut ctx) {
+            return Some(result);
+        }
+
+        self.exchange(respond);
+
+        None
+    }
+
+    /// Poll `fut` to completion, answering every frame it sends with
+    /// [`exchange`](FakeNetwork::exchange).
+    pub fn run<F: Future>(
+        &mut self,
+        fut: F,
+        mut respond: impl FnMut(&mut FakePdu<'_>) -> u16,
+    ) -> F::Output {
+        let mut fut = pin!(fut);
+
+        loop {
+            if let Some(result) = self.step(fut.as_mut(), &mut respond) {
+                break result;
+            }
+        }
+    }
+
+    /// Poll `fut` to completion, answering each whole frame it sends with `respond`.
+    ///
+    /// `respond` returns whether to reply to the frame, or drop it as if it was lost.
+    pub fn run_frames<F: Future>(
+        &mut self,
+        fut: F,
+        mut respond: impl FnMut(&mut SentFrame) -> bool,
+    ) -> F::Output {
+        let mut fut = pin!(fut);
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        loop {
+            if let Poll::Ready(result) = fut.as_mut().poll(&mut ctx) {
+                break result;
+            }
+
+            let mut frame = self.next_frame().expect("need a frame");
+
+            if respond(&mut frame) {
+                self.reply(frame);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Command, MainDevice, MainDeviceConfig, PduStorage, Timeouts,
+        command::{FPRD, FPWR},
+    };
+
+    #[test]
+    fn run_answers_reads_and_writes() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut registers = vec![0u8; 0x1000];
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let respond = |pdu: &mut FakePdu<'_>, registers: &mut Vec<u8>| {
+            assert_eq!(pdu.adp, 0x1000);
+
+            match pdu.command {
+                FPRD => pdu.read_from(registers),
+                FPWR => pdu.write_to(registers),
+                other => panic!("unexpected command {:#04x}", other),
+            }
+
+            1
+        };
+
+        network
+            .run(
+                Command::fpwr(0x1000, 0x0120).send(&maindevice, 0xabcdu16),
+                |pdu| respond(pdu, &mut registers),
+            )
+            .expect("write");
+
+        assert_eq!(registers[0x0120..0x0122], [0xcd, 0xab]);
+
+        let value = network
+            .run(
+                Command::fprd(0x1000, 0x0120).receive::<u16>(&maindevice),
+                |pdu| respond(pdu, &mut registers),
+            )
+            .expect("read");
+
+        assert_eq!(value, 0xabcd);
+    }
+}
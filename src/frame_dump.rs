@@ -0,0 +1,85 @@
+//! Hex dumps of EtherCAT frame payloads in `Debug` output.
+//!
+//! This module is only available with the `frame-dumps` feature. Without it, the `Debug` output of
+//! frames only contains metadata like frame state, storage slot, first PDU index and lengths, and
+//! never any payload bytes.
+//!
+//! With the feature enabled, each dump is truncated to [`DEFAULT_MAX_BYTES`] by default. This can
+//! be changed with [`set_max_bytes`]. A [`RedactionHook`] can be installed with
+//! [`set_redaction_hook`] to mask sensitive data, e.g. process data values, before it is written to
+//! logs.
+//!
+//! # Examples
+//!
+//! Mask everything except PDU headers in frames containing a single PDU:
+//!
+//! ```rust
+//! // PDU header is 10 bytes long
+//! fn headers_only(offset: usize, byte: u8) -> Option<u8> {
+//!     (offset < 10).then_some(byte)
+//! }
+//!
+//! ethercrab::frame_dump::set_redaction_hook(Some(headers_only));
+//! ethercrab::frame_dump::set_max_bytes(32);
+//! ```
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The default maximum number of bytes printed per frame.
+pub const DEFAULT_MAX_BYTES: usize = 64;
+
+/// A function called for every byte in a frame dump.
+///
+/// It is passed the byte's offset from the start of the first PDU header in the frame, and the
+/// byte's value. Return `Some` with the value to print, or `None` to print `**` instead.
+pub type RedactionHook = fn(offset: usize, byte: u8) -> Option<u8>;
+
+static MAX_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_BYTES);
+
+static REDACTION_HOOK: spin::RwLock<Option<RedactionHook>> = spin::RwLock::new(None);
+
+/// Set the maximum number of payload bytes printed per frame.
+///
+/// Any remaining bytes are summarised as a count.
+pub fn set_max_bytes(max: usize) {
+    MAX_BYTES.store(max, Ordering::Relaxed);
+}
+
+/// Set or clear the hook used to redact bytes in frame dumps.
+pub fn set_redaction_hook(hook: Option<RedactionHook>) {
+    *REDACTION_HOOK.write() = hook;
+}
+
+/// Formats a frame payload as truncated, redacted hex.
+pub(crate) struct HexDump<'a>(pub &'a [u8]);
+
+impl fmt::Debug for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max = MAX_BYTES.load(Ordering::Relaxed);
+        let hook = *REDACTION_HOOK.read();
+
+        f.write_str("[")?;
+
+        for (offset, byte) in self.0.iter().take(max).enumerate() {
+            if offset > 0 {
+                f.write_str(", ")?;
+            }
+
+            match hook.map_or(Some(*byte), |hook| hook(offset, *byte)) {
+                Some(byte) => write!(f, "{:02x}", byte)?,
+                None => f.write_str("**")?,
+            }
+        }
+
+        f.write_str("]")?;
+
+        if self.0.len() > max {
+            write!(f, " (+{} bytes)", self.0.len() - max)?;
+        }
+
+        Ok(())
+    }
+}
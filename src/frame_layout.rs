@@ -0,0 +1,87 @@
+//! Sizes of the headers that make up an EtherCAT frame.
+//!
+//! An EtherCAT frame as sent by EtherCrab is laid out as follows:
+//!
+//! ```text
+//! | Ethernet header | EtherCAT header | PDU header | PDU data | WKC | ... more PDUs ... | (FCS) |
+//! ```
+//!
+//! Every PDU in a frame adds [`PDU_OVERHEAD`] bytes on top of its data. Every frame adds
+//! [`FRAME_OVERHEAD`] bytes, regardless of how many PDUs it holds.
+//!
+//! These values can be used to size [`PduStorage`](crate::PduStorage) or to work out how many PDUs
+//! fit in a single frame.
+//!
+//! # Examples
+//!
+//! Compute how many 2 byte status reads, e.g. of the AL status register, fit into one frame of a
+//! `PduStorage` with 128 byte elements:
+//!
+//! ```rust
+//! use ethercrab::{PduStorage, frame_layout};
+//!
+//! const ELEMENT_SIZE: usize = 128;
+//! const PAYLOAD: usize = 2;
+//!
+//! let available = ELEMENT_SIZE - frame_layout::FRAME_OVERHEAD;
+//! let max_pdus = available / (PAYLOAD + frame_layout::PDU_OVERHEAD);
+//!
+//! // 8, or 7 if the `software-fcs` feature is enabled
+//! assert!(max_pdus >= 7);
+//!
+//! // A frame holding a single PDU of `n` bytes needs `element_size(n)` bytes.
+//! assert_eq!(
+//!     PduStorage::element_size(100),
+//!     frame_layout::FRAME_OVERHEAD + frame_layout::PDU_OVERHEAD + 100
+//! );
+//! ```
+
+use crate::{
+    ethernet::EthernetFrame,
+    pdu_loop::{EthercatFrameHeader, FCS_RESERVED_LEN, PduHeader},
+};
+use ethercrab_wire::EtherCrabWireSized;
+
+/// Length of the Ethernet II header: destination and source MAC addresses and EtherType.
+pub const ETHERNET_HEADER_LEN: usize = EthernetFrame::<&[u8]>::header_len();
+
+/// Length of the EtherCAT frame header holding the payload length and protocol type.
+pub const ETHERCAT_HEADER_LEN: usize = EthercatFrameHeader::header_len();
+
+/// Length of a single PDU header: command, index, address, flags and IRQ.
+pub const PDU_HEADER_LEN: usize = PduHeader::PACKED_LEN;
+
+/// Length of the working counter that follows every PDU's data.
+pub const WKC_LEN: usize = u16::PACKED_LEN;
+
+/// Bytes added to a PDU's data length when it is placed in a frame.
+pub const PDU_OVERHEAD: usize = PDU_HEADER_LEN + WKC_LEN;
+
+/// Bytes reserved at the end of every frame for a software computed Ethernet FCS.
+///
+/// This is zero unless the `software-fcs` feature is enabled.
+pub const FCS_LEN: usize = FCS_RESERVED_LEN;
+
+/// Bytes used by every frame on top of the PDUs it holds.
+pub const FRAME_OVERHEAD: usize = ETHERNET_HEADER_LEN + ETHERCAT_HEADER_LEN + FCS_LEN;
+
+/// The smallest [`PduStorage`](crate::PduStorage) element size, able to hold a single PDU with no
+/// data.
+pub const MIN_ELEMENT_SIZE: usize = FRAME_OVERHEAD + PDU_OVERHEAD;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overhead_sums() {
+        assert_eq!(ETHERNET_HEADER_LEN, 14);
+        assert_eq!(ETHERCAT_HEADER_LEN, 2);
+        assert_eq!(PDU_HEADER_LEN, 10);
+        assert_eq!(WKC_LEN, 2);
+        assert_eq!(PDU_OVERHEAD, 12);
+
+        assert_eq!(MIN_ELEMENT_SIZE - FCS_LEN, 28);
+        assert_eq!(crate::PduStorage::element_size(128) - FCS_LEN, 156);
+    }
+}
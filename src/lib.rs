@@ -9,6 +9,17 @@
 //!   when the `std` feature is enabled.
 //! - `serde` - enable `serde` impls for some public items.
 //! - `xdp` - enable support for XDP on some (currently only Linux) systems.
+//! - `manual-pdu-index` - expose `PduLoop::alloc_frame`, `CreatedFrame::push_pdu_with_index` and
+//!   `CreatedFrame::push_pdu_terminal` to build frames by hand with explicitly chosen PDU indices or
+//!   PDU chains that end early. Intended for testing only.
+//! - `frame-dumps` - include (truncated, optionally redacted) frame payloads in the `Debug` output
+//!   of frames. See the `frame_dump` module for configuration.
+//! - `software-fcs` - append a software computed Ethernet FCS to sent frames, and verify and strip
+//!   it from received frames. Short frames are padded to the 60 byte Ethernet minimum first, so
+//!   `PduStorage` elements must be at least 64 bytes long. Intended for testing over virtual or
+//!   loopback interfaces without hardware FCS offload.
+//! - `debug-introspection` - expose `PduLoop::frame_states` and `FrameState` to inspect the state
+//!   of every frame slot, e.g. to find frames that never complete.
 //!
 //! For `no_std` targets, it is recommended to add this crate with
 //!
@@ -163,11 +174,18 @@ mod base_data_types;
 mod coe;
 mod command;
 mod dc;
+mod discovery;
+mod dl_control;
 mod dl_status;
-mod eeprom;
+pub mod eeprom;
 pub mod error;
 mod ethernet;
+#[cfg(test)]
+mod fake_network;
 mod fmmu;
+#[cfg(feature = "frame-dumps")]
+pub mod frame_dump;
+pub mod frame_layout;
 mod generate;
 mod mailbox;
 mod maindevice;
@@ -188,19 +206,36 @@ pub mod std;
 pub use al_status_code::AlStatusCode;
 pub use coe::SubIndex;
 pub use command::{Command, Reads, WrappedRead, WrappedWrite, Writes};
+pub use discovery::{DiscoveredSubDevice, NetworkReport, SubDeviceDescriptor};
+pub use dl_control::{DlControl, LoopControl, PortLoopState};
+pub use eeprom::types::MailboxProtocols;
 pub use ethercrab_wire::{
     EtherCrabWireRead, EtherCrabWireReadSized, EtherCrabWireReadWrite, EtherCrabWireSized,
     EtherCrabWireWrite, EtherCrabWireWriteSized,
 };
 use ethernet::EthernetAddress;
+pub use ethernet::VlanTag;
 pub use maindevice::MainDevice;
-pub use maindevice_config::{MainDeviceConfig, RetryBehaviour};
-pub use pdu_loop::{PduLoop, PduRx, PduStorage, PduTx, ReceiveAction, SendableFrame};
-pub use register::{DcSupport, RegisterAddress};
-pub use subdevice::{DcSync, SubDevice, SubDeviceIdentity, SubDevicePdi, SubDeviceRef};
+pub use maindevice_config::{
+    BroadcastResetBehaviour, DcErrorBehaviour, EepromErrorBehaviour, MainDeviceConfig,
+    RetryBehaviour,
+};
+#[cfg(feature = "debug-introspection")]
+pub use pdu_loop::FrameState;
+#[cfg(feature = "manual-pdu-index")]
+pub use pdu_loop::{CreatedFrame, PduResponseHandle};
+pub use pdu_loop::{
+    EthercatFrameHeader, FrameAllocation, PduLoop, PduRx, PduStats, PduStorage, PduTx,
+    ProtocolType, ReceiveAction, SendableFrame,
+};
+pub use register::{DcSupport, DpramLayout, EscKind, EscType, RegisterAddress};
+pub use subdevice::{
+    DcControlLoopParams, DcSync, DeviceConfigSnapshot, FmmuConfig, PdiView, SubDevice,
+    SubDeviceIdentity, SubDevicePdi, SubDeviceRef, SyncCapabilities, SyncManagerConfig, SyncModes,
+};
 pub use subdevice_group::{GroupId, SubDeviceGroup, SubDeviceGroupHandle, TxRxResponse};
 pub use subdevice_state::SubDeviceState;
-pub use timer_factory::Timeouts;
+pub use timer_factory::{Timeouts, TimeoutsBuilder};
 
 const LEN_MASK: u16 = 0b0000_0111_1111_1111;
 const ETHERCAT_ETHERTYPE: u16 = 0x88a4;
@@ -1,16 +1,18 @@
 use crate::{
-    BASE_SUBDEVICE_ADDRESS, MainDeviceConfig, SubDeviceGroup, Timeouts,
+    BASE_SUBDEVICE_ADDRESS, BroadcastResetBehaviour, DcControlLoopParams, DcErrorBehaviour,
+    MainDeviceConfig, SubDeviceGroup, Timeouts,
     al_control::AlControl,
     al_status_code::AlStatusCode,
     command::Command,
     dc,
+    discovery::{self, DiscoveredSubDevice, NetworkReport, SubDeviceDescriptor},
     eeprom::types::SyncManager,
-    error::{Error, Item},
+    error::{DistributedClockError, Error, Item},
     fmmu::Fmmu,
     fmt,
     pdi::PdiOffset,
-    pdu_loop::{PduLoop, ReceivedPdu},
-    register::RegisterAddress,
+    pdu_loop::{HeldPdu, PduLoop, PduStats},
+    register::{DcSupport, RegisterAddress},
     subdevice::SubDevice,
     subdevice_group::{self, SubDeviceGroupHandle},
     subdevice_state::SubDeviceState,
@@ -21,9 +23,12 @@ use core::{
     mem::size_of,
     sync::atomic::{AtomicU16, Ordering},
 };
-use ethercrab_wire::{EtherCrabWireSized, EtherCrabWireWrite};
+use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireSized, EtherCrabWireWrite};
 use heapless::FnvIndexMap;
 
+/// DL status event bit in the ECAT event request register.
+const ECAT_EVENT_DL_STATUS: u16 = 1 << 2;
+
 /// The main EtherCAT controller.
 ///
 /// The `MainDevice` is passed by reference to [`SubDeviceGroup`]s to drive their TX/RX methods. It
@@ -51,10 +56,12 @@ unsafe impl Sync for MainDevice<'_> {}
 impl<'sto> MainDevice<'sto> {
     /// Create a new EtherCrab MainDevice.
     pub const fn new(
-        pdu_loop: PduLoop<'sto>,
+        mut pdu_loop: PduLoop<'sto>,
         timeouts: Timeouts,
         config: MainDeviceConfig,
     ) -> Self {
+        pdu_loop.ignore_self_filter = config.ignore_self_filter;
+
         Self {
             pdu_loop,
             num_subdevices: AtomicU16::new(0),
@@ -80,15 +87,81 @@ impl<'sto> MainDevice<'sto> {
 
     // FIXME: When adding a powered on SubDevice to the network, something breaks. Maybe need to reset
     // the configured address? But this broke other stuff so idk...
-    async fn reset_subdevices(&self) -> Result<(), Error> {
+    async fn reset_subdevices(&self, num_subdevices: u16) -> Result<(), Error> {
         fmt::debug!("Beginning reset");
 
         // Reset SubDevices to init
-        Command::bwr(RegisterAddress::AlControl.into())
+        let wkc = Command::bwr(RegisterAddress::AlControl.into())
+            .ignore_wkc()
+            .send_receive_slice(self, AlControl::reset())
+            .await?
+            .working_counter();
+
+        if wkc != num_subdevices {
+            match self.config.broadcast_reset {
+                BroadcastResetBehaviour::Fail => {
+                    fmt::error!(
+                        "Only {} of {} SubDevices acknowledged broadcast reset",
+                        wkc,
+                        num_subdevices
+                    );
+
+                    return Err(Error::WorkingCounter {
+                        expected: num_subdevices,
+                        received: wkc,
+                    });
+                }
+                BroadcastResetBehaviour::Warn => {
+                    fmt::warn!(
+                        "Only {} of {} SubDevices acknowledged broadcast reset",
+                        wkc,
+                        num_subdevices
+                    );
+                }
+                BroadcastResetBehaviour::PerDevice => {
+                    fmt::warn!(
+                        "Only {} of {} SubDevices acknowledged broadcast reset, resetting individually",
+                        wkc,
+                        num_subdevices
+                    );
+
+                    self.reset_subdevices_individually(num_subdevices).await?;
+                }
+            }
+        }
+
+        if self.config.skip_reset_blanking {
+            fmt::debug!("--> Skipping FMMU, SM and DC register blanking");
+        } else {
+            self.blank_registers().await?;
+        }
+
+        // ETG1020 Section 22.2.4 defines these initial parameters. The data types are defined in
+        // ETG1000.4 Table 60 – Distributed clock local time parameter, helpfully named "Control
+        // Loop Parameter 1" to 3.
+        //
+        // According to ETG1020, we'll use the mode where the DC reference clock is adjusted to the
+        // master clock.
+        let dc_params = DcControlLoopParams::default();
+
+        Command::bwr(RegisterAddress::DcControlLoopParam3.into())
             .ignore_wkc()
-            .send(self, AlControl::reset())
+            .send(self, dc_params.param3())
             .await?;
+        // Must be after param 3 so DC control unit is reset
+        Command::bwr(RegisterAddress::DcControlLoopParam1.into())
+            .ignore_wkc()
+            .send(self, dc_params.param1())
+            .await?;
+
+        fmt::debug!("--> Reset complete");
+
+        Ok(())
+    }
 
+    /// Zero the FMMU, SM and DC registers of every SubDevice so no configuration from a previous
+    /// run is left behind.
+    async fn blank_registers(&self) -> Result<(), Error> {
         // Clear FMMUs - see ETG1000.4 Table 57
         // Some devices aren't able to blank the entire region so we loop through all offsets.
         for fmmu_idx in 0..16 {
@@ -123,23 +196,31 @@ impl<'sto> MainDevice<'sto> {
         self.blank_memory::<{ size_of::<u32>() }>(RegisterAddress::DcSync1CycleTime)
             .await?;
 
-        // ETG1020 Section 22.2.4 defines these initial parameters. The data types are defined in
-        // ETG1000.4 Table 60 – Distributed clock local time parameter, helpfully named "Control
-        // Loop Parameter 1" to 3.
-        //
-        // According to ETG1020, we'll use the mode where the DC reference clock is adjusted to the
-        // master clock.
-        Command::bwr(RegisterAddress::DcControlLoopParam3.into())
-            .ignore_wkc()
-            .send(self, 0x0c00u16)
-            .await?;
-        // Must be after param 3 so DC control unit is reset
-        Command::bwr(RegisterAddress::DcControlLoopParam1.into())
-            .ignore_wkc()
-            .send(self, 0x1000u16)
-            .await?;
+        Ok(())
+    }
 
-        fmt::debug!("--> Reset complete");
+    /// Reset any SubDevice that is not in INIT or has its error flag set, addressing each one by
+    /// position as configured addresses are not yet assigned.
+    async fn reset_subdevices_individually(&self, num_subdevices: u16) -> Result<(), Error> {
+        for position in 0..num_subdevices {
+            let status = Command::aprd(position, RegisterAddress::AlStatus.into())
+                .receive::<AlControl>(self)
+                .await?;
+
+            if status.state == SubDeviceState::Init && !status.error {
+                continue;
+            }
+
+            fmt::debug!(
+                "--> Resetting SubDevice at position {} (status {:?})",
+                position,
+                status
+            );
+
+            Command::apwr(position, RegisterAddress::AlControl.into())
+                .send_receive_slice(self, AlControl::reset())
+                .await?;
+        }
 
         Ok(())
     }
@@ -236,7 +317,7 @@ impl<'sto> MainDevice<'sto> {
             return Ok(groups);
         }
 
-        self.reset_subdevices().await?;
+        self.reset_subdevices(num_subdevices).await?;
 
         // This is the only place we store the number of SubDevices, so the ordering can be
         // pretty much anything.
@@ -266,14 +347,47 @@ impl<'sto> MainDevice<'sto> {
 
         // Configure distributed clock offsets/propagation delays, perform static drift
         // compensation. We need the SubDevices in a single list so we can read the topology.
-        let dc_master = dc::configure_dc(self, subdevices.as_mut_slices().0, now).await?;
+        let dc_result = async {
+            let dc_master = dc::configure_dc(self, subdevices.as_mut_slices().0, now).await?;
+
+            // If there are SubDevices that support distributed clocks, run static drift
+            // compensation
+            if let Some(dc_master) = dc_master {
+                fmt::debug!(
+                    "Using SubDevice {:#06x} {} as DC reference",
+                    dc_master.configured_address(),
+                    dc_master.name
+                );
+
+                self.dc_reference_configured_address
+                    .store(dc_master.configured_address(), Ordering::Relaxed);
+
+                dc::run_dc_static_sync(
+                    self,
+                    dc_master.configured_address(),
+                    self.config.dc_static_sync_iterations,
+                )
+                .await?;
+            }
 
-        // If there are SubDevices that support distributed clocks, run static drift compensation
-        if let Some(dc_master) = dc_master {
-            self.dc_reference_configured_address
-                .store(dc_master.configured_address(), Ordering::Relaxed);
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        if let Err(e) = dc_result {
+            match self.config.dc_error {
+                DcErrorBehaviour::Fail => return Err(e),
+                DcErrorBehaviour::Warn => {
+                    fmt::warn!(
+                        "Failed to configure distributed clocks, continuing without DC: {}",
+                        e
+                    );
 
-            dc::run_dc_static_sync(self, dc_master, self.config.dc_static_sync_iterations).await?;
+                    // Without a reference SubDevice, DC sync can't be used by any group
+                    self.dc_reference_configured_address
+                        .store(0, Ordering::Relaxed);
+                }
+            }
         }
 
         // This block is to reduce the lifetime of the groups map references
@@ -313,6 +427,52 @@ impl<'sto> MainDevice<'sto> {
         Ok(groups)
     }
 
+    /// Like [`init`](MainDevice::init), but retry the whole initialisation sequence up to
+    /// `attempts` times if it fails with a transient error.
+    ///
+    /// Only [`Error::Timeout`] and [`Error::WorkingCounter`] are considered transient, e.g. from
+    /// a dropped frame or a SubDevice that is slow to power up. Any other error, such as
+    /// [`Error::UnknownSubDevice`] returned from `group_filter`, is returned immediately.
+    ///
+    /// Every attempt starts from scratch: all SubDevices are reset and a fresh set of groups is
+    /// created, so nothing from a failed attempt is carried over. `group_filter` may be called
+    /// multiple times for the same SubDevice.
+    ///
+    /// An `attempts` value of `0` is treated as `1`. The error from the last attempt is returned if
+    /// all attempts fail.
+    pub async fn init_with_retries<const MAX_SUBDEVICES: usize, G>(
+        &self,
+        attempts: usize,
+        now: impl Fn() -> u64 + Copy,
+        mut group_filter: impl for<'g> FnMut(
+            &'g G,
+            &SubDevice,
+        ) -> Result<&'g dyn SubDeviceGroupHandle, Error>,
+    ) -> Result<G, Error>
+    where
+        G: Default,
+    {
+        let attempts = attempts.max(1);
+
+        let mut attempt = 1;
+
+        loop {
+            match self.init::<MAX_SUBDEVICES, G>(now, &mut group_filter).await {
+                Err(e) if e.is_transient() && attempt < attempts => {
+                    fmt::warn!(
+                        "Init attempt {}/{} failed: {}. Retrying",
+                        attempt,
+                        attempts,
+                        e
+                    );
+
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        }
+    }
+
     /// A convenience method to allow the quicker creation of a single group containing all
     /// discovered SubDevices.
     ///
@@ -402,6 +562,214 @@ impl<'sto> MainDevice<'sto> {
             .await
     }
 
+    /// Enumerate all SubDevices on the network without changing their configuration or state.
+    ///
+    /// Unlike [`init`](MainDevice::init), this method does not reset SubDevices, assign configured
+    /// station addresses or request any state transitions, so it is safe to run against a network
+    /// that is already in operation, e.g. to check whether EtherCrab is compatible with it.
+    /// SubDevices are addressed by their position in the network throughout. The only registers
+    /// written are the SII control registers used to request EEPROM reads.
+    ///
+    /// SubDevices whose EEPROM is currently assigned to their PDI are still reported, but without
+    /// any of the information stored in the EEPROM.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ethercrab::{MainDevice, MainDeviceConfig, PduStorage, Timeouts};
+    ///
+    /// static PDU_STORAGE: PduStorage<8, 128> = PduStorage::new();
+    ///
+    /// let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    ///
+    /// let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    ///
+    /// # async {
+    /// for subdevice in maindevice.discover::<16>().await? {
+    ///     println!("{}: {:?}", subdevice.position, subdevice.identity);
+    /// }
+    /// # Ok::<(), ethercrab::error::Error>(())
+    /// # };
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Capacity`] if more than `MAX_SUBDEVICES` SubDevices are found, or any PDU or
+    /// EEPROM error encountered while reading from a SubDevice.
+    pub async fn discover<const MAX_SUBDEVICES: usize>(
+        &self,
+    ) -> Result<heapless::Vec<DiscoveredSubDevice, MAX_SUBDEVICES>, Error> {
+        let num_subdevices = self.count_subdevices().await?;
+
+        fmt::debug!("Discovering {} SubDevices", num_subdevices);
+
+        let mut subdevices = heapless::Vec::new();
+
+        for position in 0..num_subdevices {
+            let subdevice = discovery::discover_subdevice(self, position).await?;
+
+            subdevices
+                .push(subdevice)
+                .map_err(|_| Error::Capacity(Item::SubDevice))?;
+        }
+
+        Ok(subdevices)
+    }
+
+    /// Scan the network for commissioning, assigning configured addresses and reading each
+    /// SubDevice's identity, name and position in the network topology.
+    ///
+    /// Unlike [`init`](MainDevice::init), SubDevices are not reset, no Distributed Clocks
+    /// configuration is performed and no groups are created, so SubDevices are left in whatever
+    /// state they are currently in. Apart from the configured station address, the only registers
+    /// written are those needed to read the EEPROM.
+    ///
+    /// To enumerate a network that is already in operation without changing configured
+    /// addresses, use [`discover`](MainDevice::discover) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ethercrab::{MainDevice, MainDeviceConfig, PduStorage, Timeouts};
+    ///
+    /// static PDU_STORAGE: PduStorage<8, 128> = PduStorage::new();
+    ///
+    /// let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    ///
+    /// let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    ///
+    /// # async {
+    /// let report = maindevice.scan::<16>().await?;
+    ///
+    /// for subdevice in report.subdevices {
+    ///     println!(
+    ///         "{:#06x} {} ({}), parent {:?}",
+    ///         subdevice.configured_address, subdevice.name, subdevice.identity, subdevice.parent_index
+    ///     );
+    /// }
+    /// # Ok::<(), ethercrab::error::Error>(())
+    /// # };
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Capacity`] if more than `MAX_SUBDEVICES` SubDevices are found, or any PDU or
+    /// EEPROM error encountered while reading from a SubDevice.
+    pub async fn scan<const MAX_SUBDEVICES: usize>(
+        &self,
+    ) -> Result<NetworkReport<MAX_SUBDEVICES>, Error> {
+        let num_subdevices = self.count_subdevices().await?;
+
+        fmt::debug!("Scanning {} SubDevices", num_subdevices);
+
+        self.num_subdevices.store(num_subdevices, Ordering::Relaxed);
+
+        let mut subdevices = heapless::Vec::<SubDevice, MAX_SUBDEVICES>::new();
+
+        for subdevice_idx in 0..num_subdevices {
+            let configured_address = BASE_SUBDEVICE_ADDRESS.wrapping_add(subdevice_idx);
+
+            Command::apwr(
+                subdevice_idx,
+                RegisterAddress::ConfiguredStationAddress.into(),
+            )
+            .send(self, configured_address)
+            .await?;
+
+            let subdevice =
+                SubDevice::read_identity(self, subdevice_idx, configured_address).await?;
+
+            subdevices
+                .push(subdevice)
+                .map_err(|_| Error::Capacity(Item::SubDevice))?;
+        }
+
+        // Only computes parent/child relationships from the port link status already read, so
+        // sends nothing to the network.
+        dc::assign_parent_relationships(&mut subdevices)?;
+
+        Ok(NetworkReport {
+            subdevices: subdevices.iter().map(SubDeviceDescriptor::from).collect(),
+        })
+    }
+
+    /// Check that frames can be sent to and received from the network, without needing any
+    /// SubDevices to be connected.
+    ///
+    /// A single broadcast read is sent and this method waits for it to return. The working counter
+    /// is not checked, so this succeeds on a network with any number of SubDevices, including a
+    /// segment that returns the frame with a working counter of zero. This exercises the TX/RX
+    /// task, frame storage and response matching, so is a useful first diagnostic if nothing else
+    /// works.
+    ///
+    /// A frame that is looped back without passing through a SubDevice still carries the
+    /// MainDevice's own source MAC address, so it is dropped as an echo unless
+    /// [`MainDeviceConfig::ignore_self_filter`] is set. Enable that option to run this test with
+    /// the network interface looped back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Timeout`] if the frame does not return within
+    /// [`Timeouts::pdu`](crate::Timeouts::pdu), e.g. if the TX/RX task isn't running or the
+    /// network interface is not connected.
+    pub async fn self_test(&self) -> Result<(), Error> {
+        let wkc = self.count_subdevices().await?;
+
+        fmt::debug!("Self test frame returned with working counter {}", wkc);
+
+        Ok(())
+    }
+
+    /// Write a value to the same register on every SubDevice with a single broadcast write (`BWR`).
+    ///
+    /// This saves computing addresses and sending one PDU per SubDevice when applying the same
+    /// setting to many SubDevices at once. Each SubDevice that accepts the write increments the
+    /// working counter, so `expected_wkc` would usually be the number of SubDevices on the network,
+    /// as returned by [`num_subdevices`](MainDevice::num_subdevices).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ethercrab::{MainDevice, MainDeviceConfig, PduStorage, RegisterAddress, Timeouts};
+    ///
+    /// static PDU_STORAGE: PduStorage<8, 128> = PduStorage::new();
+    ///
+    /// let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    ///
+    /// let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    ///
+    /// # async {
+    /// let expected_wkc = maindevice.num_subdevices() as u16;
+    ///
+    /// // Reset the lost link counters of every SubDevice
+    /// maindevice
+    ///     .bwr_typed(RegisterAddress::LostLinkCounter, [0u8; 4], expected_wkc)
+    ///     .await?;
+    /// # Ok::<(), ethercrab::error::Error>(())
+    /// # };
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::WorkingCounter`] if the number of SubDevices that accepted the write is not
+    /// `expected_wkc`.
+    pub async fn bwr_typed<T>(
+        &self,
+        register: RegisterAddress,
+        value: T,
+        expected_wkc: u16,
+    ) -> Result<(), Error>
+    where
+        T: EtherCrabWireWrite,
+    {
+        Command::bwr(register.into())
+            .with_wkc(expected_wkc)
+            .send_receive_slice(self, value)
+            .await?;
+
+        Ok(())
+    }
+
     /// Count the number of SubDevices on the network.
     async fn count_subdevices(&self) -> Result<u16, Error> {
         Command::brd(RegisterAddress::Type.into())
@@ -417,6 +785,158 @@ impl<'sto> MainDevice<'sto> {
         usize::from(self.num_subdevices.load(Ordering::Relaxed))
     }
 
+    /// Get the number of EtherCAT frames that are queued for sending, currently being sent, or
+    /// waiting for a response from the network.
+    ///
+    /// This can be used to pace acyclic traffic so the frame storage isn't exhausted. See also
+    /// [`tx_idle`](MainDevice::tx_idle).
+    pub fn pending_sendable_frames(&self) -> usize {
+        self.pdu_loop.pending_sendable_frames()
+    }
+
+    /// Get the total number of PDUs sent and received through this `MainDevice`'s PDU loop.
+    ///
+    /// One EtherCAT frame can carry many PDUs, so these counters can be sampled periodically to
+    /// compute the effective number of PDUs per second. See [`PduStats`] for details.
+    pub fn pdu_stats(&self) -> PduStats {
+        self.pdu_loop.pdu_stats()
+    }
+
+    /// Wait until fewer than `threshold` frames are queued or in flight.
+    ///
+    /// This future is woken when a response is received or a frame is released, so does not poll
+    /// the frame storage in a busy loop. Only one task should wait on this method at a time.
+    ///
+    /// # Examples
+    ///
+    /// Stream a number of writes without taking up more than half of the available frame storage,
+    /// leaving room for cyclic process data traffic.
+    ///
+    /// ```rust,no_run
+    /// use ethercrab::{MainDevice, MainDeviceConfig, PduStorage, Timeouts};
+    ///
+    /// const MAX_FRAMES: usize = 16;
+    ///
+    /// static PDU_STORAGE: PduStorage<MAX_FRAMES, { PduStorage::element_size(1100) }> =
+    ///     PduStorage::new();
+    ///
+    /// let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    ///
+    /// let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    ///
+    /// # async {
+    /// for _segment in 0..100 {
+    ///     maindevice.tx_idle(MAX_FRAMES / 2).await;
+    ///
+    ///     // Send next segment here
+    /// }
+    /// # };
+    /// ```
+    pub async fn tx_idle(&self, threshold: usize) {
+        self.pdu_loop.tx_idle(threshold).await
+    }
+
+    /// Get the configured station address assigned to the SubDevice at the given position in the
+    /// network.
+    ///
+    /// SubDevices are given consecutive configured addresses in the order they are discovered by
+    /// [`init`](crate::MainDevice::init), starting at `0x1000` for the SubDevice at position `0`.
+    ///
+    /// Returns `None` if there is no SubDevice at the given position, or if `init` has not yet been
+    /// called.
+    pub fn configured_address_for_position(&self, position: u16) -> Option<u16> {
+        (position < self.num_subdevices.load(Ordering::Relaxed))
+            .then(|| BASE_SUBDEVICE_ADDRESS.wrapping_add(position))
+    }
+
+    /// List the configured address and Distributed Clocks (DC) support level of every DC-capable
+    /// SubDevice in `subdevices`, in network order.
+    ///
+    /// This includes SubDevices that can only be used as a reference clock
+    /// ([`DcSupport::RefOnly`]) as well as those with 32 or 64 bit DC that can be configured for
+    /// `SYNC0`/`SYNC1`. Choosing a 64 bit device as the DC reference avoids system time rollover
+    /// every ~4.2 seconds.
+    ///
+    /// If more than `N` SubDevices support DC, only the first `N` are returned.
+    pub fn dc_capable_devices<const N: usize>(
+        &self,
+        subdevices: &[SubDevice],
+    ) -> heapless::Vec<(u16, DcSupport), N> {
+        subdevices
+            .iter()
+            .filter(|subdevice| subdevice.dc_support().any())
+            .map(|subdevice| (subdevice.configured_address(), subdevice.dc_support()))
+            .take(N)
+            .collect()
+    }
+
+    /// Check whether the EtherCAT network topology has changed since this method was last called,
+    /// or since [`init`](crate::MainDevice::init) if it has never been called.
+    ///
+    /// This sends a single broadcast read of the ECAT event request register. Every SubDevice
+    /// raises an event when a link on any of its ports comes up or goes down. If any SubDevice
+    /// has raised the event, the DL status register is read to acknowledge it. The topology is
+    /// also considered changed if the number of SubDevices responding differs from
+    /// [`num_subdevices`](MainDevice::num_subdevices).
+    ///
+    /// This is cheap enough to poll periodically to decide whether to rediscover the network.
+    pub async fn topology_changed(&self) -> Result<bool, Error> {
+        let num_subdevices = self.num_subdevices.load(Ordering::Relaxed);
+
+        let events = Command::brd(RegisterAddress::EcatEventRequest.into())
+            .ignore_wkc()
+            .receive_slice(self, 2)
+            .await?;
+
+        let wkc = events.working_counter();
+        let events = u16::unpack_from_slice(&events)?;
+
+        let dl_status_changed = events & ECAT_EVENT_DL_STATUS != 0;
+
+        if dl_status_changed {
+            // Reading DL status clears the event
+            Command::brd(RegisterAddress::DlStatus.into())
+                .ignore_wkc()
+                .receive::<u16>(self)
+                .await?;
+        }
+
+        if wkc != num_subdevices {
+            fmt::debug!("{} SubDevices responded, expected {}", wkc, num_subdevices);
+        }
+
+        Ok(dl_status_changed || wkc != num_subdevices)
+    }
+
+    /// Re-run Distributed Clocks static drift compensation against the DC reference SubDevice
+    /// chosen during [`init`](crate::MainDevice::init).
+    ///
+    /// This sends `iterations` `FRMW` frames to distribute the reference clock's system time to all
+    /// SubDevices, the same as is done during initialisation with
+    /// [`MainDeviceConfig::dc_static_sync_iterations`](crate::MainDeviceConfig::dc_static_sync_iterations).
+    /// It can be used to let the SubDevices' clocks settle again after a transient disturbance
+    /// without reinitialising the network.
+    ///
+    /// Each `FRMW` reads the DC system time (`0x0910`) of the reference SubDevice and writes it to
+    /// the same register of every other SubDevice. A write to this register doesn't set the
+    /// SubDevice's local time. Instead, the ESC compares it to its own system time and feeds the
+    /// difference into its drift compensation. This is the same `FRMW` that
+    /// [`SubDeviceGroup::tx_rx_dc`](crate::SubDeviceGroup::tx_rx_dc) sends every cycle, so it is
+    /// safe to call in any state, including OP. Note that the frames are sent in addition to any
+    /// process data cycle, so a large number of iterations may delay cyclic frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistributedClock(DistributedClockError::NoReference)`](Error::DistributedClock)
+    /// if there is no DC reference SubDevice on the network.
+    pub async fn resync_dc(&self, iterations: u32) -> Result<(), Error> {
+        let reference = self
+            .dc_ref_address()
+            .ok_or(DistributedClockError::NoReference)?;
+
+        dc::run_dc_static_sync(self, reference, iterations).await
+    }
+
     /// Get the configured address of the designated DC reference subdevice.
     pub(crate) fn dc_ref_address(&self) -> Option<u16> {
         let addr = self.dc_reference_configured_address.load(Ordering::Relaxed);
@@ -424,6 +944,13 @@ impl<'sto> MainDevice<'sto> {
         if addr > 0 { Some(addr) } else { None }
     }
 
+    /// Designate a DC reference SubDevice without running DC configuration.
+    #[cfg(test)]
+    pub(crate) fn set_dc_ref_address(&self, configured_address: u16) {
+        self.dc_reference_configured_address
+            .store(configured_address, Ordering::Relaxed);
+    }
+
     /// Wait for all SubDevices on the network to reach a given state.
     pub async fn wait_for_state(&self, desired_state: SubDeviceState) -> Result<(), Error> {
         let num_subdevices = self.num_subdevices.load(Ordering::Relaxed);
@@ -474,8 +1001,62 @@ impl<'sto> MainDevice<'sto> {
         .await
     }
 
-    #[allow(unused)]
-    pub(crate) const fn max_frame_data(&self) -> usize {
+    /// Wait for all SubDevices in every given group to reach OP.
+    ///
+    /// This is intended to be used with groups transitioned with
+    /// [`request_into_op`](SubDeviceGroup::request_into_op), giving the application a single point
+    /// to wait on before starting its control loop. Each group is given the
+    /// [`state_transition`](Timeouts::state_transition) timeout to reach OP.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use ethercrab::{
+    /// #     error::Error, MainDevice, MainDeviceConfig, PduStorage, Timeouts, std::ethercat_now,
+    /// #     SubDeviceGroup,
+    /// # };
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
+    /// # let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    /// # async {
+    /// # let fast = SubDeviceGroup::<4, 32>::default();
+    /// # let slow = SubDeviceGroup::<4, 32>::default();
+    /// let fast = fast.into_safe_op(&maindevice).await?.request_into_op(&maindevice).await?;
+    /// let slow = slow.into_safe_op(&maindevice).await?.request_into_op(&maindevice).await?;
+    ///
+    /// // Start process data tasks for each group here, then wait for everything to reach OP.
+    ///
+    /// maindevice.wait_all_groups_op(&[&fast, &slow]).await?;
+    /// # Ok::<(), ethercrab::error::Error>(())
+    /// # };
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Timeout`] if any group does not reach OP in time, or any other error if a
+    /// state check could not be sent or received.
+    pub async fn wait_all_groups_op(
+        &self,
+        groups: &[&dyn SubDeviceGroupHandle],
+    ) -> Result<(), Error> {
+        fmt::debug!("Waiting for {} groups to reach OP", groups.len());
+
+        for group in groups {
+            subdevice_group::wait_for_subdevices_state(
+                self,
+                group.subdevices(subdevice_group::Internal(())),
+                SubDeviceState::Op,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// The largest PDU payload in bytes that fits in a single frame.
+    ///
+    /// See [`PduLoop::max_frame_data`] for details.
+    pub fn max_frame_data(&self) -> usize {
         self.pdu_loop.max_frame_data()
     }
 
@@ -485,9 +1066,19 @@ impl<'sto> MainDevice<'sto> {
         command: Command,
         data: impl EtherCrabWireWrite,
         len_override: Option<u16>,
-    ) -> Result<ReceivedPdu<'sto>, Error> {
+        scrub: bool,
+    ) -> Result<HeldPdu<'sto>, Error> {
+        command.check_logical_range(
+            data.packed_len()
+                .max(len_override.map(usize::from).unwrap_or(0)),
+        )?;
+
         let mut frame = self.pdu_loop.alloc_frame()?;
 
+        if scrub && self.config.scrub_mailbox_frames {
+            frame.set_scrub();
+        }
+
         let handle = frame.push_pdu(command, data, len_override)?;
 
         let frame = frame.mark_sendable(
@@ -498,7 +1089,7 @@ impl<'sto> MainDevice<'sto> {
 
         self.pdu_loop.wake_sender();
 
-        frame.await?.first_pdu(handle)
+        frame.await?.into_held_pdu(handle)
     }
 
     /// Release the [`PduLoop`] storage **without** resetting it.
@@ -557,3 +1148,644 @@ impl<'sto> MainDevice<'sto> {
         self.pdu_loop
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        EepromErrorBehaviour, PduStorage, ReceiveAction, SubDeviceIdentity,
+        command::{APRD, APWR, BRD, BWR, FPRD, FRMW},
+        fake_network::{FakeEsc, FakeNetwork},
+        timer_factory::manual::ManualClock,
+    };
+    use core::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+        time::Duration,
+    };
+    use ethercrab_wire::EtherCrabWireWriteSized;
+
+    #[test]
+    fn configured_address_for_position() {
+        static PDU_STORAGE: PduStorage<1, 64> = PduStorage::new();
+        let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        // Nothing discovered yet
+        assert_eq!(maindevice.configured_address_for_position(0), None);
+
+        maindevice.num_subdevices.store(3, Ordering::Relaxed);
+
+        assert_eq!(maindevice.configured_address_for_position(0), Some(0x1000));
+        assert_eq!(maindevice.configured_address_for_position(2), Some(0x1002));
+        assert_eq!(maindevice.configured_address_for_position(3), None);
+        assert_eq!(maindevice.configured_address_for_position(u16::MAX), None);
+    }
+
+    #[test]
+    fn dc_capable_devices() {
+        static PDU_STORAGE: PduStorage<1, 64> = PduStorage::new();
+        let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevices = [
+            (0x1000, DcSupport::Bits64),
+            (0x1001, DcSupport::None),
+            (0x1002, DcSupport::Bits32),
+            (0x1003, DcSupport::RefOnly),
+            (0x1004, DcSupport::None),
+            (0x1005, DcSupport::Bits64),
+        ]
+        .map(|(configured_address, dc_support)| SubDevice {
+            configured_address,
+            dc_support,
+            ..SubDevice::default()
+        });
+
+        assert_eq!(
+            maindevice.dc_capable_devices::<8>(&subdevices).as_slice(),
+            &[
+                (0x1000, DcSupport::Bits64),
+                (0x1002, DcSupport::Bits32),
+                (0x1003, DcSupport::RefOnly),
+                (0x1005, DcSupport::Bits64),
+            ]
+        );
+
+        // Truncated to capacity
+        assert_eq!(
+            maindevice.dc_capable_devices::<2>(&subdevices).as_slice(),
+            &[(0x1000, DcSupport::Bits64), (0x1002, DcSupport::Bits32)]
+        );
+
+        assert!(maindevice.dc_capable_devices::<8>(&[]).is_empty());
+    }
+
+    #[test]
+    fn wait_all_groups_op() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let fast = SubDeviceGroup::<1, 1>::default();
+        let slow = SubDeviceGroup::<1, 1>::default();
+
+        unsafe {
+            fast.push(SubDevice {
+                configured_address: 0x1000,
+                ..SubDevice::default()
+            })
+            .unwrap();
+            slow.push(SubDevice {
+                configured_address: 0x1001,
+                ..SubDevice::default()
+            })
+            .unwrap();
+        }
+
+        // The slow group's SubDevice reports SAFE-OP for its first two status checks
+        let mut slow_checks = 0;
+
+        let groups: [&dyn SubDeviceGroupHandle; 2] = [&fast, &slow];
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run(maindevice.wait_all_groups_op(&groups), |pdu| {
+                // Single FPRD of AL status
+                assert_eq!(pdu.command, FPRD);
+
+                let state = match pdu.adp {
+                    0x1000 => SubDeviceState::Op,
+                    0x1001 if slow_checks < 2 => SubDeviceState::SafeOp,
+                    0x1001 => SubDeviceState::Op,
+                    other => panic!("unexpected address {:#06x}", other),
+                };
+
+                if pdu.adp == 0x1001 {
+                    slow_checks += 1;
+                }
+
+                assert!(
+                    slow_checks <= 3,
+                    "barrier should complete once both groups are in OP"
+                );
+
+                pdu.data.copy_from_slice(&AlControl::new(state).pack());
+
+                1
+            });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(slow_checks, 3);
+    }
+
+    #[test]
+    fn init_retries_after_timeout() {
+        crate::test_logger();
+
+        let clock = ManualClock::default();
+        let _guard = clock.drive_pdu_timeouts();
+
+        let storage = PduStorage::<2, { PduStorage::element_size(8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts {
+                pdu: Duration::from_millis(10),
+                ..Timeouts::default()
+            },
+            MainDeviceConfig::default(),
+        );
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let mut fut = pin!(maindevice.init_with_retries::<2, SubDeviceGroup<1, 1>>(
+            3,
+            || 0,
+            |group, _subdevice| Ok(group),
+        ));
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        let mut sent_count = 0;
+
+        let result = loop {
+            if let Poll::Ready(result) = fut.as_mut().poll(&mut ctx) {
+                break result;
+            }
+
+            let Some(frame) = network.next_frame() else {
+                // Nothing more to send, so the lost frame's PDU timeout can expire
+                clock.advance(Duration::from_millis(10));
+
+                continue;
+            };
+
+            sent_count += 1;
+
+            // Drop the first SubDevice count request on the floor so the first attempt times out
+            if sent_count == 1 {
+                continue;
+            }
+
+            // An empty network: the BRD comes back with a working counter of zero
+            network.reply(frame);
+        };
+
+        assert!(result.is_ok());
+        assert_eq!(sent_count, 2);
+
+        assert!(!Error::UnknownSubDevice.is_transient());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn self_test_loopback() {
+        crate::test_logger();
+
+        // A raw loopback returns the sent frame unchanged, so it's dropped as an echo unless the
+        // self filter is disabled.
+        for (ignore_self_filter, expected) in [(false, Err(Error::Timeout)), (true, Ok(()))] {
+            let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+            let maindevice = MainDevice::new(
+                pdu_loop,
+                Timeouts {
+                    pdu: Duration::from_millis(10),
+                    ..Timeouts::default()
+                },
+                MainDeviceConfig {
+                    ignore_self_filter,
+                    ..MainDeviceConfig::default()
+                },
+            );
+
+            let mut ctx = Context::from_waker(Waker::noop());
+            let mut fut = pin!(maindevice.self_test());
+
+            let result = loop {
+                if let Poll::Ready(result) = fut.as_mut().poll(&mut ctx) {
+                    break result;
+                }
+
+                let mut sent = Vec::new();
+
+                match tx.next_sendable_frame() {
+                    Some(frame) => {
+                        frame
+                            .send_blocking(|bytes| {
+                                sent.extend_from_slice(bytes);
+
+                                Ok(bytes.len())
+                            })
+                            .expect("send");
+
+                        // Loop the frame back with the MainDevice's own source MAC and a working
+                        // counter of zero
+                        rx.receive_frame(&sent).expect("receive");
+                    }
+                    None => std::thread::sleep(Duration::from_millis(1)),
+                }
+            };
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn ignore_self_filter() {
+        crate::test_logger();
+
+        for (ignore_self_filter, expected) in [
+            (false, ReceiveAction::Ignored),
+            (true, ReceiveAction::Processed),
+        ] {
+            let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+            let maindevice = MainDevice::new(
+                pdu_loop,
+                Timeouts::default(),
+                MainDeviceConfig {
+                    ignore_self_filter,
+                    ..MainDeviceConfig::default()
+                },
+            );
+
+            let mut ctx = Context::from_waker(Waker::noop());
+            // Not sent by `init` or any other method that counts SubDevices first
+            let command = Command::fprd(0x1001, RegisterAddress::AlStatus.into());
+            let mut fut = pin!(command.receive_wkc::<u8>(&maindevice));
+
+            assert!(fut.as_mut().poll(&mut ctx).is_pending());
+
+            let mut sent = Vec::new();
+
+            tx.next_sendable_frame()
+                .expect("need a frame")
+                .send_blocking(|bytes| {
+                    sent.extend_from_slice(bytes);
+
+                    Ok(bytes.len())
+                })
+                .expect("send");
+
+            // Pass the frame back unchanged, still with the MainDevice's own source MAC
+            assert_eq!(rx.receive_frame(&sent), Ok(expected));
+
+            if ignore_self_filter {
+                assert_eq!(fut.as_mut().poll(&mut ctx), Poll::Ready(Ok(0)));
+            } else {
+                assert!(fut.as_mut().poll(&mut ctx).is_pending());
+            }
+        }
+    }
+
+    #[test]
+    fn bwr_typed() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let mut write = |responding: u16| {
+            network.run(
+                maindevice.bwr_typed(RegisterAddress::DcSyncActive, 0x03u8, 3),
+                |pdu| {
+                    assert_eq!(pdu.command, BWR);
+                    assert_eq!(pdu.ado, u16::from(RegisterAddress::DcSyncActive));
+                    assert_eq!(pdu.data, [0x03]);
+
+                    responding
+                },
+            )
+        };
+
+        assert_eq!(write(3), Ok(()));
+        assert_eq!(
+            write(2),
+            Err(Error::WorkingCounter {
+                expected: 3,
+                received: 2
+            })
+        );
+    }
+
+    #[test]
+    fn broadcast_reset_per_device_fallback() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts::default(),
+            MainDeviceConfig {
+                broadcast_reset: BroadcastResetBehaviour::PerDevice,
+                ..MainDeviceConfig::default()
+            },
+        );
+
+        let al_control = u16::from(RegisterAddress::AlControl);
+        let al_status = u16::from(RegisterAddress::AlStatus);
+
+        // Auto increment addresses of individually reset SubDevices
+        let mut individual_resets = Vec::new();
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run(maindevice.reset_subdevices(2), |pdu| {
+                match pdu.command {
+                    // The second SubDevice ignores the broadcast reset
+                    BWR if pdu.ado == al_control => 1,
+                    // First SubDevice is in INIT, second is still in PRE-OP
+                    APRD if pdu.ado == al_status => {
+                        let state = match pdu.adp {
+                            0x0000 => SubDeviceState::Init,
+                            0xffff => SubDeviceState::PreOp,
+                            other => panic!("unexpected position {:#06x}", other),
+                        };
+
+                        pdu.data.copy_from_slice(&AlControl::new(state).pack());
+
+                        1
+                    }
+                    APWR if pdu.ado == al_control => {
+                        individual_resets.push(pdu.adp);
+
+                        1
+                    }
+                    // Register blanking and DC parameter writes
+                    BWR => 2,
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+            });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(individual_resets, [0xffff]);
+    }
+
+    #[test]
+    fn reset_without_blanking() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts::default(),
+            MainDeviceConfig {
+                skip_reset_blanking: true,
+                ..MainDeviceConfig::default()
+            },
+        );
+
+        let mut writes = Vec::new();
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run(maindevice.reset_subdevices(2), |pdu| {
+                assert_eq!(pdu.command, BWR);
+
+                writes.push(pdu.ado);
+
+                2
+            });
+
+        assert_eq!(result, Ok(()));
+
+        // Only the AL reset and DC control loop parameters are written
+        assert_eq!(
+            writes,
+            [
+                u16::from(RegisterAddress::AlControl),
+                u16::from(RegisterAddress::DcControlLoopParam3),
+                u16::from(RegisterAddress::DcControlLoopParam1),
+            ]
+        );
+    }
+
+    #[test]
+    fn topology_changed() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        maindevice.num_subdevices.store(2, Ordering::Relaxed);
+
+        let event_request = u16::from(RegisterAddress::EcatEventRequest);
+        let dl_status = u16::from(RegisterAddress::DlStatus);
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        // Pending DL status event, cleared by reading DL status
+        let mut event_pending = true;
+
+        let mut check = |responding: u16| {
+            network.run(maindevice.topology_changed(), |pdu| {
+                assert_eq!(pdu.command, BRD);
+
+                if pdu.ado == event_request {
+                    let events: u16 = if event_pending { 1 << 2 } else { 0 };
+
+                    pdu.data.copy_from_slice(&events.to_le_bytes());
+                } else if pdu.ado == dl_status {
+                    event_pending = false;
+                } else {
+                    panic!("unexpected register {:#06x}", pdu.ado);
+                }
+
+                responding
+            })
+        };
+
+        // A link changed
+        assert_eq!(check(2), Ok(true));
+        // Event was acknowledged
+        assert_eq!(check(2), Ok(false));
+        // A SubDevice dropped off the network
+        assert_eq!(check(1), Ok(true));
+    }
+
+    #[test]
+    fn dc_error_warn() {
+        crate::test_logger();
+
+        // A SubDevice with 64 bit DC support and the given DL status
+        let fake_esc = |dl_status: u8| {
+            FakeEsc::new(include_bytes!("../dumps/eeprom/el2889.hex"))
+                .with_register(RegisterAddress::SupportFlags, &[0x0c, 0x01])
+                .with_register(RegisterAddress::DlStatus, &[dl_status, 0x00])
+        };
+
+        let init = |dc_error| {
+            let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+            let maindevice = MainDevice::new(
+                pdu_loop,
+                Timeouts::default(),
+                MainDeviceConfig {
+                    dc_static_sync_iterations: 0,
+                    dc_error,
+                    ..MainDeviceConfig::default()
+                },
+            );
+
+            // Links on ports 0 and 1, then on port 0 only. The second SubDevice reports DC support
+            // but doesn't respond to DC receive time latches or reads.
+            let mut subdevices = [fake_esc(0b0011_0000), fake_esc(0b0001_0000)];
+
+            subdevices[1].dc_latch = false;
+
+            let result = FakeNetwork::new(&mut tx, &mut rx)
+                .run(maindevice.init_single_group::<2, 8>(|| 0), |pdu| {
+                    FakeEsc::process(&mut subdevices, pdu)
+                });
+
+            (result.map(|group| group.len()), maindevice.dc_ref_address())
+        };
+
+        assert_eq!(
+            init(DcErrorBehaviour::Fail),
+            (
+                Err(Error::WorkingCounter {
+                    expected: 1,
+                    received: 0
+                }),
+                None
+            )
+        );
+
+        // Both SubDevices reach PRE-OP, but without a DC reference
+        assert_eq!(init(DcErrorBehaviour::Warn), (Ok(2), None));
+    }
+
+    #[test]
+    fn eeprom_error_placeholder() {
+        crate::test_logger();
+
+        // `sii_control_wkc` is the working counter returned by the second SubDevice for SII control
+        // register accesses
+        let init = |eeprom_error, sii_control_wkc| {
+            let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+            let maindevice = MainDevice::new(
+                pdu_loop,
+                Timeouts {
+                    eeprom: Duration::from_millis(10),
+                    ..Timeouts::default()
+                },
+                MainDeviceConfig {
+                    eeprom_error,
+                    ..MainDeviceConfig::default()
+                },
+            );
+
+            let mut subdevices = [
+                FakeEsc::new(include_bytes!("../dumps/eeprom/el2889.hex"))
+                    .with_register(RegisterAddress::DlStatus, &[0b0011_0000, 0x00]),
+                FakeEsc::new(include_bytes!("../dumps/eeprom/el2889.hex"))
+                    .with_register(RegisterAddress::DlStatus, &[0b0001_0000, 0x00]),
+            ];
+
+            // The second SubDevice's EEPROM never finishes a read
+            subdevices[1].eeprom_busy = true;
+
+            let mut seen = Vec::new();
+
+            let result = FakeNetwork::new(&mut tx, &mut rx).run(
+                maindevice.init::<2, SubDeviceGroup<2, 8>>(
+                    || 0,
+                    |group, subdevice| {
+                        seen.push((subdevice.name().to_string(), subdevice.identity()));
+
+                        if subdevice.identity() == SubDeviceIdentity::default() {
+                            Err(Error::UnknownSubDevice)
+                        } else {
+                            Ok(group)
+                        }
+                    },
+                ),
+                |pdu| {
+                    if pdu.adp == 0x1001 && pdu.ado == u16::from(RegisterAddress::SiiControl) {
+                        FakeEsc::process(&mut subdevices, pdu).min(sii_control_wkc)
+                    } else {
+                        FakeEsc::process(&mut subdevices, pdu)
+                    }
+                },
+            );
+
+            (result.map(|group| group.len()), seen)
+        };
+
+        let (result, seen) = init(EepromErrorBehaviour::Fail, 1);
+
+        assert_eq!(result, Err(Error::Timeout));
+        assert!(seen.is_empty());
+
+        // Discovery continues and `group_filter` is given the placeholder to decide what to do
+        // with it
+        let (result, seen) = init(EepromErrorBehaviour::Placeholder, 1);
+
+        assert_eq!(result, Err(Error::UnknownSubDevice));
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].0, "EL2889");
+        assert_eq!(seen[1], (String::new(), SubDeviceIdentity::default()));
+
+        // Errors not caused by the EEPROM still fail discovery
+        let (result, seen) = init(EepromErrorBehaviour::Placeholder, 0);
+
+        assert_eq!(
+            result,
+            Err(Error::WorkingCounter {
+                expected: 1,
+                received: 0
+            })
+        );
+        assert!(seen.is_empty());
+    }
+
+    #[test]
+    fn resync_dc() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        assert_eq!(
+            network.run(maindevice.resync_dc(10), |_| panic!(
+                "nothing should be sent"
+            )),
+            Err(Error::DistributedClock(DistributedClockError::NoReference))
+        );
+
+        maindevice.set_dc_ref_address(0x1002);
+
+        let mut sent = 0;
+
+        let result = network.run(maindevice.resync_dc(10), |pdu| {
+            assert_eq!(pdu.command, FRMW);
+            assert_eq!(pdu.adp, 0x1002);
+            assert_eq!(pdu.ado, u16::from(RegisterAddress::DcSystemTime));
+
+            sent += 1;
+
+            3
+        });
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(sent, 10);
+    }
+}
@@ -13,6 +13,132 @@ pub struct MainDeviceConfig {
 
     /// EtherCAT packet (PDU) network retry behaviour.
     pub retry_behaviour: RetryBehaviour,
+
+    /// Zero frame buffers used for mailbox traffic (CoE, etc) once they are released.
+    ///
+    /// Mailbox payloads may carry data that shouldn't linger in the PDU storage after use. When
+    /// this option is enabled, frames used for mailbox reads and writes are overwritten with
+    /// zeros when a response has been processed, or if the frame is dropped before being sent.
+    /// Cyclic process data frames are never scrubbed.
+    ///
+    /// Defaults to `false`.
+    pub scrub_mailbox_frames: bool,
+
+    /// Configured addresses of SubDevices that Distributed Clocks (DC) setup should not write a
+    /// system time offset or transmission delay to.
+    ///
+    /// This is useful for SubDevices whose DC unit is synchronised by some other means, e.g. an
+    /// external clock source, and whose offset must not be overwritten by EtherCrab. Excluded
+    /// SubDevices are still latched and included in the network topology, so propagation delays
+    /// of SubDevices after them in the network are calculated correctly.
+    ///
+    /// Defaults to an empty list.
+    pub dc_exclude: &'static [u16],
+
+    /// What to do if not every SubDevice acknowledges the broadcast reset at the start of
+    /// [`MainDevice::init`](crate::MainDevice::init).
+    ///
+    /// Defaults to [`BroadcastResetBehaviour::Warn`].
+    pub broadcast_reset: BroadcastResetBehaviour,
+
+    /// The maximum number of mailbox transactions, e.g. SDO reads or writes, that may be in
+    /// progress on a single SubDevice at once.
+    ///
+    /// Further transactions wait until one of the in-progress ones completes. A SubDevice mailbox
+    /// only holds one request at a time, so raising this is only useful for SubDevices that can
+    /// queue requests. SDO transfers are always serialised as a SubDevice's SDO server only
+    /// handles one at a time. A value of `0` is treated as `1`.
+    ///
+    /// Defaults to 1.
+    pub mailbox_concurrency: u8,
+
+    /// Record the AL status codes each SubDevice reports while waiting for a group state
+    /// transition.
+    ///
+    /// This helps diagnose SubDevices that briefly report an error during a transition and then
+    /// recover. The codes can be read with
+    /// [`SubDeviceRef::al_status_history`](crate::SubDeviceRef::al_status_history). Enabling this
+    /// makes each status check read the AL status code alongside the AL status register.
+    ///
+    /// Defaults to `false`.
+    pub al_status_history: bool,
+
+    /// Skip zeroing FMMU, Sync Manager and Distributed Clocks registers when SubDevices are reset
+    /// at the start of [`MainDevice::init`](crate::MainDevice::init).
+    ///
+    /// Blanking sends a few dozen broadcast writes, which is unnecessary on a network that has just
+    /// been powered up as every register is already at its default. Init then goes straight from
+    /// resetting SubDevices to INIT to assigning addresses.
+    ///
+    /// **This is only safe if every SubDevice was power cycled since it was last configured.** A
+    /// SubDevice that was previously configured, e.g. by an earlier run of the application, keeps
+    /// its old FMMU and SM mappings and DC settings, which can cause process data to be exchanged
+    /// with the wrong memory or SYNC0 pulses to be generated unexpectedly.
+    ///
+    /// Defaults to `false`.
+    pub skip_reset_blanking: bool,
+
+    /// The number of times a CoE mailbox transaction, e.g. an SDO read or write, is retried if
+    /// the SubDevice's mailbox is busy or the response doesn't arrive in time.
+    ///
+    /// Each retry resends the request with a new mailbox counter and only accepts a response with
+    /// that counter, or with a counter of 0. SubDevices that don't implement the mailbox counter
+    /// always respond with 0, so for these a late response to an earlier attempt can still be
+    /// taken as the response to a retry.
+    ///
+    /// This is separate from [`retry_behaviour`](Self::retry_behaviour), which retries individual
+    /// lost frames, so slow or busy SubDevices can be given more chances without affecting process
+    /// data. Mailbox responses that are rejected by the SubDevice, e.g.
+    /// an SDO abort, are never retried.
+    ///
+    /// Defaults to `0`.
+    pub mailbox_retries: usize,
+
+    /// What to do if a SubDevice's EEPROM can't be read when it is discovered during
+    /// [`MainDevice::init`](crate::MainDevice::init).
+    ///
+    /// Defaults to [`EepromErrorBehaviour::Fail`].
+    pub eeprom_error: EepromErrorBehaviour,
+
+    /// Process received EtherCAT frames whose source MAC address is the MainDevice's own, instead
+    /// of ignoring them.
+    ///
+    /// The first SubDevice sets the U/L bit of the source MAC address of every frame it forwards,
+    /// so frames with an unchanged source address are normally echoes of frames the MainDevice sent
+    /// itself, e.g. from a switch or the network driver looping them back. Some mirrored or tapped
+    /// capture setups and virtual interfaces return responses without this change, which are then
+    /// wrongly dropped.
+    ///
+    /// **With this set, echoes are processed as if they were responses.** An echo carries the
+    /// unmodified frame with working counters of zero, so it may complete a PDU before the real
+    /// response arrives, causing working counter errors or stale data to be read. Only enable this
+    /// if the network interface is known not to loop sent frames back.
+    ///
+    /// This applies to every frame sent by the [`MainDevice`](crate::MainDevice), including
+    /// process data frames sent by SubDevice groups.
+    ///
+    /// Defaults to `false`.
+    pub ignore_self_filter: bool,
+
+    /// What to do if Distributed Clocks (DC) can't be configured during
+    /// [`MainDevice::init`](crate::MainDevice::init), e.g. because a SubDevice that reports DC
+    /// support doesn't latch its receive times.
+    ///
+    /// Defaults to [`DcErrorBehaviour::Fail`].
+    pub dc_error: DcErrorBehaviour,
+
+    /// Read the SII items needed to configure each SubDevice once, when it is discovered during
+    /// [`MainDevice::init`](crate::MainDevice::init), and reuse them in every configuration stage.
+    ///
+    /// This saves searching each SubDevice's EEPROM for the General and SyncManager categories a
+    /// second time while configuring mailboxes and FMMUs. If the items can't be read during
+    /// discovery, they are read again when the SubDevice is configured.
+    ///
+    /// When this is disabled, the SII items are read from the EEPROM by each configuration stage
+    /// as they are needed.
+    ///
+    /// Defaults to `true`.
+    pub cache_sii_config: bool,
 }
 
 impl Default for MainDeviceConfig {
@@ -20,6 +146,17 @@ impl Default for MainDeviceConfig {
         Self {
             dc_static_sync_iterations: 10_000,
             retry_behaviour: RetryBehaviour::default(),
+            scrub_mailbox_frames: false,
+            dc_exclude: &[],
+            broadcast_reset: BroadcastResetBehaviour::default(),
+            mailbox_concurrency: 1,
+            al_status_history: false,
+            skip_reset_blanking: false,
+            mailbox_retries: 0,
+            eeprom_error: EepromErrorBehaviour::default(),
+            ignore_self_filter: false,
+            dc_error: DcErrorBehaviour::default(),
+            cache_sii_config: true,
         }
     }
 }
@@ -61,6 +198,63 @@ impl RetryBehaviour {
     }
 }
 
+/// Handling of SubDevices that don't acknowledge the broadcast `AlControl` reset.
+///
+/// Some SubDevices NAK or ignore a broadcast write to the AL control register. This is detected by
+/// the working counter of the broadcast being lower than the number of SubDevices on the network.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum BroadcastResetBehaviour {
+    /// Return an [`Error::WorkingCounter`](crate::error::Error::WorkingCounter).
+    Fail,
+
+    /// Log a warning and continue with initialisation (default).
+    #[default]
+    Warn,
+
+    /// Read the AL status of each SubDevice and reset any that are not in `INIT` or have their
+    /// error flag set with an individually addressed write.
+    ///
+    /// Configured station addresses are not assigned yet at this point, so SubDevices are
+    /// addressed by their position in the network.
+    PerDevice,
+}
+
+/// Handling of SubDevices whose EEPROM can't be read during discovery.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum EepromErrorBehaviour {
+    /// Return the error, stopping initialisation (default).
+    #[default]
+    Fail,
+
+    /// Log a warning and continue with an unknown SubDevice, which has an empty name and an
+    /// identity of all zeros.
+    ///
+    /// The `group_filter` passed to [`MainDevice::init`](crate::MainDevice::init) can then decide
+    /// what to do with the SubDevice, e.g. return an error if it's required by the application.
+    /// Configuring the SubDevice into PRE-OP also reads its EEPROM, so will most likely fail if the
+    /// SubDevice is added to a group.
+    ///
+    /// Only errors from the EEPROM itself are handled this way, i.e. an
+    /// [`Error::Eeprom`](crate::error::Error::Eeprom) or the EEPROM staying busy for longer than
+    /// [`Timeouts::eeprom`](crate::Timeouts::eeprom). Other errors, e.g. a lost frame or a working
+    /// counter error, are still returned.
+    Placeholder,
+}
+
+/// Handling of errors while configuring Distributed Clocks during initialisation.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DcErrorBehaviour {
+    /// Return the error, stopping initialisation (default).
+    #[default]
+    Fail,
+
+    /// Log a warning and continue initialisation without DC.
+    ///
+    /// No DC reference SubDevice is designated, so groups can't be configured to use DC sync. Any
+    /// system time offsets or transmission delays written before the error are left in place.
+    Warn,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -15,7 +15,7 @@ pub struct PdiOffset {
 impl PdiOffset {
     /// Increment the address accumulator by a given number of bits, aligned to the next byte.
     pub fn increment_byte_aligned(self, bits: u16) -> Self {
-        let inc_bytes = (bits + 7) / 8;
+        let inc_bytes = bits.div_ceil(8);
 
         self.increment_inner(0, inc_bytes)
     }
@@ -85,6 +85,14 @@ impl PdiSegment {
     // }
 }
 
+/// Extend an optional logical address range to also cover `other`.
+pub fn extend_logical_range(range: Option<Range<u32>>, other: Range<u32>) -> Range<u32> {
+    match range {
+        Some(range) => range.start.min(other.start)..range.end.max(other.end),
+        None => other,
+    }
+}
+
 impl core::fmt::Display for PdiSegment {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if !self.bytes.is_empty() {
@@ -129,7 +137,7 @@ mod tests {
             let new = offset.increment_byte_aligned(incr_bits);
 
             let incr_bits = u32::from(incr_bits);
-            let incr_bytes = (incr_bits + 7) / 8;
+            let incr_bytes = incr_bits.div_ceil(8);
 
             assert_eq!(
                 new.start_address,
@@ -0,0 +1,22 @@
+//! Software computed Ethernet frame check sequence (FCS).
+//!
+//! Network interfaces normally append and strip the FCS in hardware, so EtherCrab never sees it.
+//! Virtual and loopback interfaces used for testing may not, in which case this module can be
+//! used to add it to sent frames and check it on received frames.
+
+/// Ethernet FCS: CRC-32 as defined in IEEE 802.3.
+const ETHERNET_FCS: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+/// Compute the FCS of the given Ethernet frame, not including any existing FCS.
+pub(crate) fn compute(frame: &[u8]) -> [u8; super::FCS_RESERVED_LEN] {
+    ETHERNET_FCS.checksum(frame).to_le_bytes()
+}
+
+/// Check the FCS at the end of a received Ethernet frame, returning the frame without it.
+///
+/// Returns `None` if the frame is too short to hold an FCS or the FCS is invalid.
+pub(crate) fn verify(frame: &[u8]) -> Option<&[u8]> {
+    let (data, fcs) = frame.split_at_checked(frame.len().checked_sub(super::FCS_RESERVED_LEN)?)?;
+
+    (compute(data) == fcs).then_some(data)
+}
@@ -1,24 +1,30 @@
 use crate::{
     Command, PduLoop,
     error::PduError,
-    fmt,
+    fmt, frame_layout,
     generate::write_packed,
     pdu_loop::{
-        frame_element::{FrameBox, FrameElement, FrameState, receiving_frame::ReceiveFrameFut},
+        frame_element::{
+            FrameBox, FrameElement, FrameState,
+            receiving_frame::{MAX_BUSY_TIMEOUTS, ReceiveFrameFut, ReceiveFrameFutInner},
+        },
         frame_header::EthercatFrameHeader,
         pdu_flags::PduFlags,
         pdu_header::PduHeader,
     },
+    timer_factory::{Clock, PduClock},
 };
-use core::{ptr::NonNull, sync::atomic::AtomicU8, time::Duration};
-use ethercrab_wire::{
-    EtherCrabWireRead, EtherCrabWireSized, EtherCrabWireWrite, EtherCrabWireWriteSized,
+use core::{
+    ptr::NonNull,
+    sync::atomic::{AtomicU8, AtomicU32, Ordering},
+    time::Duration,
 };
+use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireWrite, EtherCrabWireWriteSized};
 
 /// A frame in a freshly allocated state.
 ///
 /// This typestate may only be created by
-/// [`alloc_frame`](crate::pdu_loop::storage::PduStorageRef::alloc_frame).
+/// [`PduLoop::alloc_frame`](crate::PduLoop::alloc_frame).
 #[derive(Debug)]
 pub struct CreatedFrame<'sto> {
     inner: FrameBox<'sto>,
@@ -27,18 +33,16 @@ pub struct CreatedFrame<'sto> {
     ///
     /// Used for updating the `more_follows` flag when pushing a new PDU.
     last_header_location: Option<usize>,
+    /// Shared counter of all PDUs pushed into frames, for [`PduStats`](crate::pdu_loop::storage::PduStats).
+    pdus_pushed: &'sto AtomicU32,
 }
 
 impl<'sto> CreatedFrame<'sto> {
-    /// The size of a completely empty PDU.
-    ///
-    /// Includes header and 2 bytes for working counter.
-    pub const PDU_OVERHEAD_BYTES: usize = PduHeader::PACKED_LEN + 2;
-
     pub(in crate::pdu_loop) fn claim_created(
         frame: NonNull<FrameElement<0>>,
         frame_index: u8,
         pdu_idx: &'sto AtomicU8,
+        pdus_pushed: &'sto AtomicU32,
         frame_data_len: usize,
     ) -> Result<Self, PduError> {
         let frame = unsafe { FrameElement::claim_created(frame, frame_index)? };
@@ -51,54 +55,91 @@ impl<'sto> CreatedFrame<'sto> {
             inner,
             pdu_count: 0,
             last_header_location: None,
+            pdus_pushed,
         })
     }
 
+    /// The index of the storage slot holding this frame.
     pub fn storage_slot_index(&self) -> u8 {
         self.inner.storage_slot_index()
     }
 
+    /// Whether no PDUs have been pushed into this frame yet.
     pub fn is_empty(&self) -> bool {
         self.pdu_count == 0
     }
 
+    /// Zero the frame buffer when it is released, whether that's after a response is received or
+    /// if this frame is dropped without being sent.
+    pub(crate) fn set_scrub(&mut self) {
+        self.inner.set_scrub();
+    }
+
     /// The frame has been initialised, filled with a data payload (if required), and is now ready
     /// to be sent.
     ///
     /// This method returns a future that should be fulfilled when a response to the sent frame is
     /// received.
     pub fn mark_sendable(
-        mut self,
+        self,
         pdu_loop: &'sto PduLoop<'sto>,
         timeout: Duration,
         retries: usize,
     ) -> ReceiveFrameFut<'sto> {
+        ReceiveFrameFut::new(self.mark_sendable_with_clock(
+            pdu_loop,
+            timeout,
+            retries,
+            PduClock::default(),
+        ))
+    }
+
+    /// [`mark_sendable`](CreatedFrame::mark_sendable), but with timeouts measured by the given
+    /// clock.
+    pub(in crate::pdu_loop) fn mark_sendable_with_clock<C: Clock>(
+        mut self,
+        pdu_loop: &'sto PduLoop<'sto>,
+        timeout: Duration,
+        retries: usize,
+        clock: C,
+    ) -> ReceiveFrameFutInner<'sto, C> {
         EthercatFrameHeader::pdu(self.inner.pdu_payload_len() as u16)
             .pack_to_slice_unchecked(self.inner.ecat_frame_header_mut());
 
         self.inner.set_state(FrameState::Sendable);
 
-        ReceiveFrameFut {
+        ReceiveFrameFutInner {
             frame: Some(self.inner),
             pdu_loop,
-            timeout_timer: crate::timer_factory::timer(timeout),
+            timeout_timer: clock.timer(timeout),
+            clock,
             timeout,
             retries_left: retries,
+            busy_timeouts_left: MAX_BUSY_TIMEOUTS,
         }
     }
 
     /// Push a PDU into this frame, consuming as much space as possible.
     ///
-    /// Returns the number of bytes from the given `data` that were written into the frame, or
-    /// `None` if the input slice is empty or the frame is full.
+    /// The PDU length is the larger of `bytes.len()` and `len_override`, capped to the space
+    /// remaining in the frame. As with [`push_pdu`](CreatedFrame::push_pdu), any part of the PDU not
+    /// covered by `bytes` is sent as zeros, so an override longer than `bytes` can be used to read
+    /// more data than is written, e.g. for a large `LRD`.
+    ///
+    /// Returns the PDU payload length that was allocated in the frame, or `None` if there is
+    /// nothing to push or the frame is full. The first `min(len, bytes.len())` bytes of `bytes`
+    /// were written into the PDU.
     pub(crate) fn push_pdu_slice_rest(
         &mut self,
         command: Command,
         bytes: &[u8],
+        len_override: Option<u16>,
     ) -> Result<Option<(usize, PduResponseHandle)>, PduError> {
         let consumed = self.inner.pdu_payload_len();
 
-        if bytes.is_empty() {
+        let requested_len = len_override.map_or(bytes.len(), |l| usize::from(l).max(bytes.len()));
+
+        if requested_len == 0 {
             return Ok(None);
         }
 
@@ -108,21 +149,21 @@ impl<'sto> CreatedFrame<'sto> {
             .pdu_buf()
             .len()
             .saturating_sub(consumed)
-            .saturating_sub(Self::PDU_OVERHEAD_BYTES);
+            .saturating_sub(frame_layout::PDU_OVERHEAD);
 
         if max_bytes == 0 {
-            fmt::trace!("Pushed 0 bytes of {} into PDU", bytes.len());
+            fmt::trace!("Pushed 0 bytes of {} into PDU", requested_len);
 
             return Ok(None);
         }
 
-        let sub_slice_len = max_bytes.min(bytes.packed_len());
+        let sub_slice_len = max_bytes.min(requested_len);
 
-        let bytes = &bytes[0..sub_slice_len];
+        let bytes = &bytes[0..sub_slice_len.min(bytes.len())];
 
         let flags = PduFlags::new(sub_slice_len as u16, false);
 
-        let alloc_size = sub_slice_len + Self::PDU_OVERHEAD_BYTES;
+        let alloc_size = sub_slice_len + frame_layout::PDU_OVERHEAD;
 
         let buf_range = consumed..(consumed + alloc_size);
 
@@ -135,7 +176,7 @@ impl<'sto> CreatedFrame<'sto> {
             self.inner.storage_slot_index(),
             command,
             sub_slice_len,
-            Self::PDU_OVERHEAD_BYTES,
+            frame_layout::PDU_OVERHEAD,
             buf_range
         );
 
@@ -178,6 +219,8 @@ impl<'sto> CreatedFrame<'sto> {
 
         self.pdu_count += 1;
 
+        self.pdus_pushed.fetch_add(1, Ordering::Relaxed);
+
         // Frame was added successfully, so now we can update the previous PDU `more_follows` flag to true.
         if let Some(last_header_location) = self.last_header_location.as_mut() {
             // Flags start at 6th bit of header
@@ -213,7 +256,7 @@ impl<'sto> CreatedFrame<'sto> {
     }
 
     pub(crate) fn can_push_pdu_payload(&self, packed_len: usize) -> bool {
-        let alloc_size = packed_len + Self::PDU_OVERHEAD_BYTES;
+        let alloc_size = packed_len + frame_layout::PDU_OVERHEAD;
 
         let start_byte = self.inner.pdu_payload_len();
 
@@ -222,6 +265,12 @@ impl<'sto> CreatedFrame<'sto> {
 
     /// Push a PDU into this frame.
     ///
+    /// The PDU payload length is the larger of `data.packed_len()` and `len_override`. An override
+    /// shorter than `data` is ignored so data is never truncated. The frame buffer is zeroed when
+    /// the frame is allocated, so any bytes after `data` are sent as zeros. This is how reads are
+    /// made: send a PDU with no data and a length override of the number of bytes to read, and the
+    /// response will contain that many bytes.
+    ///
     /// # Errors
     ///
     /// Returns [`PduError::TooLong`] if the remaining space in the frame is not enough to hold the
@@ -231,6 +280,76 @@ impl<'sto> CreatedFrame<'sto> {
         command: Command,
         data: impl EtherCrabWireWrite,
         len_override: Option<u16>,
+    ) -> Result<PduResponseHandle, PduError> {
+        // Establish mapping between this PDU index and the Ethernet frame it's being put in
+        let pdu_idx = self.inner.next_pdu_idx();
+
+        self.push_pdu_inner(command, data, len_override, pdu_idx, true)
+    }
+
+    /// Push a PDU into this frame with an explicit PDU index instead of taking the next one from
+    /// the shared counter.
+    ///
+    /// This is intended for deterministic tests and for reproducing SubDevice bugs that depend on
+    /// a particular index value. It otherwise behaves the same as [`push_pdu`](Self::push_pdu).
+    ///
+    /// # Collisions
+    ///
+    /// Indices given here are not reserved: the shared counter used by
+    /// [`push_pdu`](Self::push_pdu) keeps incrementing regardless and will hand out the same value
+    /// again once it wraps. If a frame in flight already uses `index` as its first PDU index, a
+    /// response may be matched to the wrong frame and is then rejected, or the frame times out.
+    /// Only use this method when nothing else is sending through the same
+    /// [`PduStorage`](crate::PduStorage), or pick indices that cannot be in flight at the same time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PduError::TooLong`] if the remaining space in the frame is not enough to hold the
+    /// new PDU.
+    #[cfg(any(test, feature = "manual-pdu-index"))]
+    pub fn push_pdu_with_index(
+        &mut self,
+        command: Command,
+        data: impl EtherCrabWireWrite,
+        index: u8,
+    ) -> Result<PduResponseHandle, PduError> {
+        self.push_pdu_inner(command, data, None, index, true)
+    }
+
+    /// Push a PDU into this frame without setting the `more_follows` flag of the PDU before it.
+    ///
+    /// [`push_pdu`](Self::push_pdu) marks the previous PDU as having more PDUs following it. This
+    /// method leaves the previous PDU's flag as it was written, so the chain ends before the PDU
+    /// pushed here. SubDevices stop processing the frame at the end of the chain, so this can be
+    /// used to build frames that test how a SubDevice handles a chain that ends early. The PDU
+    /// pushed here is still chained to any PDU pushed after it with [`push_pdu`](Self::push_pdu).
+    ///
+    /// The response to this PDU will likely have a working counter of zero, as no SubDevice
+    /// processes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PduError::TooLong`] if the remaining space in the frame is not enough to hold the
+    /// new PDU.
+    #[cfg(any(test, feature = "manual-pdu-index"))]
+    pub fn push_pdu_terminal(
+        &mut self,
+        command: Command,
+        data: impl EtherCrabWireWrite,
+        len_override: Option<u16>,
+    ) -> Result<PduResponseHandle, PduError> {
+        let pdu_idx = self.inner.next_pdu_idx();
+
+        self.push_pdu_inner(command, data, len_override, pdu_idx, false)
+    }
+
+    fn push_pdu_inner(
+        &mut self,
+        command: Command,
+        data: impl EtherCrabWireWrite,
+        len_override: Option<u16>,
+        pdu_idx: u8,
+        chain: bool,
     ) -> Result<PduResponseHandle, PduError> {
         let data_length_usize =
             len_override.map_or(data.packed_len(), |l| usize::from(l).max(data.packed_len()));
@@ -239,7 +358,7 @@ impl<'sto> CreatedFrame<'sto> {
 
         // PDU header + data + working counter (space is required for the response value - we never
         // actually write it)
-        let alloc_size = data_length_usize + Self::PDU_OVERHEAD_BYTES;
+        let alloc_size = data_length_usize + frame_layout::PDU_OVERHEAD;
 
         // The number of payload bytes already consumed in this frame (e.g. from prior PDU
         // insertions). This is the start byte of the current PDU we want to push.
@@ -248,9 +367,6 @@ impl<'sto> CreatedFrame<'sto> {
         // Comprises PDU header, body, working counter
         let buf_range = start_byte..(start_byte + alloc_size);
 
-        // Establish mapping between this PDU index and the Ethernet frame it's being put in
-        let pdu_idx = self.inner.next_pdu_idx();
-
         fmt::trace!(
             "Write PDU {:#04x} into frame index {} ({}, {} bytes at {:?})",
             pdu_idx,
@@ -299,22 +415,27 @@ impl<'sto> CreatedFrame<'sto> {
 
         self.pdu_count += 1;
 
-        // Frame was added successfully, so now we can update the previous PDU `more_follows` flag to true.
-        if let Some(last_header_location) = self.last_header_location.as_mut() {
-            // Flags start at 6th bit of header
-            let flags_offset = 6usize;
+        self.pdus_pushed.fetch_add(1, Ordering::Relaxed);
 
-            let last_flags_buf = fmt::unwrap_opt!(
-                self.inner
-                    .pdu_buf_mut()
-                    .get_mut((*last_header_location + flags_offset)..)
-            );
+        // Frame was added successfully, so now we can update the previous PDU `more_follows` flag to
+        // true, unless the chain should end before this PDU.
+        if let Some(last_header_location) = self.last_header_location.as_mut() {
+            if chain {
+                // Flags start at 6th bit of header
+                let flags_offset = 6usize;
+
+                let last_flags_buf = fmt::unwrap_opt!(
+                    self.inner
+                        .pdu_buf_mut()
+                        .get_mut((*last_header_location + flags_offset)..)
+                );
 
-            let mut last_flags = fmt::unwrap!(PduFlags::unpack_from_slice(last_flags_buf));
+                let mut last_flags = fmt::unwrap!(PduFlags::unpack_from_slice(last_flags_buf));
 
-            last_flags.more_follows = true;
+                last_flags.more_follows = true;
 
-            last_flags.pack_to_slice_unchecked(last_flags_buf);
+                last_flags.pack_to_slice_unchecked(last_flags_buf);
+            }
 
             // Previous header is now the one we just inserted
             *last_header_location = buf_range.start;
@@ -335,7 +456,13 @@ impl Drop for CreatedFrame<'_> {
     fn drop(&mut self) {
         // ONLY free the frame if it's still in created state. If it's been moved into
         // sending/sent/receiving/etc, we must leave it alone.
-        let _ = self.inner.swap_state(FrameState::Created, FrameState::None);
+        if self.inner.state() == FrameState::Created {
+            // Frame was never sent, so any payload written into it must be scrubbed here.
+            self.inner.scrub_if_marked();
+            self.inner.clear_first_pdu();
+
+            let _ = self.inner.swap_state(FrameState::Created, FrameState::None);
+        }
     }
 }
 
@@ -348,27 +475,39 @@ impl Drop for CreatedFrame<'_> {
 // a 'static bound.
 unsafe impl Send for CreatedFrame<'_> {}
 
-#[derive(Debug)]
+/// A handle to a PDU pushed into a frame, used to find its response in the received frame.
+#[derive(Debug, Copy, Clone)]
 #[cfg_attr(test, derive(Eq, PartialEq))]
 pub struct PduResponseHandle {
     // Might want this in the future
     #[allow(unused)]
-    pub index_in_frame: u8,
+    pub(crate) index_in_frame: u8,
 
     /// PDU wire index and command used to validate response match.
-    pub pdu_idx: u8,
-    pub command_code: u8,
+    pub(crate) pdu_idx: u8,
+    pub(crate) command_code: u8,
 
     /// The number of bytes allocated for the PDU header, payload and WKC in the frame.
-    pub alloc_size: usize,
+    pub(crate) alloc_size: usize,
+}
+
+impl PduResponseHandle {
+    /// Whether this handle refers to a [`Command::Nop`] PDU.
+    ///
+    /// NOP PDUs only pad a frame. SubDevices return them untouched, so their response always
+    /// extracts as an empty payload with a working counter of zero. As the NOP command code is
+    /// `0`, which is indistinguishable from an uninitialised header, the echoed command is not
+    /// checked either.
+    pub(crate) fn is_nop(&self) -> bool {
+        self.command_code == Command::Nop.code()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        PduStorage, RegisterAddress,
-        ethernet::EthernetFrame,
+        PduStorage, Reads, RegisterAddress,
         pdu_loop::frame_element::{AtomicFrameState, FIRST_PDU_EMPTY, FrameElement},
     };
     use atomic_waker::AtomicWaker;
@@ -377,6 +516,7 @@ mod tests {
         ptr::NonNull,
         sync::atomic::{AtomicU8, AtomicU16},
     };
+    use ethercrab_wire::EtherCrabWireSized;
 
     #[test]
     fn chunked_send() {
@@ -387,6 +527,7 @@ mod tests {
         const BUF_LEN: usize = PduStorage::element_size(MAX_PAYLOAD);
 
         let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
 
         let frames = UnsafeCell::new([FrameElement {
             storage_slot_index: 0xab,
@@ -395,12 +536,15 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         }]);
 
         let mut created = CreatedFrame::claim_created(
             unsafe { NonNull::new_unchecked(frames.get().cast()) },
             0xab,
             &pdu_idx,
+            &pdus_pushed,
             BUF_LEN,
         )
         .expect("Claim created");
@@ -412,7 +556,7 @@ mod tests {
         let big_frame = [0xaau8; MAX_PAYLOAD * 2];
 
         let (rest, _handle) = created
-            .push_pdu_slice_rest(Command::fpwr(0x1000, 0x0918).into(), &big_frame)
+            .push_pdu_slice_rest(Command::fpwr(0x1000, 0x0918).into(), &big_frame, None)
             .expect("Should not fail")
             .unwrap();
 
@@ -426,6 +570,7 @@ mod tests {
         const BUF_LEN: usize = 16;
 
         let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
 
         let frames = UnsafeCell::new([FrameElement {
             storage_slot_index: 0xab,
@@ -434,12 +579,15 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         }]);
 
         let mut created = CreatedFrame::claim_created(
             unsafe { NonNull::new_unchecked(frames.get().cast()) },
             0xab,
             &pdu_idx,
+            &pdus_pushed,
             BUF_LEN,
         )
         .expect("Claim created");
@@ -449,6 +597,67 @@ mod tests {
         assert_eq!(handle.unwrap_err(), PduError::TooLong);
     }
 
+    #[test]
+    fn terminal_pdu_leaves_more_follows() {
+        crate::test_logger();
+
+        const BUF_LEN: usize = 64;
+
+        let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
+
+        let frames = UnsafeCell::new([FrameElement {
+            storage_slot_index: 0xab,
+            status: AtomicFrameState::new(FrameState::None),
+            waker: AtomicWaker::default(),
+            ethernet_frame: [0u8; BUF_LEN],
+            pdu_payload_len: 0,
+            first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
+        }]);
+
+        let mut created = CreatedFrame::claim_created(
+            unsafe { NonNull::new_unchecked(frames.get().cast()) },
+            0xab,
+            &pdu_idx,
+            &pdus_pushed,
+            BUF_LEN,
+        )
+        .expect("Claim created");
+
+        created
+            .push_pdu(Command::fpwr(0x1000, 0x0918).into(), (), None)
+            .expect("First PDU");
+        created
+            .push_pdu_terminal(Command::fpwr(0x1001, 0x0918).into(), (), None)
+            .expect("Terminal PDU");
+        created
+            .push_pdu(Command::fpwr(0x1002, 0x0918).into(), (), None)
+            .expect("Last PDU");
+
+        const FLAGS_OFFSET: usize = 6;
+        // Header plus working counter, as no PDU has any data
+        const PDU_LEN: usize = PduHeader::PACKED_LEN + 2;
+
+        // Chain ends at the first PDU
+        assert_eq!(
+            created.inner.pdu_buf()[FLAGS_OFFSET..][..2],
+            PduFlags::new(0, false).pack()
+        );
+
+        // Terminal PDU is chained to the one after it
+        assert_eq!(
+            created.inner.pdu_buf()[PDU_LEN + FLAGS_OFFSET..][..2],
+            PduFlags::new(0, true).pack()
+        );
+
+        assert_eq!(
+            created.inner.pdu_buf()[PDU_LEN * 2 + FLAGS_OFFSET..][..2],
+            PduFlags::new(0, false).pack()
+        );
+    }
+
     #[test]
     fn auto_more_follows() {
         crate::test_logger();
@@ -456,6 +665,7 @@ mod tests {
         const BUF_LEN: usize = 64;
 
         let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
 
         let frames = UnsafeCell::new([FrameElement {
             storage_slot_index: 0xab,
@@ -464,12 +674,15 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         }]);
 
         let mut created = CreatedFrame::claim_created(
             unsafe { NonNull::new_unchecked(frames.get().cast()) },
             0xab,
             &pdu_idx,
+            &pdus_pushed,
             BUF_LEN,
         )
         .expect("Claim created");
@@ -505,9 +718,10 @@ mod tests {
     fn push_rest_too_long() {
         crate::test_logger();
 
-        const BUF_LEN: usize = 32;
+        const BUF_LEN: usize = 32 + frame_layout::FCS_LEN;
 
         let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
 
         let frames = UnsafeCell::new([FrameElement {
             storage_slot_index: 0xab,
@@ -516,26 +730,30 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         }]);
 
         let mut created = CreatedFrame::claim_created(
             unsafe { NonNull::new_unchecked(frames.get().cast()) },
             0xab,
             &pdu_idx,
+            &pdus_pushed,
             BUF_LEN,
         )
         .expect("Claim created");
 
         let data = [0xaau8; 128];
 
-        let res = created.push_pdu_slice_rest(Command::Nop, &data);
+        let res = created.push_pdu_slice_rest(Command::Nop, &data, None);
 
         // 32 byte frame contains all the headers with a little bit left over for writing some data
         // into.
         let expected_written = BUF_LEN
-            - CreatedFrame::PDU_OVERHEAD_BYTES
-            - EthercatFrameHeader::header_len()
-            - EthernetFrame::<&[u8]>::header_len();
+            - frame_layout::PDU_OVERHEAD
+            - frame_layout::ETHERCAT_HEADER_LEN
+            - frame_layout::ETHERNET_HEADER_LEN
+            - frame_layout::FCS_LEN;
 
         // Just double checking
         assert_eq!(expected_written, 4);
@@ -549,13 +767,13 @@ mod tests {
                     pdu_idx: 0,
                     command_code: 0,
                     // The size of this PDU with a 4 byte payload (plus 12 byte header)
-                    alloc_size: 4 + CreatedFrame::PDU_OVERHEAD_BYTES
+                    alloc_size: 4 + frame_layout::PDU_OVERHEAD
                 }
             )))
         );
 
         // Can't push anything else
-        let res = created.push_pdu_slice_rest(Command::Nop, &data);
+        let res = created.push_pdu_slice_rest(Command::Nop, &data, None);
 
         assert_eq!(res, Ok(None));
     }
@@ -564,9 +782,10 @@ mod tests {
     fn push_rest_after_dc_sync() {
         crate::test_logger();
 
-        const BUF_LEN: usize = 64;
+        const BUF_LEN: usize = 64 + frame_layout::FCS_LEN;
 
         let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
 
         let frames = UnsafeCell::new([FrameElement {
             storage_slot_index: 0xab,
@@ -575,12 +794,15 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         }]);
 
         let mut created = CreatedFrame::claim_created(
             unsafe { NonNull::new_unchecked(frames.get().cast()) },
             0xab,
             &pdu_idx,
+            &pdus_pushed,
             BUF_LEN,
         )
         .expect("Claim created");
@@ -599,19 +821,20 @@ mod tests {
         let data = [0xaau8; 128];
 
         let remaining = BUF_LEN
-            - EthernetFrame::<&[u8]>::header_len()
-            - EthercatFrameHeader::header_len()
-            - dc_handle.alloc_size;
+            - frame_layout::ETHERNET_HEADER_LEN
+            - frame_layout::ETHERCAT_HEADER_LEN
+            - dc_handle.alloc_size
+            - frame_layout::FCS_LEN;
 
         // Just double checking
         assert_eq!(remaining, 28);
 
-        let res = created.push_pdu_slice_rest(Command::Nop, &data);
+        let res = created.push_pdu_slice_rest(Command::Nop, &data, None);
 
         assert_eq!(
             res,
             Ok(Some((
-                remaining - CreatedFrame::PDU_OVERHEAD_BYTES,
+                remaining - frame_layout::PDU_OVERHEAD,
                 PduResponseHandle {
                     index_in_frame: 1,
                     pdu_idx: 1,
@@ -622,7 +845,7 @@ mod tests {
         );
 
         // Can't push anything else
-        let res = created.push_pdu_slice_rest(Command::Nop, &data);
+        let res = created.push_pdu_slice_rest(Command::Nop, &data, None);
 
         assert_eq!(res, Ok(None));
     }
@@ -634,6 +857,7 @@ mod tests {
         const BUF_LEN: usize = 64;
 
         let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
 
         let frames = UnsafeCell::new([FrameElement {
             storage_slot_index: 0xab,
@@ -642,12 +866,15 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         }]);
 
         let mut created = CreatedFrame::claim_created(
             unsafe { NonNull::new_unchecked(frames.get().cast()) },
             0xab,
             &pdu_idx,
+            &pdus_pushed,
             BUF_LEN,
         )
         .expect("Claim created");
@@ -655,9 +882,197 @@ mod tests {
         assert_eq!(
             created.push_pdu_slice_rest(
                 Command::frmw(0x1000, RegisterAddress::DcSystemTime.into()).into(),
-                &[]
+                &[],
+                None
             ),
             Ok(None)
         );
     }
+
+    #[test]
+    fn push_read_longer_than_data() {
+        crate::test_logger();
+
+        const BUF_LEN: usize = 64;
+
+        let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
+
+        // Garbage in the buffer must be cleared when the frame is claimed
+        let frames = UnsafeCell::new([FrameElement {
+            storage_slot_index: 0xab,
+            status: AtomicFrameState::new(FrameState::None),
+            waker: AtomicWaker::default(),
+            ethernet_frame: [0xffu8; BUF_LEN],
+            pdu_payload_len: 0,
+            first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
+        }]);
+
+        let mut created = CreatedFrame::claim_created(
+            unsafe { NonNull::new_unchecked(frames.get().cast()) },
+            0xab,
+            &pdu_idx,
+            &pdus_pushed,
+            BUF_LEN,
+        )
+        .expect("Claim created");
+
+        let handle = created
+            .push_pdu(
+                Command::fprd(0x1000, 0x0130).into(),
+                [0xaau8, 0xbb],
+                Some(6),
+            )
+            .expect("Push");
+
+        assert_eq!(handle.alloc_size, 6 + frame_layout::PDU_OVERHEAD);
+
+        assert_eq!(
+            &created.inner.pdu_buf()[PduHeader::PACKED_LEN..][..8],
+            // Data, zero padding up to override length, then working counter
+            &[0xaa, 0xbb, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn push_override_shorter_than_data() {
+        crate::test_logger();
+
+        const BUF_LEN: usize = 64;
+
+        let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
+
+        let frames = UnsafeCell::new([FrameElement {
+            storage_slot_index: 0xab,
+            status: AtomicFrameState::new(FrameState::None),
+            waker: AtomicWaker::default(),
+            ethernet_frame: [0u8; BUF_LEN],
+            pdu_payload_len: 0,
+            first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
+        }]);
+
+        let mut created = CreatedFrame::claim_created(
+            unsafe { NonNull::new_unchecked(frames.get().cast()) },
+            0xab,
+            &pdu_idx,
+            &pdus_pushed,
+            BUF_LEN,
+        )
+        .expect("Claim created");
+
+        let handle = created
+            .push_pdu(
+                Command::fpwr(0x1000, 0x0130).into(),
+                [0x01u8, 0x02, 0x03, 0x04],
+                Some(2),
+            )
+            .expect("Push");
+
+        // Data is never truncated by a shorter override
+        assert_eq!(handle.alloc_size, 4 + frame_layout::PDU_OVERHEAD);
+    }
+
+    #[test]
+    fn push_rest_len_override() {
+        crate::test_logger();
+
+        const BUF_LEN: usize = 32 + frame_layout::FCS_LEN;
+
+        let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
+
+        let frames = UnsafeCell::new([FrameElement {
+            storage_slot_index: 0xab,
+            status: AtomicFrameState::new(FrameState::None),
+            waker: AtomicWaker::default(),
+            ethernet_frame: [0xffu8; BUF_LEN],
+            pdu_payload_len: 0,
+            first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
+        }]);
+
+        let mut created = CreatedFrame::claim_created(
+            unsafe { NonNull::new_unchecked(frames.get().cast()) },
+            0xab,
+            &pdu_idx,
+            &pdus_pushed,
+            BUF_LEN,
+        )
+        .expect("Claim created");
+
+        // Only 4 payload bytes fit in this frame, so the 100 byte read is capped
+        let res = created.push_pdu_slice_rest(
+            Reads::Lrd { address: 0x1234 }.into(),
+            &[0xaa, 0xbb],
+            Some(100),
+        );
+
+        assert_eq!(
+            res,
+            Ok(Some((
+                4,
+                PduResponseHandle {
+                    index_in_frame: 0,
+                    pdu_idx: 0,
+                    command_code: 0x0a,
+                    alloc_size: 4 + frame_layout::PDU_OVERHEAD
+                }
+            )))
+        );
+
+        assert_eq!(
+            &created.inner.pdu_buf()[PduHeader::PACKED_LEN..][..6],
+            &[0xaa, 0xbb, 0x00, 0x00, 0x00, 0x00]
+        );
+
+        // Frame is full
+        assert_eq!(
+            created.push_pdu_slice_rest(Reads::Lrd { address: 0x1234 }.into(), &[], Some(4)),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn push_rest_empty_with_override() {
+        crate::test_logger();
+
+        const BUF_LEN: usize = 64;
+
+        let pdu_idx = AtomicU8::new(0);
+        let pdus_pushed = AtomicU32::new(0);
+
+        let frames = UnsafeCell::new([FrameElement {
+            storage_slot_index: 0xab,
+            status: AtomicFrameState::new(FrameState::None),
+            waker: AtomicWaker::default(),
+            ethernet_frame: [0u8; BUF_LEN],
+            pdu_payload_len: 0,
+            first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
+        }]);
+
+        let mut created = CreatedFrame::claim_created(
+            unsafe { NonNull::new_unchecked(frames.get().cast()) },
+            0xab,
+            &pdu_idx,
+            &pdus_pushed,
+            BUF_LEN,
+        )
+        .expect("Claim created");
+
+        let res = created
+            .push_pdu_slice_rest(Reads::Lrd { address: 0x1234 }.into(), &[], Some(8))
+            .expect("Push")
+            .expect("Some");
+
+        assert_eq!(res.0, 8);
+        assert_eq!(res.1.alloc_size, 8 + frame_layout::PDU_OVERHEAD);
+    }
 }
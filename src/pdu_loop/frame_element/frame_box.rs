@@ -1,7 +1,8 @@
 use crate::{
     ETHERCAT_ETHERTYPE, MAINDEVICE_ADDR,
-    ethernet::{EthernetAddress, EthernetFrame},
+    ethernet::{EthernetAddress, EthernetFrame, VLAN_ETHERTYPE, VLAN_TAG_LEN, VlanTag},
     pdu_loop::{
+        FCS_RESERVED_LEN, MIN_ETHERNET_FRAME_LEN,
         frame_element::{FrameElement, FrameState},
         frame_header::EthercatFrameHeader,
     },
@@ -11,7 +12,7 @@ use core::{
     fmt::Debug,
     marker::PhantomData,
     ptr::{NonNull, addr_of, addr_of_mut},
-    sync::atomic::{AtomicU8, Ordering},
+    sync::atomic::{AtomicU8, Ordering, compiler_fence},
     task::Waker,
 };
 use ethercrab_wire::EtherCrabWireSized;
@@ -29,15 +30,28 @@ pub struct FrameBox<'sto> {
 
 impl Debug for FrameBox<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let data = self.pdu_buf();
+        let mut s = f.debug_struct("FrameBox");
+
+        s.field("state", unsafe {
+            &(*addr_of!((*self.frame.as_ptr()).status))
+        })
+        .field("frame_index", &self.storage_slot_index())
+        .field("first_pdu", &self.first_pdu())
+        .field("pdu_payload_len", &self.pdu_payload_len())
+        .field("max_len", &self.max_len);
+
+        // Payloads can be large and may contain process data, so they're only printed on request.
+        #[cfg(feature = "frame-dumps")]
+        {
+            let data = self.pdu_buf();
+
+            s.field(
+                "data_hex",
+                &crate::frame_dump::HexDump(data.get(..self.pdu_payload_len()).unwrap_or(data)),
+            );
+        }
 
-        f.debug_struct("FrameBox")
-            .field("state", unsafe {
-                &(*addr_of!((*self.frame.as_ptr()).status))
-            })
-            .field("frame_index", &self.storage_slot_index())
-            .field("data_hex", &format_args!("{:02x?}", data))
-            .finish()
+        s.finish()
     }
 }
 
@@ -65,6 +79,10 @@ impl<'sto> FrameBox<'sto> {
                 .store(FIRST_PDU_EMPTY, Ordering::Relaxed);
 
             addr_of_mut!((*self.frame.as_ptr()).pdu_payload_len).write(0);
+
+            addr_of_mut!((*self.frame.as_ptr()).scrub).write(false);
+
+            addr_of_mut!((*self.frame.as_ptr()).vlan_tagged).write(false);
         }
 
         let mut ethernet_frame = self.ethernet_frame_mut();
@@ -123,7 +141,13 @@ impl<'sto> FrameBox<'sto> {
         let pdu_payload_start =
             EthernetFrame::<&[u8]>::header_len() + EthercatFrameHeader::header_len();
 
-        unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr(), self.max_len - pdu_payload_start) }
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                ptr.as_ptr(),
+                self.max_len
+                    .saturating_sub(pdu_payload_start + FCS_RESERVED_LEN),
+            )
+        }
     }
 
     /// Get frame payload area. This contains one or more PDUs and is located after the EtherCAT
@@ -134,7 +158,13 @@ impl<'sto> FrameBox<'sto> {
         let pdu_payload_start =
             EthernetFrame::<&[u8]>::header_len() + EthercatFrameHeader::header_len();
 
-        unsafe { core::slice::from_raw_parts(ptr.as_ptr(), self.max_len - pdu_payload_start) }
+        unsafe {
+            core::slice::from_raw_parts(
+                ptr.as_ptr(),
+                self.max_len
+                    .saturating_sub(pdu_payload_start + FCS_RESERVED_LEN),
+            )
+        }
     }
 
     fn ethernet_frame_mut(&mut self) -> EthernetFrame<&mut [u8]> {
@@ -157,11 +187,93 @@ impl<'sto> FrameBox<'sto> {
         }
     }
 
+    /// The length of the Ethernet frame up to the end of the last PDU, including the VLAN tag if
+    /// one was inserted but excluding any FCS.
+    pub fn frame_len(&self) -> usize {
+        let len = EthernetFrame::<&[u8]>::buffer_len(
+            EthercatFrameHeader::PACKED_LEN + self.pdu_payload_len(),
+        );
+
+        if self.is_vlan_tagged() {
+            len + VLAN_TAG_LEN
+        } else {
+            len
+        }
+    }
+
+    pub fn is_vlan_tagged(&self) -> bool {
+        unsafe { *addr_of!((*self.frame.as_ptr()).vlan_tagged) }
+    }
+
+    /// Insert an 802.1Q VLAN tag after the source MAC address, moving the EtherType and payload
+    /// back to make room.
+    ///
+    /// Does nothing if the frame is already tagged. Returns `false` if the tagged frame would not
+    /// fit in the frame buffer.
+    pub fn insert_vlan_tag(&mut self, tag: VlanTag) -> bool {
+        if self.is_vlan_tagged() {
+            return true;
+        }
+
+        let len = self.frame_len();
+
+        if len + VLAN_TAG_LEN + FCS_RESERVED_LEN > self.max_len {
+            return false;
+        }
+
+        let ethertype_start = EthernetFrame::<&[u8]>::header_len() - 2;
+
+        let frame = self.ethernet_frame_mut().into_inner();
+
+        frame.copy_within(ethertype_start..len, ethertype_start + VLAN_TAG_LEN);
+
+        frame[ethertype_start..(ethertype_start + 2)]
+            .copy_from_slice(&VLAN_ETHERTYPE.to_be_bytes());
+        frame[(ethertype_start + 2)..(ethertype_start + VLAN_TAG_LEN)]
+            .copy_from_slice(&tag.tci().to_be_bytes());
+
+        unsafe { addr_of_mut!((*self.frame.as_ptr()).vlan_tagged).write(true) };
+
+        true
+    }
+
+    /// The length of the frame as sent. If the `software-fcs` feature is enabled, this includes
+    /// any padding up to the minimum Ethernet frame length, and the FCS.
+    pub fn wire_len(&self) -> usize {
+        let len = self.frame_len();
+
+        if cfg!(feature = "software-fcs") {
+            len.max(MIN_ETHERNET_FRAME_LEN) + FCS_RESERVED_LEN
+        } else {
+            len
+        }
+    }
+
+    /// Pad the frame with zeros up to the minimum Ethernet frame length, then write the FCS of the
+    /// padded frame after it.
+    #[cfg(feature = "software-fcs")]
+    pub fn append_fcs(&mut self) {
+        let len = self.frame_len();
+        let padded_len = len.max(MIN_ETHERNET_FRAME_LEN);
+
+        let frame = self.ethernet_frame_mut().into_inner();
+
+        frame[len..padded_len].fill(0);
+
+        let fcs = crate::pdu_loop::fcs::compute(&frame[0..padded_len]);
+
+        frame[padded_len..(padded_len + fcs.len())].copy_from_slice(&fcs);
+    }
+
     /// Get the number of bytes consumed in the region of the frame used to store one or more PDUs.
     pub fn pdu_payload_len(&self) -> usize {
         unsafe { *addr_of!((*self.frame.as_ptr()).pdu_payload_len) }
     }
 
+    pub fn state(&self) -> FrameState {
+        unsafe { FrameElement::state(self.frame) }
+    }
+
     pub fn set_state(&self, to: FrameState) {
         unsafe { FrameElement::set_state(self.frame, to) };
     }
@@ -170,12 +282,60 @@ impl<'sto> FrameBox<'sto> {
         unsafe { FrameElement::swap_state(self.frame, from, to) }.map(|_| ())
     }
 
+    /// The index of the first PDU in this frame, if any have been pushed.
+    fn first_pdu(&self) -> Option<u8> {
+        let raw = unsafe { (*addr_of!((*self.frame.as_ptr()).first_pdu)).load(Ordering::Relaxed) };
+
+        (raw != FIRST_PDU_EMPTY).then_some(raw as u8)
+    }
+
     pub fn clear_first_pdu(&self) {
         unsafe {
             FrameElement::<0>::clear_first_pdu(self.frame);
         }
     }
 
+    /// Release a frame that was [`Abandoned`](FrameState::Abandoned) while it was held by the TX or
+    /// RX path, now that the holder is done with it.
+    pub fn release_abandoned(&self) {
+        self.scrub_if_marked();
+        self.clear_first_pdu();
+
+        let _ = self.swap_state(FrameState::Abandoned, FrameState::None);
+    }
+
+    /// Mark this frame to be zeroed when it is released back to the `None` state.
+    pub fn set_scrub(&mut self) {
+        unsafe { addr_of_mut!((*self.frame.as_ptr()).scrub).write(true) };
+    }
+
+    pub fn is_scrub_marked(&self) -> bool {
+        unsafe { *addr_of!((*self.frame.as_ptr()).scrub) }
+    }
+
+    /// Zero the entire Ethernet frame buffer if the frame was marked with
+    /// [`set_scrub`](FrameBox::set_scrub).
+    ///
+    /// This must be called before the frame is put back into the `None` state, as after that point
+    /// the storage may be claimed by another user.
+    pub fn scrub_if_marked(&self) {
+        if !self.is_scrub_marked() {
+            return;
+        }
+
+        let ptr = unsafe { FrameElement::<0>::ptr(self.frame) }.as_ptr();
+
+        // Volatile writes so the zeroing isn't elided by the optimiser: nothing reads this memory
+        // again before it's reinitialised by the next `init()`.
+        for i in 0..self.max_len {
+            unsafe { ptr.add(i).write_volatile(0) };
+        }
+
+        compiler_fence(Ordering::SeqCst);
+
+        unsafe { addr_of_mut!((*self.frame.as_ptr()).scrub).write(false) };
+    }
+
     /// Add the given number of bytes in `alloc_size` to the consumed bytes counter in the frame.
     ///
     /// Also sets the first PDU index if it hasn't already been set.
@@ -185,3 +345,54 @@ impl<'sto> FrameBox<'sto> {
         unsafe { FrameElement::<0>::set_first_pdu(self.frame, pdu_idx) };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Command, PduStorage};
+
+    #[test]
+    #[cfg(not(feature = "frame-dumps"))]
+    fn debug_omits_payload() {
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (_tx, _rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mut frame = pdu_loop.alloc_frame().unwrap();
+
+        frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), [0xa5u8; 8], None)
+            .unwrap();
+
+        let out = format!("{:?}", frame);
+
+        assert!(out.contains("pdu_payload_len: 20"), "{}", out);
+        assert!(!out.contains("a5") && !out.contains("165"), "{}", out);
+    }
+
+    #[test]
+    #[cfg(feature = "frame-dumps")]
+    fn debug_truncated_and_redacted() {
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (_tx, _rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mut frame = pdu_loop.alloc_frame().unwrap();
+
+        frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), [0xa5u8; 8], None)
+            .unwrap();
+
+        // Hide everything after the 10 byte PDU header
+        crate::frame_dump::set_redaction_hook(Some(|offset, byte| (offset < 10).then_some(byte)));
+        crate::frame_dump::set_max_bytes(12);
+
+        let out = format!("{:?}", frame);
+
+        crate::frame_dump::set_redaction_hook(None);
+        crate::frame_dump::set_max_bytes(crate::frame_dump::DEFAULT_MAX_BYTES);
+
+        assert!(
+            out.contains("data_hex: [05, 00, 00, 10, 00, 0f, 08, 00, 00, 00, **, **] (+8 bytes)"),
+            "{}",
+            out
+        );
+    }
+}
@@ -42,10 +42,14 @@ pub enum FrameState {
     /// A frame response has been received and validation/parsing is in progress.
     RxBusy = 5,
     /// Frame response parsing is complete and the returned data is now stored in the frame. The
-    /// frame and its data is ready to be returned in `Poll::Ready` of [`ReceiveFrameFut`].
+    /// frame and its data is ready to be returned to the code waiting for the response.
     RxDone = 6,
     /// The frame TX/RX is complete, but the frame memory is still held by calling code.
     RxProcessing = 7,
+    /// The code waiting for a response gave up while the frame was held by the TX or RX path. The
+    /// frame is released back to [`None`](FrameState::None) by whichever path holds it once it's
+    /// done with the frame buffer.
+    Abandoned = 8,
 }
 
 /// An individual frame state, PDU header config, and data buffer.
@@ -70,7 +74,6 @@ pub enum FrameState {
 //    FrameState::RxDone -->|"Wake future\nCalling code can now use response data"| FrameState::RxProcessing
 //    FrameState::RxProcessing -->|"Calling code is done with frame\nReceivedFrame::drop()"| FrameState::None
 //    ```
-#[derive(Debug)]
 #[repr(C)]
 pub struct FrameElement<const N: usize> {
     /// Ethernet frame index in storage. Has nothing to do with PDU header index field.
@@ -91,11 +94,39 @@ pub struct FrameElement<const N: usize> {
     /// whether the PDU has been set or not.
     first_pdu: AtomicU16,
 
+    /// When set, the Ethernet frame buffer is zeroed before the frame is released back to the
+    /// `None` state.
+    ///
+    /// Only set for frames carrying mailbox traffic, and only when
+    /// [`MainDeviceConfig::scrub_mailbox_frames`](crate::MainDeviceConfig::scrub_mailbox_frames)
+    /// is enabled, so cyclic process data frames don't pay for the extra memset.
+    scrub: bool,
+
+    /// Set once an 802.1Q VLAN tag has been inserted into the Ethernet header, moving the rest of
+    /// the frame back by [`VLAN_TAG_LEN`](crate::ethernet::VLAN_TAG_LEN) bytes.
+    ///
+    /// Stops the tag being inserted again when the frame is resent after a timeout.
+    vlan_tagged: bool,
+
     // MUST be the last element otherwise pointer arithmetic doesn't work for
     // `NonNull<FrameElement<0>>`.
     ethernet_frame: [u8; N],
 }
 
+impl<const N: usize> core::fmt::Debug for FrameElement<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Frame data is deliberately left out. See `FrameBox`'s `Debug` impl.
+        f.debug_struct("FrameElement")
+            .field("storage_slot_index", &self.storage_slot_index)
+            .field("status", &self.status)
+            .field("pdu_payload_len", &self.pdu_payload_len)
+            .field("first_pdu", &self.first_pdu)
+            .field("scrub", &self.scrub)
+            .field("vlan_tagged", &self.vlan_tagged)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<const N: usize> Default for FrameElement<N> {
     fn default() -> Self {
         Self {
@@ -104,6 +135,8 @@ impl<const N: usize> Default for FrameElement<N> {
             storage_slot_index: 0,
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
             waker: AtomicWaker::default(),
         }
     }
@@ -130,6 +163,11 @@ impl<const N: usize> FrameElement<N> {
         }
     }
 
+    /// Get the frame's current state.
+    pub(in crate::pdu_loop) unsafe fn state(this: NonNull<FrameElement<N>>) -> FrameState {
+        unsafe { (*addr_of!((*this.as_ptr()).status)).load(Ordering::Acquire) }
+    }
+
     /// Set the frame's state without checking its current state.
     pub(in crate::pdu_loop) unsafe fn set_state(this: NonNull<FrameElement<N>>, state: FrameState) {
         let fptr = this.as_ptr();
@@ -196,17 +234,35 @@ impl<const N: usize> FrameElement<N> {
         unsafe { Self::swap_state(this, FrameState::Sendable, FrameState::Sending) }.ok()
     }
 
-    unsafe fn claim_receiving(this: NonNull<FrameElement<N>>) -> Option<NonNull<FrameElement<N>>> {
-        unsafe { Self::swap_state(this, FrameState::Sent, FrameState::RxBusy) }
+    /// Attempt to claim a frame as RX_BUSY. Succeeds if the frame is currently SENT and its first
+    /// PDU index is still `first_pdu` once claimed.
+    unsafe fn claim_receiving(
+        this: NonNull<FrameElement<N>>,
+        first_pdu: u8,
+    ) -> Option<NonNull<FrameElement<N>>> {
+        let this = unsafe { Self::swap_state(this, FrameState::Sent, FrameState::RxBusy) }
             .map_err(|actual_state| {
-                fmt::error!(
+                fmt::trace!(
                     "Failed to claim receiving frame {}: expected state {:?}, but got {:?}",
                     unsafe { *addr_of_mut!((*this.as_ptr()).storage_slot_index) },
                     FrameState::Sent,
                     actual_state
                 );
             })
-            .ok()
+            .ok()?;
+
+        // The frame may have been released and reallocated with a different first PDU between the
+        // caller's search and the claim above. Its first PDU can't change while we hold it, so
+        // check again and put the frame back if it belongs to someone else.
+        let actual = unsafe { (*addr_of!((*this.as_ptr()).first_pdu)).load(Ordering::Acquire) };
+
+        if actual != u16::from(first_pdu) {
+            let _ = unsafe { Self::swap_state(this, FrameState::RxBusy, FrameState::Sent) };
+
+            return None;
+        }
+
+        Some(this)
     }
 
     unsafe fn storage_slot_index(this: NonNull<FrameElement<0>>) -> u8 {
@@ -245,6 +301,15 @@ impl<const N: usize> FrameElement<N> {
 
         first_pdu.store(FIRST_PDU_EMPTY, Ordering::Release);
     }
+
+    /// Get the raw Ethernet frame buffer of `len` bytes, regardless of frame state.
+    #[cfg(test)]
+    pub(in crate::pdu_loop) unsafe fn test_only_ethernet_frame<'a>(
+        this: NonNull<FrameElement<0>>,
+        len: usize,
+    ) -> &'a [u8] {
+        unsafe { core::slice::from_raw_parts(FrameElement::<0>::ptr(this).as_ptr(), len) }
+    }
 }
 
 #[cfg(test)]
@@ -267,6 +332,8 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         };
 
         let frame_ptr = NonNull::from(&frame);
@@ -290,6 +357,8 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         };
 
         let frame_ptr = NonNull::from(&frame);
@@ -310,6 +379,8 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         };
 
         let frame_ptr = NonNull::from(&frame);
@@ -332,6 +403,8 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         };
 
         let frame_ptr_0 = NonNull::from(&frame_0);
@@ -347,6 +420,8 @@ mod tests {
             ethernet_frame: [0u8; BUF_LEN],
             pdu_payload_len: 0,
             first_pdu: AtomicU16::new(FIRST_PDU_EMPTY),
+            scrub: false,
+            vlan_tagged: false,
         };
 
         let frame_ptr_1 = NonNull::from(&frame_1);
@@ -381,6 +456,8 @@ mod tests {
             // Should be zero but we'll set it to a random value for debugging
             pdu_payload_len: 0xbb,
             first_pdu: AtomicU16::new(0xcc),
+            scrub: false,
+            vlan_tagged: false,
             // Fill with a canary value
             ethernet_frame: [0xabu8; N],
         };
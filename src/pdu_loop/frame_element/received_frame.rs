@@ -1,6 +1,6 @@
 use crate::{
     error::{Error, PduError},
-    fmt,
+    fmt, frame_layout,
     pdu_loop::{
         frame_element::{FrameBox, FrameState, created_frame::PduResponseHandle},
         pdu_header::PduHeader,
@@ -40,6 +40,30 @@ impl<'sto> ReceivedFrame<'sto> {
     }
 
     pub fn first_pdu(self, handle: PduResponseHandle) -> Result<ReceivedPdu<'sto>, Error> {
+        self.first_pdu_inner(&handle)
+    }
+
+    /// Get the response to the first PDU in this frame, keeping the frame claimed for as long as
+    /// the response is in use if the frame is marked to be scrubbed.
+    ///
+    /// Scrubbed frames are zeroed when released, so the response data would otherwise be cleared
+    /// before the caller has read it. Other frames are released straight away as with
+    /// [`first_pdu`](ReceivedFrame::first_pdu).
+    pub(crate) fn into_held_pdu(self, handle: PduResponseHandle) -> Result<HeldPdu<'sto>, Error> {
+        if self.inner.is_scrub_marked() {
+            Ok(HeldPdu {
+                pdu: self.first_pdu_inner(&handle)?,
+                _frame: Some(self),
+            })
+        } else {
+            Ok(HeldPdu {
+                pdu: self.first_pdu(handle)?,
+                _frame: None,
+            })
+        }
+    }
+
+    fn first_pdu_inner(&self, handle: &PduResponseHandle) -> Result<ReceivedPdu<'sto>, Error> {
         let buf = self.inner.pdu_buf();
 
         let pdu_header = PduHeader::unpack_from_slice(buf)?;
@@ -48,11 +72,11 @@ impl<'sto> ReceivedFrame<'sto> {
 
         // If buffer isn't long enough to hold payload and WKC, this is probably a corrupt PDU or
         // someone is committing epic haxx.
-        if buf.len() < payload_len + 2 {
+        if buf.len() < payload_len + frame_layout::WKC_LEN {
             return Err(Error::Pdu(PduError::TooLong));
         }
 
-        if pdu_header.command_code != handle.command_code {
+        if !handle.is_nop() && pdu_header.command_code != handle.command_code {
             return Err(Error::Pdu(PduError::Decode));
         }
 
@@ -76,25 +100,26 @@ impl<'sto> ReceivedFrame<'sto> {
 
         Ok(ReceivedPdu {
             data_start: payload_ptr,
-            len: payload_len,
-            working_counter,
+            len: if handle.is_nop() { 0 } else { payload_len },
+            working_counter: if handle.is_nop() { 0 } else { working_counter },
             _storage: PhantomData,
         })
     }
 
+    /// Get the response to any PDU in this frame by its handle.
+    ///
+    /// Unlike [`first_pdu`](ReceivedFrame::first_pdu), this borrows the frame so the responses to
+    /// multiple PDUs can be read.
     // Might want this in the future
     #[allow(unused)]
-    pub fn pdu<'pdu>(&'sto self, handle: PduResponseHandle) -> Result<ReceivedPdu<'pdu>, Error>
-    where
-        'sto: 'pdu,
-    {
+    pub fn pdu(&self, handle: PduResponseHandle) -> Result<ReceivedPdu<'_>, Error> {
         let mut buf = self.inner.pdu_buf();
 
         // Skip over any preceding PDUs
         for _ in 0..handle.index_in_frame {
             let pdu_header = PduHeader::unpack_from_slice(buf)?;
             let payload_len = usize::from(pdu_header.flags.len());
-            let this_pdu_len = PduHeader::PACKED_LEN + payload_len + 2;
+            let this_pdu_len = PduHeader::PACKED_LEN + payload_len + frame_layout::WKC_LEN;
 
             // Start buffer at beginning of next PDU
             buf = buf.get(this_pdu_len..).ok_or(Error::Internal)?;
@@ -103,7 +128,7 @@ impl<'sto> ReceivedFrame<'sto> {
         // This checks for buffer min length
         let pdu_header = PduHeader::unpack_from_slice(buf)?;
 
-        if pdu_header.command_code != handle.command_code {
+        if !handle.is_nop() && pdu_header.command_code != handle.command_code {
             return Err(Error::Pdu(PduError::Decode));
         }
 
@@ -115,7 +140,7 @@ impl<'sto> ReceivedFrame<'sto> {
 
         // If buffer isn't long enough to hold payload and WKC, this is probably a corrupt PDU or
         // someone is committing epic haxx.
-        if buf.len() < payload_len + 2 {
+        if buf.len() < payload_len + frame_layout::WKC_LEN {
             return Err(Error::Pdu(PduError::TooLong));
         }
 
@@ -135,12 +160,22 @@ impl<'sto> ReceivedFrame<'sto> {
 
         Ok(ReceivedPdu {
             data_start: payload_ptr,
-            len: payload_len,
-            working_counter,
+            len: if handle.is_nop() { 0 } else { payload_len },
+            working_counter: if handle.is_nop() { 0 } else { working_counter },
             _storage: PhantomData,
         })
     }
 
+    /// Get the working counter of any PDU in this frame by its handle without consuming the frame.
+    ///
+    /// This can be used to check the working counter before deciding how to handle the response
+    /// data, which can then be read with [`first_pdu`](ReceivedFrame::first_pdu) or
+    /// [`pdu`](ReceivedFrame::pdu).
+    #[allow(unused)]
+    pub fn wkc(&self, handle: PduResponseHandle) -> Result<u16, Error> {
+        self.pdu(handle).map(|pdu| pdu.working_counter)
+    }
+
     pub fn into_pdu_iter(self) -> ReceivedPduIter<'sto> {
         ReceivedPduIter {
             frame: self,
@@ -151,15 +186,21 @@ impl<'sto> ReceivedFrame<'sto> {
 
 impl Drop for ReceivedFrame<'_> {
     fn drop(&mut self) {
+        // Must happen before the frame is released, otherwise it could be zeroed while in use by
+        // something else.
+        self.inner.scrub_if_marked();
+
+        // Set frame empty sentinel so we don't get false-positive matches when receiving frames.
+        // This must happen before the frame is released: once it's `None` it may be claimed and
+        // given a new first PDU by another thread, which a late clear would then overwrite.
+        self.inner.clear_first_pdu();
+
         // Invariant: the frame can only be in `RxProcessing` at this point, so if this swap fails
         // there's either a logic bug, or we should panic anyway because the hardware failed.
         fmt::unwrap!(
             self.inner
                 .swap_state(FrameState::RxProcessing, FrameState::None)
         );
-
-        // Set frame empty sentinel so we don't get false-positive matches when receiving frames
-        self.inner.clear_first_pdu();
     }
 }
 
@@ -186,11 +227,11 @@ impl<'sto> Iterator for ReceivedPduIter<'sto> {
         };
 
         let payload_len = usize::from(pdu_header.flags.len());
-        let this_pdu_len = PduHeader::PACKED_LEN + payload_len + 2;
+        let this_pdu_len = PduHeader::PACKED_LEN + payload_len + frame_layout::WKC_LEN;
 
         // If buffer isn't long enough to hold payload and WKC, this is probably a corrupt PDU or
         // someone is committing epic haxx.
-        if buf.len() < payload_len + 2 {
+        if buf.len() < payload_len + frame_layout::WKC_LEN {
             return Some(Err(Error::Pdu(PduError::TooLong)));
         }
 
@@ -281,3 +322,42 @@ impl Deref for ReceivedPdu<'_> {
         unsafe { core::slice::from_raw_parts(self.data_start.as_ptr(), len) }
     }
 }
+
+/// The response to a single PDU, holding its frame if the frame is marked to be scrubbed.
+///
+/// Created by [`ReceivedFrame::into_held_pdu`].
+#[derive(Debug)]
+pub struct HeldPdu<'sto> {
+    pdu: ReceivedPdu<'sto>,
+    /// Keeps a scrubbed frame claimed until the response has been dropped.
+    _frame: Option<ReceivedFrame<'sto>>,
+}
+
+impl HeldPdu<'_> {
+    pub fn working_counter(&self) -> u16 {
+        self.pdu.working_counter
+    }
+
+    pub fn trim_front(&mut self, ct: usize) {
+        self.pdu.trim_front(ct)
+    }
+
+    pub fn maybe_wkc(self, expected: Option<u16>) -> Result<Self, Error> {
+        Ok(Self {
+            pdu: self.pdu.maybe_wkc(expected)?,
+            ..self
+        })
+    }
+}
+
+// SAFETY: The held frame is claimed in the `RxProcessing` state, so nothing else accesses it until
+// it's released on drop, whichever thread that happens on.
+unsafe impl Send for HeldPdu<'_> {}
+
+impl Deref for HeldPdu<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.pdu
+    }
+}
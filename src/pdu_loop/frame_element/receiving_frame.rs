@@ -3,6 +3,7 @@ use crate::{
     error::{Error, PduError},
     fmt,
     pdu_loop::frame_element::{FrameBox, FrameElement, FrameState, received_frame::ReceivedFrame},
+    timer_factory::{Clock, PduClock},
 };
 use core::{future::Future, ptr::NonNull, sync::atomic::AtomicU8, task::Poll, time::Duration};
 use futures_lite::FutureExt;
@@ -16,12 +17,15 @@ pub struct ReceivingFrame<'sto> {
 }
 
 impl<'sto> ReceivingFrame<'sto> {
+    /// Claim the given frame if it is awaiting a response and its first PDU has the index
+    /// `first_pdu`.
     pub(in crate::pdu_loop) fn claim_receiving(
         frame: NonNull<FrameElement<0>>,
+        first_pdu: u8,
         pdu_idx: &'sto AtomicU8,
         frame_data_len: usize,
     ) -> Option<Self> {
-        let frame = unsafe { FrameElement::claim_receiving(frame)? };
+        let frame = unsafe { FrameElement::claim_receiving(frame, first_pdu)? };
 
         Some(Self {
             inner: FrameBox::new(frame, pdu_idx, frame_data_len),
@@ -39,17 +43,32 @@ impl<'sto> ReceivingFrame<'sto> {
 
         // NOTE: claim_receiving sets the state to `RxBusy` during parsing of the incoming frame
         // so the previous state here should be RxBusy.
-        self.inner
+        match self
+            .inner
             .swap_state(FrameState::RxBusy, FrameState::RxDone)
-            .map_err(|bad| {
+        {
+            Ok(()) => (),
+            // Nothing is waiting for the response any more
+            Err(FrameState::Abandoned) => {
+                fmt::warn!(
+                    "Frame {:#04x} was abandoned while receiving, releasing it",
+                    self.storage_slot_index()
+                );
+
+                self.inner.release_abandoned();
+
+                return Ok(());
+            }
+            Err(bad) => {
                 fmt::error!(
                     "Failed to set frame {:#04x} state from RxBusy -> RxDone, got {:?}",
                     self.storage_slot_index(),
                     bad
                 );
 
-                PduError::InvalidFrameState
-            })?;
+                return Err(PduError::InvalidFrameState);
+            }
+        }
 
         // wake() returns an error if there is no waker. A frame might have no waker if the response
         // is received over the network before the chosen executor has a chance to poll the future
@@ -63,21 +82,69 @@ impl<'sto> ReceivingFrame<'sto> {
         self.inner.pdu_buf_mut()
     }
 
+    /// Give the frame back to the network if the response could not be stored, so the future
+    /// waiting on it can still time out and retry.
+    pub(in crate::pdu_loop) fn release_receiving_claim(&self) {
+        if self.inner.swap_state(FrameState::RxBusy, FrameState::Sent) == Err(FrameState::Abandoned)
+        {
+            self.inner.release_abandoned();
+        }
+    }
+
     /// Ethernet frame index.
     fn storage_slot_index(&self) -> u8 {
         self.inner.storage_slot_index()
     }
 }
 
+/// The number of extra timeout periods to wait for a frame that is still being sent or having its
+/// response stored when its timeout expires.
+pub(in crate::pdu_loop::frame_element) const MAX_BUSY_TIMEOUTS: u8 = 3;
+
+/// A future resolving to the response to a sent frame, returned by
+/// [`CreatedFrame::mark_sendable`](crate::pdu_loop::CreatedFrame::mark_sendable).
 pub struct ReceiveFrameFut<'sto> {
+    inner: ReceiveFrameFutInner<'sto, PduClock>,
+}
+
+impl<'sto> ReceiveFrameFut<'sto> {
+    pub(in crate::pdu_loop::frame_element) fn new(
+        inner: ReceiveFrameFutInner<'sto, PduClock>,
+    ) -> Self {
+        Self { inner }
+    }
+
+    /// Get entire frame buffer. Only really useful for assertions in tests.
+    #[cfg(test)]
+    pub fn buf(&self) -> &[u8] {
+        self.inner.buf()
+    }
+}
+
+impl<'sto> Future for ReceiveFrameFut<'sto> {
+    type Output = Result<ReceivedFrame<'sto>, Error>;
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Self::Output> {
+        self.inner.poll(cx)
+    }
+}
+
+/// [`ReceiveFrameFut`] with a [`Clock`] that can be replaced in tests.
+pub(in crate::pdu_loop) struct ReceiveFrameFutInner<'sto, C: Clock> {
     pub(in crate::pdu_loop::frame_element) frame: Option<FrameBox<'sto>>,
     pub(in crate::pdu_loop::frame_element) pdu_loop: &'sto PduLoop<'sto>,
-    pub(in crate::pdu_loop::frame_element) timeout_timer: crate::timer_factory::Timer,
+    pub(in crate::pdu_loop::frame_element) clock: C,
+    pub(in crate::pdu_loop::frame_element) timeout_timer: C::Timer,
     pub(in crate::pdu_loop::frame_element) timeout: Duration,
     pub(in crate::pdu_loop::frame_element) retries_left: usize,
+    /// How many more timeout periods to wait for a frame the TX or RX path is still busy with.
+    pub(in crate::pdu_loop::frame_element) busy_timeouts_left: u8,
 }
 
-impl<'sto> ReceiveFrameFut<'sto> {
+impl<'sto, C: Clock> ReceiveFrameFutInner<'sto, C> {
     /// Get entire frame buffer. Only really useful for assertions in tests.
     #[cfg(test)]
     pub fn buf(&self) -> &[u8] {
@@ -94,10 +161,44 @@ impl<'sto> ReceiveFrameFut<'sto> {
         &b.into_inner()[0..len]
     }
 
+    /// Take the frame back from the TX/RX path so it can be released, or mark it
+    /// [`Abandoned`](FrameState::Abandoned) if the TX or RX path is using it, in which case that
+    /// path releases it when it's done.
+    ///
+    /// Returns `true` if the frame should be released by the caller.
+    fn release_or_abandon(r: &FrameBox<'sto>) -> bool {
+        loop {
+            let state = r.state();
+
+            let to = match state {
+                FrameState::Sending | FrameState::RxBusy => FrameState::Abandoned,
+                // Neither path may claim the frame after this, so it's safe to release
+                FrameState::Sendable | FrameState::Sent => FrameState::Created,
+                _ => return true,
+            };
+
+            if r.swap_state(state, to).is_ok() {
+                return to == FrameState::Created;
+            }
+        }
+    }
+
     fn release(r: FrameBox<'sto>) {
         // Make frame available for reuse if this future is dropped.
+        r.scrub_if_marked();
+        // Must be cleared before the frame is released, otherwise a stale first PDU index could
+        // match a response intended for another frame, or clobber the index of whoever claims
+        // this frame next.
+        r.clear_first_pdu();
         r.set_state(FrameState::None);
     }
+
+    /// Start a new timeout period, registering the timer with the executor.
+    fn rearm_timer(&mut self, cx: &mut core::task::Context<'_>) {
+        self.timeout_timer = self.clock.timer(self.timeout);
+
+        let _ = self.timeout_timer.poll(cx);
+    }
 }
 
 // SAFETY: This unsafe impl is required due to `FrameBox` containing a `NonNull`, however this impl
@@ -107,9 +208,17 @@ impl<'sto> ReceiveFrameFut<'sto> {
 // For example, if the backing storage is is `'static`, we can send things between threads. If it's
 // not, the associated lifetime will prevent the framebox from being used in anything that requires
 // a 'static bound.
-unsafe impl Send for ReceiveFrameFut<'_> {}
+unsafe impl<C> Send for ReceiveFrameFutInner<'_, C>
+where
+    C: Clock + Send,
+    C::Timer: Send,
+{
+}
 
-impl<'sto> Future for ReceiveFrameFut<'sto> {
+// The future is never structurally pinned: the timer is `Unpin` and the clock is only borrowed.
+impl<C: Clock> Unpin for ReceiveFrameFutInner<'_, C> {}
+
+impl<'sto, C: Clock> Future for ReceiveFrameFutInner<'sto, C> {
     type Output = Result<ReceivedFrame<'sto>, Error>;
 
     fn poll(
@@ -140,69 +249,590 @@ impl<'sto> Future for ReceiveFrameFut<'sto> {
 
         fmt::trace!("frame index {} not ready yet ({:?})", frame_idx, was);
 
-        // Timeout checked after frame handling so we get at least one chance to receive reply from
-        // network. This should mitigate race conditions when timeout expires just as the frame is
-        // received.
-        match self.timeout_timer.poll(cx) {
-            Poll::Ready(_) => {
-                // We timed out
-                fmt::trace!(
-                    "PDU response timeout with {} retries remaining",
-                    self.retries_left
-                );
+        if !matches!(
+            was,
+            FrameState::Sendable | FrameState::Sending | FrameState::Sent | FrameState::RxBusy
+        ) {
+            fmt::error!("Frame is in invalid state {:?}", was);
 
-                if self.retries_left == 0 {
-                    // Release frame and PDU slots for reuse
-                    Self::release(rxin);
+            return Poll::Ready(Err(PduError::InvalidFrameState.into()));
+        }
 
-                    return Poll::Ready(Err(Error::Timeout));
-                }
+        // Timeout checked after frame handling so a response that is already stored is returned
+        // even if the timeout has also expired by the time this future is polled.
+        if self.timeout_timer.poll(cx).is_pending() {
+            // Still waiting to be woken from the network response.
+            self.frame = Some(rxin);
 
-                // If we have retry loops left:
+            return Poll::Pending;
+        }
 
-                // Assign new timeout
-                self.timeout_timer = crate::timer_factory::timer(self.timeout);
-                // Poll timer once to register with the executor
-                let _ = self.timeout_timer.poll(cx);
+        // The response may have arrived between the state check above and the timer firing. Take
+        // the frame back from the TX/RX path before releasing or resending it, so a late response
+        // can't be written into it. `Created` is never claimed by either path.
+        let reclaimed = [FrameState::Sent, FrameState::Sendable]
+            .into_iter()
+            .any(|from| rxin.swap_state(from, FrameState::Created).is_ok());
+
+        if !reclaimed {
+            // The response won the race
+            if rxin
+                .swap_state(FrameState::RxDone, FrameState::RxProcessing)
+                .is_ok()
+            {
+                fmt::trace!("frame index {} is ready at timeout", frame_idx);
 
-                // Mark frame as sendable once more
-                rxin.set_state(FrameState::Sendable);
-                // Wake frame sender so it picks up this frame we've just marked
-                self.pdu_loop.wake_sender();
+                return Poll::Ready(Ok(ReceivedFrame::new(rxin)));
+            }
+
+            let state = rxin.state();
 
-                self.retries_left -= 1;
+            if !matches!(state, FrameState::Sending | FrameState::RxBusy) {
+                fmt::error!("Frame is in invalid state {:?} at timeout", state);
+
+                return Poll::Ready(Err(PduError::InvalidFrameState.into()));
             }
-            Poll::Pending => {
-                // Haven't timed out yet. Nothing to do - still waiting to be woken from the network
-                // response.
+
+            // The frame will never be handed back if e.g. a `SendableFrame` was dropped without
+            // being sent. Give up on it instead of waiting forever. The TX or RX path may still be
+            // using the frame buffer, so the slot is left for it to release.
+            if self.busy_timeouts_left == 0 {
+                fmt::error!(
+                    "Frame index {} stuck in state {:?}, abandoning it",
+                    frame_idx,
+                    state
+                );
+
+                if Self::release_or_abandon(&rxin) {
+                    Self::release(rxin);
+                    self.pdu_loop.wake_tx_idle();
+                }
+
+                return Poll::Ready(Err(Error::Timeout));
             }
+
+            // The frame is still being sent or its response is being stored. Give it a further
+            // timeout period without using up a retry. The RX path wakes this future as soon as the
+            // response is stored.
+            fmt::trace!("frame index {} busy at timeout ({:?})", frame_idx, state);
+
+            self.busy_timeouts_left -= 1;
+
+            self.rearm_timer(cx);
+            self.frame = Some(rxin);
+
+            return Poll::Pending;
         }
 
-        match was {
-            FrameState::Sendable | FrameState::Sending | FrameState::Sent | FrameState::RxBusy => {
-                self.frame = Some(rxin);
+        fmt::trace!(
+            "PDU response timeout with {} retries remaining",
+            self.retries_left
+        );
 
-                Poll::Pending
-            }
-            state => {
-                fmt::error!("Frame is in invalid state {:?}", state);
+        if self.retries_left == 0 {
+            // Release frame and PDU slots for reuse
+            Self::release(rxin);
+            self.pdu_loop.wake_tx_idle();
 
-                Poll::Ready(Err(PduError::InvalidFrameState.into()))
-            }
+            return Poll::Ready(Err(Error::Timeout));
         }
+
+        self.retries_left -= 1;
+
+        self.rearm_timer(cx);
+
+        // Mark frame as sendable once more
+        rxin.set_state(FrameState::Sendable);
+        // Wake frame sender so it picks up this frame we've just marked
+        self.pdu_loop.wake_sender();
+
+        self.frame = Some(rxin);
+
+        Poll::Pending
     }
 }
 
 // If this impl is removed, timed out frames will never be reclaimed, clogging up the PDU loop and
 // crashing the program.
-impl Drop for ReceiveFrameFut<'_> {
+impl<C: Clock> Drop for ReceiveFrameFutInner<'_, C> {
     fn drop(&mut self) {
         // Frame option is taken when future completes successfully, so this drop logic will only
         // fire if the future is dropped before it completes.
         if let Some(r) = self.frame.take() {
             fmt::debug!("Dropping in-flight future, possibly caused by timeout");
 
-            Self::release(r);
+            if Self::release_or_abandon(&r) {
+                Self::release(r);
+                self.pdu_loop.wake_tx_idle();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAX_BUSY_TIMEOUTS;
+    use crate::{
+        Command, PduRx, PduStorage, PduTx, ReceiveAction, error::Error, fake_network::SentFrame,
+        pdu_loop::frame_element::FrameState, timer_factory::manual::ManualClock,
+    };
+    use core::{
+        future::Future,
+        ops::Deref,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Context, Poll, Waker},
+        time::Duration,
+    };
+    use std::{sync::Arc, task::Wake};
+
+    const TIMEOUT: Duration = Duration::from_millis(10);
+
+    const DATA: [u8; 4] = [0xaa, 0xbb, 0xcc, 0xdd];
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl CountingWaker {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Send the next frame, returning it with the source address changed as if it had passed
+    /// through the network.
+    fn send(tx: &mut PduTx<'_>) -> Vec<u8> {
+        let mut packet = Vec::new();
+
+        tx.next_sendable_frame()
+            .expect("need a frame")
+            .send_blocking(|bytes| {
+                packet.extend_from_slice(bytes);
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        SentFrame::new(packet).into_reply()
+    }
+
+    fn receive(rx: &mut PduRx<'_>, packet: &[u8]) {
+        assert_eq!(rx.receive_frame(packet), Ok(ReceiveAction::Processed));
+    }
+
+    #[test]
+    fn response_wins_at_timeout() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        let handle = frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 0, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        let packet = send(&mut tx);
+
+        // Both the response and the timeout are ready by the time the future is polled again
+        clock.advance(TIMEOUT);
+        receive(&mut rx, &packet);
+
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(Ok(frame)) => {
+                assert_eq!(frame.first_pdu(handle).unwrap().deref(), &DATA);
+            }
+            other => panic!("Expected Ready(Ok()), got {:?}", other),
         }
     }
+
+    #[test]
+    fn response_being_stored_at_timeout() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+        let waker = Arc::new(CountingWaker::default());
+        let waker_ref = Waker::from(Arc::clone(&waker));
+        let mut cx = Context::from_waker(&waker_ref);
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        let handle = frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 0, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        send(&mut tx);
+
+        // The RX path has claimed the frame but hasn't finished storing the response when the
+        // timeout fires.
+        let receiving = pdu_loop
+            .storage
+            .claim_receiving_by_first_pdu(handle.pdu_idx)
+            .expect("claim");
+
+        clock.advance(TIMEOUT);
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        let wakes = waker.count();
+
+        receiving.mark_received().expect("mark received");
+
+        assert_eq!(waker.count(), wakes + 1, "RX should wake the future");
+        assert!(matches!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(_))));
+    }
+
+    #[test]
+    fn unsent_frame_times_out() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 0, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        // Frame is claimed for sending but never sent, so it's stuck in `Sending`
+        let _ = tx.next_sendable_frame().expect("need a frame");
+
+        for _ in 0..MAX_BUSY_TIMEOUTS {
+            clock.advance(TIMEOUT);
+
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+        }
+
+        clock.advance(TIMEOUT);
+
+        assert!(matches!(
+            fut.as_mut().poll(&mut cx),
+            Poll::Ready(Err(Error::Timeout))
+        ));
+    }
+
+    #[test]
+    fn abandoned_frame_is_released_by_sender() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 0, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        // Claimed for sending but held on to until after the future gives up
+        let stuck = tx.next_sendable_frame().expect("need a frame");
+
+        let len = stuck.len();
+
+        for _ in 0..MAX_BUSY_TIMEOUTS {
+            clock.advance(TIMEOUT);
+
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+        }
+
+        clock.advance(TIMEOUT);
+
+        assert!(matches!(
+            fut.as_mut().poll(&mut cx),
+            Poll::Ready(Err(Error::Timeout))
+        ));
+
+        // The slot can't be reused while the TX path still holds it
+        assert_eq!(
+            pdu_loop.frame_states::<1>().as_slice(),
+            &[(0, FrameState::Abandoned)]
+        );
+        assert!(pdu_loop.storage.alloc_frame().is_err());
+
+        // The frame buffer is left untouched for the late send, which then releases the slot
+        stuck
+            .send_blocking(|bytes| {
+                assert_eq!(bytes.len(), len);
+                assert!(bytes.windows(DATA.len()).any(|window| window == DATA));
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        assert_eq!(
+            pdu_loop.frame_states::<1>().as_slice(),
+            &[(0, FrameState::None)]
+        );
+        assert!(pdu_loop.storage.alloc_frame().is_ok());
+    }
+
+    #[test]
+    fn abandoned_frame_is_released_by_receiver() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        let handle = frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 0, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        send(&mut tx);
+
+        // The RX path claims the frame and never finishes storing the response in time
+        let receiving = pdu_loop
+            .storage
+            .claim_receiving_by_first_pdu(handle.pdu_idx)
+            .expect("claim");
+
+        for _ in 0..MAX_BUSY_TIMEOUTS {
+            clock.advance(TIMEOUT);
+
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+        }
+
+        clock.advance(TIMEOUT);
+
+        assert!(matches!(
+            fut.as_mut().poll(&mut cx),
+            Poll::Ready(Err(Error::Timeout))
+        ));
+
+        assert!(pdu_loop.storage.alloc_frame().is_err());
+
+        receiving.mark_received().expect("mark received");
+
+        assert_eq!(
+            pdu_loop.frame_states::<1>().as_slice(),
+            &[(0, FrameState::None)]
+        );
+    }
+
+    #[test]
+    fn retries_exhausted_releases_frame() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 0, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        let packet = send(&mut tx);
+
+        // Not quite timed out yet
+        clock.advance(TIMEOUT - Duration::from_millis(1));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        clock.advance(Duration::from_millis(1));
+
+        assert!(matches!(
+            fut.as_mut().poll(&mut cx),
+            Poll::Ready(Err(Error::Timeout))
+        ));
+        assert_eq!(pdu_loop.pending_sendable_frames(), 0);
+
+        // A late response must not be written into the released frame
+        assert_ne!(rx.receive_frame(&packet), Ok(ReceiveAction::Processed));
+
+        assert!(
+            pdu_loop.storage.alloc_frame().is_ok(),
+            "slot should be free"
+        );
+    }
+
+    #[test]
+    fn retry_requeues_frame_and_wakes_tx() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+
+        let tx_waker = Arc::new(CountingWaker::default());
+        tx.replace_waker(&Waker::from(Arc::clone(&tx_waker)));
+
+        let fut_waker = Arc::new(CountingWaker::default());
+        let fut_waker_ref = Waker::from(Arc::clone(&fut_waker));
+        let mut cx = Context::from_waker(&fut_waker_ref);
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        let handle = frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 1, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        send(&mut tx);
+
+        assert!(tx.next_sendable_frame().is_none());
+
+        clock.advance(TIMEOUT);
+
+        assert_eq!(fut_waker.count(), 1, "timer should wake the future");
+
+        let tx_wakes = tx_waker.count();
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        assert_eq!(
+            tx_waker.count(),
+            tx_wakes + 1,
+            "TX should be woken to resend"
+        );
+
+        let packet = send(&mut tx);
+
+        // The new timeout period must be registered with the waker
+        clock.advance(TIMEOUT - Duration::from_millis(1));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        receive(&mut rx, &packet);
+
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(Ok(frame)) => {
+                assert_eq!(frame.first_pdu(handle).unwrap().deref(), &DATA);
+            }
+            other => panic!("Expected Ready(Ok()), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn retry_rearms_timer() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+
+        let waker = Arc::new(CountingWaker::default());
+        let waker_ref = Waker::from(Arc::clone(&waker));
+        let mut cx = Context::from_waker(&waker_ref);
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 1, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        send(&mut tx);
+        clock.advance(TIMEOUT);
+
+        // Retry
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        send(&mut tx);
+
+        let wakes = waker.count();
+
+        clock.advance(TIMEOUT);
+
+        assert_eq!(waker.count(), wakes + 1, "new timer should wake the future");
+        assert!(matches!(
+            fut.as_mut().poll(&mut cx),
+            Poll::Ready(Err(Error::Timeout))
+        ));
+    }
+
+    #[test]
+    fn drop_mid_retry_releases_frame() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+        let clock = ManualClock::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        frame
+            .push_pdu(Command::fpwr(0x1000, 0x0f00).into(), DATA, None)
+            .expect("Push PDU");
+
+        let mut fut =
+            Box::pin(frame.mark_sendable_with_clock(&pdu_loop, TIMEOUT, 3, clock.clone()));
+
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        send(&mut tx);
+        clock.advance(TIMEOUT);
+
+        // Frame is queued to be resent
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(pdu_loop.pending_sendable_frames(), 1);
+
+        drop(fut);
+
+        assert_eq!(pdu_loop.pending_sendable_frames(), 0);
+        assert!(tx.next_sendable_frame().is_none());
+        assert!(
+            pdu_loop.storage.alloc_frame().is_ok(),
+            "slot should be free"
+        );
+    }
 }
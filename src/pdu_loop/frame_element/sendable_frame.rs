@@ -1,14 +1,10 @@
 use crate::{
     error::Error,
-    ethernet::EthernetFrame,
+    ethernet::VlanTag,
     fmt,
-    pdu_loop::{
-        frame_element::{FrameBox, FrameElement, FrameState},
-        frame_header::EthercatFrameHeader,
-    },
+    pdu_loop::frame_element::{FrameBox, FrameElement, FrameState},
 };
 use core::{ptr::NonNull, sync::atomic::AtomicU8};
-use ethercrab_wire::EtherCrabWireSized;
 
 /// An EtherCAT frame that is ready to be sent over the network.
 ///
@@ -59,19 +55,32 @@ impl<'sto> SendableFrame<'sto> {
         frame: NonNull<FrameElement<0>>,
         pdu_idx: &'sto AtomicU8,
         frame_data_len: usize,
+        vlan_tag: Option<VlanTag>,
     ) -> Option<Self> {
         let frame = unsafe { FrameElement::claim_sending(frame)? };
 
-        Some(Self {
-            inner: FrameBox::new(frame, pdu_idx, frame_data_len),
-        })
+        let mut inner = FrameBox::new(frame, pdu_idx, frame_data_len);
+
+        if let Some(tag) = vlan_tag {
+            if !inner.insert_vlan_tag(tag) {
+                fmt::error!(
+                    "Frame {} is too long to add a VLAN tag, sending untagged",
+                    inner.storage_slot_index()
+                );
+            }
+        }
+
+        #[cfg(feature = "software-fcs")]
+        inner.append_fcs();
+
+        Some(Self { inner })
     }
 
     /// The frame has been sent by the network driver.
     fn mark_sent(&self) {
         fmt::trace!("Frame index {} is sent", self.inner.storage_slot_index());
 
-        self.inner.set_state(FrameState::Sent);
+        self.leave_sending(FrameState::Sent);
     }
 
     pub(crate) fn storage_slot_index(&self) -> u8 {
@@ -81,15 +90,37 @@ impl<'sto> SendableFrame<'sto> {
     /// Used on send failure to release the frame sending claim so the frame can attempt to be sent
     /// again, or reclaimed for reuse.
     fn release_sending_claim(&self) {
-        self.inner.set_state(FrameState::Sendable);
+        self.leave_sending(FrameState::Sendable);
+    }
+
+    /// Move the frame out of `Sending`, or release it if the future waiting on it gave up while it
+    /// was held here.
+    fn leave_sending(&self, to: FrameState) {
+        match self.inner.swap_state(FrameState::Sending, to) {
+            Ok(()) => (),
+            Err(FrameState::Abandoned) => {
+                fmt::warn!(
+                    "Frame index {} was abandoned while sending, releasing it",
+                    self.inner.storage_slot_index()
+                );
+
+                self.inner.release_abandoned();
+            }
+            Err(state) => {
+                fmt::error!(
+                    "Frame index {} left sending in unexpected state {:?}",
+                    self.inner.storage_slot_index(),
+                    state
+                );
+            }
+        }
     }
 
     fn as_bytes(&self) -> &[u8] {
         let frame = self.inner.ethernet_frame().into_inner();
 
-        let len = EthernetFrame::<&[u8]>::buffer_len(
-            EthercatFrameHeader::PACKED_LEN + self.inner.pdu_payload_len(),
-        );
+        // Includes the VLAN tag, padding and FCS added in `claim_sending`, if any
+        let len = self.inner.wire_len();
 
         &frame[0..len]
     }
@@ -104,6 +135,8 @@ impl<'sto> SendableFrame<'sto> {
     ///
     /// The closure must return the number of bytes sent over the network interface. If this does
     /// not match the length of the packet passed to the closure, this method will return an error.
+    ///
+    /// If the `software-fcs` feature is enabled, the packet ends with a software computed FCS.
     pub fn send_blocking(
         self,
         send: impl FnOnce(&[u8]) -> Result<usize, Error>,
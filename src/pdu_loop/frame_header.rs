@@ -3,22 +3,32 @@
 use crate::LEN_MASK;
 use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireSized, EtherCrabWireWrite};
 
+/// The type of an EtherCAT frame, stored in the upper 4 bits of the [`EthercatFrameHeader`].
+///
+/// Defined in ETG1000.4 Table 53 – EtherCAT frame header.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, ethercrab_wire::EtherCrabWireRead)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
-pub(crate) enum ProtocolType {
+#[non_exhaustive]
+pub enum ProtocolType {
+    /// The frame contains one or more EtherCAT PDUs (DLPDUs).
     DlPdu = 0x01u8,
     // Not currently supported.
     // NetworkVariables = 0x04,
-    // Mailbox = 0x05,
+    /// The frame contains a mailbox gateway message.
+    ///
+    /// EtherCrab does not process these frames and ignores them on receipt.
+    Mailbox = 0x05,
     // #[wire(catch_all)]
     // Unknown(u8),
 }
 
 /// An EtherCAT frame header.
 ///
-/// An EtherCAT frame can contain one or more PDUs after this header, each starting with a
-/// [`PduHeader`](crate::pdu_loop::pdu_header).
+/// An EtherCAT frame of type [`ProtocolType::DlPdu`] contains one or more PDUs after this header,
+/// each starting with its own PDU header.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct EthercatFrameHeader {
     pub(crate) payload_len: u16,
     pub(crate) protocol: ProtocolType,
@@ -69,11 +79,34 @@ impl EthercatFrameHeader {
 
         Self {
             payload_len: len & LEN_MASK,
-            // Only support DlPdu (for now?)
             protocol: ProtocolType::DlPdu,
         }
     }
 
+    /// Create a new mailbox frame header.
+    pub fn mailbox(len: u16) -> Self {
+        debug_assert!(
+            len <= LEN_MASK,
+            "Frame length may not exceed {} bytes",
+            LEN_MASK
+        );
+
+        Self {
+            payload_len: len & LEN_MASK,
+            protocol: ProtocolType::Mailbox,
+        }
+    }
+
+    /// The type of data contained in this frame.
+    pub fn protocol(&self) -> ProtocolType {
+        self.protocol
+    }
+
+    /// The number of bytes following this header in the frame.
+    pub fn payload_len(&self) -> u16 {
+        self.payload_len
+    }
+
     /// Convenience method for naming consistency.
     pub(crate) const fn header_len() -> usize {
         Self::PACKED_LEN
@@ -117,4 +150,33 @@ mod tests {
         assert_eq!(header.payload_len, 0x3c);
         assert_eq!(header.protocol, ProtocolType::DlPdu);
     }
+
+    #[test]
+    fn round_trip() {
+        for (header, protocol) in [
+            (EthercatFrameHeader::pdu(0x28), ProtocolType::DlPdu),
+            (EthercatFrameHeader::mailbox(0x7ff), ProtocolType::Mailbox),
+        ] {
+            let mut buf = [0u8; 2];
+
+            let packed = header.pack_to_slice_unchecked(&mut buf);
+
+            let unpacked = EthercatFrameHeader::unpack_from_slice(packed).unwrap();
+
+            assert_eq!(unpacked, header);
+            assert_eq!(unpacked.protocol(), protocol);
+            assert_eq!(unpacked.payload_len(), header.payload_len());
+        }
+    }
+
+    #[test]
+    fn mailbox_header() {
+        let header = EthercatFrameHeader::mailbox(0x28);
+
+        let mut buf = [0u8; 2];
+
+        let packed = header.pack_to_slice_unchecked(&mut buf);
+
+        assert_eq!(packed, &0b0101_0000_0010_1000u16.to_le_bytes());
+    }
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "software-fcs")]
+pub(crate) mod fcs;
 mod frame_element;
 mod frame_header;
 mod pdu_flags;
@@ -7,21 +9,39 @@ mod pdu_tx;
 // NOTE: Pub so doc links work
 pub mod storage;
 
-use crate::{command::Command, error::Error, pdu_loop::storage::PduStorageRef};
-use core::{sync::atomic::Ordering, time::Duration};
+use crate::{command::Command, error::Error, frame_layout, pdu_loop::storage::PduStorageRef};
+use core::{future::poll_fn, sync::atomic::Ordering, task::Poll, time::Duration};
+pub use frame_header::{EthercatFrameHeader, ProtocolType};
+pub(crate) use pdu_header::PduHeader;
 pub use pdu_rx::PduRx;
 // NOTE: Allowing unused because `ReceiveAction` isn't used when `xdp` is not enabled.
 #[allow(unused)]
 pub use pdu_rx::ReceiveAction;
 pub use pdu_tx::PduTx;
-pub use storage::PduStorage;
+pub use storage::{FrameAllocation, PduStats, PduStorage};
 
+#[cfg(not(feature = "manual-pdu-index"))]
 pub(crate) use self::frame_element::created_frame::CreatedFrame;
+#[cfg(feature = "manual-pdu-index")]
+pub use self::frame_element::created_frame::{CreatedFrame, PduResponseHandle};
+#[cfg(feature = "debug-introspection")]
+pub use frame_element::FrameState;
 #[cfg(test)]
 pub(crate) use frame_element::received_frame::ReceivedFrame;
-pub(crate) use frame_element::received_frame::ReceivedPdu;
+pub(crate) use frame_element::received_frame::{HeldPdu, ReceivedPdu};
 pub use frame_element::sendable_frame::SendableFrame;
 
+/// Bytes reserved at the end of every frame buffer for a software computed FCS.
+pub(crate) const FCS_RESERVED_LEN: usize = if cfg!(feature = "software-fcs") { 4 } else { 0 };
+
+/// The shortest Ethernet frame, not including the FCS. Shorter frames are padded with zeros up to
+/// this length, and the FCS is computed over the padding.
+pub(crate) const MIN_ETHERNET_FRAME_LEN: usize = 60;
+
+/// The smallest frame buffer that can hold a padded frame followed by a software computed FCS.
+#[cfg(feature = "software-fcs")]
+pub(crate) const MIN_FRAME_BUFFER_LEN: usize = MIN_ETHERNET_FRAME_LEN + FCS_RESERVED_LEN;
+
 /// The core EtherCrab network communications driver.
 ///
 // TODO: Update the following docs. The current text is out of date.
@@ -53,6 +73,9 @@ pub use frame_element::sendable_frame::SendableFrame;
 #[derive(Debug)]
 pub struct PduLoop<'sto> {
     storage: PduStorageRef<'sto>,
+    /// Process received frames sent from the MainDevice's own MAC address, set from
+    /// [`MainDeviceConfig::ignore_self_filter`](crate::MainDeviceConfig::ignore_self_filter).
+    pub(crate) ignore_self_filter: bool,
 }
 
 impl<'sto> PduLoop<'sto> {
@@ -60,7 +83,10 @@ impl<'sto> PduLoop<'sto> {
     pub(in crate::pdu_loop) const fn new(storage: PduStorageRef<'sto>) -> Self {
         assert!(storage.num_frames <= u8::MAX as usize);
 
-        Self { storage }
+        Self {
+            storage,
+            ignore_self_filter: false,
+        }
     }
 
     /// Reset all internal state so the PDU loop can be reused.
@@ -85,15 +111,77 @@ impl<'sto> PduLoop<'sto> {
         &self.storage
     }
 
-    pub(crate) const fn max_frame_data(&self) -> usize {
+    /// The length of each frame buffer in bytes, including all headers.
+    #[cfg(test)]
+    pub(crate) const fn max_frame_len(&self) -> usize {
         self.storage.frame_data_len
     }
 
+    /// The largest PDU payload in bytes that fits in a single frame.
+    ///
+    /// This is the [`PduStorage`] element size minus the Ethernet, EtherCAT and PDU header overhead
+    /// described in [`frame_layout`](crate::frame_layout), and a further four bytes less if a VLAN
+    /// tag is set with [`PduTx::set_vlan_tag`]. A PDU carrying more data than this can't be sent
+    /// and is rejected with [`PduError::TooLong`](crate::error::PduError::TooLong).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ethercrab::PduStorage;
+    ///
+    /// static STORAGE: PduStorage<4, { PduStorage::element_size(128) }> = PduStorage::new();
+    ///
+    /// let (_tx, _rx, pdu_loop) = STORAGE.try_split().expect("can only split once");
+    ///
+    /// assert_eq!(pdu_loop.max_frame_data(), 128);
+    /// ```
+    pub fn max_frame_data(&self) -> usize {
+        self.storage
+            .created_frame_len()
+            .saturating_sub(frame_layout::MIN_ELEMENT_SIZE)
+    }
+
+    /// Get the total number of PDUs pushed into frames and parsed from received frames.
+    ///
+    /// See [`PduStats`] for details.
+    pub fn pdu_stats(&self) -> PduStats {
+        self.storage.pdu_stats()
+    }
+
     /// Tell the packet sender there are PDUs ready to send.
     pub(crate) fn wake_sender(&self) {
         self.storage.tx_waker.wake();
     }
 
+    /// The number of frames queued for sending or waiting for a response.
+    pub(crate) fn pending_sendable_frames(&self) -> usize {
+        self.storage.pending_sendable_frames()
+    }
+
+    /// Wake any task waiting in [`tx_idle`](PduLoop::tx_idle).
+    pub(crate) fn wake_tx_idle(&self) {
+        self.storage.tx_idle_waker.wake();
+    }
+
+    /// Wait until fewer than `threshold` frames are pending.
+    ///
+    /// Only one task should wait on this at a time, as only the most recently registered waker will
+    /// be woken.
+    pub(crate) async fn tx_idle(&self, threshold: usize) {
+        poll_fn(|ctx| {
+            // Register first so a frame completing between the check below and returning
+            // `Pending` still wakes us.
+            self.storage.tx_idle_waker.register(ctx.waker());
+
+            if self.pending_sendable_frames() < threshold {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
     /// Broadcast (BWR) a packet full of zeroes, up to `payload_length`.
     pub(crate) async fn pdu_broadcast_zeros(
         &self,
@@ -102,7 +190,7 @@ impl<'sto> PduLoop<'sto> {
         timeout: Duration,
         retries: usize,
     ) -> Result<(), Error> {
-        let mut frame = self.storage.alloc_frame()?;
+        let mut frame = self.alloc_frame()?;
 
         frame.push_pdu(Command::bwr(register).into(), (), Some(payload_length))?;
 
@@ -115,28 +203,79 @@ impl<'sto> PduLoop<'sto> {
         Ok(())
     }
 
+    /// Allocate a frame to push PDUs into by hand.
+    ///
+    /// Returns [`PduError::SwapState`](crate::error::PduError::SwapState) if no frame slot is free.
+    #[cfg(feature = "manual-pdu-index")]
+    pub fn alloc_frame(&self) -> Result<CreatedFrame<'sto>, Error> {
+        self.alloc_frame_inner()
+    }
+
+    #[cfg(not(feature = "manual-pdu-index"))]
     pub(crate) fn alloc_frame(&self) -> Result<CreatedFrame<'sto>, Error> {
+        self.alloc_frame_inner()
+    }
+
+    fn alloc_frame_inner(&self) -> Result<CreatedFrame<'sto>, Error> {
+        // Every frame is allocated here, so the receive filter is always set before a response can
+        // arrive, whichever path sent the frame.
+        self.storage
+            .set_self_filter_enabled(!self.ignore_self_filter);
+
         self.storage.alloc_frame()
     }
+
+    /// Get the current state of every frame slot in the backing storage, with each slot's index.
+    ///
+    /// This is intended for debugging frames that never complete. For example, a slot that stays
+    /// in [`FrameState::Sent`] is waiting for a response that was lost, and one that stays in
+    /// [`FrameState::RxProcessing`] is held by code that hasn't released the response. The states
+    /// are read one slot at a time, so may be inconsistent with each other if frames are being sent
+    /// or received concurrently.
+    ///
+    /// If the storage has more than `N` slots, only the first `N` are returned.
+    #[cfg(any(test, feature = "debug-introspection"))]
+    pub fn frame_states<const N: usize>(
+        &self,
+    ) -> heapless::Vec<(u8, frame_element::FrameState), N> {
+        (0..self.storage.num_frames)
+            .take(N)
+            .map(|idx| {
+                // SAFETY: `frame_at_index` always returns a pointer to a valid frame element.
+                let state = unsafe {
+                    frame_element::FrameElement::<0>::state(self.storage.frame_at_index(idx))
+                };
+
+                (idx as u8, state)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ethernet::{EthernetAddress, EthernetFrame};
-    use crate::pdu_loop::frame_element::created_frame::PduResponseHandle;
+    use crate::ethernet::EthernetFrame;
+    use crate::fake_network::{SentFrame, wire_len, with_fcs};
     use crate::pdu_loop::frame_element::received_frame::ReceivedFrame;
-    use crate::pdu_loop::frame_header::EthercatFrameHeader;
+    use crate::pdu_loop::frame_element::{FrameElement, FrameState};
     use crate::{
-        Command, PduStorage, Reads,
+        Command, PduStats, PduStorage, Reads,
         error::{Error, PduError},
-        fmt,
+        fmt, frame_layout,
         pdu_loop::frame_element::created_frame::CreatedFrame,
         timer_factory::IntoTimeout,
     };
     use cassette::Cassette;
-    use core::{future::poll_fn, ops::Deref, pin::pin, task::Poll, time::Duration};
+    use core::{
+        future::poll_fn,
+        ops::Deref,
+        pin::pin,
+        sync::atomic::{AtomicBool, Ordering},
+        task::{Context, Poll, Waker},
+        time::Duration,
+    };
     use futures_lite::Future;
-    use std::{sync::Arc, thread};
+    use std::{sync::Arc, task::Wake, thread};
 
     #[test]
     fn timed_out_frame_is_reallocatable() {
@@ -176,6 +315,53 @@ mod tests {
         assert_eq!(f2.unwrap_err(), PduError::SwapState.into());
     }
 
+    #[test]
+    fn frame_states_shows_sent() {
+        crate::test_logger();
+
+        let storage = PduStorage::<2, 128>::new();
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+
+        let poller = poll_fn(|ctx| {
+            let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+            let slot = frame.storage_slot_index();
+
+            frame
+                .push_pdu(Command::fpwr(0x5678, 0x1234).into(), [0xaau8; 4], None)
+                .expect("Push PDU");
+
+            let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+            assert!(
+                matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
+                "frame fut should be pending"
+            );
+
+            assert!(
+                pdu_loop
+                    .frame_states::<2>()
+                    .contains(&(slot, FrameState::Sendable))
+            );
+
+            tx.next_sendable_frame()
+                .expect("need a frame")
+                .send_blocking(|bytes| Ok(bytes.len()))
+                .expect("send");
+
+            // No response is received, so the frame is left waiting for one
+            let states = pdu_loop.frame_states::<2>();
+
+            assert_eq!(states.len(), 2);
+            assert!(states.contains(&(slot, FrameState::Sent)));
+            assert!(states.contains(&(1 - slot, FrameState::None)));
+
+            Poll::Ready(())
+        });
+
+        cassette::block_on(poller);
+    }
+
     #[test]
     fn write_frame() {
         crate::test_logger();
@@ -213,21 +399,713 @@ mod tests {
     }
 
     #[test]
-    fn single_frame_round_trip() {
+    fn single_frame_round_trip() {
+        crate::test_logger();
+
+        const FRAME_OVERHEAD: usize = 28;
+
+        // 1 frame, up to 128 bytes payload
+        let storage = PduStorage::<1, 128>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let data = [0xaau8, 0xbb, 0xcc, 0xdd];
+
+        // Using poll_fn so we can manually poll the frame future multiple times
+        let poller = poll_fn(|ctx| {
+            let mut written_packet = Vec::new();
+
+            let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+            let handle = frame
+                .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
+                .expect("Push PDU");
+
+            let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+            // Poll future up to first await point. This gets the frame ready and marks it as
+            // sendable so TX can pick it up, but we don't want to wait for the response so we won't
+            // poll it again.
+            assert!(
+                matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
+                "frame fut should be pending"
+            );
+
+            let frame = tx.next_sendable_frame().expect("need a frame");
+
+            let send_fut = pin!(async move {
+                frame
+                    .send_blocking(|bytes| {
+                        written_packet.extend_from_slice(bytes);
+
+                        Ok(bytes.len())
+                    })
+                    .expect("send");
+
+                // Munge fake sent frame into a fake received frame
+                SentFrame::new(written_packet).into_reply()
+            });
+
+            let Poll::Ready(written_packet) = send_fut.poll(ctx) else {
+                panic!("no send")
+            };
+
+            assert_eq!(written_packet.len(), wire_len(FRAME_OVERHEAD + data.len()));
+
+            // ---
+
+            let result = rx.receive_frame(&written_packet);
+
+            assert_eq!(result, Ok(crate::ReceiveAction::Processed));
+
+            // The frame has received a response at this point so should be ready to get the data
+            // from
+            match frame_fut.poll(ctx) {
+                Poll::Ready(Ok(frame)) => {
+                    let response = frame.first_pdu(handle).expect("Handle");
+
+                    assert_eq!(response.deref(), &data);
+                }
+                Poll::Ready(other) => panic!("Expected Ready(Ok()), got {:?}", other),
+                Poll::Pending => panic!("frame future still pending"),
+            }
+
+            // We should only ever be going through this loop once as the number of individual
+            // `poll()` calls is calculated.
+            Poll::Ready(())
+        });
+
+        // Using `cassette` otherwise miri complains about a memory leak inside whichever other
+        // `block_on` or `.await` we use.
+        cassette::block_on(poller);
+    }
+
+    #[test]
+    fn peek_wkc_before_consuming() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let data = [0xaau8, 0xbb, 0xcc, 0xdd];
+
+        let poller = poll_fn(|ctx| {
+            let mut written_packet = Vec::new();
+
+            let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+            let handle = frame
+                .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
+                .expect("Push PDU");
+
+            let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+            assert!(
+                matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
+                "frame fut should be pending"
+            );
+
+            tx.next_sendable_frame()
+                .expect("need a frame")
+                .send_blocking(|bytes| {
+                    written_packet.extend_from_slice(bytes);
+
+                    Ok(bytes.len())
+                })
+                .expect("send");
+
+            let mut sent = SentFrame::new(written_packet);
+
+            // Three SubDevices processed the PDU
+            sent.set_wkcs(&[3]);
+
+            let written_packet = sent.into_reply();
+
+            assert_eq!(
+                rx.receive_frame(&written_packet),
+                Ok(crate::ReceiveAction::Processed)
+            );
+
+            match frame_fut.poll(ctx) {
+                Poll::Ready(Ok(frame)) => {
+                    assert_eq!(frame.wkc(handle), Ok(3));
+                    assert_eq!(frame.wkc(handle), Ok(3));
+
+                    let response = frame.first_pdu(handle).expect("Handle");
+
+                    assert_eq!(response.deref(), &data);
+                    assert_eq!(response.working_counter, 3);
+                }
+                Poll::Ready(other) => panic!("Expected Ready(Ok()), got {:?}", other),
+                Poll::Pending => panic!("frame future still pending"),
+            }
+
+            Poll::Ready(())
+        });
+
+        cassette::block_on(poller);
+    }
+
+    #[test]
+    fn pdu_stats_multi_pdu_frame() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        assert_eq!(pdu_loop.pdu_stats(), PduStats::default());
+
+        let poller = poll_fn(|ctx| {
+            let mut written_packet = Vec::new();
+
+            let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+            frame
+                .push_pdu(Command::fpwr(0x1000, 0x0120).into(), 0x02u16, None)
+                .expect("Push PDU 1");
+            frame
+                .push_pdu(Command::fprd(0x1001, 0x0130).into(), (), Some(2))
+                .expect("Push PDU 2");
+            frame
+                .push_pdu(Command::brd(0x0000).into(), (), Some(1))
+                .expect("Push PDU 3");
+
+            assert_eq!(
+                pdu_loop.pdu_stats(),
+                PduStats {
+                    pdus_pushed: 3,
+                    pdus_received: 0
+                }
+            );
+
+            let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+            assert!(
+                matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
+                "frame fut should be pending"
+            );
+
+            tx.next_sendable_frame()
+                .expect("need a frame")
+                .send_blocking(|bytes| {
+                    written_packet.extend_from_slice(bytes);
+
+                    Ok(bytes.len())
+                })
+                .expect("send");
+
+            let written_packet = SentFrame::new(written_packet).into_reply();
+
+            assert_eq!(
+                rx.receive_frame(&written_packet),
+                Ok(crate::ReceiveAction::Processed)
+            );
+
+            assert!(matches!(frame_fut.poll(ctx), Poll::Ready(Ok(_))));
+
+            Poll::Ready(())
+        });
+
+        cassette::block_on(poller);
+
+        assert_eq!(
+            pdu_loop.pdu_stats(),
+            PduStats {
+                pdus_pushed: 3,
+                pdus_received: 3
+            }
+        );
+    }
+
+    #[test]
+    fn scrubbed_frame_round_trip() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let data = [0xaau8, 0xbb, 0xcc, 0xdd];
+
+        let poller = poll_fn(|ctx| {
+            let mut written_packet = Vec::new();
+
+            let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+            frame.set_scrub();
+
+            let handle = frame
+                .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
+                .expect("Push PDU");
+
+            let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+            assert!(
+                matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
+                "frame fut should be pending"
+            );
+
+            let frame = tx.next_sendable_frame().expect("need a frame");
+
+            frame
+                .send_blocking(|bytes| {
+                    written_packet.extend_from_slice(bytes);
+
+                    Ok(bytes.len())
+                })
+                .expect("send");
+
+            let written_packet = SentFrame::new(written_packet).into_reply();
+
+            assert_eq!(
+                rx.receive_frame(&written_packet),
+                Ok(crate::ReceiveAction::Processed)
+            );
+
+            let Poll::Ready(Ok(frame)) = frame_fut.poll(ctx) else {
+                panic!("frame future should be ready");
+            };
+
+            let response = frame.into_held_pdu(handle).expect("Handle");
+
+            // The response holds the frame, so the data must still be intact and the slot must not
+            // be reusable yet.
+            assert_eq!(response.deref(), &data);
+            assert!(pdu_loop.storage.alloc_frame().is_err());
+
+            drop(response);
+
+            let raw = unsafe {
+                FrameElement::<0>::test_only_ethernet_frame(
+                    pdu_loop.test_only_storage_ref().frame_at_index(0),
+                    128,
+                )
+            };
+
+            assert!(
+                raw.iter().all(|b| *b == 0),
+                "frame not scrubbed: {:02x?}",
+                raw
+            );
+
+            // Slot is released for reuse after scrubbing
+            assert!(pdu_loop.storage.alloc_frame().is_ok());
+
+            Poll::Ready(())
+        });
+
+        cassette::block_on(poller);
+    }
+
+    #[test]
+    fn unsent_frame_is_scrubbed() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+
+        let (_tx, _rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        frame.set_scrub();
+
+        frame
+            .push_pdu(Command::fpwr(0x5678, 0x1234).into(), [0xaau8; 16], None)
+            .expect("Push PDU");
+
+        drop(frame);
+
+        let raw = unsafe {
+            FrameElement::<0>::test_only_ethernet_frame(
+                pdu_loop.test_only_storage_ref().frame_at_index(0),
+                128,
+            )
+        };
+
+        assert!(
+            raw.iter().all(|b| *b == 0),
+            "frame not scrubbed: {:02x?}",
+            raw
+        );
+    }
+
+    #[test]
+    fn receive_with_colliding_first_pdu() {
+        crate::test_logger();
+
+        let storage = PduStorage::<2, 128>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let data = [0xaau8, 0xbb, 0xcc, 0xdd];
+
+        let poller = poll_fn(|ctx| {
+            // Frame in slot 0 is still being filled when the response for slot 1 comes back, and
+            // has the same first PDU index because the PDU index counter wrapped around in the
+            // meantime.
+            let mut stale = pdu_loop.storage.alloc_frame().expect("Stale frame alloc");
+
+            pdu_loop.storage.pdu_idx.store(0x20, Ordering::Relaxed);
+
+            stale
+                .push_pdu(Command::fprd(0x1000, 0x0130).into(), (), Some(2))
+                .expect("Push stale PDU");
+
+            pdu_loop.storage.pdu_idx.store(0x20, Ordering::Relaxed);
+
+            let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+            assert_eq!(frame.storage_slot_index(), 1);
+
+            let handle = frame
+                .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
+                .expect("Push PDU");
+
+            let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+            assert!(
+                matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
+                "frame fut should be pending"
+            );
+
+            let mut written_packet = Vec::new();
+
+            tx.next_sendable_frame()
+                .expect("need a frame")
+                .send_blocking(|bytes| {
+                    written_packet.extend_from_slice(bytes);
+
+                    Ok(bytes.len())
+                })
+                .expect("send");
+
+            let written_packet = SentFrame::new(written_packet).into_reply();
+
+            // The stale frame must be skipped rather than causing the response to be dropped
+            assert_eq!(
+                rx.receive_frame(&written_packet),
+                Ok(crate::ReceiveAction::Processed)
+            );
+
+            let Poll::Ready(Ok(frame)) = frame_fut.poll(ctx) else {
+                panic!("frame future should be ready");
+            };
+
+            assert_eq!(frame.first_pdu(handle).expect("Handle").deref(), &data);
+
+            drop(stale);
+
+            Poll::Ready(())
+        });
+
+        cassette::block_on(poller);
+    }
+
+    #[test]
+    fn released_frame_clears_first_pdu() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, 128>::new();
+
+        let (_tx, _rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        frame
+            .push_pdu(Command::fprd(0x1000, 0x0130).into(), (), Some(2))
+            .expect("Push PDU");
+
+        // Released via `ReceiveFrameFut` drop, e.g. on timeout
+        drop(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+        let element = pdu_loop.test_only_storage_ref().frame_at_index(0);
+
+        assert!(!unsafe { FrameElement::<0>::first_pdu_is(element, 0) });
+
+        // A stale match must not turn into an error for an unrelated response either
+        assert_eq!(
+            pdu_loop.storage.claim_receiving_by_first_pdu(0).map(|_| ()),
+            Err(PduError::Decode)
+        );
+    }
+
+    #[test]
+    fn tx_idle_wakes_on_response() {
+        crate::test_logger();
+
+        struct FlagWaker(AtomicBool);
+
+        impl Wake for FlagWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let storage = PduStorage::<2, 128>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let frame_waker = Waker::noop();
+        let mut frame_ctx = Context::from_waker(frame_waker);
+
+        let idle_flag = Arc::new(FlagWaker(AtomicBool::new(false)));
+        let idle_waker = Waker::from(idle_flag.clone());
+        let mut idle_ctx = Context::from_waker(&idle_waker);
+
+        let data = [0xaau8, 0xbb, 0xcc, 0xdd];
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        let handle = frame
+            .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
+            .expect("Push PDU");
+
+        // Created frames aren't pending yet
+        assert_eq!(tx.pending_sendable_frames(), 0);
+
+        let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+        assert!(frame_fut.as_mut().poll(&mut frame_ctx).is_pending());
+
+        assert_eq!(tx.pending_sendable_frames(), 1);
+
+        let mut idle = pin!(pdu_loop.tx_idle(1));
+
+        assert!(idle.as_mut().poll(&mut idle_ctx).is_pending());
+
+        let mut written_packet = Vec::new();
+
+        tx.next_sendable_frame()
+            .expect("need a frame")
+            .send_blocking(|bytes| {
+                written_packet.extend_from_slice(bytes);
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        // Frame is now in flight, which is still pending
+        assert_eq!(tx.pending_sendable_frames(), 1);
+        assert!(!idle_flag.0.load(Ordering::Relaxed));
+        assert!(idle.as_mut().poll(&mut idle_ctx).is_pending());
+
+        let written_packet = SentFrame::new(written_packet).into_reply();
+
+        assert_eq!(
+            rx.receive_frame(&written_packet),
+            Ok(crate::ReceiveAction::Processed)
+        );
+
+        // Receiving the response must wake the idle future
+        assert!(idle_flag.0.load(Ordering::Relaxed));
+        assert_eq!(tx.pending_sendable_frames(), 0);
+        assert!(idle.as_mut().poll(&mut idle_ctx).is_ready());
+
+        let Poll::Ready(Ok(frame)) = frame_fut.poll(&mut frame_ctx) else {
+            panic!("frame future should be ready");
+        };
+
+        assert_eq!(frame.first_pdu(handle).expect("Handle").deref(), &data);
+    }
+
+    #[test]
+    fn write_multiple_frame() {
+        static STORAGE: PduStorage<1, 128> = PduStorage::<1, 128>::new();
+        let (_tx, _rx, pdu_loop) = STORAGE.try_split().unwrap();
+
+        // ---
+
+        let data = [0xaau8, 0xbb, 0xcc];
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        let _handle = frame
+            .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
+            .expect("Push PDU");
+
+        // Drop frame future to reset its state to `FrameState::None`
+        drop(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+        // ---
+
+        let data = [0xaau8, 0xbb];
+
+        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+        let _handle = frame
+            .push_pdu(Command::fpwr(0x6789, 0x1234).into(), data, None)
+            .expect("Push second PDU");
+
+        let frame = frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX);
+
+        // ---
+
+        assert_eq!(
+            frame.buf(),
+            &[
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Broadcast address
+                0x10, 0x10, 0x10, 0x10, 0x10, 0x10, // Master address
+                0x88, 0xa4, // EtherCAT ethertype
+                0x0e, 0x10, // EtherCAT frame header: type PDU, length 2 (plus header)
+                0x05, // Command: FPWR
+                0x01, // Frame index 1 (first dropped frame used up index 0)
+                0x89, 0x67, // SubDevice address,
+                0x34, 0x12, // Register address
+                0x02, 0x00, // Flags, 2 byte length
+                0x00, 0x00, // IRQ
+                0xaa, 0xbb, // Our payload
+                0x00, 0x00, // Working counter
+            ]
+        );
+    }
+
+    #[test]
+    fn receive_frame() {
+        crate::test_logger();
+
+        let ethernet_packet = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Broadcast address
+            0x12, 0x10, 0x10, 0x10, 0x10, 0x10, // Return to master address
+            0x88, 0xa4, // EtherCAT ethertype
+            0x10, 0x10, // EtherCAT frame header: type PDU, length 4 (plus header)
+            0x05, // Command: FPWR
+            0x00, // Frame index 0
+            0x89, 0x67, // SubDevice address,
+            0x34, 0x12, // Register address
+            0x04, 0x00, // Flags, 4 byte length
+            0x00, 0x00, // IRQ
+            0xdd, 0xcc, 0xbb, 0xaa, // Our payload, LE
+            0x00, 0x00, // Working counter
+        ];
+
+        // 1 frame, up to 128 bytes payload
+        let storage = PduStorage::<1, 128>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let data = 0xAABBCCDDu32;
+        let data_bytes = data.to_le_bytes();
+
+        let poller = poll_fn(|ctx| {
+            let mut frame = pdu_loop.storage.alloc_frame().unwrap();
+
+            let handle = frame
+                .push_pdu(Command::fpwr(0x6789, 0x1234).into(), data_bytes, None)
+                .expect("Push PDU");
+
+            let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+            // Poll future up to first await point. This gets the frame ready and marks it as
+            // sendable so TX can pick it up, but we don't want to wait for the response so we won't
+            // poll it again.
+            assert!(
+                matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
+                "frame fut should be pending"
+            );
+
+            let frame = tx.next_sendable_frame().expect("need a frame");
+
+            frame.send_blocking(|bytes| Ok(bytes.len())).expect("send");
+
+            // ---
+
+            let result = rx.receive_frame(&with_fcs(&ethernet_packet));
+
+            assert_eq!(result, Ok(crate::ReceiveAction::Processed));
+
+            // The frame has received a response at this point so should be ready to get the data
+            // from
+            match frame_fut.poll(ctx) {
+                Poll::Ready(Ok(frame)) => {
+                    assert_eq!(frame.first_pdu(handle).unwrap().deref(), &data_bytes);
+                }
+                Poll::Ready(other) => panic!("Expected Ready(Ok()), got {:?}", other),
+                Poll::Pending => panic!("frame future still pending"),
+            }
+
+            // We should only ever be going through this loop once as the number of individual
+            // `poll()` calls is calculated.
+            Poll::Ready(())
+        });
+
+        cassette::block_on(poller);
+    }
+
+    #[test]
+    fn max_frame_data() {
+        crate::test_logger();
+
+        const DATA: usize = 256;
+
+        let storage = PduStorage::<2, DATA>::new();
+
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+
+        assert_eq!(pdu_loop.max_frame_data(), DATA - 28 - frame_layout::FCS_LEN);
+
+        let max = pdu_loop.max_frame_data();
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        assert!(
+            frame
+                .push_pdu(
+                    Command::fpwr(0x1000, 0x1000).into(),
+                    &[0xaau8; DATA][..max],
+                    None
+                )
+                .is_ok()
+        );
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        assert_eq!(
+            frame
+                .push_pdu(
+                    Command::fpwr(0x1000, 0x1000).into(),
+                    &[0xaau8; DATA][..=max],
+                    None
+                )
+                .unwrap_err(),
+            PduError::TooLong
+        );
+
+        // A VLAN tag takes space away from PDU data
+        tx.set_vlan_tag(Some(crate::VlanTag {
+            priority: 0,
+            drop_eligible: false,
+            vlan_id: 1,
+        }));
+
+        assert_eq!(
+            pdu_loop.max_frame_data(),
+            max - crate::ethernet::VLAN_TAG_LEN
+        );
+    }
+
+    #[test]
+    fn vlan_tagged_round_trip() {
         crate::test_logger();
 
-        const FRAME_OVERHEAD: usize = 28;
-
         // 1 frame, up to 128 bytes payload
         let storage = PduStorage::<1, 128>::new();
 
         let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
 
+        let tag = crate::VlanTag {
+            priority: 5,
+            drop_eligible: false,
+            vlan_id: 0x123,
+        };
+
+        tx.set_vlan_tag(Some(tag));
+
         let data = [0xaau8, 0xbb, 0xcc, 0xdd];
 
-        // Using poll_fn so we can manually poll the frame future multiple times
         let poller = poll_fn(|ctx| {
-            let mut written_packet = vec![0; FRAME_OVERHEAD + data.len()];
+            let mut written_packet = Vec::new();
 
             let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
 
@@ -237,9 +1115,6 @@ mod tests {
 
             let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
 
-            // Poll future up to first await point. This gets the frame ready and marks it as
-            // sendable so TX can pick it up, but we don't want to wait for the response so we won't
-            // poll it again.
             assert!(
                 matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
                 "frame fut should be pending"
@@ -247,110 +1122,68 @@ mod tests {
 
             let frame = tx.next_sendable_frame().expect("need a frame");
 
-            let send_fut = pin!(async move {
-                frame
-                    .send_blocking(|bytes| {
-                        written_packet.copy_from_slice(bytes);
-
-                        Ok(bytes.len())
-                    })
-                    .expect("send");
+            frame
+                .send_blocking(|bytes| {
+                    written_packet.extend_from_slice(bytes);
 
-                // Munge fake sent frame into a fake received frame
-                {
-                    let mut frame = EthernetFrame::new_checked(written_packet).unwrap();
-                    frame.set_src_addr(EthernetAddress([0x12, 0x10, 0x10, 0x10, 0x10, 0x10]));
-                    frame.into_inner()
-                }
-            });
+                    Ok(bytes.len())
+                })
+                .expect("send");
 
-            let Poll::Ready(written_packet) = send_fut.poll(ctx) else {
-                panic!("no send")
-            };
+            assert_eq!(
+                written_packet[0..26],
+                [
+                    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Broadcast address
+                    0x10, 0x10, 0x10, 0x10, 0x10, 0x10, // Master address
+                    0x81, 0x00, // 802.1Q TPID
+                    0xa1, 0x23, // PCP 5, DEI 0, VID 0x123
+                    0x88, 0xa4, // EtherCAT ethertype
+                    0x10, 0x10, // EtherCAT frame header: type PDU, length 4 (plus header)
+                    0x05, // Command: FPWR
+                    0x00, // Frame index 0
+                    0x78, 0x56, // SubDevice address,
+                    0x34, 0x12, // Register address
+                ]
+            );
+            assert_eq!(
+                written_packet.len(),
+                wire_len(
+                    frame_layout::MIN_ELEMENT_SIZE - frame_layout::FCS_LEN
+                        + data.len()
+                        + crate::ethernet::VLAN_TAG_LEN
+                )
+            );
 
-            assert_eq!(written_packet.len(), FRAME_OVERHEAD + data.len());
+            // Fake a response from the network, keeping the VLAN tag
+            let response = SentFrame::new(written_packet).into_reply();
 
-            // ---
+            assert_eq!(
+                EthernetFrame::new_checked(response.as_slice())
+                    .unwrap()
+                    .vlan_tag(),
+                Some(tag)
+            );
 
-            let result = rx.receive_frame(&written_packet);
+            let result = rx.receive_frame(&response);
 
             assert_eq!(result, Ok(crate::ReceiveAction::Processed));
 
-            // The frame has received a response at this point so should be ready to get the data
-            // from
             match frame_fut.poll(ctx) {
                 Poll::Ready(Ok(frame)) => {
-                    let response = frame.first_pdu(handle).expect("Handle");
-
-                    assert_eq!(response.deref(), &data);
+                    assert_eq!(frame.first_pdu(handle).unwrap().deref(), &data);
                 }
                 Poll::Ready(other) => panic!("Expected Ready(Ok()), got {:?}", other),
                 Poll::Pending => panic!("frame future still pending"),
             }
 
-            // We should only ever be going through this loop once as the number of individual
-            // `poll()` calls is calculated.
             Poll::Ready(())
         });
 
-        // Using `cassette` otherwise miri complains about a memory leak inside whichever other
-        // `block_on` or `.await` we use.
         cassette::block_on(poller);
     }
 
     #[test]
-    fn write_multiple_frame() {
-        static STORAGE: PduStorage<1, 128> = PduStorage::<1, 128>::new();
-        let (_tx, _rx, pdu_loop) = STORAGE.try_split().unwrap();
-
-        // ---
-
-        let data = [0xaau8, 0xbb, 0xcc];
-
-        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
-
-        let _handle = frame
-            .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
-            .expect("Push PDU");
-
-        // Drop frame future to reset its state to `FrameState::None`
-        drop(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
-
-        // ---
-
-        let data = [0xaau8, 0xbb];
-
-        let mut frame = pdu_loop.storage.alloc_frame().unwrap();
-
-        let _handle = frame
-            .push_pdu(Command::fpwr(0x6789, 0x1234).into(), data, None)
-            .expect("Push second PDU");
-
-        let frame = frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX);
-
-        // ---
-
-        assert_eq!(
-            frame.buf(),
-            &[
-                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Broadcast address
-                0x10, 0x10, 0x10, 0x10, 0x10, 0x10, // Master address
-                0x88, 0xa4, // EtherCAT ethertype
-                0x0e, 0x10, // EtherCAT frame header: type PDU, length 2 (plus header)
-                0x05, // Command: FPWR
-                0x01, // Frame index 1 (first dropped frame used up index 0)
-                0x89, 0x67, // SubDevice address,
-                0x34, 0x12, // Register address
-                0x02, 0x00, // Flags, 2 byte length
-                0x00, 0x00, // IRQ
-                0xaa, 0xbb, // Our payload
-                0x00, 0x00, // Working counter
-            ]
-        );
-    }
-
-    #[test]
-    fn receive_frame() {
+    fn receive_frame_manual_index() {
         crate::test_logger();
 
         let ethernet_packet = [
@@ -359,35 +1192,32 @@ mod tests {
             0x88, 0xa4, // EtherCAT ethertype
             0x10, 0x10, // EtherCAT frame header: type PDU, length 4 (plus header)
             0x05, // Command: FPWR
-            0x00, // Frame index 0
+            0xa5, // PDU index set by caller
             0x89, 0x67, // SubDevice address,
             0x34, 0x12, // Register address
             0x04, 0x00, // Flags, 4 byte length
             0x00, 0x00, // IRQ
             0xdd, 0xcc, 0xbb, 0xaa, // Our payload, LE
-            0x00, 0x00, // Working counter
+            0x01, 0x00, // Working counter
         ];
 
-        // 1 frame, up to 128 bytes payload
         let storage = PduStorage::<1, 128>::new();
 
         let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
 
-        let data = 0xAABBCCDDu32;
-        let data_bytes = data.to_le_bytes();
+        let data_bytes = 0xAABBCCDDu32.to_le_bytes();
 
         let poller = poll_fn(|ctx| {
             let mut frame = pdu_loop.storage.alloc_frame().unwrap();
 
             let handle = frame
-                .push_pdu(Command::fpwr(0x6789, 0x1234).into(), data_bytes, None)
+                .push_pdu_with_index(Command::fpwr(0x6789, 0x1234).into(), data_bytes, 0xa5)
                 .expect("Push PDU");
 
+            assert_eq!(handle.pdu_idx, 0xa5);
+
             let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
 
-            // Poll future up to first await point. This gets the frame ready and marks it as
-            // sendable so TX can pick it up, but we don't want to wait for the response so we won't
-            // poll it again.
             assert!(
                 matches!(frame_fut.as_mut().poll(ctx), Poll::Pending),
                 "frame fut should be pending"
@@ -395,32 +1225,67 @@ mod tests {
 
             let frame = tx.next_sendable_frame().expect("need a frame");
 
-            frame.send_blocking(|bytes| Ok(bytes.len())).expect("send");
-
-            // ---
+            frame
+                .send_blocking(|bytes| {
+                    // Index must be written as given
+                    assert_eq!(bytes[17], 0xa5);
 
-            let result = rx.receive_frame(&ethernet_packet);
+                    Ok(bytes.len())
+                })
+                .expect("send");
 
-            assert_eq!(result, Ok(crate::ReceiveAction::Processed));
+            assert_eq!(
+                rx.receive_frame(&with_fcs(&ethernet_packet)),
+                Ok(crate::ReceiveAction::Processed)
+            );
 
-            // The frame has received a response at this point so should be ready to get the data
-            // from
             match frame_fut.poll(ctx) {
                 Poll::Ready(Ok(frame)) => {
-                    assert_eq!(frame.first_pdu(handle).unwrap().deref(), &data_bytes);
+                    let response = frame.first_pdu(handle).unwrap();
+
+                    assert_eq!(response.deref(), &data_bytes);
+                    assert_eq!(response.working_counter, 1);
                 }
                 Poll::Ready(other) => panic!("Expected Ready(Ok()), got {:?}", other),
                 Poll::Pending => panic!("frame future still pending"),
             }
 
-            // We should only ever be going through this loop once as the number of individual
-            // `poll()` calls is calculated.
             Poll::Ready(())
         });
 
         cassette::block_on(poller);
     }
 
+    #[test]
+    fn receive_truncated_frame() {
+        crate::test_logger();
+
+        let ethernet_packet = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Broadcast address
+            0x12, 0x10, 0x10, 0x10, 0x10, 0x10, // Return to master address
+            0x88, 0xa4, // EtherCAT ethertype
+            0x10, 0x10, // EtherCAT frame header: type PDU, length 16 (plus header)
+            0x05, // Command: FPWR
+            0x00, // Frame index 0
+            0x89, 0x67, // SubDevice address,
+            0x34, 0x12, // Register address
+            0x04, 0x00, // Flags, 4 byte length
+            0x00, 0x00, // IRQ, then payload and working counter are missing
+        ];
+
+        let storage = PduStorage::<1, 128>::new();
+
+        let (_tx, mut rx, _pdu_loop) = storage.try_split().unwrap();
+
+        assert_eq!(
+            rx.receive_frame(&with_fcs(&ethernet_packet)),
+            Err(Error::Pdu(PduError::PayloadLengthMismatch {
+                declared: 16,
+                actual: 10
+            }))
+        );
+    }
+
     // Frames whos response is received from the network and ready for use before the first poll
     // should still complete, instead of failing with a `NoWaker` error.
     //
@@ -466,7 +1331,7 @@ mod tests {
 
         // ---
 
-        let result = rx.receive_frame(&ethernet_packet);
+        let result = rx.receive_frame(&with_fcs(&ethernet_packet));
 
         assert_eq!(result, Ok(crate::ReceiveAction::Processed));
 
@@ -518,11 +1383,7 @@ mod tests {
                     fmt::trace!("RX task received packet");
 
                     // Munge fake sent frame into a fake received frame
-                    let ethernet_frame = {
-                        let mut frame = EthernetFrame::new_checked(ethernet_frame).unwrap();
-                        frame.set_src_addr(EthernetAddress([0x12, 0x10, 0x10, 0x10, 0x10, 0x10]));
-                        frame.into_inner()
-                    };
+                    let ethernet_frame = SentFrame::new(ethernet_frame).into_reply();
 
                     rx.receive_frame(&ethernet_frame).expect("RX");
                 }
@@ -611,11 +1472,7 @@ mod tests {
                     thread::sleep(Duration::from_millis(1));
 
                     // Munge fake sent frame into a fake received frame
-                    let ethernet_frame = {
-                        let mut frame = EthernetFrame::new_checked(ethernet_frame).unwrap();
-                        frame.set_src_addr(EthernetAddress([0x12, 0x10, 0x10, 0x10, 0x10, 0x10]));
-                        frame.into_inner()
-                    };
+                    let ethernet_frame = SentFrame::new(ethernet_frame).into_reply();
 
                     while rx.receive_frame(&ethernet_frame).is_err() {}
 
@@ -679,28 +1536,31 @@ mod tests {
 
     #[test]
     fn split_pdi() {
+        use crate::pdu_loop::frame_element::created_frame::PduResponseHandle;
+
         crate::test_logger();
 
-        const DATA: usize = 48;
+        const DATA: usize = 64 + frame_layout::FCS_LEN;
 
-        // 8 frames, 32 bytes each
+        // 8 frames, each with room for a 36 byte PDU payload
         static STORAGE: PduStorage<8, DATA> = PduStorage::new();
         let (_tx, _rx, pdu_loop) = STORAGE.try_split().unwrap();
 
-        let pdi = [0xaau8; 64];
+        let pdi = [0xaau8; 112];
         let mut remaining = &pdi[..];
 
         let (sent, _handle) = {
             let mut frame = pdu_loop.alloc_frame().expect("No frame");
 
-            let res = frame.push_pdu_slice_rest(Command::Nop, remaining);
+            let res = frame.push_pdu_slice_rest(Command::Nop, remaining, None);
 
             let expected_pushed_bytes = DATA
-                - EthernetFrame::<&[u8]>::header_len()
-                - EthercatFrameHeader::header_len()
-                - CreatedFrame::PDU_OVERHEAD_BYTES;
+                - frame_layout::ETHERNET_HEADER_LEN
+                - frame_layout::ETHERCAT_HEADER_LEN
+                - frame_layout::PDU_OVERHEAD
+                - frame_layout::FCS_LEN;
 
-            assert_eq!(expected_pushed_bytes, 20);
+            assert_eq!(expected_pushed_bytes, 36);
 
             assert_eq!(
                 res,
@@ -711,9 +1571,10 @@ mod tests {
                         pdu_idx: 0,
                         command_code: 0,
                         // We've used as much of the frame as possible
-                        alloc_size: pdu_loop.max_frame_data()
-                            - EthernetFrame::<&[u8]>::header_len()
-                            - EthercatFrameHeader::header_len()
+                        alloc_size: pdu_loop.max_frame_len()
+                            - frame_layout::ETHERNET_HEADER_LEN
+                            - frame_layout::ETHERCAT_HEADER_LEN
+                            - frame_layout::FCS_LEN
                     }
                 )))
             );
@@ -723,19 +1584,20 @@ mod tests {
 
         remaining = &remaining[sent..];
 
-        assert_eq!(remaining.len(), 44);
+        assert_eq!(remaining.len(), 76);
 
         let (sent, _handle) = {
             let mut frame = pdu_loop.alloc_frame().expect("No frame");
 
-            let res = frame.push_pdu_slice_rest(Command::Nop, remaining);
+            let res = frame.push_pdu_slice_rest(Command::Nop, remaining, None);
 
             let expected_pushed_bytes = DATA
-                - EthernetFrame::<&[u8]>::header_len()
-                - EthercatFrameHeader::header_len()
-                - CreatedFrame::PDU_OVERHEAD_BYTES;
+                - frame_layout::ETHERNET_HEADER_LEN
+                - frame_layout::ETHERCAT_HEADER_LEN
+                - frame_layout::PDU_OVERHEAD
+                - frame_layout::FCS_LEN;
 
-            assert_eq!(expected_pushed_bytes, 20);
+            assert_eq!(expected_pushed_bytes, 36);
 
             assert_eq!(
                 res,
@@ -746,9 +1608,10 @@ mod tests {
                         pdu_idx: 1,
                         command_code: 0,
                         // We've used as much of the frame as possible
-                        alloc_size: pdu_loop.max_frame_data()
-                            - EthernetFrame::<&[u8]>::header_len()
-                            - EthercatFrameHeader::header_len()
+                        alloc_size: pdu_loop.max_frame_len()
+                            - frame_layout::ETHERNET_HEADER_LEN
+                            - frame_layout::ETHERCAT_HEADER_LEN
+                            - frame_layout::FCS_LEN
                     }
                 )))
             );
@@ -758,19 +1621,20 @@ mod tests {
 
         remaining = &remaining[sent..];
 
-        assert_eq!(remaining.len(), 24);
+        assert_eq!(remaining.len(), 40);
 
         let (sent, _handle) = {
             let mut frame = pdu_loop.alloc_frame().expect("No frame");
 
-            let res = frame.push_pdu_slice_rest(Command::Nop, remaining);
+            let res = frame.push_pdu_slice_rest(Command::Nop, remaining, None);
 
             let expected_pushed_bytes = DATA
-                - EthernetFrame::<&[u8]>::header_len()
-                - EthercatFrameHeader::header_len()
-                - CreatedFrame::PDU_OVERHEAD_BYTES;
+                - frame_layout::ETHERNET_HEADER_LEN
+                - frame_layout::ETHERCAT_HEADER_LEN
+                - frame_layout::PDU_OVERHEAD
+                - frame_layout::FCS_LEN;
 
-            assert_eq!(expected_pushed_bytes, 20);
+            assert_eq!(expected_pushed_bytes, 36);
 
             assert_eq!(
                 res,
@@ -781,9 +1645,10 @@ mod tests {
                         pdu_idx: 2,
                         command_code: 0,
                         // We've used as much of the frame as possible
-                        alloc_size: pdu_loop.max_frame_data()
-                            - EthernetFrame::<&[u8]>::header_len()
-                            - EthercatFrameHeader::header_len()
+                        alloc_size: pdu_loop.max_frame_len()
+                            - frame_layout::ETHERNET_HEADER_LEN
+                            - frame_layout::ETHERCAT_HEADER_LEN
+                            - frame_layout::FCS_LEN
                     }
                 )))
             );
@@ -798,7 +1663,7 @@ mod tests {
         let (sent, _handle) = {
             let mut frame = pdu_loop.alloc_frame().expect("No frame");
 
-            let res = frame.push_pdu_slice_rest(Command::Nop, remaining);
+            let res = frame.push_pdu_slice_rest(Command::Nop, remaining, None);
 
             // Partial frame as we're at the end of the data we want to send
             let expected_pushed_bytes = 4;
@@ -812,7 +1677,7 @@ mod tests {
                         pdu_idx: 3,
                         command_code: 0,
                         // Partial write because we're at the end of our data
-                        alloc_size: expected_pushed_bytes + CreatedFrame::PDU_OVERHEAD_BYTES
+                        alloc_size: expected_pushed_bytes + frame_layout::PDU_OVERHEAD
                     }
                 )))
             );
@@ -824,4 +1689,273 @@ mod tests {
 
         assert_eq!(&remaining[sent..], empty);
     }
+
+    #[test]
+    fn receive_mailbox_frame_ignored() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        frame
+            .push_pdu(Command::fprd(0x1000, 0x0130).into(), (), Some(2))
+            .expect("Push");
+
+        let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        assert!(frame_fut.as_mut().poll(&mut ctx).is_pending());
+
+        let mut sent = Vec::new();
+
+        tx.next_sendable_frame()
+            .expect("need a frame")
+            .send_blocking(|bytes| {
+                sent.extend_from_slice(bytes);
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        // Same frame, but with a mailbox gateway protocol type in the upper 4 bits of the EtherCAT
+        // header
+        let mut mailbox = sent.clone();
+        mailbox[15] = (mailbox[15] & 0x0f) | ((crate::ProtocolType::Mailbox as u8) << 4);
+
+        assert_eq!(
+            rx.receive_frame(&SentFrame::new(mailbox).into_reply()),
+            Ok(crate::ReceiveAction::Ignored)
+        );
+
+        // The PDU frame is still waiting for its response
+        assert!(frame_fut.as_mut().poll(&mut ctx).is_pending());
+
+        assert_eq!(
+            rx.receive_frame(&SentFrame::new(sent).into_reply()),
+            Ok(crate::ReceiveAction::Processed)
+        );
+
+        assert!(matches!(
+            frame_fut.as_mut().poll(&mut ctx),
+            Poll::Ready(Ok(_))
+        ));
+    }
+
+    #[test]
+    fn nop_padding_round_trip() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(64) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        let read = frame
+            .push_pdu(Command::fprd(0x1001, 0x0130).into(), (), Some(2))
+            .expect("Push read");
+        let nop = frame.push_pdu(Command::Nop, (), Some(4)).expect("Push NOP");
+        let write = frame
+            .push_pdu(
+                Command::fpwr(0x1001, 0x0120).into(),
+                [0x01u8, 0x02, 0x03],
+                None,
+            )
+            .expect("Push write");
+
+        // NOP padding reserves its full length, just like any other PDU
+        assert_eq!(nop.alloc_size, 4 + frame_layout::PDU_OVERHEAD);
+        assert!(nop.is_nop());
+        assert!(!read.is_nop());
+
+        let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        assert!(frame_fut.as_mut().poll(&mut ctx).is_pending());
+
+        let mut sent = Vec::new();
+
+        tx.next_sendable_frame()
+            .expect("need a frame")
+            .send_blocking(|bytes| {
+                sent.extend_from_slice(bytes);
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        // Read, NOP and write PDUs with 2, 4 and 3 byte payloads
+        assert_eq!(
+            sent.len(),
+            frame_layout::ETHERNET_HEADER_LEN
+                + frame_layout::ETHERCAT_HEADER_LEN
+                + (2 + 4 + 3)
+                + frame_layout::PDU_OVERHEAD * 3
+                + frame_layout::FCS_LEN
+        );
+
+        let mut response = sent;
+
+        // Read data and WKC
+        response[26..28].copy_from_slice(&[0xaa, 0xbb]);
+        response[28..30].copy_from_slice(&1u16.to_le_bytes());
+        // Garbage in the NOP must not leak into its response
+        response[40..44].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        response[44..46].copy_from_slice(&5u16.to_le_bytes());
+        // Write WKC
+        response[59..61].copy_from_slice(&1u16.to_le_bytes());
+
+        let response = SentFrame::new(response).into_reply();
+
+        assert_eq!(
+            rx.receive_frame(&response),
+            Ok(crate::ReceiveAction::Processed)
+        );
+
+        let Poll::Ready(Ok(frame)) = frame_fut.as_mut().poll(&mut ctx) else {
+            panic!("Expected a response");
+        };
+
+        let read = frame.pdu(read).expect("Read");
+        assert_eq!(read.deref(), &[0xaa, 0xbb]);
+        assert_eq!(read.working_counter, 1);
+
+        let nop = frame.pdu(nop).expect("NOP");
+        assert!(nop.is_empty());
+        assert_eq!(nop.working_counter, 0);
+
+        let write = frame.pdu(write).expect("Write");
+        assert_eq!(write.deref(), &[0x01, 0x02, 0x03]);
+        assert_eq!(write.working_counter, 1);
+    }
+
+    #[test]
+    fn nop_first_in_frame() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        let nop = frame.push_pdu(Command::Nop, (), Some(2)).expect("Push NOP");
+        let read = frame
+            .push_pdu(Command::brd(0x0000).into(), (), Some(1))
+            .expect("Push read");
+
+        let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        assert!(frame_fut.as_mut().poll(&mut ctx).is_pending());
+
+        let mut sent = Vec::new();
+
+        tx.next_sendable_frame()
+            .expect("need a frame")
+            .send_blocking(|bytes| {
+                sent.extend_from_slice(bytes);
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        let mut response = sent;
+
+        // NOP is returned untouched. BRD data and WKC follow it.
+        response[40] = 0x42;
+        response[41..43].copy_from_slice(&3u16.to_le_bytes());
+
+        let response = SentFrame::new(response).into_reply();
+
+        assert_eq!(
+            rx.receive_frame(&response),
+            Ok(crate::ReceiveAction::Processed)
+        );
+
+        let Poll::Ready(Ok(frame)) = frame_fut.as_mut().poll(&mut ctx) else {
+            panic!("Expected a response");
+        };
+
+        {
+            let read = frame.pdu(read).expect("Read");
+            assert_eq!(read.deref(), &[0x42]);
+            assert_eq!(read.working_counter, 3);
+        }
+
+        let nop = frame.first_pdu(nop).expect("NOP");
+        assert!(nop.is_empty());
+        assert_eq!(nop.working_counter, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "software-fcs")]
+    fn software_fcs_loopback() {
+        crate::test_logger();
+
+        // Computed independently of EtherCrab, as the first SubDevice would
+        const ETHERNET_FCS: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+        let storage = PduStorage::<1, { PduStorage::element_size(4) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let data = [0xaau8, 0xbb, 0xcc, 0xdd];
+
+        let mut frame = pdu_loop.storage.alloc_frame().expect("Frame alloc");
+
+        let handle = frame
+            .push_pdu(Command::fpwr(0x5678, 0x1234).into(), data, None)
+            .expect("Push PDU");
+
+        let mut frame_fut = pin!(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        assert!(frame_fut.as_mut().poll(&mut ctx).is_pending());
+
+        let mut sent = Vec::new();
+
+        tx.next_sendable_frame()
+            .expect("need a frame")
+            .send_blocking(|bytes| {
+                sent.extend_from_slice(bytes);
+
+                Ok(bytes.len())
+            })
+            .expect("send");
+
+        // 32 byte frame padded to the 60 byte Ethernet minimum, then the FCS
+        assert_eq!(sent.len(), 64);
+
+        let (sent, fcs) = sent.split_at(sent.len() - 4);
+
+        assert!(sent[32..].iter().all(|b| *b == 0), "padding not zeroed");
+        assert_eq!(fcs, ETHERNET_FCS.checksum(sent).to_le_bytes());
+
+        // Loop the frame back, recomputing the FCS after the source address is changed
+        let mut frame = EthernetFrame::new_checked(sent.to_vec()).unwrap();
+        frame.set_src_addr(crate::ethernet::EthernetAddress([
+            0x12, 0x10, 0x10, 0x10, 0x10, 0x10,
+        ]));
+        let mut looped = frame.into_inner();
+        looped.extend_from_slice(&ETHERNET_FCS.checksum(&looped).to_le_bytes());
+
+        let mut corrupted = looped.clone();
+        corrupted[26] ^= 0xff;
+
+        assert_eq!(rx.receive_frame(&corrupted), Err(Error::ReceiveFrame));
+        assert_eq!(
+            rx.receive_frame(&looped),
+            Ok(crate::ReceiveAction::Processed)
+        );
+
+        match frame_fut.as_mut().poll(&mut ctx) {
+            Poll::Ready(Ok(frame)) => {
+                let response = frame.first_pdu(handle).expect("Handle");
+
+                assert_eq!(response.deref(), &data);
+            }
+            Poll::Ready(other) => panic!("Expected Ready(Ok()), got {:?}", other),
+            Poll::Pending => panic!("frame future still pending"),
+        }
+    }
 }
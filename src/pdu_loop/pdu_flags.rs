@@ -26,17 +26,9 @@ impl PduFlags {
         }
     }
 
-    pub const fn with_len(len: u16) -> Self {
-        Self::new(len, false)
-    }
-
     pub const fn len(self) -> u16 {
         self.length
     }
-
-    pub const fn const_default() -> Self {
-        Self::with_len(0)
-    }
 }
 
 impl ethercrab_wire::EtherCrabWireWrite for PduFlags {
@@ -3,8 +3,11 @@ use crate::ethernet::{EthernetAddress, EthernetFrame};
 use crate::{
     ETHERCAT_ETHERTYPE, MAINDEVICE_ADDR,
     error::{Error, PduError},
-    fmt,
-    pdu_loop::frame_header::EthercatFrameHeader,
+    fmt, frame_layout,
+    pdu_loop::{
+        PduHeader,
+        frame_header::{EthercatFrameHeader, ProtocolType},
+    },
 };
 use core::sync::atomic::Ordering;
 use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireSized};
@@ -47,6 +50,15 @@ impl<'sto> PduRx<'sto> {
 
     /// Given a complete Ethernet II frame, parse a response PDU from it and wake the future that
     /// sent the frame.
+    ///
+    /// Frames carrying an 802.1Q VLAN tag are accepted, with the tag skipped over. Note that the
+    /// Linux raw socket used by `std::tx_rx_task` is bound to the EtherCAT EtherType, so a tagged
+    /// frame is only received there if the kernel removes the tag before delivering it, e.g. with
+    /// VLAN offload enabled on the interface. Network drivers that pass tagged frames to this
+    /// method unchanged are not affected.
+    ///
+    /// If the `software-fcs` feature is enabled, the frame must end with a valid FCS, otherwise
+    /// [`Error::ReceiveFrame`] is returned.
     // NOTE: &mut self so this struct can only be used in one place.
     pub fn receive_frame(&mut self, ethernet_frame: &[u8]) -> Result<ReceiveAction, Error> {
         if self.should_exit() {
@@ -60,14 +72,33 @@ impl<'sto> PduRx<'sto> {
         // first SubDevice will set the second bit of the MSB of the MAC address (U/L bit). This means
         // if we send e.g. 10:10:10:10:10:10, we receive 12:10:10:10:10:10 which passes through this
         // filter.
-        if raw_packet.ethertype() != ETHERCAT_ETHERTYPE || raw_packet.src_addr() == self.source_mac
+        //
+        // EtherCAT frames may also carry an 802.1Q VLAN tag, which is skipped over.
+        //
+        // The self-sourced frame filter can be turned off with
+        // `MainDeviceConfig::ignore_self_filter` for setups where responses don't come back with
+        // the U/L bit set.
+        if raw_packet.inner_ethertype() != ETHERCAT_ETHERTYPE
+            || (self.storage.self_filter_enabled() && raw_packet.src_addr() == self.source_mac)
         {
             fmt::trace!("Ignore frame");
 
             return Ok(ReceiveAction::Ignored);
         }
 
-        let i = raw_packet.payload();
+        #[cfg(feature = "software-fcs")]
+        let raw_packet =
+            EthernetFrame::new_checked(super::fcs::verify(ethernet_frame).ok_or_else(|| {
+                fmt::error!("Received frame has an invalid FCS");
+
+                Error::ReceiveFrame
+            })?)?;
+
+        if let Some(tag) = raw_packet.vlan_tag() {
+            fmt::trace!("Frame has VLAN tag {:?}", tag);
+        }
+
+        let i = raw_packet.inner_payload();
 
         let frame_header = EthercatFrameHeader::unpack_from_slice(i).inspect_err(|&e| {
             fmt::error!("Failed to parse frame header: {}", e);
@@ -79,6 +110,12 @@ impl<'sto> PduRx<'sto> {
             return Ok(ReceiveAction::Ignored);
         }
 
+        if frame_header.protocol != ProtocolType::DlPdu {
+            fmt::trace!("Ignoring non-PDU frame {:?}", frame_header.protocol);
+
+            return Ok(ReceiveAction::Ignored);
+        }
+
         // Skip EtherCAT header and get PDU(s) payload
         let i = i
             .get(
@@ -86,9 +123,18 @@ impl<'sto> PduRx<'sto> {
                     ..(EthercatFrameHeader::PACKED_LEN + usize::from(frame_header.payload_len)),
             )
             .ok_or_else(|| {
-                fmt::error!("Received frame is too short");
-
-                Error::ReceiveFrame
+                let actual = i.len().saturating_sub(EthercatFrameHeader::PACKED_LEN) as u16;
+
+                fmt::error!(
+                    "Received frame is too short: header declares {} payload bytes, got {}",
+                    frame_header.payload_len,
+                    actual
+                );
+
+                PduError::PayloadLengthMismatch {
+                    declared: frame_header.payload_len,
+                    actual,
+                }
             })?;
 
         // `i` now contains the EtherCAT frame payload, consisting of one or more PDUs including
@@ -101,31 +147,26 @@ impl<'sto> PduRx<'sto> {
         // use the first one.
 
         // PDU has its own EtherCAT index. This needs mapping back to the original frame.
-        let frame_index = self
-            .storage
-            .frame_index_by_first_pdu_index(pdu_idx)
-            .ok_or(Error::Pdu(PduError::Decode))?;
+        let mut frame = self.storage.claim_receiving_by_first_pdu(pdu_idx)?;
 
-        fmt::trace!(
-            "Receiving frame index {} (found from PDU {:#04x})",
-            frame_index,
-            pdu_idx
-        );
+        if frame.buf_mut().len() < i.len() {
+            // Let the waiting future time out and retry instead of leaving the frame claimed.
+            frame.release_receiving_claim();
 
-        let mut frame = self
-            .storage
-            .claim_receiving(frame_index)
-            .ok_or(PduError::InvalidIndex(frame_index))?;
+            return Err(Error::Internal);
+        }
 
-        let frame_data = frame.buf_mut();
+        frame.buf_mut()[0..i.len()].copy_from_slice(i);
 
-        frame_data
-            .get_mut(0..i.len())
-            .ok_or(Error::Internal)?
-            .copy_from_slice(i);
+        self.storage
+            .pdus_received
+            .fetch_add(count_pdus(i), Ordering::Relaxed);
 
         frame.mark_received()?;
 
+        // The frame is no longer pending, so let anything waiting on a less busy TX queue know.
+        self.storage.tx_idle_waker.wake();
+
         Ok(ReceiveAction::Processed)
     }
 
@@ -146,3 +187,29 @@ impl<'sto> PduRx<'sto> {
         self
     }
 }
+
+/// Count the PDUs in an EtherCAT frame payload by following each header's `more_follows` flag.
+///
+/// Counting stops at the first PDU that is truncated or can't be parsed.
+fn count_pdus(mut buf: &[u8]) -> u32 {
+    let mut count = 0;
+
+    while let Ok(header) = PduHeader::unpack_from_slice(buf) {
+        let this_pdu_len =
+            PduHeader::PACKED_LEN + usize::from(header.flags.len()) + frame_layout::WKC_LEN;
+
+        let Some(rest) = buf.get(this_pdu_len..) else {
+            break;
+        };
+
+        count += 1;
+
+        if !header.flags.more_follows {
+            break;
+        }
+
+        buf = rest;
+    }
+
+    count
+}
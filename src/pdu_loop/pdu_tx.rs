@@ -1,4 +1,5 @@
 use super::{frame_element::sendable_frame::SendableFrame, storage::PduStorageRef};
+use crate::VlanTag;
 use core::{sync::atomic::Ordering, task::Waker};
 
 /// EtherCAT frame transmit adapter.
@@ -16,6 +17,28 @@ impl<'sto> PduTx<'sto> {
         self.storage.num_frames
     }
 
+    /// The number of frames that are queued for sending, currently being sent, or waiting for a
+    /// response from the network.
+    ///
+    /// This can be compared against [`capacity`](PduTx::capacity) to get an idea of how backed up
+    /// the network is.
+    pub fn pending_sendable_frames(&self) -> usize {
+        self.storage.pending_sendable_frames()
+    }
+
+    /// Insert an IEEE 802.1Q VLAN tag into every frame sent from now on, or pass `None` to send
+    /// untagged frames (the default).
+    ///
+    /// The tag takes up 4 bytes of each frame, so the maximum PDU payload per frame is reduced by
+    /// the same amount. This should be set before the PDU loop is used, as frames that were already
+    /// queued may be too long to be tagged.
+    ///
+    /// Responses are accepted whether they are tagged or not, but see
+    /// [`PduRx::receive_frame`](crate::PduRx::receive_frame) for a limitation on Linux.
+    pub fn set_vlan_tag(&mut self, tag: Option<VlanTag>) {
+        self.storage.set_vlan_tag(tag);
+    }
+
     /// Get the next sendable frame, if any are available.
     // NOTE: Mutable so it can only be used in one task.
     pub fn next_sendable_frame(&mut self) -> Option<SendableFrame<'sto>> {
@@ -30,6 +53,7 @@ impl<'sto> PduTx<'sto> {
                 frame,
                 self.storage.pdu_idx,
                 self.storage.frame_data_len,
+                self.storage.vlan_tag(),
             ) else {
                 continue;
             };
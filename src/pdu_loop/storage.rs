@@ -1,16 +1,13 @@
-use super::{
-    frame_element::FrameState, frame_header::EthercatFrameHeader, pdu_rx::PduRx, pdu_tx::PduTx,
-};
-use crate::ethernet::EthernetFrame;
+#[cfg(feature = "software-fcs")]
+use super::MIN_FRAME_BUFFER_LEN;
+use super::{frame_element::FrameState, pdu_rx::PduRx, pdu_tx::PduTx};
 use crate::{
     PduLoop,
     error::{Error, PduError},
-    fmt,
-    pdu_loop::{
-        frame_element::{
-            FrameElement, created_frame::CreatedFrame, receiving_frame::ReceivingFrame,
-        },
-        pdu_flags::PduFlags,
+    ethernet::{VLAN_TAG_LEN, VlanTag},
+    fmt, frame_layout,
+    pdu_loop::frame_element::{
+        FrameElement, created_frame::CreatedFrame, receiving_frame::ReceivingFrame,
     },
 };
 use atomic_waker::AtomicWaker;
@@ -20,20 +17,10 @@ use core::{
     marker::PhantomData,
     mem::MaybeUninit,
     ptr::NonNull,
-    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+    sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering},
 };
 use ethercrab_wire::EtherCrabWireSized;
 
-/// Smallest frame size with a data payload of 0 length
-const MIN_DATA: usize = EthernetFrame::<&[u8]>::buffer_len(
-    EthercatFrameHeader::header_len()
-                    + super::pdu_header::PduHeader::PACKED_LEN
-                    // PDU payload
-                    + PduFlags::const_default().len() as usize
-                    // Working counter
-                    + 2,
-);
-
 /// Stores PDU frames that are currently being prepared to send, in flight, or being received and
 /// processed.
 ///
@@ -45,10 +32,69 @@ pub struct PduStorage<const N: usize, const DATA: usize> {
     is_split: AtomicBool,
     /// A waker used to wake up the TX task when a new frame is ready to be sent.
     pub(in crate::pdu_loop) tx_waker: AtomicWaker,
+    /// A waker used to wake a task waiting for the number of pending frames to drop.
+    pub(in crate::pdu_loop) tx_idle_waker: AtomicWaker,
     /// A flag used to signal that the TX/RX loop should exit.
     ///
     /// Used by [`MainDevice::release`](crate::MainDevice::release) et al.
     exit_flag: AtomicBool,
+    /// The 802.1Q VLAN tag to insert into sent frames. See [`PduStorageRef::vlan_tag`].
+    vlan: AtomicU32,
+    /// How free frame slots are picked. See [`PduStorage::with_frame_allocation`].
+    allocation: FrameAllocation,
+    /// Whether received frames sent from the MainDevice's own MAC address are processed instead of
+    /// ignored. Set from [`MainDeviceConfig`](crate::MainDeviceConfig).
+    self_filter_disabled: AtomicBool,
+    /// The total number of PDUs pushed into frames. See [`PduStats`].
+    pdus_pushed: AtomicU32,
+    /// The total number of PDUs parsed from received frames. See [`PduStats`].
+    pdus_received: AtomicU32,
+}
+
+/// PDU counters for throughput monitoring.
+///
+/// A single EtherCAT frame can carry many PDUs, so these counters are separate from any frame
+/// counts. Both counters wrap around to zero on overflow, so the difference between two snapshots
+/// should be computed with [`u32::wrapping_sub`].
+///
+/// Created by [`MainDevice::pdu_stats`](crate::MainDevice::pdu_stats) or
+/// [`PduLoop::pdu_stats`](crate::PduLoop::pdu_stats).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PduStats {
+    /// The total number of PDUs pushed into frames to be sent.
+    pub pdus_pushed: u32,
+    /// The total number of PDUs parsed from received frames that matched a frame waiting for a
+    /// response.
+    pub pdus_received: u32,
+}
+
+/// How [`PduStorage`] picks a free slot when a new frame is allocated.
+///
+/// Set with [`PduStorage::with_frame_allocation`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum FrameAllocation {
+    /// Rotate through every slot in the storage (default).
+    #[default]
+    RoundRobin,
+
+    /// Split the storage into `partitions` equally sized groups of consecutive slots, and rotate
+    /// through the group returned by `partition` when allocating a frame.
+    ///
+    /// `partition` is called on every allocation, and would usually return an index assigned to
+    /// the current thread or CPU core, so that each thread tends to reuse the same few frames. This
+    /// can improve cache and NUMA locality on systems where frames are sent from multiple threads.
+    /// The returned index is wrapped to the number of partitions.
+    ///
+    /// If every slot in the partition is in use, the frame is allocated from any free slot in the
+    /// storage instead.
+    Partitioned {
+        /// The number of partitions. Must be a power of two, and no greater than the number of
+        /// storage elements.
+        partitions: u8,
+        /// Get the partition to allocate from.
+        partition: fn() -> u8,
+    },
 }
 
 unsafe impl<const N: usize, const DATA: usize> Sync for PduStorage<N, DATA> {}
@@ -57,7 +103,8 @@ impl PduStorage<0, 0> {
     /// Calculate the size of a `PduStorage` buffer element to hold the given number of data bytes.
     ///
     /// This computes the additional overhead the Ethernet, EtherCAT frame and EtherCAT PDU headers
-    /// require.
+    /// require. If the `software-fcs` feature is enabled, the size is at least 64 bytes so frames
+    /// can be padded to the minimum Ethernet frame length before the FCS is appended.
     ///
     /// # Examples
     ///
@@ -69,13 +116,73 @@ impl PduStorage<0, 0> {
     /// const NUM_FRAMES: usize = 16;
     /// const FRAME_SIZE: usize = PduStorage::element_size(128);
     ///
-    /// // 28 byte overhead
-    /// assert_eq!(FRAME_SIZE, 156);
+    /// // 28 byte overhead, or 32 with the `software-fcs` feature enabled
+    /// assert_eq!(
+    ///     FRAME_SIZE,
+    ///     if cfg!(feature = "software-fcs") { 160 } else { 156 }
+    /// );
     ///
     /// let storage = PduStorage::<NUM_FRAMES, FRAME_SIZE>::new();
     /// ```
     pub const fn element_size(data_len: usize) -> usize {
-        MIN_DATA + data_len
+        let len = frame_layout::MIN_ELEMENT_SIZE + data_len;
+
+        #[cfg(feature = "software-fcs")]
+        if len < MIN_FRAME_BUFFER_LEN {
+            return MIN_FRAME_BUFFER_LEN;
+        }
+
+        len
+    }
+
+    /// Calculate the smallest `PduStorage` element size that can send the process data of every
+    /// group and the largest mailbox transfer without splitting them across frames.
+    ///
+    /// `pdi_sizes` holds the PDI length in bytes of each group, and `max_mailbox` is the largest
+    /// mailbox size of any SubDevice on the network. Room is reserved next to each group's PDI for
+    /// the Distributed Clocks `FRMW` PDU sent by
+    /// [`SubDeviceGroup::tx_rx_dc`](crate::SubDeviceGroup::tx_rx_dc).
+    ///
+    /// This method can be used in `const` context to size `PduStorage` at compile time.
+    ///
+    /// # Examples
+    ///
+    /// Size storage for two groups with 32 and 1024 bytes of PDI, and SubDevices with mailboxes up
+    /// to 128 bytes long:
+    ///
+    /// ```rust
+    /// use ethercrab::PduStorage;
+    ///
+    /// const NUM_FRAMES: usize = 16;
+    /// const FRAME_SIZE: usize = PduStorage::required_element_size(&[32, 1024], 128);
+    ///
+    /// // Largest PDI, plus 20 bytes for the DC PDU and the frame and PDU header overhead. This is
+    /// // 1072 bytes, or 1076 with the `software-fcs` feature enabled.
+    /// assert_eq!(
+    ///     FRAME_SIZE,
+    ///     if cfg!(feature = "software-fcs") { 1076 } else { 1072 }
+    /// );
+    ///
+    /// let storage = PduStorage::<NUM_FRAMES, FRAME_SIZE>::new();
+    /// ```
+    pub const fn required_element_size(pdi_sizes: &[usize], max_mailbox: usize) -> usize {
+        const DC_PDU_SIZE: usize = frame_layout::PDU_OVERHEAD + u64::PACKED_LEN;
+
+        let mut data_len = max_mailbox;
+
+        let mut i = 0;
+
+        while i < pdi_sizes.len() {
+            let group_len = pdi_sizes[i] + DC_PDU_SIZE;
+
+            if group_len > data_len {
+                data_len = group_len;
+            }
+
+            i += 1;
+        }
+
+        Self::element_size(data_len)
     }
 }
 
@@ -90,8 +197,11 @@ impl<const N: usize, const DATA: usize> PduStorage<N, DATA> {
     /// This method will panic if
     ///
     /// - `N` is larger than `u8::MAX, or not a power of two, or
-    /// - `DATA` is less than 28 as this is the minimum size required to hold an EtherCAT frame with
-    ///   zero PDU length.
+    /// - `DATA` is less than [`frame_layout::MIN_ELEMENT_SIZE`](crate::frame_layout::MIN_ELEMENT_SIZE)
+    ///   as this is the minimum size required to hold an EtherCAT frame with zero PDU length, or
+    ///   less than 64 if the `software-fcs` feature is enabled.
+    // Static storage is created in a `const` context, so `Default` isn't useful here.
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> Self {
         // MSRV: Make `N` a `u8` when `generic_const_exprs` is stablised
         // If possible, try using `NonZeroU8`.
@@ -104,8 +214,13 @@ impl<const N: usize, const DATA: usize> PduStorage<N, DATA> {
         assert!(N > 0, "Storage must contain at least one element");
 
         assert!(
-            DATA >= MIN_DATA,
-            "DATA must be at least 28 bytes large to hold all frame headers"
+            DATA >= frame_layout::MIN_ELEMENT_SIZE,
+            "DATA must be at least frame_layout::MIN_ELEMENT_SIZE bytes large to hold all frame headers"
+        );
+        #[cfg(feature = "software-fcs")]
+        assert!(
+            DATA >= MIN_FRAME_BUFFER_LEN,
+            "DATA must be at least 64 bytes large to hold a padded frame and its FCS"
         );
 
         // Index wrapping limitations require a power of 2 number of storage elements.
@@ -124,8 +239,58 @@ impl<const N: usize, const DATA: usize> PduStorage<N, DATA> {
             pdu_idx: AtomicU8::new(0),
             is_split: AtomicBool::new(false),
             tx_waker: AtomicWaker::new(),
+            tx_idle_waker: AtomicWaker::new(),
             exit_flag: AtomicBool::new(false),
+            vlan: AtomicU32::new(0),
+            allocation: FrameAllocation::RoundRobin,
+            self_filter_disabled: AtomicBool::new(false),
+            pdus_pushed: AtomicU32::new(0),
+            pdus_received: AtomicU32::new(0),
+        }
+    }
+
+    /// Set how free frame slots are picked when a frame is allocated.
+    ///
+    /// Defaults to [`FrameAllocation::RoundRobin`].
+    ///
+    /// # Examples
+    ///
+    /// Give each of two TX threads its own half of the storage:
+    ///
+    /// ```rust
+    /// use core::cell::Cell;
+    /// use ethercrab::{FrameAllocation, PduStorage};
+    ///
+    /// std::thread_local! {
+    ///     static PARTITION: Cell<u8> = const { Cell::new(0) };
+    /// }
+    ///
+    /// static PDU_STORAGE: PduStorage<16, { PduStorage::element_size(1100) }> = PduStorage::new()
+    ///     .with_frame_allocation(FrameAllocation::Partitioned {
+    ///         partitions: 2,
+    ///         partition: || PARTITION.with(Cell::get),
+    ///     });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if a [`FrameAllocation::Partitioned`] strategy is given with a number
+    /// of partitions that is zero, not a power of two, or greater than `N`.
+    pub const fn with_frame_allocation(mut self, allocation: FrameAllocation) -> Self {
+        if let FrameAllocation::Partitioned { partitions, .. } = allocation {
+            assert!(
+                partitions.count_ones() == 1,
+                "The number of partitions must be a power of 2"
+            );
+            assert!(
+                partitions as usize <= N,
+                "The number of partitions cannot be greater than the number of storage elements"
+            );
         }
+
+        self.allocation = allocation;
+
+        self
     }
 
     /// Create a PDU loop backed by this storage.
@@ -154,7 +319,39 @@ impl<const N: usize, const DATA: usize> PduStorage<N, DATA> {
         ))
     }
 
-    fn as_ref(&self) -> PduStorageRef {
+    /// Return this storage to the state it was in when created, so it can be split again.
+    ///
+    /// All frames are released and all frame and PDU indices are reset, along with any VLAN tag,
+    /// TX/RX task exit request, self-sourced frame filter setting and [`PduStats`] counters. This is
+    /// useful when a `static` `PduStorage` is reused after recovering from a crash, where the
+    /// [`PduTx`], [`PduRx`] and [`PduLoop`] from the previous split could not be cleanly
+    /// [released](crate::MainDevice::release).
+    ///
+    /// # Safety
+    ///
+    /// No TX/RX task may be running on this storage, and no [`PduTx`], [`PduRx`], [`PduLoop`] or
+    /// [`MainDevice`](crate::MainDevice) created from a previous call to
+    /// [`try_split`](PduStorage::try_split) may be used after this method is called.
+    pub unsafe fn recover(&self) {
+        // SAFETY: The caller guarantees nothing else is accessing the frames. All-zeroes is the
+        // same state `new` creates. Any wakers left in frames are leaked instead of dropped.
+        unsafe { core::ptr::write_bytes(self.frames.get(), 0, 1) };
+
+        self.frame_idx.store(0, Ordering::Relaxed);
+        self.pdu_idx.store(0, Ordering::Relaxed);
+        self.exit_flag.store(false, Ordering::Relaxed);
+        self.vlan.store(0, Ordering::Relaxed);
+        self.self_filter_disabled.store(false, Ordering::Relaxed);
+        self.pdus_pushed.store(0, Ordering::Relaxed);
+        self.pdus_received.store(0, Ordering::Relaxed);
+
+        drop(self.tx_waker.take());
+        drop(self.tx_idle_waker.take());
+
+        self.is_split.store(false, Ordering::Release);
+    }
+
+    fn as_ref(&self) -> PduStorageRef<'_> {
         PduStorageRef {
             frames: unsafe { NonNull::new_unchecked(self.frames.get().cast()) },
             frame_element_stride: Layout::array::<FrameElement<DATA>>(N).unwrap().size() / N,
@@ -163,7 +360,13 @@ impl<const N: usize, const DATA: usize> PduStorage<N, DATA> {
             frame_idx: &self.frame_idx,
             pdu_idx: &self.pdu_idx,
             tx_waker: &self.tx_waker,
+            tx_idle_waker: &self.tx_idle_waker,
             exit_flag: &self.exit_flag,
+            vlan: &self.vlan,
+            allocation: self.allocation,
+            self_filter_disabled: &self.self_filter_disabled,
+            pdus_pushed: &self.pdus_pushed,
+            pdus_received: &self.pdus_received,
             _lifetime: PhantomData,
         }
     }
@@ -179,11 +382,65 @@ pub(crate) struct PduStorageRef<'sto> {
     frame_idx: &'sto AtomicU8,
     pub pdu_idx: &'sto AtomicU8,
     pub tx_waker: &'sto AtomicWaker,
+    pub tx_idle_waker: &'sto AtomicWaker,
     pub exit_flag: &'sto AtomicBool,
+    vlan: &'sto AtomicU32,
+    allocation: FrameAllocation,
+    self_filter_disabled: &'sto AtomicBool,
+    pdus_pushed: &'sto AtomicU32,
+    pub pdus_received: &'sto AtomicU32,
     _lifetime: PhantomData<&'sto ()>,
 }
 
+/// Set in [`PduStorage::vlan`] when a VLAN tag is configured, with the tag's TCI in the lower 16
+/// bits.
+const VLAN_ENABLED: u32 = 1 << 16;
+
 impl<'sto> PduStorageRef<'sto> {
+    /// The VLAN tag to insert into every sent frame, if any.
+    pub(in crate::pdu_loop) fn vlan_tag(&self) -> Option<VlanTag> {
+        let raw = self.vlan.load(Ordering::Relaxed);
+
+        (raw & VLAN_ENABLED != 0).then(|| VlanTag::from_tci(raw as u16))
+    }
+
+    pub(in crate::pdu_loop) fn set_vlan_tag(&self, tag: Option<VlanTag>) {
+        let raw = tag.map_or(0, |tag| VLAN_ENABLED | u32::from(tag.tci()));
+
+        self.vlan.store(raw, Ordering::Relaxed);
+    }
+
+    /// Whether frames sent from the MainDevice's own MAC address should be ignored when received.
+    pub(in crate::pdu_loop) fn self_filter_enabled(&self) -> bool {
+        !self.self_filter_disabled.load(Ordering::Relaxed)
+    }
+
+    /// Only writes the flag if it changes, so it can be called for every frame without the
+    /// receive path's cache line being invalidated each time.
+    pub(in crate::pdu_loop) fn set_self_filter_enabled(&self, enabled: bool) {
+        if self.self_filter_enabled() != enabled {
+            self.self_filter_disabled.store(!enabled, Ordering::Relaxed);
+        }
+    }
+
+    /// A snapshot of the PDU counters.
+    pub(in crate::pdu_loop) fn pdu_stats(&self) -> PduStats {
+        PduStats {
+            pdus_pushed: self.pdus_pushed.load(Ordering::Relaxed),
+            pdus_received: self.pdus_received.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The number of bytes new frames may fill, leaving room for a VLAN tag to be inserted when the
+    /// frame is sent.
+    pub(in crate::pdu_loop) fn created_frame_len(&self) -> usize {
+        if self.vlan_tag().is_some() {
+            self.frame_data_len - VLAN_TAG_LEN
+        } else {
+            self.frame_data_len
+        }
+    }
+
     /// Reset all state ready for a fresh MainDevice or other reuse.
     pub(crate) fn reset(&mut self) {
         // NOTE: Don't reset waker so this `PduStorageRef` can still wake an existing TX/RX handler
@@ -196,10 +453,75 @@ impl<'sto> PduStorageRef<'sto> {
 
             unsafe { FrameElement::set_state(frame, FrameState::None) };
         }
+
+        // All frames were released, so anything waiting for the TX queue to drain can continue.
+        self.tx_idle_waker.wake();
+    }
+
+    /// The number of frames that are waiting to be sent, being sent, or waiting for a response.
+    pub(in crate::pdu_loop) fn pending_sendable_frames(&self) -> usize {
+        (0..self.num_frames)
+            .filter(|idx| {
+                // SAFETY: `frame_at_index` always returns a pointer to a valid frame element.
+                let state = unsafe { FrameElement::<0>::state(self.frame_at_index(*idx)) };
+
+                matches!(
+                    state,
+                    FrameState::Sendable | FrameState::Sending | FrameState::Sent
+                )
+            })
+            .count()
+    }
+
+    /// The first slot and number of slots to allocate frames from, as chosen by the configured
+    /// [`FrameAllocation`].
+    fn alloc_partition(&self) -> (u8, u8) {
+        match self.allocation {
+            FrameAllocation::RoundRobin => (0, self.num_frames as u8),
+            FrameAllocation::Partitioned {
+                partitions,
+                partition,
+            } => {
+                let len = self.num_frames as u8 / partitions;
+
+                ((partition() % partitions) * len, len)
+            }
+        }
     }
 
     /// Allocate a PDU frame with the given command and data length.
     pub(in crate::pdu_loop) fn alloc_frame(&self) -> Result<CreatedFrame<'sto>, Error> {
+        let (start, len) = self.alloc_partition();
+
+        if let Some(frame) = self.alloc_frame_in(start, len) {
+            return Ok(frame);
+        }
+
+        // The partition is full, so take any free slot rather than failing the allocation
+        if usize::from(len) < self.num_frames {
+            fmt::debug!(
+                "No available frames in partition starting at slot {}, searching all slots",
+                start
+            );
+
+            if let Some(frame) = self.alloc_frame_in(0, self.num_frames as u8) {
+                return Ok(frame);
+            }
+        }
+
+        // We've searched twice and found no free slots. This means the application should
+        // either slow down its packet sends, or increase `N` in `PduStorage` as there
+        // aren't enough slots to hold all in-flight packets.
+        fmt::error!("No available frames in {} slots", self.num_frames);
+
+        Err(PduError::SwapState.into())
+    }
+
+    /// Claim the next free frame in the `len` slots starting at `start`, in round-robin order.
+    ///
+    /// `len` must be a power of two so the rotation doesn't skip slots when the index counter
+    /// wraps.
+    fn alloc_frame_in(&self, start: u8, len: u8) -> Option<CreatedFrame<'sto>> {
         // Find next frame that is not currently in use.
         //
         // Escape hatch: we'll only loop through the frame storage array twice to put an upper
@@ -208,8 +530,8 @@ impl<'sto> PduStorageRef<'sto> {
         // this safety check.
         //
         // This can be mitigated by using a `RetryBehaviour` of `Count` or `Forever`.
-        for _ in 0..(self.num_frames * 2) {
-            let frame_idx = self.frame_idx.fetch_add(1, Ordering::Relaxed) % self.num_frames as u8;
+        for _ in 0..(usize::from(len) * 2) {
+            let frame_idx = start + self.frame_idx.fetch_add(1, Ordering::Relaxed) % len;
 
             fmt::trace!("Try to allocate frame {}", frame_idx);
 
@@ -219,62 +541,89 @@ impl<'sto> PduStorageRef<'sto> {
             // variable in the frame, and the atomic index counter above.
             let frame = self.frame_at_index(usize::from(frame_idx));
 
-            let frame =
-                CreatedFrame::claim_created(frame, frame_idx, self.pdu_idx, self.frame_data_len);
+            let frame = CreatedFrame::claim_created(
+                frame,
+                frame_idx,
+                self.pdu_idx,
+                self.pdus_pushed,
+                self.created_frame_len(),
+            );
 
             if let Ok(f) = frame {
-                return Ok(f);
+                return Some(f);
             }
         }
 
-        // We've searched twice and found no free slots. This means the application should
-        // either slow down its packet sends, or increase `N` in `PduStorage` as there
-        // aren't enough slots to hold all in-flight packets.
-        fmt::error!("No available frames in {} slots", self.num_frames);
-
-        Err(PduError::SwapState.into())
+        None
     }
 
-    /// Updates state from SENDING -> RX_BUSY
-    pub(in crate::pdu_loop) fn claim_receiving(
-        &self,
-        frame_idx: u8,
-    ) -> Option<ReceivingFrame<'sto>> {
-        let frame_idx = usize::from(frame_idx);
-
-        if frame_idx >= self.num_frames {
-            return None;
-        }
-
-        fmt::trace!("--> Claim receiving frame index {}", frame_idx);
-
-        ReceivingFrame::claim_receiving(
-            self.frame_at_index(frame_idx),
+    /// Allocate the frame in storage slot `idx` instead of the next slot in round-robin order.
+    ///
+    /// Used to set up specific slot layouts in tests.
+    #[cfg(test)]
+    pub(in crate::pdu_loop) fn alloc_frame_at(&self, idx: u8) -> Result<CreatedFrame<'sto>, Error> {
+        let frame = self.frame_at_index(usize::from(idx));
+
+        CreatedFrame::claim_created(
+            frame,
+            idx,
             self.pdu_idx,
-            self.frame_data_len,
+            self.pdus_pushed,
+            self.created_frame_len(),
         )
+        .map_err(Error::from)
     }
 
-    pub(in crate::pdu_loop) fn frame_index_by_first_pdu_index(
+    /// Find the frame whose first PDU has the given index and claim it, updating its state from
+    /// SENT -> RX_BUSY.
+    ///
+    /// Searching and claiming are done in one pass so a frame that happens to carry the same first
+    /// PDU index but isn't waiting for a response (e.g. it's still being filled, or is being
+    /// released by another thread) doesn't shadow the frame the response actually belongs to.
+    pub(in crate::pdu_loop) fn claim_receiving_by_first_pdu(
         &self,
         search_pdu_idx: u8,
-    ) -> Option<u8> {
+    ) -> Result<ReceivingFrame<'sto>, PduError> {
+        let mut matched = None;
+
         for frame_index in 0..self.num_frames {
-            // SAFETY: Frames pointer will always be non-null as it was created by Rust code.
-            let frame = unsafe {
-                NonNull::new_unchecked(
-                    self.frames
-                        .as_ptr()
-                        .byte_add(frame_index * self.frame_element_stride),
-                )
-            };
+            let frame = self.frame_at_index(frame_index);
+
+            // SAFETY: `frame_at_index` always returns a pointer to a valid frame element.
+            if !unsafe { FrameElement::<0>::first_pdu_is(frame, search_pdu_idx) } {
+                continue;
+            }
 
-            if unsafe { FrameElement::<0>::first_pdu_is(frame, search_pdu_idx) } {
-                return Some(frame_index as u8);
+            matched = Some(frame_index as u8);
+
+            if let Some(frame) = ReceivingFrame::claim_receiving(
+                frame,
+                search_pdu_idx,
+                self.pdu_idx,
+                self.frame_data_len,
+            ) {
+                fmt::trace!(
+                    "--> Claim receiving frame index {} (found from PDU {:#04x})",
+                    frame_index,
+                    search_pdu_idx
+                );
+
+                return Ok(frame);
             }
         }
 
-        None
+        match matched {
+            Some(frame_index) => {
+                fmt::error!(
+                    "Frame {} has first PDU {:#04x} but is not awaiting a response",
+                    frame_index,
+                    search_pdu_idx
+                );
+
+                Err(PduError::InvalidIndex(frame_index))
+            }
+            None => Err(PduError::Decode),
+        }
     }
 
     /// Retrieve a frame at the given index.
@@ -303,7 +652,7 @@ unsafe impl Sync for PduStorageRef<'_> {}
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Command, pdu_loop::pdu_header::PduHeader};
+    use crate::{Command, ReceiveAction, fake_network::SentFrame};
     use core::time::Duration;
 
     #[test]
@@ -329,9 +678,9 @@ mod tests {
 
         frame.push_pdu(Command::Nop, (), Some(LEN as u16)).unwrap();
 
-        let pdu_start = EthernetFrame::<&[u8]>::header_len()
-            + EthercatFrameHeader::header_len()
-            + PduHeader::PACKED_LEN;
+        let pdu_start = frame_layout::ETHERNET_HEADER_LEN
+            + frame_layout::ETHERCAT_HEADER_LEN
+            + frame_layout::PDU_HEADER_LEN;
 
         let frame = frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX);
 
@@ -340,7 +689,7 @@ mod tests {
             // Skip all headers
             &frame.buf()[pdu_start..],
             // PDU payload plus working counter
-            &[0u8; { LEN + 2 }]
+            &[0u8; { LEN + frame_layout::WKC_LEN }]
         );
     }
 
@@ -368,6 +717,244 @@ mod tests {
         );
     }
 
+    /// Interleave the two steps of finding and claiming a frame for a received response with
+    /// the frame being released and reused by its owner, at every point the RX thread could be
+    /// preempted.
+    ///
+    /// Whatever the interleaving, the response must only ever claim the frame while it still
+    /// carries the response's first PDU index, and must never leave a frame it didn't claim in
+    /// an unexpected state.
+    #[test]
+    fn receive_vs_reclaim_interleavings() {
+        crate::test_logger();
+
+        const OLD: u8 = 0x20;
+        const NEW: u8 = 0x21;
+
+        // Release the old frame, allocate and fill a new frame in the same slot, send it.
+        const RECLAIM_STEPS: usize = 3;
+
+        for search_at in 0..=RECLAIM_STEPS {
+            for claim_at in search_at..=RECLAIM_STEPS {
+                let storage: PduStorage<1, { PduStorage::element_size(8) }> = PduStorage::new();
+                let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+                let s = pdu_loop.test_only_storage_ref();
+                let slot = s.frame_at_index(0);
+
+                s.pdu_idx.store(OLD, Ordering::Relaxed);
+
+                let mut frame = s.alloc_frame().unwrap();
+
+                frame
+                    .push_pdu(Command::fprd(0x1000, 0x0130).into(), (), Some(2))
+                    .unwrap();
+
+                let mut old = Some(frame.mark_sendable(&pdu_loop, Duration::MAX, usize::MAX));
+
+                tx.next_sendable_frame()
+                    .unwrap()
+                    .send_blocking(|bytes| Ok(bytes.len()))
+                    .unwrap();
+
+                let mut created = None;
+                let mut new = None;
+                let mut new_sent = Vec::new();
+                let mut found = false;
+
+                for step in 0..=RECLAIM_STEPS {
+                    if step == search_at {
+                        found = unsafe { FrameElement::<0>::first_pdu_is(slot, OLD) };
+
+                        // Only the old frame carries the old index
+                        assert_eq!(found, step == 0, "search {search_at}, claim {claim_at}");
+                    }
+
+                    if step == claim_at && found {
+                        let claimed =
+                            ReceivingFrame::claim_receiving(slot, OLD, s.pdu_idx, s.frame_data_len);
+
+                        assert_eq!(
+                            claimed.is_some(),
+                            step == 0,
+                            "search {search_at}, claim {claim_at}"
+                        );
+
+                        // Give the frame back so the owner can carry on
+                        if let Some(claimed) = claimed {
+                            claimed.release_receiving_claim();
+                        }
+
+                        assert_ne!(
+                            unsafe { FrameElement::<0>::state(slot) },
+                            FrameState::RxBusy,
+                            "search {search_at}, claim {claim_at}"
+                        );
+                    }
+
+                    match step {
+                        0 => drop(old.take()),
+                        1 => {
+                            s.pdu_idx.store(NEW, Ordering::Relaxed);
+
+                            let mut frame = s.alloc_frame().expect("slot should be free");
+
+                            frame
+                                .push_pdu(Command::fprd(0x1000, 0x0130).into(), (), Some(2))
+                                .unwrap();
+
+                            created = Some(frame);
+                        }
+                        2 => {
+                            new = Some(created.take().unwrap().mark_sendable(
+                                &pdu_loop,
+                                Duration::MAX,
+                                usize::MAX,
+                            ));
+
+                            tx.next_sendable_frame()
+                                .unwrap()
+                                .send_blocking(|bytes| {
+                                    new_sent.extend_from_slice(bytes);
+
+                                    Ok(bytes.len())
+                                })
+                                .unwrap();
+                        }
+                        _ => (),
+                    }
+                }
+
+                // The new frame's response is still received
+                assert_eq!(
+                    rx.receive_frame(&SentFrame::new(new_sent).into_reply()),
+                    Ok(ReceiveAction::Processed),
+                    "search {search_at}, claim {claim_at}"
+                );
+                assert_eq!(
+                    unsafe { FrameElement::<0>::state(slot) },
+                    FrameState::RxDone
+                );
+
+                drop(new);
+            }
+        }
+    }
+
+    #[test]
+    fn partitioned_allocation() {
+        crate::test_logger();
+
+        const NUM_FRAMES: usize = 8;
+        const DATA: usize = PduStorage::element_size(8);
+
+        std::thread_local! {
+            static PARTITION: core::cell::Cell<u8> = const { core::cell::Cell::new(0) };
+        }
+
+        let storage: PduStorage<NUM_FRAMES, DATA> =
+            PduStorage::new().with_frame_allocation(FrameAllocation::Partitioned {
+                partitions: 2,
+                partition: || PARTITION.with(core::cell::Cell::get),
+            });
+        let s = storage.as_ref();
+
+        let slots = std::thread::scope(|scope| {
+            let threads = [0u8, 1].map(|partition| {
+                let s = &s;
+
+                scope.spawn(move || {
+                    PARTITION.with(|p| p.set(partition));
+
+                    (0..20)
+                        .map(|_| {
+                            // Hold two frames at once so the rotation has to skip an in-use slot
+                            let a = s.alloc_frame().expect("alloc");
+                            let b = s.alloc_frame().expect("alloc");
+
+                            [a.storage_slot_index(), b.storage_slot_index()]
+                        })
+                        .collect::<Vec<_>>()
+                })
+            });
+
+            threads.map(|t| t.join().expect("thread panicked"))
+        });
+
+        assert!(slots[0].iter().flatten().all(|slot| (0..4).contains(slot)));
+        assert!(slots[1].iter().flatten().all(|slot| (4..8).contains(slot)));
+
+        // Fill this thread's partition, after which frames come from the rest of the storage
+        for _ in 0..4 {
+            let frame = s.alloc_frame().expect("alloc");
+
+            assert!((0..4).contains(&frame.storage_slot_index()));
+
+            core::mem::forget(frame);
+        }
+
+        let frame = s.alloc_frame().expect("fall back to other partition");
+
+        assert!((4..8).contains(&frame.storage_slot_index()));
+    }
+
+    #[test]
+    fn alloc_at_slot() {
+        crate::test_logger();
+
+        const NUM_FRAMES: usize = 4;
+        const DATA: usize = PduStorage::element_size(8);
+
+        let storage: PduStorage<NUM_FRAMES, DATA> = PduStorage::new();
+        let s = storage.as_ref();
+
+        let mut first_pdus = Vec::new();
+
+        // Fill slots out of round-robin order, each with a different payload
+        for (slot, payload) in [(3u8, 0xaau8), (1, 0xbb)] {
+            let mut frame = s.alloc_frame_at(slot).expect("slot should be free");
+
+            assert_eq!(frame.storage_slot_index(), slot);
+
+            let handle = frame
+                .push_pdu(Command::bwr(0x1000).into(), [payload; 4], None)
+                .unwrap();
+
+            first_pdus.push((handle.pdu_idx, payload));
+
+            // Pretend the frame was sent without dropping it, which would release the slot
+            core::mem::forget(frame);
+
+            unsafe {
+                FrameElement::set_state(s.frame_at_index(usize::from(slot)), FrameState::Sent)
+            };
+        }
+
+        assert!(
+            s.alloc_frame_at(3).is_err(),
+            "slot is in use so can't be claimed again"
+        );
+
+        // Resolve in reverse order to make sure lookup doesn't depend on allocation order
+        for (pdu_idx, payload) in first_pdus.into_iter().rev() {
+            let mut frame = s
+                .claim_receiving_by_first_pdu(pdu_idx)
+                .expect("find frame by first PDU");
+
+            // Skip PDU header
+            assert_eq!(frame.buf_mut()[frame_layout::PDU_HEADER_LEN], payload);
+        }
+
+        // Both frames are now being received
+        assert_eq!(
+            s.claim_receiving_by_first_pdu(0).err(),
+            Some(PduError::InvalidIndex(3))
+        );
+        assert_eq!(
+            s.claim_receiving_by_first_pdu(0x55).err(),
+            Some(PduError::Decode)
+        );
+    }
+
     #[test]
     fn reset() {
         crate::test_logger();
@@ -398,4 +985,56 @@ mod tests {
 
         assert!(s.alloc_frame().is_err());
     }
+
+    #[test]
+    fn recover() {
+        crate::test_logger();
+
+        const NUM_FRAMES: usize = 4;
+        const DATA: usize = PduStorage::element_size(32);
+
+        let storage: PduStorage<NUM_FRAMES, DATA> = PduStorage::new();
+
+        {
+            let (mut tx, _rx, pdu_loop) = storage.try_split().expect("split");
+
+            tx.set_vlan_tag(Some(VlanTag {
+                priority: 1,
+                drop_eligible: false,
+                vlan_id: 2,
+            }));
+
+            // Leave every frame claimed, as if the application crashed mid-cycle
+            for _ in 0..NUM_FRAMES {
+                let f = pdu_loop.alloc_frame().expect("should have frame slots");
+
+                core::mem::forget(f);
+            }
+
+            assert!(pdu_loop.alloc_frame().is_err());
+            assert_ne!(storage.frame_idx.load(Ordering::Relaxed), 0);
+        }
+
+        assert!(storage.try_split().is_err(), "already split");
+
+        unsafe { storage.recover() };
+
+        let (_tx, _rx, pdu_loop) = storage.try_split().expect("split after recover");
+
+        let s = pdu_loop.test_only_storage_ref();
+
+        assert_eq!(s.vlan_tag(), None);
+        assert!(!s.exit_flag.load(Ordering::Relaxed));
+
+        // Frames are handed out from the start again
+        for i in 0..NUM_FRAMES {
+            let f = s.alloc_frame().expect("should have frame slots");
+
+            assert_eq!(f.storage_slot_index(), i as u8);
+
+            core::mem::forget(f);
+        }
+
+        assert!(s.alloc_frame().is_err());
+    }
 }
@@ -27,6 +27,9 @@ pub enum RegisterAddress {
     /// The SubDevice's address alias, `u16`.
     ConfiguredStationAlias = 0x0012,
 
+    /// Defined in ETG1000.4 Table 33 - DL control, `u32`.
+    DlControl = 0x0100,
+
     /// Defined in ETG1000.4 Table 34 - DL status, `u16`.
     DlStatus = 0x0110,
 
@@ -40,6 +43,17 @@ pub enum RegisterAddress {
     /// Application Layer (AL) status code register.
     AlStatusCode = 0x0134,
 
+    /// ECAT event request, `u16`.
+    ///
+    /// Bit 2 is set when the DL status register changes, e.g. on a link change. It is cleared by
+    /// reading [`DlStatus`](RegisterAddress::DlStatus).
+    EcatEventRequest = 0x0210,
+
+    /// Lost link counters for ports 0 to 3, one `u8` per port.
+    ///
+    /// Each counter saturates at 255. Writing to any of them clears all four.
+    LostLinkCounter = 0x0310,
+
     /// Watchdog divider, `u16`.
     ///
     /// See ETG1000.4 section 6.3 Watchdogs.
@@ -367,6 +381,7 @@ impl core::fmt::Display for SupportFlags {
 /// SubDevice DC support status.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum DcSupport {
     /// No support at all.
     #[default]
@@ -394,6 +409,130 @@ impl DcSupport {
     }
 }
 
+/// The DL information registers `0x0000` - `0x0009`.
+///
+/// Described in ETG1000.4 Table 31 - DL information.
+#[derive(Clone, Debug, PartialEq, ethercrab_wire::EtherCrabWireRead)]
+#[wire(bytes = 10)]
+pub struct DlInformation {
+    #[wire(bytes = 1)]
+    pub esc_type: u8,
+    #[wire(bytes = 1)]
+    pub revision: u8,
+    #[wire(bytes = 2)]
+    pub build: u16,
+    #[wire(bytes = 1)]
+    pub fmmu_count: u8,
+    #[wire(bytes = 1)]
+    pub sync_manager_count: u8,
+    #[wire(bytes = 1)]
+    pub ram_size: u8,
+    /// Two bits per port, port 0 in the least significant bits.
+    #[wire(bytes = 1)]
+    pub port_descriptors: u8,
+    #[wire(bytes = 2)]
+    pub support_flags: SupportFlags,
+}
+
+impl DlInformation {
+    /// Decode the ESC chip and its capabilities.
+    pub fn esc_type(&self) -> EscType {
+        let ports = (0..4u8)
+            .filter(|port| (self.port_descriptors >> (*port * 2)) & 0b11 != 0)
+            .count() as u8;
+
+        EscType {
+            kind: EscKind::from(self.esc_type),
+            revision: self.revision,
+            build: self.build,
+            ports,
+            dc_support: self.support_flags.dc_support(),
+        }
+    }
+
+    /// Decode the location and size of the ESC's process data RAM.
+    pub fn dpram_layout(&self) -> DpramLayout {
+        // The RAM size is in KiB. The RAM can't extend past the end of the 16 bit address space.
+        let max_size = ESC_ADDRESS_SPACE_LEN - u32::from(PROCESS_DATA_RAM_START);
+
+        DpramLayout {
+            start: PROCESS_DATA_RAM_START,
+            size: (u32::from(self.ram_size) * 1024).min(max_size),
+        }
+    }
+}
+
+/// EtherCAT SubDevice Controller (ESC) chip family, read from the `Type` register (`0x0000`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EscKind {
+    /// Beckhoff ESC10 or ESC20 FPGA implementation.
+    Esc10,
+    /// Beckhoff IP core for FPGAs.
+    IpCore,
+    /// Beckhoff ET1100.
+    Et1100,
+    /// Beckhoff ET1200.
+    Et1200,
+    /// Microchip LAN9252.
+    Lan9252,
+    /// An ESC type not known to EtherCrab.
+    Unknown(u8),
+}
+
+impl From<u8> for EscKind {
+    fn from(value: u8) -> Self {
+        match value {
+            0x02 => Self::Esc10,
+            0x04 => Self::IpCore,
+            0x11 => Self::Et1100,
+            0x12 => Self::Et1200,
+            0xc0 => Self::Lan9252,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// ESC chip type and capabilities.
+///
+/// Retrieved with [`SubDevice::esc_type`](crate::SubDevice::esc_type).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EscType {
+    /// ESC chip family.
+    pub kind: EscKind,
+    /// ESC revision.
+    pub revision: u8,
+    /// ESC build.
+    pub build: u16,
+    /// Number of physical ports implemented by the ESC.
+    ///
+    /// Note that this is the number of ports the chip has, not the number of ports with an active
+    /// link.
+    pub ports: u8,
+    /// Distributed Clocks support.
+    pub dc_support: DcSupport,
+}
+
+/// Start address of the process data RAM in every ESC. The registers occupy the address space
+/// below this.
+const PROCESS_DATA_RAM_START: u16 = 0x1000;
+
+/// Size of the 16 bit ESC address space.
+const ESC_ADDRESS_SPACE_LEN: u32 = 0x1_0000;
+
+/// Location and size of the process data RAM in an ESC, used to hold mailboxes and process data.
+///
+/// Retrieved with [`SubDeviceRef::dpram_layout`](crate::SubDeviceRef::dpram_layout).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DpramLayout {
+    /// Address of the first byte of process data RAM.
+    pub start: u16,
+    /// Size of the process data RAM in bytes.
+    pub size: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -465,4 +604,87 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn esc_type_et1200() {
+        // EL2828
+        let input = [0x12u8, 0x03, 0x01, 0x00, 0x03, 0x04, 0x01, 0x0a, 0xfc, 0x01];
+
+        let unpacked = DlInformation::unpack_from_slice(&input).expect("Unpack");
+
+        pretty_assertions::assert_eq!(
+            unpacked.esc_type(),
+            EscType {
+                kind: EscKind::Et1200,
+                revision: 0x03,
+                build: 0x0001,
+                ports: 2,
+                dc_support: DcSupport::Bits64,
+            }
+        );
+    }
+
+    #[test]
+    fn esc_type_lan9252() {
+        let input = [0xc0u8, 0x01, 0x00, 0x00, 0x03, 0x04, 0x04, 0x0f, 0x0c, 0x01];
+
+        let unpacked = DlInformation::unpack_from_slice(&input).expect("Unpack");
+
+        pretty_assertions::assert_eq!(
+            unpacked.esc_type(),
+            EscType {
+                kind: EscKind::Lan9252,
+                revision: 0x01,
+                build: 0x0000,
+                ports: 2,
+                dc_support: DcSupport::Bits64,
+            }
+        );
+    }
+
+    #[test]
+    fn esc_type_unknown() {
+        // Four MII ports, no DC
+        let input = [0x99u8, 0x00, 0x00, 0x00, 0x08, 0x08, 0x08, 0xff, 0x00, 0x00];
+
+        let unpacked = DlInformation::unpack_from_slice(&input).expect("Unpack");
+
+        let esc = unpacked.esc_type();
+
+        assert_eq!(esc.kind, EscKind::Unknown(0x99));
+        assert_eq!(esc.ports, 4);
+        assert_eq!(esc.dc_support, DcSupport::None);
+    }
+
+    #[test]
+    fn dpram_layout_et1100() {
+        // 8 KiB of process data RAM
+        let input = [0x11u8, 0x00, 0x00, 0x00, 0x08, 0x08, 0x08, 0x0f, 0xfc, 0x01];
+
+        let unpacked = DlInformation::unpack_from_slice(&input).expect("Unpack");
+
+        assert_eq!(
+            unpacked.dpram_layout(),
+            DpramLayout {
+                start: 0x1000,
+                size: 0x2000
+            }
+        );
+    }
+
+    #[test]
+    fn dpram_layout_ip_core() {
+        // IP core configured with the maximum 60 KiB of process data RAM
+        let input = [0x04u8, 0x01, 0x00, 0x00, 0x08, 0x08, 0x3c, 0x0f, 0xfc, 0x01];
+
+        let unpacked = DlInformation::unpack_from_slice(&input).expect("Unpack");
+
+        assert_eq!(
+            unpacked.dpram_layout(),
+            DpramLayout {
+                start: 0x1000,
+                size: 0xf000
+            }
+        );
+    }
 }
@@ -2,6 +2,7 @@
 
 #[cfg(target_os = "linux")]
 mod io_uring;
+mod process_image;
 #[cfg(unix)]
 mod unix;
 #[cfg(target_os = "windows")]
@@ -16,9 +17,13 @@ use std::{
 };
 
 #[cfg(target_os = "windows")]
-pub use self::windows::{TxRxTaskConfig, ethercat_now, tx_rx_task_blocking};
+pub use self::windows::{TxRxTaskConfig, ethercat_now, interface_link_up, tx_rx_task_blocking};
+pub use process_image::{ProcessImageMap, SignalType, SignalValue};
 #[cfg(unix)]
-pub use unix::{ethercat_now, tx_rx_task};
+pub use unix::{
+    TxRxTaskConfig, ethercat_now, interface_link_up, tx_rx_task, tx_rx_task_split,
+    tx_rx_task_with_config,
+};
 // io_uring is Linux-only
 #[cfg(target_os = "linux")]
 pub use io_uring::tx_rx_task_io_uring;
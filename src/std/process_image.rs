@@ -0,0 +1,372 @@
+//! Named signal access to a process data image.
+
+use crate::error::{Error, Item};
+use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireWrite, WireError};
+use std::collections::BTreeMap;
+
+/// The wire type of a signal in a [`ProcessImageMap`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignalType {
+    /// A single bit, where `bit` is the bit position (`0` - `7`) in the byte at the signal's offset.
+    Bool {
+        /// Bit position in the byte.
+        bit: u8,
+    },
+    /// Unsigned 8 bit integer.
+    U8,
+    /// Signed 8 bit integer.
+    I8,
+    /// Little endian unsigned 16 bit integer.
+    U16,
+    /// Little endian signed 16 bit integer.
+    I16,
+    /// Little endian unsigned 32 bit integer.
+    U32,
+    /// Little endian signed 32 bit integer.
+    I32,
+    /// Little endian unsigned 64 bit integer.
+    U64,
+    /// Little endian signed 64 bit integer.
+    I64,
+    /// Little endian 32 bit float.
+    F32,
+    /// Little endian 64 bit float.
+    F64,
+}
+
+/// The value of a signal in a [`ProcessImageMap`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SignalValue {
+    /// A [`SignalType::Bool`] value.
+    Bool(bool),
+    /// A [`SignalType::U8`] value.
+    U8(u8),
+    /// A [`SignalType::I8`] value.
+    I8(i8),
+    /// A [`SignalType::U16`] value.
+    U16(u16),
+    /// A [`SignalType::I16`] value.
+    I16(i16),
+    /// A [`SignalType::U32`] value.
+    U32(u32),
+    /// A [`SignalType::I32`] value.
+    I32(i32),
+    /// A [`SignalType::U64`] value.
+    U64(u64),
+    /// A [`SignalType::I64`] value.
+    I64(i64),
+    /// A [`SignalType::F32`] value.
+    F32(f32),
+    /// A [`SignalType::F64`] value.
+    F64(f64),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Signal {
+    offset: usize,
+    ty: SignalType,
+}
+
+impl Signal {
+    /// Check that `value` has this signal's type and fits in a PDI of `pdi_len` bytes.
+    fn check(&self, value: &SignalValue, pdi_len: usize) -> Result<(), Error> {
+        let len = match (self.ty, value) {
+            (SignalType::Bool { bit }, SignalValue::Bool(_)) if bit < 8 => 1,
+            (SignalType::U8, SignalValue::U8(_)) | (SignalType::I8, SignalValue::I8(_)) => 1,
+            (SignalType::U16, SignalValue::U16(_)) | (SignalType::I16, SignalValue::I16(_)) => 2,
+            (SignalType::U32, SignalValue::U32(_))
+            | (SignalType::I32, SignalValue::I32(_))
+            | (SignalType::F32, SignalValue::F32(_)) => 4,
+            (SignalType::U64, SignalValue::U64(_))
+            | (SignalType::I64, SignalValue::I64(_))
+            | (SignalType::F64, SignalValue::F64(_)) => 8,
+            _ => return Err(WireError::InvalidValue.into()),
+        };
+
+        if self.offset.saturating_add(len) > pdi_len {
+            return Err(WireError::WriteBufferTooShort.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// A mapping of signal names to their location and type in a process data image.
+///
+/// This allows applications to read and write the PDI by signal name instead of by byte offset.
+/// Offsets are relative to the start of the slice passed to [`pack`](ProcessImageMap::pack) and
+/// [`unpack`](ProcessImageMap::unpack), e.g. a single SubDevice's outputs or a whole group's PDI.
+///
+/// # Examples
+///
+/// ```rust
+/// use ethercrab::std::{ProcessImageMap, SignalType, SignalValue};
+/// use std::collections::BTreeMap;
+///
+/// let map = ProcessImageMap::new()
+///     .with_signal("enable", 0, SignalType::Bool { bit: 0 })
+///     .with_signal("setpoint", 1, SignalType::I16);
+///
+/// let mut outputs = [0u8; 3];
+///
+/// map.pack(
+///     &BTreeMap::from([
+///         ("enable".to_string(), SignalValue::Bool(true)),
+///         ("setpoint".to_string(), SignalValue::I16(-2)),
+///     ]),
+///     &mut outputs,
+/// )
+/// .expect("Pack");
+///
+/// assert_eq!(outputs, [0x01, 0xfe, 0xff]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessImageMap {
+    signals: BTreeMap<String, Signal>,
+}
+
+impl ProcessImageMap {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a signal at the given byte offset, replacing any existing signal with the same name.
+    pub fn with_signal(mut self, name: impl Into<String>, offset: usize, ty: SignalType) -> Self {
+        self.insert(name, offset, ty);
+
+        self
+    }
+
+    /// Add a signal at the given byte offset, replacing any existing signal with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, offset: usize, ty: SignalType) {
+        self.signals.insert(name.into(), Signal { offset, ty });
+    }
+
+    /// Get the byte offset and type of a signal.
+    pub fn get(&self, name: &str) -> Option<(usize, SignalType)> {
+        self.signals
+            .get(name)
+            .map(|signal| (signal.offset, signal.ty))
+    }
+
+    /// Write the given values into `pdi`.
+    ///
+    /// Only the signals named in `values` are written. All other bytes and bits in `pdi` are left
+    /// unchanged. Every value is checked before anything is written, so `pdi` is not modified if an
+    /// error is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] with [`Item::Signal`] if a value names a signal that is not in
+    /// this map, [`WireError::InvalidValue`] if a value does not match its signal's type, or
+    /// [`WireError::WriteBufferTooShort`] if a signal lies outside `pdi`.
+    pub fn pack(
+        &self,
+        values: &BTreeMap<String, SignalValue>,
+        pdi: &mut [u8],
+    ) -> Result<(), Error> {
+        let signals = values
+            .iter()
+            .map(|(name, value)| {
+                let signal = self.signals.get(name).ok_or(Error::NotFound {
+                    item: Item::Signal,
+                    index: None,
+                })?;
+
+                signal.check(value, pdi.len())?;
+
+                Ok((signal, value))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        for (signal, value) in signals {
+            let buf = &mut pdi[signal.offset..];
+
+            match (signal.ty, *value) {
+                (SignalType::Bool { bit }, SignalValue::Bool(value)) => {
+                    if value {
+                        buf[0] |= 1 << bit;
+                    } else {
+                        buf[0] &= !(1 << bit);
+                    }
+                }
+                (_, SignalValue::Bool(_)) => unreachable!("checked above"),
+                (_, SignalValue::U8(value)) => pack(value, buf)?,
+                (_, SignalValue::I8(value)) => pack(value, buf)?,
+                (_, SignalValue::U16(value)) => pack(value, buf)?,
+                (_, SignalValue::I16(value)) => pack(value, buf)?,
+                (_, SignalValue::U32(value)) => pack(value, buf)?,
+                (_, SignalValue::I32(value)) => pack(value, buf)?,
+                (_, SignalValue::U64(value)) => pack(value, buf)?,
+                (_, SignalValue::I64(value)) => pack(value, buf)?,
+                (_, SignalValue::F32(value)) => pack(value, buf)?,
+                (_, SignalValue::F64(value)) => pack(value, buf)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the value of every signal in this map from `pdi`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::ReadBufferTooShort`] if a signal lies outside `pdi`.
+    pub fn unpack(&self, pdi: &[u8]) -> Result<BTreeMap<String, SignalValue>, Error> {
+        self.signals
+            .iter()
+            .map(|(name, signal)| {
+                let buf = pdi
+                    .get(signal.offset..)
+                    .ok_or(WireError::ReadBufferTooShort)?;
+
+                let value = match signal.ty {
+                    SignalType::Bool { bit } if bit < 8 => {
+                        let byte = buf.first().ok_or(WireError::ReadBufferTooShort)?;
+
+                        SignalValue::Bool(byte & (1 << bit) != 0)
+                    }
+                    SignalType::Bool { .. } => return Err(WireError::InvalidValue.into()),
+                    SignalType::U8 => SignalValue::U8(u8::unpack_from_slice(buf)?),
+                    SignalType::I8 => SignalValue::I8(i8::unpack_from_slice(buf)?),
+                    SignalType::U16 => SignalValue::U16(u16::unpack_from_slice(buf)?),
+                    SignalType::I16 => SignalValue::I16(i16::unpack_from_slice(buf)?),
+                    SignalType::U32 => SignalValue::U32(u32::unpack_from_slice(buf)?),
+                    SignalType::I32 => SignalValue::I32(i32::unpack_from_slice(buf)?),
+                    SignalType::U64 => SignalValue::U64(u64::unpack_from_slice(buf)?),
+                    SignalType::I64 => SignalValue::I64(i64::unpack_from_slice(buf)?),
+                    SignalType::F32 => SignalValue::F32(f32::unpack_from_slice(buf)?),
+                    SignalType::F64 => SignalValue::F64(f64::unpack_from_slice(buf)?),
+                };
+
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
+}
+
+fn pack(value: impl EtherCrabWireWrite, buf: &mut [u8]) -> Result<(), WireError> {
+    value.pack_to_slice(buf).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> ProcessImageMap {
+        ProcessImageMap::new()
+            .with_signal("enable", 0, SignalType::Bool { bit: 0 })
+            .with_signal("fault_reset", 0, SignalType::Bool { bit: 7 })
+            .with_signal("setpoint", 1, SignalType::I16)
+            .with_signal("velocity", 3, SignalType::U32)
+            .with_signal("scale", 7, SignalType::F32)
+    }
+
+    #[test]
+    fn pack_named_signals() {
+        let map = map();
+
+        let values = BTreeMap::from([
+            ("enable".to_string(), SignalValue::Bool(true)),
+            ("fault_reset".to_string(), SignalValue::Bool(true)),
+            ("setpoint".to_string(), SignalValue::I16(-1000)),
+            ("velocity".to_string(), SignalValue::U32(0x1234_5678)),
+            ("scale".to_string(), SignalValue::F32(1.5)),
+        ]);
+
+        let mut pdi = [0u8; 12];
+
+        map.pack(&values, &mut pdi).expect("Pack");
+
+        assert_eq!(
+            pdi,
+            [
+                0x81, // enable and fault_reset
+                0x18, 0xfc, // setpoint
+                0x78, 0x56, 0x34, 0x12, // velocity
+                0x00, 0x00, 0xc0, 0x3f, // scale
+                0x00, // unmapped
+            ]
+        );
+
+        assert_eq!(map.unpack(&pdi), Ok(values));
+    }
+
+    #[test]
+    fn pack_leaves_other_bits() {
+        let map = map();
+
+        let mut pdi = [0xffu8; 11];
+
+        map.pack(
+            &BTreeMap::from([("enable".to_string(), SignalValue::Bool(false))]),
+            &mut pdi,
+        )
+        .expect("Pack");
+
+        assert_eq!(pdi[0], 0xfe);
+        assert!(pdi[1..].iter().all(|byte| *byte == 0xff));
+    }
+
+    #[test]
+    fn pack_errors() {
+        let map = map();
+
+        let mut pdi = [0u8; 11];
+
+        assert_eq!(
+            map.pack(
+                &BTreeMap::from([("missing".to_string(), SignalValue::U8(1))]),
+                &mut pdi
+            ),
+            Err(Error::NotFound {
+                item: Item::Signal,
+                index: None
+            })
+        );
+
+        assert_eq!(
+            map.pack(
+                &BTreeMap::from([("setpoint".to_string(), SignalValue::U16(1))]),
+                &mut pdi
+            ),
+            Err(Error::Wire(WireError::InvalidValue))
+        );
+
+        assert_eq!(
+            map.pack(
+                &BTreeMap::from([("scale".to_string(), SignalValue::F32(1.0))]),
+                &mut pdi[..8]
+            ),
+            Err(Error::Wire(WireError::WriteBufferTooShort))
+        );
+    }
+
+    #[test]
+    fn pack_error_writes_nothing() {
+        let map = map();
+
+        // Values are packed in name order, so the valid signals before and after the bad ones
+        // would be written first if they weren't all checked up front
+        for (name, value) in [
+            ("missing", SignalValue::U8(1)),
+            ("setpoint", SignalValue::U16(1)),
+            ("scale", SignalValue::F32(1.0)),
+        ] {
+            let mut pdi = [0u8; 10];
+
+            let result = map.pack(
+                &BTreeMap::from([
+                    ("enable".to_string(), SignalValue::Bool(true)),
+                    (name.to_string(), value),
+                    ("velocity".to_string(), SignalValue::U32(0x1234_5678)),
+                ]),
+                &mut pdi,
+            );
+
+            assert!(result.is_err());
+            assert_eq!(pdi, [0u8; 10], "{name}");
+        }
+    }
+}
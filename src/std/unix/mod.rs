@@ -11,22 +11,84 @@ use self::bpf::BpfDevice as RawSocketDesc;
 pub(in crate::std) use self::linux::RawSocketDesc;
 
 use crate::{
-    error::Error,
+    error::{Error, Item},
     fmt,
     pdu_loop::{PduRx, PduTx},
 };
 use async_io::Async;
-use core::{future::Future, pin::Pin, task::Poll};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
 use futures_lite::{AsyncRead, AsyncWrite};
 
-struct TxRxFut<'a> {
-    socket: Async<RawSocketDesc>,
+/// The socket(s) used by [`TxRxFut`] to send and receive frames.
+trait FrameIo {
+    fn poll_send(&mut self, ctx: &mut Context<'_>, data: &[u8]) -> Poll<std::io::Result<usize>>;
+
+    fn poll_receive(
+        &mut self,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>>;
+}
+
+/// Send and receive on the same socket.
+struct SingleSocket<S>(S);
+
+impl<S> FrameIo for SingleSocket<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_send(&mut self, ctx: &mut Context<'_>, data: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(ctx, data)
+    }
+
+    fn poll_receive(
+        &mut self,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(ctx, buf)
+    }
+}
+
+/// Send on one socket and receive on another.
+struct SplitSockets<W, R> {
+    tx: W,
+    rx: R,
+}
+
+impl<W, R> FrameIo for SplitSockets<W, R>
+where
+    W: AsyncWrite + Unpin,
+    R: AsyncRead + Unpin,
+{
+    fn poll_send(&mut self, ctx: &mut Context<'_>, data: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.tx).poll_write(ctx, data)
+    }
+
+    fn poll_receive(
+        &mut self,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.rx).poll_read(ctx, buf)
+    }
+}
+
+struct TxRxFut<'a, S> {
+    socket: S,
     mtu: usize,
     tx: Option<PduTx<'a>>,
     rx: Option<PduRx<'a>>,
 }
 
-impl<'a> Future for TxRxFut<'a> {
+impl<'a, S> Future for TxRxFut<'a, S>
+where
+    S: FrameIo + Unpin,
+{
     type Output = Result<(PduTx<'a>, PduRx<'a>), Error>;
 
     fn poll(mut self: Pin<&mut Self>, ctx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
@@ -49,28 +111,26 @@ impl<'a> Future for TxRxFut<'a> {
 
         while let Some(frame) = unsafe { self.tx.as_mut().unwrap_unchecked() }.next_sendable_frame()
         {
-            let res = frame.send_blocking(|data| {
-                match Pin::new(&mut self.socket).poll_write(ctx, data) {
-                    Poll::Ready(Ok(bytes_written)) => {
-                        if bytes_written != data.len() {
-                            fmt::error!("Only wrote {} of {} bytes", bytes_written, data.len());
-
-                            Err(Error::PartialSend {
-                                len: data.len(),
-                                sent: bytes_written,
-                            })
-                        } else {
-                            Ok(bytes_written)
-                        }
+            let res = frame.send_blocking(|data| match self.socket.poll_send(ctx, data) {
+                Poll::Ready(Ok(bytes_written)) => {
+                    if bytes_written != data.len() {
+                        fmt::error!("Only wrote {} of {} bytes", bytes_written, data.len());
+
+                        Err(Error::PartialSend {
+                            len: data.len(),
+                            sent: bytes_written,
+                        })
+                    } else {
+                        Ok(bytes_written)
                     }
+                }
 
-                    Poll::Ready(Err(e)) => {
-                        fmt::error!("Send PDU failed: {}", e);
+                Poll::Ready(Err(e)) => {
+                    fmt::error!("Send PDU failed: {}", e);
 
-                        Err(Error::SendFrame)
-                    }
-                    Poll::Pending => Ok(0),
+                    Err(Error::SendFrame)
                 }
+                Poll::Pending => Ok(0),
             });
 
             if let Err(e) = res {
@@ -82,7 +142,7 @@ impl<'a> Future for TxRxFut<'a> {
 
         let mut buf = vec![0; self.mtu];
 
-        match Pin::new(&mut self.socket).poll_read(ctx, &mut buf) {
+        match self.socket.poll_receive(ctx, &mut buf) {
             Poll::Ready(Ok(n)) => {
                 fmt::trace!("Poll ready");
                 // Wake again in case there are more frames to consume. This is additionally
@@ -114,13 +174,52 @@ impl<'a> Future for TxRxFut<'a> {
     }
 }
 
+/// Configuration for [`tx_rx_task_with_config`].
+#[derive(Copy, Clone, Debug)]
+pub struct TxRxTaskConfig {
+    /// If set to `true`, return an error with an [`Error::LinkDown`] payload when the TX/RX task is
+    /// created if the interface reports no carrier.
+    ///
+    /// Defaults to `true`.
+    pub check_link: bool,
+}
+
+impl Default for TxRxTaskConfig {
+    fn default() -> Self {
+        Self { check_link: true }
+    }
+}
+
 /// Spawn a TX and RX task.
+///
+/// This fails fast if `interface` has no link. Use [`tx_rx_task_with_config`] to disable this
+/// check.
 pub fn tx_rx_task<'sto>(
+    interface: &str,
+    pdu_tx: PduTx<'sto>,
+    pdu_rx: PduRx<'sto>,
+) -> Result<impl Future<Output = Result<(PduTx<'sto>, PduRx<'sto>), Error>> + 'sto, std::io::Error>
+{
+    tx_rx_task_with_config(interface, pdu_tx, pdu_rx, TxRxTaskConfig::default())
+}
+
+/// Spawn a TX and RX task with the given configuration.
+pub fn tx_rx_task_with_config<'sto>(
     interface: &str,
     pdu_tx: PduTx<'sto>,
     #[allow(unused_mut)] mut pdu_rx: PduRx<'sto>,
+    config: TxRxTaskConfig,
 ) -> Result<impl Future<Output = Result<(PduTx<'sto>, PduRx<'sto>), Error>> + 'sto, std::io::Error>
 {
+    if config.check_link && !interface_link_up(interface).map_err(std::io::Error::other)? {
+        fmt::error!("Interface {} has no link", interface);
+
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NetworkDown,
+            Error::LinkDown,
+        ));
+    }
+
     let mut socket = RawSocketDesc::new(interface)?;
 
     // macOS forcibly sets the source address to the NIC's MAC, so instead of using `MASTER_ADDR`
@@ -139,7 +238,67 @@ pub fn tx_rx_task<'sto>(
     let async_socket = Async::new(socket)?;
 
     let task = TxRxFut {
-        socket: async_socket,
+        socket: SingleSocket(async_socket),
+        mtu,
+        tx: Some(pdu_tx),
+        rx: Some(pdu_rx),
+    };
+
+    Ok(task)
+}
+
+/// Spawn a TX and RX task that sends frames on `tx_interface` and receives responses on
+/// `rx_interface`.
+///
+/// This is useful for tap or monitoring setups where responses arrive on a different port to the
+/// one frames are sent from, e.g. a mirror port. Received frames are filtered in the same way as
+/// [`tx_rx_task`]. Frames received on `tx_interface` are ignored.
+///
+/// This fails fast if either interface has no link.
+pub fn tx_rx_task_split<'sto>(
+    tx_interface: &str,
+    rx_interface: &str,
+    pdu_tx: PduTx<'sto>,
+    #[allow(unused_mut)] mut pdu_rx: PduRx<'sto>,
+) -> Result<impl Future<Output = Result<(PduTx<'sto>, PduRx<'sto>), Error>> + 'sto, std::io::Error>
+{
+    for interface in [tx_interface, rx_interface] {
+        if !interface_link_up(interface).map_err(std::io::Error::other)? {
+            fmt::error!("Interface {} has no link", interface);
+
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NetworkDown,
+                Error::LinkDown,
+            ));
+        }
+    }
+
+    let tx_socket = RawSocketDesc::new(tx_interface)?;
+    let mut rx_socket = RawSocketDesc::new(rx_interface)?;
+
+    // Responses carry the source MAC of the sending NIC, so filter on that. See
+    // `tx_rx_task_with_config`.
+    #[cfg(all(not(target_os = "linux"), unix))]
+    if let Some(mac) = tx_socket.mac().ok().flatten() {
+        fmt::debug!("Setting source MAC to {}", mac);
+
+        pdu_rx.set_source_mac(mac);
+    }
+
+    let mtu = rx_socket.interface_mtu()?;
+
+    fmt::debug!(
+        "Opening {} for TX and {} for RX with MTU {}",
+        tx_interface,
+        rx_interface,
+        mtu
+    );
+
+    let task = TxRxFut {
+        socket: SplitSockets {
+            tx: Async::new(tx_socket)?,
+            rx: Async::new(rx_socket)?,
+        },
         mtu,
         tx: Some(pdu_tx),
         rx: Some(pdu_rx),
@@ -167,6 +326,112 @@ pub fn ethercat_now() -> u64 {
     t.saturating_sub(946684800)
 }
 
+/// Check whether a network interface is up and has a carrier.
+///
+/// Returns [`Error::NotFound`] if the interface does not exist or could not be queried.
+///
+/// # Examples
+///
+/// Wait up to 5 seconds for a cable to be plugged in before starting the TX/RX task:
+///
+/// ```rust,no_run
+/// use ethercrab::{error::Error, std::interface_link_up};
+/// use std::time::{Duration, Instant};
+///
+/// fn wait_for_link(interface: &str, timeout: Duration) -> Result<(), Error> {
+///     let start = Instant::now();
+///
+///     while !interface_link_up(interface)? {
+///         if start.elapsed() > timeout {
+///             return Err(Error::LinkDown);
+///         }
+///
+///         std::thread::sleep(Duration::from_millis(100));
+///     }
+///
+///     Ok(())
+/// }
+///
+/// wait_for_link("eth0", Duration::from_secs(5)).expect("no link");
+/// ```
+pub fn interface_link_up(interface: &str) -> Result<bool, Error> {
+    link_up_with(interface, get_interface_flags)
+}
+
+/// Decode the interface flags filled in by `get_flags`. Split out so the `ioctl` can be mocked in
+/// tests.
+#[allow(trivial_numeric_casts)]
+fn link_up_with(
+    interface: &str,
+    get_flags: impl FnOnce(&mut libc::ifreq) -> std::io::Result<()>,
+) -> Result<bool, Error> {
+    // SAFETY: `ifreq` is plain old data, for which all zeroes is a valid value
+    let mut ifreq: libc::ifreq = unsafe { core::mem::zeroed() };
+
+    for (dst, src) in ifreq.ifr_name.iter_mut().zip(interface.as_bytes()) {
+        *dst = *src as libc::c_char;
+    }
+
+    get_flags(&mut ifreq).map_err(|e| {
+        fmt::error!("Failed to get flags for interface {}: {}", interface, e);
+
+        Error::NotFound {
+            item: Item::Interface,
+            index: None,
+        }
+    })?;
+
+    // SAFETY: `ifru_flags` is the union member filled in by `SIOCGIFFLAGS`. FreeBSD splits the
+    // flags into a low and high half, where the low half holds `IFF_UP` and `IFF_RUNNING`.
+    #[cfg(target_os = "freebsd")]
+    let flags = unsafe { ifreq.ifr_ifru.ifru_flags[0] };
+    #[cfg(not(target_os = "freebsd"))]
+    let flags = unsafe { ifreq.ifr_ifru.ifru_flags };
+
+    let flags = libc::c_int::from(flags);
+
+    Ok(flags & libc::IFF_UP != 0 && flags & libc::IFF_RUNNING != 0)
+}
+
+/// get interface flags
+#[cfg(target_os = "linux")]
+#[allow(trivial_numeric_casts)]
+const SIOCGIFFLAGS: libc::c_ulong = libc::SIOCGIFFLAGS as libc::c_ulong;
+/// get interface flags
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "openbsd",
+    target_os = "freebsd"
+))]
+const SIOCGIFFLAGS: libc::c_ulong = 0xc0206911;
+/// get interface flags. NetBSD's `ifreq` is larger as its union holds a `sockaddr_storage`.
+#[cfg(target_os = "netbsd")]
+const SIOCGIFFLAGS: libc::c_ulong = 0xc0906911;
+
+/// Query interface flags with `SIOCGIFFLAGS`. This needs a socket, but not a privileged one.
+fn get_interface_flags(ifreq: &mut libc::ifreq) -> std::io::Result<()> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+
+    if fd == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    #[allow(trivial_casts, trivial_numeric_casts)]
+    let res = unsafe { libc::ioctl(fd, SIOCGIFFLAGS as _, ifreq as *mut libc::ifreq) };
+
+    // Capture the error before `close` can overwrite it
+    let res = if res == -1 {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok(())
+    };
+
+    unsafe { libc::close(fd) };
+
+    res
+}
+
 // Unix only
 #[allow(trivial_numeric_casts)]
 fn ifreq_for(name: &str) -> ifreq {
@@ -187,3 +452,174 @@ struct ifreq {
     ifr_name: [libc::c_char; libc::IF_NAMESIZE],
     ifr_data: libc::c_int, /* ifr_ifindex or ifr_mtu */
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, PduStorage, fake_network::SentFrame};
+    use core::{pin::pin, task::Waker, time::Duration};
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    };
+
+    type Mirror = Arc<Mutex<VecDeque<Vec<u8>>>>;
+
+    /// A send-only interface. Every frame written to it is answered by a SubDevice, with the
+    /// response copied to a separate mirror port.
+    struct MockTx {
+        mirror: Mirror,
+    }
+
+    impl AsyncWrite for MockTx {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _ctx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            let mut frame = SentFrame::new(buf.to_vec());
+            frame.set_wkcs(&[1]);
+
+            self.mirror.lock().unwrap().push_back(frame.into_reply());
+
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _ctx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _ctx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// A receive-only mirror port.
+    struct MockRx {
+        mirror: Mirror,
+    }
+
+    impl AsyncRead for MockRx {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _ctx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            match self.mirror.lock().unwrap().pop_front() {
+                Some(frame) => {
+                    buf[0..frame.len()].copy_from_slice(&frame);
+
+                    Poll::Ready(Ok(frame.len()))
+                }
+                None => Poll::Pending,
+            }
+        }
+    }
+
+    #[test]
+    fn split_sockets() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+        let (pdu_tx, pdu_rx, pdu_loop) = storage.try_split().unwrap();
+
+        let mirror = Mirror::default();
+
+        let mut task = pin!(TxRxFut {
+            socket: SplitSockets {
+                tx: MockTx {
+                    mirror: mirror.clone(),
+                },
+                rx: MockRx {
+                    mirror: mirror.clone(),
+                },
+            },
+            mtu: 1500,
+            tx: Some(pdu_tx),
+            rx: Some(pdu_rx),
+        });
+
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        let mut frame = pdu_loop.alloc_frame().expect("alloc");
+
+        let handle = frame
+            .push_pdu(Command::fpwr(0x1000, 0x0980).into(), [0xaau8, 0xbb], None)
+            .expect("push");
+
+        let mut frame = pin!(frame.mark_sendable(&pdu_loop, Duration::from_secs(1), 0));
+
+        assert!(frame.as_mut().poll(&mut ctx).is_pending());
+
+        // Sends on the TX socket, then receives the response from the RX socket
+        assert!(task.as_mut().poll(&mut ctx).is_pending());
+
+        assert!(mirror.lock().unwrap().is_empty(), "response not received");
+
+        let Poll::Ready(received) = frame.as_mut().poll(&mut ctx) else {
+            panic!("frame should have a response");
+        };
+
+        let pdu = received.expect("response").first_pdu(handle).expect("PDU");
+
+        assert_eq!(&*pdu, &[0xaa, 0xbb]);
+        assert_eq!(pdu.working_counter, 1);
+    }
+
+    fn set_flags(ifreq: &mut libc::ifreq, flags: libc::c_int) {
+        #[cfg(target_os = "freebsd")]
+        {
+            ifreq.ifr_ifru.ifru_flags = [flags as libc::c_short, 0];
+        }
+        #[cfg(not(target_os = "freebsd"))]
+        {
+            ifreq.ifr_ifru.ifru_flags = flags as libc::c_short;
+        }
+    }
+
+    #[test]
+    fn link_flags() {
+        let up = link_up_with("eth0", |ifreq| {
+            set_flags(
+                ifreq,
+                libc::IFF_UP | libc::IFF_RUNNING | libc::IFF_BROADCAST,
+            );
+
+            Ok(())
+        });
+
+        assert_eq!(up, Ok(true));
+
+        // Administratively up, but no cable plugged in
+        let no_carrier = link_up_with("eth0", |ifreq| {
+            set_flags(ifreq, libc::IFF_UP | libc::IFF_BROADCAST);
+
+            Ok(())
+        });
+
+        assert_eq!(no_carrier, Ok(false));
+
+        let down = link_up_with("eth0", |ifreq| {
+            set_flags(ifreq, libc::IFF_RUNNING);
+
+            Ok(())
+        });
+
+        assert_eq!(down, Ok(false));
+    }
+
+    #[test]
+    fn link_unknown_interface() {
+        let res = link_up_with("nope0", |_ifreq| {
+            Err(std::io::Error::from_raw_os_error(libc::ENODEV))
+        });
+
+        assert_eq!(
+            res,
+            Err(Error::NotFound {
+                item: Item::Interface,
+                index: None
+            })
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     ReceiveAction,
-    error::Error,
+    error::{Error, Item},
     fmt,
     pdu_loop::{PduRx, PduTx},
     std::ParkSignal,
@@ -48,7 +48,7 @@ fn get_tx_rx(
 }
 
 /// Windows-specific configuration for [`tx_rx_task_blocking`].
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug)]
 pub struct TxRxTaskConfig {
     /// If set to `true`, use a spinloop to wait for packet TX or RX instead of putting the thread
     /// to sleep.
@@ -58,15 +58,56 @@ pub struct TxRxTaskConfig {
     /// [`thread_priority`](https://docs.rs/thread-priority/latest/x86_64-pc-windows-msvc/thread_priority/index.html)
     /// or similar.
     pub spinloop: bool,
+
+    /// If set to `true`, [`tx_rx_task_blocking`] returns an error with an [`Error::LinkDown`]
+    /// payload if the interface reports no link when it is started.
+    ///
+    /// Defaults to `true`.
+    pub check_link: bool,
+}
+
+impl Default for TxRxTaskConfig {
+    fn default() -> Self {
+        Self {
+            spinloop: false,
+            check_link: true,
+        }
+    }
+}
+
+/// Check whether a network interface is up.
+///
+/// Returns [`Error::NotFound`] if no interface with the given name exists.
+///
+/// To wait for a link before starting the TX/RX task, poll this function in a loop with a short
+/// sleep until it returns `true` or a timeout elapses.
+pub fn interface_link_up(interface: &str) -> Result<bool, Error> {
+    pnet_datalink::interfaces()
+        .iter()
+        .find(|candidate| candidate.name == interface)
+        .map(|interface| interface.is_up())
+        .ok_or(Error::NotFound {
+            item: Item::Interface,
+            index: None,
+        })
 }
 
 /// Create a blocking task that waits for PDUs to send, and receives PDU responses.
+///
+/// This fails fast if `device` has no link. Set [`TxRxTaskConfig::check_link`] to `false` to disable
+/// this check.
 pub fn tx_rx_task_blocking<'sto>(
     device: &str,
     mut pdu_tx: PduTx<'sto>,
     mut pdu_rx: PduRx<'sto>,
     config: TxRxTaskConfig,
 ) -> Result<(PduTx<'sto>, PduRx<'sto>), io::Error> {
+    if config.check_link && !interface_link_up(device).map_err(io::Error::other)? {
+        fmt::error!("Interface {} has no link", device);
+
+        return Err(io::Error::new(io::ErrorKind::NetworkDown, Error::LinkDown));
+    }
+
     let signal = Arc::new(ParkSignal::new());
     let waker = Waker::from(Arc::clone(&signal));
 
@@ -0,0 +1,135 @@
+use crate::al_status_code::AlStatusCode;
+use core::sync::atomic::{AtomicU16, AtomicU32, Ordering};
+use ethercrab_wire::EtherCrabWireRead;
+
+/// The number of AL status codes kept per SubDevice.
+pub(crate) const AL_STATUS_HISTORY_LEN: usize = 8;
+
+/// A small ring buffer of the AL status codes a SubDevice reported during the most recent state
+/// transition attempt.
+///
+/// Only changes in the status code are recorded, so a SubDevice that reports the same error for
+/// many polls only takes up one slot. Once full, the oldest code is overwritten.
+///
+/// The buffer is only ever written by the single task waiting for a state transition, so the
+/// individual atomics do not need to be updated together.
+#[derive(Debug)]
+pub(crate) struct AlStatusHistory {
+    codes: [AtomicU16; AL_STATUS_HISTORY_LEN],
+    /// Total number of codes pushed since the last clear.
+    pushed: AtomicU32,
+    /// The last code seen, recorded or not.
+    last: AtomicU16,
+}
+
+impl Default for AlStatusHistory {
+    fn default() -> Self {
+        Self {
+            codes: [const { AtomicU16::new(0) }; AL_STATUS_HISTORY_LEN],
+            pushed: AtomicU32::new(0),
+            last: AtomicU16::new(0),
+        }
+    }
+}
+
+impl AlStatusHistory {
+    /// Forget all recorded codes, ready for a new transition attempt.
+    pub(crate) fn clear(&self) {
+        self.pushed.store(0, Ordering::Relaxed);
+        self.last.store(0, Ordering::Relaxed);
+    }
+
+    /// Record a raw AL status code if it differs from the previously observed code.
+    ///
+    /// The history starts from an implied "no error", so a SubDevice that never reports an error
+    /// leaves the history empty.
+    pub(crate) fn observe(&self, raw_code: u16) {
+        if self.last.swap(raw_code, Ordering::Relaxed) == raw_code {
+            return;
+        }
+
+        let pushed = self.pushed.load(Ordering::Relaxed);
+
+        self.codes[pushed as usize % AL_STATUS_HISTORY_LEN].store(raw_code, Ordering::Relaxed);
+
+        self.pushed.store(pushed.wrapping_add(1), Ordering::Relaxed);
+    }
+
+    /// Get the recorded codes, oldest first.
+    pub(crate) fn get(&self) -> heapless::Vec<AlStatusCode, AL_STATUS_HISTORY_LEN> {
+        let pushed = self.pushed.load(Ordering::Relaxed) as usize;
+
+        let len = pushed.min(AL_STATUS_HISTORY_LEN);
+
+        (pushed - len..pushed)
+            .map(|i| {
+                let raw = self.codes[i % AL_STATUS_HISTORY_LEN].load(Ordering::Relaxed);
+
+                AlStatusCode::unpack_from_slice(&raw.to_le_bytes())
+                    .unwrap_or(AlStatusCode::Unknown(raw))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_changes() {
+        let history = AlStatusHistory::default();
+
+        // Error appears briefly, then the SubDevice recovers
+        for code in [0x0000, 0x0000, 0x001d, 0x001d, 0x001d, 0x0000, 0x0000] {
+            history.observe(code);
+        }
+
+        assert_eq!(
+            history.get().as_slice(),
+            &[
+                AlStatusCode::InvalidOutputConfiguration,
+                AlStatusCode::NoError
+            ]
+        );
+    }
+
+    #[test]
+    fn overwrites_oldest() {
+        let history = AlStatusHistory::default();
+
+        for code in 1..=10u16 {
+            history.observe(code);
+        }
+
+        let expected = (3..=10u16)
+            .map(|raw| {
+                AlStatusCode::unpack_from_slice(&raw.to_le_bytes())
+                    .unwrap_or(AlStatusCode::Unknown(raw))
+            })
+            .collect::<heapless::Vec<_, AL_STATUS_HISTORY_LEN>>();
+
+        assert_eq!(history.get(), expected);
+    }
+
+    #[test]
+    fn clear() {
+        let history = AlStatusHistory::default();
+
+        history.observe(0x0011);
+
+        assert_eq!(history.get().len(), 1);
+
+        history.clear();
+
+        assert!(history.get().is_empty());
+
+        // A repeat of the code seen before clearing is recorded again
+        history.observe(0x0011);
+
+        assert_eq!(
+            history.get().as_slice(),
+            &[AlStatusCode::InvalidRequestedStateChange]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{fmmu::Fmmu, sync_manager_channel::SyncManagerChannel};
+use ethercrab_wire::EtherCrabWireWriteSized;
+
+/// The maximum number of FMMUs and Sync Managers an ESC can have.
+pub(crate) const MAX_CHANNELS: usize = 16;
+
+/// FMMU configuration as read back from a SubDevice.
+///
+/// Defined in ETG1000.4 Table 56 – Fieldbus memory management unit (FMMU) entity.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FmmuConfig {
+    /// FMMU index, from 0 to 15.
+    pub index: u8,
+    /// Start address in the logical (PDI) address space.
+    pub logical_start_address: u32,
+    /// Mapped length in bytes.
+    pub length_bytes: u16,
+    /// First bit of the logical start byte.
+    pub logical_start_bit: u8,
+    /// Last bit of the logical end byte.
+    pub logical_end_bit: u8,
+    /// Start address in the SubDevice's physical memory.
+    pub physical_start_address: u16,
+    /// First bit of the physical start byte.
+    pub physical_start_bit: u8,
+    /// The FMMU maps data read by the MainDevice (SubDevice inputs).
+    pub read_enable: bool,
+    /// The FMMU maps data written by the MainDevice (SubDevice outputs).
+    pub write_enable: bool,
+    /// The FMMU is active.
+    pub enable: bool,
+}
+
+impl FmmuConfig {
+    pub(crate) fn new(index: u8, fmmu: Fmmu) -> Self {
+        Self {
+            index,
+            logical_start_address: fmmu.logical_start_address,
+            length_bytes: fmmu.length_bytes,
+            logical_start_bit: fmmu.logical_start_bit,
+            logical_end_bit: fmmu.logical_end_bit,
+            physical_start_address: fmmu.physical_start_address,
+            physical_start_bit: fmmu.physical_start_bit,
+            read_enable: fmmu.read_enable,
+            write_enable: fmmu.write_enable,
+            enable: fmmu.enable,
+        }
+    }
+}
+
+/// Sync Manager configuration as read back from a SubDevice.
+///
+/// The SM status byte is not included as it changes with every process data or mailbox exchange.
+///
+/// Defined in ETG1000.4 6.7.2 Sync Manager Attributes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SyncManagerConfig {
+    /// Sync Manager index, from 0 to 15.
+    pub index: u8,
+    /// Start address in the SubDevice's physical memory.
+    pub physical_start_address: u16,
+    /// Buffer length in bytes.
+    pub length_bytes: u16,
+    /// Raw control byte, holding the operation mode, direction and interrupt/watchdog enables.
+    pub control: u8,
+    /// The Sync Manager is active.
+    pub enable: bool,
+}
+
+impl SyncManagerConfig {
+    pub(crate) fn new(index: u8, sm: SyncManagerChannel) -> Self {
+        let [control] = sm.control.pack();
+
+        Self {
+            index,
+            physical_start_address: sm.physical_start_address,
+            length_bytes: sm.length_bytes,
+            control,
+            enable: sm.enable.enable,
+        }
+    }
+}
+
+/// All active FMMUs and Sync Managers of a SubDevice, read back from the device.
+///
+/// Compare this against the intended configuration after a SubDevice is configured to catch any
+/// writes that were acknowledged but did not take effect.
+///
+/// Created by [`SubDeviceRef::config_snapshot`](crate::SubDeviceRef::config_snapshot).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DeviceConfigSnapshot {
+    /// Enabled FMMUs, in index order.
+    pub fmmus: heapless::Vec<FmmuConfig, MAX_CHANNELS>,
+    /// Enabled Sync Managers, in index order.
+    pub sync_managers: heapless::Vec<SyncManagerConfig, MAX_CHANNELS>,
+}
+
+impl DeviceConfigSnapshot {
+    /// Get an enabled FMMU by its index.
+    pub fn fmmu(&self, index: u8) -> Option<&FmmuConfig> {
+        self.fmmus.iter().find(|fmmu| fmmu.index == index)
+    }
+
+    /// Get an enabled Sync Manager by its index.
+    pub fn sync_manager(&self, index: u8) -> Option<&SyncManagerConfig> {
+        self.sync_managers.iter().find(|sm| sm.index == index)
+    }
+}
@@ -1,26 +1,117 @@
 use super::{SubDevice, SubDeviceRef};
 use crate::{
     coe::{SdoExpedited, SubIndex},
-    eeprom::types::{
-        CoeDetails, DefaultMailbox, FmmuUsage, MailboxProtocols, SiiGeneral, SiiOwner, SyncManager,
-        SyncManagerEnable, SyncManagerType,
+    eeprom::{
+        device_provider::DeviceEeprom,
+        types::{
+            CoeDetails, DefaultMailbox, FmmuUsage, MailboxProtocols, SiiGeneral, SiiOwner,
+            SyncManager, SyncManagerEnable, SyncManagerType,
+        },
     },
     error::{Error, IgnoreNoCategory, Item},
     fmmu::Fmmu,
     fmt,
-    pdi::{PdiOffset, PdiSegment},
+    pdi::{PdiOffset, PdiSegment, extend_logical_range},
     register::RegisterAddress,
-    subdevice::types::{Mailbox, MailboxConfig},
+    subdevice::types::{Mailbox, MailboxConfig, SiiConfigSummary},
     subdevice_state::SubDeviceState,
     sync_manager_channel::{Enable, SM_BASE_ADDRESS, SM_TYPE_ADDRESS, Status, SyncManagerChannel},
 };
-use core::ops::DerefMut;
+use core::ops::{Deref, DerefMut, Range};
+
+/// The length in bits of each process data SM in one direction, along with its index and SII
+/// config.
+type SmBitLens = heapless::Vec<(u8, SyncManager, u16), 16>;
 
 /// Configuation from EEPROM methods.
 impl<S> SubDeviceRef<'_, S>
 where
     S: DerefMut<Target = SubDevice>,
 {
+    /// Get the SII items needed for configuration.
+    ///
+    /// These are normally read once by [`SubDevice::new`] if
+    /// [`MainDeviceConfig::cache_sii_config`](crate::MainDeviceConfig::cache_sii_config) is set.
+    /// If that failed, they are read from the EEPROM again here and kept for later configuration
+    /// stages.
+    async fn sii_summary(&mut self) -> Result<SiiConfigSummary, Error> {
+        if !self.state.sii_summary.complete {
+            self.state.sii_summary = self.current_sii_summary().await?;
+        }
+
+        Ok(self.state.sii_summary.clone())
+    }
+
+    /// Get the SII items needed to configure the mailbox SMs.
+    ///
+    /// Unless
+    /// [`MainDeviceConfig::cache_sii_config`](crate::MainDeviceConfig::cache_sii_config) is set,
+    /// these are read from the EEPROM every time.
+    async fn mailbox_sii(&mut self) -> Result<SiiConfigSummary, Error> {
+        if self.maindevice.config.cache_sii_config {
+            return self.sii_summary().await;
+        }
+
+        let eeprom = self.eeprom();
+
+        let sync_managers = eeprom.sync_managers().await?;
+
+        // Read default mailbox configuration from SubDevice information area
+        let mailbox = DeviceEeprom::new(self.maindevice, self.configured_address)
+            .standard_mailbox_config()
+            .await
+            .ignore_no_category()?
+            .unwrap_or_else(|| {
+                fmt::debug!(
+                    "{:#06x} has no EEPROM mailbox config, using default",
+                    self.configured_address()
+                );
+
+                DefaultMailbox::default()
+            });
+
+        let general = eeprom
+            .general()
+            .await
+            .ignore_no_category()?
+            .unwrap_or_else(|| {
+                fmt::debug!(
+                    "{:#06x} has no EEPROM general category, using default",
+                    self.configured_address()
+                );
+
+                SiiGeneral::default()
+            });
+
+        Ok(SiiConfigSummary {
+            mailbox,
+            complete_access: general
+                .coe_details
+                .contains(CoeDetails::ENABLE_COMPLETE_ACCESS),
+            sync_managers,
+            ..SiiConfigSummary::default()
+        })
+    }
+
+    /// Get the SII items needed to configure the FMMUs.
+    ///
+    /// Unless
+    /// [`MainDeviceConfig::cache_sii_config`](crate::MainDeviceConfig::cache_sii_config) is set,
+    /// these are read from the EEPROM every time.
+    async fn fmmu_sii(&mut self) -> Result<SiiConfigSummary, Error> {
+        if self.maindevice.config.cache_sii_config {
+            return self.sii_summary().await;
+        }
+
+        let eeprom = self.eeprom();
+
+        Ok(SiiConfigSummary {
+            sync_managers: eeprom.sync_managers().await?,
+            fmmus: eeprom.fmmus().await?,
+            ..SiiConfigSummary::default()
+        })
+    }
+
     /// First stage configuration (INIT -> PRE-OP).
     ///
     /// Continue configuration by calling
@@ -31,10 +122,10 @@ where
         // to master mode here, now that the transition is complete.
         self.set_eeprom_mode(SiiOwner::Master).await?;
 
-        let sync_managers = self.eeprom().sync_managers().await?;
+        let sii = self.mailbox_sii().await?;
 
         // Mailboxes must be configured in INIT state
-        self.configure_mailbox_sms(&sync_managers).await?;
+        self.configure_mailbox_sms(&sii).await?;
 
         // Some SubDevices must be in PDI EEPROM mode to transition from INIT to PRE-OP. This is
         // mentioned in ETG2010 p. 146 under "Eeprom/@AssignToPd"
@@ -102,16 +193,20 @@ where
     /// Second state configuration (PRE-OP -> SAFE-OP).
     ///
     /// PDOs must be configured in the PRE-OP state.
+    ///
+    /// Returns the next free PDI offset, and the logical address range covered by the FMMUs
+    /// configured for `direction`, or `None` if none were configured.
     pub(crate) async fn configure_fmmus(
         &mut self,
         mut global_offset: PdiOffset,
         group_start_address: u32,
         direction: PdoDirection,
-    ) -> Result<PdiOffset, Error> {
-        let eeprom = self.eeprom();
-
-        let sync_managers = eeprom.sync_managers().await?;
-        let fmmu_usage = eeprom.fmmus().await?;
+    ) -> Result<(PdiOffset, Option<Range<u32>>), Error> {
+        let SiiConfigSummary {
+            sync_managers,
+            fmmus: fmmu_usage,
+            ..
+        } = self.fmmu_sii().await?;
 
         let state = self.state().await?;
 
@@ -138,7 +233,7 @@ where
             has_coe
         );
 
-        let range = if has_coe {
+        let (range, logical_range) = if has_coe {
             self.configure_pdos_coe(&sync_managers, &fmmu_usage, direction, &mut global_offset)
                 .await?
         } else {
@@ -170,7 +265,7 @@ where
             self.state.config.io.output.len(),
         );
 
-        Ok(global_offset)
+        Ok((global_offset, logical_range))
     }
 
     async fn write_sm_config(
@@ -207,35 +302,9 @@ where
     }
 
     /// Configure SM0 and SM1 for mailbox communication.
-    async fn configure_mailbox_sms(&mut self, sync_managers: &[SyncManager]) -> Result<(), Error> {
-        let eeprom = self.eeprom();
-
-        // Read default mailbox configuration from SubDevice information area
-        let mailbox_config = eeprom
-            .mailbox_config()
-            .await
-            .ignore_no_category()?
-            .unwrap_or_else(|| {
-                fmt::debug!(
-                    "{:#06x} has no EEPROM mailbox config, using default",
-                    self.configured_address()
-                );
-
-                DefaultMailbox::default()
-            });
-
-        let general = eeprom
-            .general()
-            .await
-            .ignore_no_category()?
-            .unwrap_or_else(|| {
-                fmt::debug!(
-                    "{:#06x} has no EEPROM general category, using default",
-                    self.configured_address()
-                );
-
-                SiiGeneral::default()
-            });
+    async fn configure_mailbox_sms(&mut self, sii: &SiiConfigSummary) -> Result<(), Error> {
+        // Default mailbox configuration from SubDevice information area
+        let mailbox_config = sii.mailbox;
 
         fmt::trace!(
             "SubDevice {:#06x} Mailbox configuration: {:#?}",
@@ -255,7 +324,7 @@ where
         let mut read_mailbox = None;
         let mut write_mailbox = None;
 
-        for (sync_manager_index, sync_manager) in sync_managers.iter().enumerate() {
+        for (sync_manager_index, sync_manager) in sii.sync_managers.iter().enumerate() {
             let sync_manager_index = sync_manager_index as u8;
 
             // Mailboxes are configured in INIT state
@@ -301,9 +370,7 @@ where
                 .supported_protocols
                 .contains(MailboxProtocols::COE)
                 && read_mailbox.is_some_and(|mbox| mbox.len > 0),
-            complete_access: general
-                .coe_details
-                .contains(CoeDetails::ENABLE_COMPLETE_ACCESS),
+            complete_access: sii.complete_access,
         };
 
         Ok(())
@@ -316,12 +383,232 @@ where
         fmmu_usage: &[FmmuUsage],
         direction: PdoDirection,
         global_offset: &mut PdiOffset,
-    ) -> Result<PdiSegment, Error> {
+    ) -> Result<(PdiSegment, Option<Range<u32>>), Error> {
+        let (desired_sm_type, desired_fmmu_type) = direction.filter_terms();
+
+        let start_offset = *global_offset;
+
+        let mut logical_range = None;
+
+        for (sync_manager_index, sync_manager, sm_bit_len) in
+            self.coe_pdo_bit_lens(sync_managers, direction).await?
+        {
+            let sm_config = self
+                .write_sm_config(sync_manager_index, &sync_manager, sm_bit_len.div_ceil(8))
+                .await?;
+
+            if sm_bit_len > 0 {
+                let fmmu_index = fmmu_usage
+                    .iter()
+                    .position(|usage| *usage == desired_fmmu_type)
+                    .ok_or(Error::NotFound {
+                        item: Item::Fmmu,
+                        index: None,
+                    })?;
+
+                let fmmu_range = self
+                    .write_fmmu_config(
+                        sm_bit_len,
+                        fmmu_index,
+                        global_offset,
+                        desired_sm_type,
+                        &sm_config,
+                    )
+                    .await?;
+
+                logical_range = Some(extend_logical_range(logical_range, fmmu_range));
+            }
+        }
+
+        Ok((
+            PdiSegment {
+                bytes: start_offset.up_to(*global_offset),
+            },
+            logical_range,
+        ))
+    }
+
+    async fn write_fmmu_config(
+        &self,
+        sm_bit_len: u16,
+        fmmu_index: usize,
+        global_offset: &mut PdiOffset,
+        desired_sm_type: SyncManagerType,
+        sm_config: &SyncManagerChannel,
+    ) -> Result<Range<u32>, Error> {
+        // Multiple SMs may use the same FMMU, so we'll read the existing config from the SubDevice
+        let mut fmmu_config = self
+            .read(RegisterAddress::fmmu(fmmu_index as u8))
+            .receive::<Fmmu>(self.maindevice)
+            .await?;
+
+        // We can use the enable flag as a sentinel for existing config because EtherCrab inits
+        // FMMUs to all zeroes on startup.
+        let fmmu_config = if fmmu_config.enable {
+            fmmu_config.length_bytes += sm_config.length_bytes;
+
+            fmmu_config
+        } else {
+            Fmmu {
+                logical_start_address: global_offset.start_address,
+                length_bytes: sm_config.length_bytes,
+                // Mapping into PDI is byte-aligned until/if we support bit-oriented SubDevices
+                logical_start_bit: 0,
+                // Always byte-aligned
+                logical_end_bit: 7,
+                physical_start_address: sm_config.physical_start_address,
+                physical_start_bit: 0x0,
+                read_enable: desired_sm_type == SyncManagerType::ProcessDataRead,
+                write_enable: desired_sm_type == SyncManagerType::ProcessDataWrite,
+                enable: true,
+            }
+        };
+
+        self.write(RegisterAddress::fmmu(fmmu_index as u8))
+            .send(self.maindevice, &fmmu_config)
+            .await?;
+
+        fmt::debug!(
+            "SubDevice {:#06x} FMMU{}: {}",
+            self.configured_address,
+            fmmu_index,
+            fmmu_config
+        );
+
+        *global_offset = global_offset.increment_byte_aligned(sm_bit_len);
+
+        Ok(fmmu_config.logical_start_address
+            ..(fmmu_config.logical_start_address + u32::from(fmmu_config.length_bytes)))
+    }
+
+    /// Configure PDOs from EEPROM
+    async fn configure_pdos_eeprom(
+        &self,
+        sync_managers: &[SyncManager],
+        direction: PdoDirection,
+        offset: &mut PdiOffset,
+    ) -> Result<(PdiSegment, Option<Range<u32>>), Error> {
+        let bit_lens = self.eeprom_pdo_bit_lens(sync_managers, direction).await?;
+
+        let fmmu_sm_mappings = self.eeprom().fmmu_mappings().await?;
+
+        let start_offset = *offset;
+
+        let mut logical_range = None;
+
+        let (sm_type, _fmmu_type) = direction.filter_terms();
+
+        for (sync_manager_index, sync_manager, bit_len) in bit_lens {
+            // Look for FMMU index using FMMU_EX section in EEPROM. If it's empty, default
+            // to looking through FMMU usage list and picking out the appropriate kind
+            // (Inputs, Outputs)
+            let fmmu_index = fmmu_sm_mappings
+                .iter()
+                .find(|fmmu| fmmu.sync_manager == sync_manager_index)
+                .map(|fmmu| fmmu.sync_manager)
+                .unwrap_or_else(|| {
+                    fmt::trace!(
+                        "Could not find FMMU for PDO SM{} in EEPROM, using SM index to pick FMMU instead",
+                        sync_manager_index,
+                    );
+
+                    sync_manager_index
+                });
+
+            let sm_config = self
+                .write_sm_config(sync_manager_index, &sync_manager, bit_len.div_ceil(8))
+                .await?;
+
+            let fmmu_range = self
+                .write_fmmu_config(
+                    bit_len,
+                    usize::from(fmmu_index),
+                    offset,
+                    sm_type,
+                    &sm_config,
+                )
+                .await?;
+
+            logical_range = Some(extend_logical_range(logical_range, fmmu_range));
+        }
+
+        Ok((
+            PdiSegment {
+                bytes: start_offset.up_to(*offset),
+            },
+            logical_range,
+        ))
+    }
+}
+
+/// Read-only configuration planning methods.
+impl<S> SubDeviceRef<'_, S>
+where
+    S: Deref<Target = SubDevice>,
+{
+    /// Get the SII items needed for configuration, reading them from the EEPROM if they weren't
+    /// read during discovery.
+    ///
+    /// Unlike `sii_summary`, a summary read here is not stored.
+    async fn current_sii_summary(&self) -> Result<SiiConfigSummary, Error> {
+        if self.state.sii_summary.complete {
+            return Ok(self.state.sii_summary.clone());
+        }
+
+        fmt::debug!(
+            "SubDevice {:#06x} SII config summary incomplete, reading from EEPROM",
+            self.configured_address
+        );
+
+        let eeprom = self.eeprom();
+
+        let general = eeprom
+            .general()
+            .await
+            .ignore_no_category()?
+            .unwrap_or_else(|| {
+                fmt::debug!(
+                    "{:#06x} has no EEPROM general category, using default",
+                    self.configured_address()
+                );
+
+                SiiGeneral::default()
+            });
+
+        eeprom.config_summary(&general).await
+    }
+
+    /// Compute the number of PDI bytes [`configure_fmmus`](SubDeviceRef::configure_fmmus) will
+    /// map for this SubDevice in the given direction, without writing any configuration.
+    ///
+    /// PDO assignments may be read over CoE, so like `configure_fmmus` this must be called in
+    /// PRE-OP.
+    pub(crate) async fn planned_pdi_len(&self, direction: PdoDirection) -> Result<usize, Error> {
+        let SiiConfigSummary { sync_managers, .. } = self.current_sii_summary().await?;
+
+        let bit_lens = if self.state.config.mailbox.has_coe {
+            self.coe_pdo_bit_lens(&sync_managers, direction).await?
+        } else {
+            self.eeprom_pdo_bit_lens(&sync_managers, direction).await?
+        };
+
+        Ok(bit_lens
+            .iter()
+            .map(|(_, _, bit_len)| usize::from(bit_len.div_ceil(8)))
+            .sum())
+    }
+
+    /// Read the PDO assignments of each process data SM in the given direction from CoE.
+    async fn coe_pdo_bit_lens(
+        &self,
+        sync_managers: &[SyncManager],
+        direction: PdoDirection,
+    ) -> Result<SmBitLens, Error> {
         if !self.state.config.mailbox.has_coe {
             fmt::warn!("Invariant: attempting to configure PDOs from COE with no SOE support");
         }
 
-        let (desired_sm_type, desired_fmmu_type) = direction.filter_terms();
+        let (desired_sm_type, _desired_fmmu_type) = direction.filter_terms();
 
         // NOTE: Commented out because this causes a timeout on various SubDevices, possibly due
         // to querying 0x1c00 after we enter PRE-OP but I'm unsure. See
@@ -332,8 +619,7 @@ where
         //     .sdo_read::<u8>(SM_TYPE_ADDRESS, SubIndex::Index(0))
         //     .await?;
 
-        let start_offset = *global_offset;
-        // let mut total_bit_len = 0;
+        let mut bit_lens = SmBitLens::new();
 
         for (sync_manager_index, sm_type) in self
             .state
@@ -429,100 +715,24 @@ where
             fmt::trace!(
                 "----= total SM bit length {} ({} bytes)",
                 sm_bit_len,
-                (sm_bit_len + 7) / 8
+                sm_bit_len.div_ceil(8)
             );
 
-            let sm_config = self
-                .write_sm_config(sync_manager_index, sync_manager, (sm_bit_len + 7) / 8)
-                .await?;
-
-            if sm_bit_len > 0 {
-                let fmmu_index = fmmu_usage
-                    .iter()
-                    .position(|usage| *usage == desired_fmmu_type)
-                    .ok_or(Error::NotFound {
-                        item: Item::Fmmu,
-                        index: None,
-                    })?;
-
-                self.write_fmmu_config(
-                    sm_bit_len,
-                    fmmu_index,
-                    global_offset,
-                    desired_sm_type,
-                    &sm_config,
-                )
-                .await?;
-            }
-
-            // total_bit_len += sm_bit_len;
+            bit_lens
+                .push((sync_manager_index, *sync_manager, sm_bit_len))
+                .map_err(|_| Error::Capacity(Item::SyncManager))?;
         }
 
-        Ok(PdiSegment {
-            // bit_len: total_bit_len.into(),
-            bytes: start_offset.up_to(*global_offset),
-        })
+        Ok(bit_lens)
     }
 
-    async fn write_fmmu_config(
-        &self,
-        sm_bit_len: u16,
-        fmmu_index: usize,
-        global_offset: &mut PdiOffset,
-        desired_sm_type: SyncManagerType,
-        sm_config: &SyncManagerChannel,
-    ) -> Result<(), Error> {
-        // Multiple SMs may use the same FMMU, so we'll read the existing config from the SubDevice
-        let mut fmmu_config = self
-            .read(RegisterAddress::fmmu(fmmu_index as u8))
-            .receive::<Fmmu>(self.maindevice)
-            .await?;
-
-        // We can use the enable flag as a sentinel for existing config because EtherCrab inits
-        // FMMUs to all zeroes on startup.
-        let fmmu_config = if fmmu_config.enable {
-            fmmu_config.length_bytes += sm_config.length_bytes;
-
-            fmmu_config
-        } else {
-            Fmmu {
-                logical_start_address: global_offset.start_address,
-                length_bytes: sm_config.length_bytes,
-                // Mapping into PDI is byte-aligned until/if we support bit-oriented SubDevices
-                logical_start_bit: 0,
-                // Always byte-aligned
-                logical_end_bit: 7,
-                physical_start_address: sm_config.physical_start_address,
-                physical_start_bit: 0x0,
-                read_enable: desired_sm_type == SyncManagerType::ProcessDataRead,
-                write_enable: desired_sm_type == SyncManagerType::ProcessDataWrite,
-                enable: true,
-            }
-        };
-
-        self.write(RegisterAddress::fmmu(fmmu_index as u8))
-            .send(self.maindevice, &fmmu_config)
-            .await?;
-
-        fmt::debug!(
-            "SubDevice {:#06x} FMMU{}: {}",
-            self.configured_address,
-            fmmu_index,
-            fmmu_config
-        );
-
-        *global_offset = global_offset.increment_byte_aligned(sm_bit_len);
-
-        Ok(())
-    }
-
-    /// Configure PDOs from EEPROM
-    async fn configure_pdos_eeprom(
+    /// Sum the lengths of the PDOs assigned to each process data SM in the given direction from
+    /// the EEPROM.
+    async fn eeprom_pdo_bit_lens(
         &self,
         sync_managers: &[SyncManager],
         direction: PdoDirection,
-        offset: &mut PdiOffset,
-    ) -> Result<PdiSegment, Error> {
+    ) -> Result<SmBitLens, Error> {
         let eeprom = self.eeprom();
 
         let pdos = match direction {
@@ -542,13 +752,10 @@ where
             }
         };
 
-        let fmmu_sm_mappings = eeprom.fmmu_mappings().await?;
-
-        let start_offset = *offset;
-        // let mut total_bit_len = 0;
-
         let (sm_type, _fmmu_type) = direction.filter_terms();
 
+        let mut bit_lens = SmBitLens::new();
+
         for (sync_manager_index, sync_manager) in sync_managers
             .iter()
             .enumerate()
@@ -560,44 +767,14 @@ where
                 .iter()
                 .filter(|pdo| pdo.sync_manager == sync_manager_index)
                 .map(|pdo| pdo.bit_len)
-                .sum();
-
-            // total_bit_len += bit_len;
-
-            // Look for FMMU index using FMMU_EX section in EEPROM. If it's empty, default
-            // to looking through FMMU usage list and picking out the appropriate kind
-            // (Inputs, Outputs)
-            let fmmu_index = fmmu_sm_mappings
-                .iter()
-                .find(|fmmu| fmmu.sync_manager == sync_manager_index)
-                .map(|fmmu| fmmu.sync_manager)
-                .unwrap_or_else(|| {
-                    fmt::trace!(
-                        "Could not find FMMU for PDO SM{} in EEPROM, using SM index to pick FMMU instead",
-                        sync_manager_index,
-                    );
-
-                    sync_manager_index
-                });
+                .sum::<u16>();
 
-            let sm_config = self
-                .write_sm_config(sync_manager_index, sync_manager, (bit_len + 7) / 8)
-                .await?;
-
-            self.write_fmmu_config(
-                bit_len,
-                usize::from(fmmu_index),
-                offset,
-                sm_type,
-                &sm_config,
-            )
-            .await?;
+            bit_lens
+                .push((sync_manager_index, *sync_manager, bit_len))
+                .map_err(|_| Error::Capacity(Item::SyncManager))?;
         }
 
-        Ok(PdiSegment {
-            // bit_len: total_bit_len.into(),
-            bytes: start_offset.up_to(*offset),
-        })
+        Ok(bit_lens)
     }
 }
 
@@ -615,3 +792,176 @@ impl PdoDirection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        MainDevice, MainDeviceConfig, PduStorage, Timeouts,
+        command::{FPRD, FPWR},
+        fake_network::{FakeNetwork, FakePdu},
+    };
+    use core::cell::Cell;
+
+    #[test]
+    fn sii_summary_saves_reads() {
+        crate::test_logger();
+
+        for eeprom in [
+            &include_bytes!("../../dumps/eeprom/akd.hex")[..],
+            &include_bytes!("../../dumps/eeprom/el2828.hex")[..],
+        ] {
+            let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+            let cached =
+                MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+            let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+            let (mut uncached_tx, mut uncached_rx, pdu_loop) = storage.try_split().unwrap();
+            let uncached = MainDevice::new(
+                pdu_loop,
+                Timeouts::default(),
+                MainDeviceConfig {
+                    cache_sii_config: false,
+                    ..MainDeviceConfig::default()
+                },
+            );
+
+            let sii_control = u16::from(RegisterAddress::SiiControl);
+
+            let mut registers = vec![0u8; 0x1000];
+
+            // INIT
+            registers[0x0130] = 0x01;
+            // 8 byte EEPROM reads
+            registers[0x0502] = 0x40;
+
+            // Number of 8 byte chunks read from the EEPROM
+            let reads = Cell::new(0);
+
+            let mut esc = |pdu: &mut FakePdu<'_>| {
+                match pdu.command {
+                    FPRD => pdu.read_from(&registers),
+                    FPWR if pdu.ado == sii_control && pdu.data[1] & 0x01 != 0 => {
+                        let start = usize::from(u16::from_le_bytes([pdu.data[2], pdu.data[3]])) * 2;
+
+                        registers[0x0508..0x0510].copy_from_slice(&eeprom[start..start + 8]);
+
+                        reads.set(reads.get() + 1);
+                    }
+                    FPWR => pdu.write_to(&mut registers),
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+
+                1
+            };
+
+            let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+            let mut subdevice = network
+                .run(SubDevice::new(&cached, 0, 0x1000), &mut esc)
+                .expect("discovery");
+            let discovery = reads.replace(0);
+
+            assert!(subdevice.sii_summary.complete);
+
+            // Mailbox and FMMU configuration both use the summary kept by discovery
+            let mut subdevice_ref = SubDeviceRef::new(&cached, 0x1000, &mut subdevice);
+
+            let (mailbox_stage, fmmu_stage) = network.run(
+                async {
+                    (
+                        subdevice_ref.mailbox_sii().await,
+                        subdevice_ref.fmmu_sii().await,
+                    )
+                },
+                &mut esc,
+            );
+
+            assert_eq!(mailbox_stage, fmmu_stage);
+            assert_eq!(reads.get(), 0);
+
+            let summary = mailbox_stage.expect("summary");
+
+            // If discovery couldn't read the summary, configuration reads it once
+            subdevice.sii_summary = SiiConfigSummary::default();
+
+            let mut subdevice_ref = SubDeviceRef::new(&cached, 0x1000, &mut subdevice);
+
+            let (mailbox_stage, fmmu_stage) = network.run(
+                async {
+                    (
+                        subdevice_ref.mailbox_sii().await,
+                        subdevice_ref.fmmu_sii().await,
+                    )
+                },
+                &mut esc,
+            );
+
+            assert_eq!(mailbox_stage.as_ref(), Ok(&summary));
+            assert_eq!(fmmu_stage.as_ref(), Ok(&summary));
+
+            let fallback = reads.replace(0);
+
+            // Reads made by each part of the summary on its own
+            let subdevice_ref = SubDeviceRef::new(&cached, 0x1000, &subdevice);
+            let e = subdevice_ref.eeprom();
+
+            let general = network.run(e.general(), &mut esc).expect("general");
+            let general_reads = reads.replace(0);
+
+            network.run(e.sync_managers(), &mut esc).expect("SMs");
+            let sm_reads = reads.replace(0);
+
+            network
+                .run(e.config_summary(&general), &mut esc)
+                .expect("summary");
+            let summary_reads = reads.replace(0);
+
+            assert_eq!(fallback, general_reads + summary_reads);
+
+            // Without the cache, discovery doesn't read the summary and each configuration stage
+            // searches the EEPROM itself.
+            let mut network = FakeNetwork::new(&mut uncached_tx, &mut uncached_rx);
+
+            let mut subdevice = network
+                .run(SubDevice::new(&uncached, 0, 0x1000), &mut esc)
+                .expect("uncached discovery");
+            let uncached_discovery = reads.replace(0);
+
+            assert!(!subdevice.sii_summary.complete);
+            assert_eq!(uncached_discovery, discovery - summary_reads);
+
+            let mut subdevice_ref = SubDeviceRef::new(&uncached, 0x1000, &mut subdevice);
+
+            let (mailbox_stage, fmmu_stage) = network.run(
+                async {
+                    (
+                        subdevice_ref.mailbox_sii().await,
+                        subdevice_ref.fmmu_sii().await,
+                    )
+                },
+                &mut esc,
+            );
+            let separate = reads.replace(0);
+
+            let (mailbox_stage, fmmu_stage) = (
+                mailbox_stage.expect("mailbox stage"),
+                fmmu_stage.expect("FMMU stage"),
+            );
+
+            assert_eq!(summary.mailbox, mailbox_stage.mailbox);
+            assert_eq!(summary.complete_access, mailbox_stage.complete_access);
+            assert_eq!(summary.sync_managers, mailbox_stage.sync_managers);
+            assert_eq!(summary.sync_managers, fmmu_stage.sync_managers);
+            assert_eq!(summary.fmmus, fmmu_stage.fmmus);
+
+            // Caching saves one search for the General category and one read of the SM category
+            // overall.
+            assert_eq!(
+                uncached_discovery + separate - discovery,
+                general_reads + sm_reads
+            );
+        }
+    }
+}
@@ -35,3 +35,155 @@ impl fmt::Display for DcSync {
         }
     }
 }
+
+/// Synchronisation capabilities of a SubDevice, read from the CoE SyncManager parameter objects
+/// `0x1C32` (outputs) and `0x1C33` (inputs).
+///
+/// Read with [`SubDeviceRef::sync_capabilities`](crate::SubDeviceRef::sync_capabilities).
+///
+/// Defined in ETG1020 section 21.1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SyncCapabilities {
+    /// Synchronisation modes supported by the SubDevice (`0x1C32:04`).
+    pub sync_modes: SyncModes,
+
+    /// The shortest cycle time the SubDevice supports. This is the larger of `0x1C32:05` and
+    /// `0x1C33:05`.
+    pub min_cycle_time: Duration,
+
+    /// The time the SubDevice needs to copy output data from the SyncManager and make it valid on
+    /// its outputs (`0x1C32:06`).
+    pub output_calc_and_copy_time: Duration,
+
+    /// The time the SubDevice needs to latch its inputs and copy them into the SyncManager
+    /// (`0x1C33:06`), or `None` if the SubDevice doesn't have object `0x1C33`.
+    pub input_calc_and_copy_time: Option<Duration>,
+}
+
+/// Synchronisation modes supported by a SubDevice (`0x1C32:04`).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, ethercrab_wire::EtherCrabWireRead)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[wire(bytes = 2)]
+pub struct SyncModes {
+    /// Free run, where the SubDevice runs on its own local timer.
+    #[wire(bits = 1)]
+    pub free_run: bool,
+    /// Synchronised with the SyncManager event raised when process data is received.
+    #[wire(bits = 1)]
+    pub sm_synchronous: bool,
+    /// Synchronised with the DC SYNC0 event.
+    #[wire(bits = 1)]
+    pub dc_sync0: bool,
+    /// Synchronised with the DC SYNC1 event.
+    #[wire(bits = 1)]
+    pub dc_sync1: bool,
+    /// Synchronised with a DC SYNC event derived from another SubDevice's SyncManager event.
+    #[wire(bits = 1, post_skip = 11)]
+    pub dc_subordinate: bool,
+}
+
+/// Distributed Clock control loop parameters (`0x0930` to `0x0935`).
+///
+/// These configure the PI loop the ESC uses to discipline its local clock to the DC system time.
+/// EtherCrab writes the [`Default`] values to all SubDevices during reset, as recommended by
+/// ETG1020 section 22.2.4: a [`speed_counter_start`](DcControlLoopParams::speed_counter_start) of
+/// `0x1000` and a [`speed_counter_filter_depth`](DcControlLoopParams::speed_counter_filter_depth)
+/// of `12`. Other values can be written to a single SubDevice with
+/// [`SubDeviceRef::configure_dc_control_loop`](crate::SubDeviceRef::configure_dc_control_loop).
+///
+/// Defined in ETG1000.4 Table 60 – Distributed clock local time parameter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ethercrab_wire::EtherCrabWireRead)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[wire(bytes = 6)]
+pub struct DcControlLoopParams {
+    /// Bandwidth of the drift compensation. Control loop parameter 1.
+    ///
+    /// Writing this register resets the control loop.
+    #[wire(bytes = 2)]
+    pub speed_counter_start: u16,
+    /// Difference between the speed counter start value and the current speed counter value,
+    /// i.e. how far the local clock is being sped up or slowed down. Control loop parameter 2.
+    #[wire(bytes = 2)]
+    pub speed_counter_diff: i16,
+    /// Depth of the system time difference filter, as a power of two. Control loop parameter 3.
+    #[wire(bits = 4, post_skip = 4)]
+    pub system_time_difference_filter_depth: u8,
+    /// Depth of the speed counter filter, as a power of two. Control loop parameter 3.
+    #[wire(bits = 4, post_skip = 4)]
+    pub speed_counter_filter_depth: u8,
+}
+
+impl Default for DcControlLoopParams {
+    fn default() -> Self {
+        Self {
+            speed_counter_start: 0x1000,
+            speed_counter_diff: 0,
+            system_time_difference_filter_depth: 0,
+            speed_counter_filter_depth: 12,
+        }
+    }
+}
+
+impl DcControlLoopParams {
+    /// The raw value of control loop parameter 1 (`0x0930`).
+    pub(crate) fn param1(&self) -> u16 {
+        self.speed_counter_start
+    }
+
+    /// The raw value of control loop parameter 3 (`0x0934`), holding both filter depths.
+    pub(crate) fn param3(&self) -> u16 {
+        u16::from_le_bytes([
+            self.system_time_difference_filter_depth & 0x0f,
+            self.speed_counter_filter_depth & 0x0f,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethercrab_wire::EtherCrabWireRead;
+
+    #[test]
+    fn decode_reset_defaults() {
+        // Param 1 as written during reset, no drift, param 3 as written during reset
+        let raw = [0x00, 0x10, 0x00, 0x00, 0x00, 0x0c];
+
+        assert_eq!(
+            DcControlLoopParams::unpack_from_slice(&raw),
+            Ok(DcControlLoopParams {
+                speed_counter_start: 0x1000,
+                speed_counter_diff: 0,
+                system_time_difference_filter_depth: 0,
+                speed_counter_filter_depth: 12,
+            })
+        );
+    }
+
+    #[test]
+    fn default_params_match_reset_values() {
+        let params = DcControlLoopParams::default();
+
+        assert_eq!(params.param1(), 0x1000);
+        assert_eq!(params.param3(), 0x0c00);
+
+        // Round trip through the raw register layout
+        let mut raw = [0u8; 6];
+
+        raw[0..2].copy_from_slice(&params.param1().to_le_bytes());
+        raw[4..6].copy_from_slice(&params.param3().to_le_bytes());
+
+        assert_eq!(DcControlLoopParams::unpack_from_slice(&raw), Ok(params));
+    }
+
+    #[test]
+    fn decode_negative_drift() {
+        let raw = [0x00, 0x10, 0xfe, 0xff, 0x04, 0x0c];
+
+        let params = DcControlLoopParams::unpack_from_slice(&raw).expect("Unpack");
+
+        assert_eq!(params.speed_counter_diff, -2);
+        assert_eq!(params.system_time_difference_filter_depth, 4);
+    }
+}
@@ -1,41 +1,154 @@
 use crate::{
     eeprom::{
-        CHECKSUM_POSITION, EepromDataProvider, EepromRange, STATION_ALIAS_CRC,
-        STATION_ALIAS_POSITION,
+        CHECKSUM_POSITION, EepromDataProvider, EepromRange, STATION_ALIAS_POSITION,
         device_provider::SII_FIRST_CATEGORY_START,
+        read_identity, sii_checksum,
         types::{
-            CategoryType, DefaultMailbox, FmmuEx, FmmuUsage, Pdo, PdoEntry, PdoType, SiiGeneral,
-            SyncManager,
+            CategoryType, CoeDetails, DefaultMailbox, FmmuEx, FmmuUsage, Pdo, PdoEntry, PdoType,
+            SiiGeneral, SyncManager,
         },
+        validate_sii_header,
     },
     error::{EepromError, Error, IgnoreNoCategory, Item},
     fmt,
-    subdevice::SubDeviceIdentity,
+    subdevice::{SubDeviceIdentity, types::SiiConfigSummary},
+};
+use core::{
+    borrow::Borrow,
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
 };
-use core::marker::PhantomData;
 use embedded_io_async::{Read, ReadExactError, Write};
 use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireReadSized, EtherCrabWireSized};
 
-pub struct SubDeviceEeprom<P> {
+pub struct SubDeviceEeprom<P, S = AtomicUsize> {
     provider: P,
+
+    /// EEPROM size in bytes, read from the SII the first time it's needed. Zero if not read yet.
+    ///
+    /// Borrowed from the [`SubDevice`](crate::SubDevice) when accessed through a
+    /// [`SubDeviceRef`](crate::SubDeviceRef), so the size is only ever read once per device.
+    size: S,
 }
 
-/// EEPROM methods.
 impl<P> SubDeviceEeprom<P>
 where
     P: EepromDataProvider,
 {
     pub(crate) fn new(provider: P) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            size: AtomicUsize::new(0),
+        }
     }
+}
 
+impl<'size, P> SubDeviceEeprom<P, &'size AtomicUsize>
+where
+    P: EepromDataProvider,
+{
+    /// Create an EEPROM accessor that stores the EEPROM size in `size` once it's read, and uses it
+    /// from there afterwards.
+    pub(crate) fn with_shared_size(provider: P, size: &'size AtomicUsize) -> Self {
+        Self { provider, size }
+    }
+}
+
+/// EEPROM methods.
+impl<P, S> SubDeviceEeprom<P, S>
+where
+    P: EepromDataProvider,
+    S: Borrow<AtomicUsize>,
+{
     /// Start a reader at the given address in words, returning at most `len` bytes.
+    ///
+    /// The range is not checked against the EEPROM size, so this should only be used for the fixed
+    /// fields at the start of the SII, which every EEPROM holds.
     pub(crate) fn start_at(&self, word_addr: u16, len_bytes: u16) -> EepromRange<P> {
         EepromRange::new(self.provider.clone(), word_addr, len_bytes / 2)
     }
 
+    /// Start a reader at the given address in words, returning at most `len` bytes.
+    ///
+    /// Returns [`EepromError::PastEnd`] if any part of the range lies past the end of the EEPROM.
+    pub(crate) async fn start_at_checked(
+        &self,
+        word_addr: u16,
+        len_bytes: u16,
+    ) -> Result<EepromRange<P>, Error> {
+        self.check_range(word_addr, len_bytes.div_ceil(2)).await?;
+
+        Ok(self.start_at(word_addr, len_bytes))
+    }
+
+    /// Read a single word from the given word address.
+    pub(crate) async fn read_word(&self, word_addr: u16) -> Result<u16, Error> {
+        self.check_range(word_addr, 1).await?;
+
+        let mut provider = self.provider.clone();
+
+        let chunk = provider.read_chunk(word_addr).await?;
+
+        let (word, _rest) = chunk
+            .split_first_chunk::<2>()
+            .ok_or(Error::Eeprom(EepromError::SectionUnderrun))?;
+
+        Ok(u16::from_le_bytes(*word))
+    }
+
+    /// Write a single word to the given word address.
+    pub(crate) async fn write_word(&self, word_addr: u16, value: u16) -> Result<(), Error> {
+        self.check_range(word_addr, 1).await?;
+
+        self.provider
+            .clone()
+            .write_word(word_addr, value.to_le_bytes())
+            .await
+    }
+
+    /// Check that the given range of words lies within the EEPROM, reading the EEPROM size if it
+    /// isn't known yet.
+    async fn check_range(&self, start_word: u16, len_words: u16) -> Result<(), Error> {
+        let size = self.size().await?;
+
+        self.check_range_within(start_word, len_words, size).await
+    }
+
+    /// Check that the given range of words lies within an EEPROM of `size` bytes.
+    async fn check_range_within(
+        &self,
+        start_word: u16,
+        len_words: u16,
+        size: usize,
+    ) -> Result<(), Error> {
+        let start = usize::from(start_word) * 2;
+        let end = start + usize::from(len_words) * 2;
+
+        if end <= size {
+            return Ok(());
+        }
+
+        fmt::error!(
+            "EEPROM range {:#06x}..{:#06x} is past the end of the {} byte EEPROM",
+            start,
+            end,
+            size
+        );
+
+        // Some ESCs latch error flags after an out of range access, which then fail every later
+        // access until cleared. Clear any left over from an earlier access so they don't hide
+        // this error or poison the next read. Failure is ignored to keep the more useful error.
+        let _ = self.provider.clear_errors().await;
+
+        Err(Error::Eeprom(EepromError::PastEnd))
+    }
+
     /// Search for a given category and return a reader over the bytes contained within the category
     /// if it is found.
+    ///
+    /// The search is bounded by the EEPROM size if it is already known, which it is for every
+    /// SubDevice once discovery has read it. It isn't read here, so a search costs no more EEPROM
+    /// accesses than walking the categories themselves.
     async fn category(&self, category: CategoryType) -> Result<Option<EepromRange<P>>, Error> {
         let mut reader = self.provider.clone();
 
@@ -44,6 +157,21 @@ where
         let mut num_empty_categories = 0u8;
 
         loop {
+            // Categories are chained by length, so a corrupt length can point past the end of the
+            // EEPROM. Stop searching instead of reading whatever the device returns there.
+            if self
+                .known_size()
+                .is_some_and(|size| (usize::from(word_addr) + 2) * 2 > size)
+            {
+                fmt::warn!(
+                    "Reached end of EEPROM at {:#06x} without finding category {:?}",
+                    usize::from(word_addr) * 2,
+                    category
+                );
+
+                break Ok(None);
+            }
+
             let chunk = reader.read_chunk(word_addr).await?;
 
             let Some(incr) = word_addr.checked_add(2) else {
@@ -87,6 +215,10 @@ where
 
             match category_type {
                 cat if cat == category => {
+                    if let Some(size) = self.known_size() {
+                        self.check_range_within(word_addr, len_words, size).await?;
+                    }
+
                     break Ok(Some(EepromRange::new(
                         self.provider.clone(),
                         word_addr,
@@ -98,7 +230,9 @@ where
             }
 
             // Next category starts after the current category's data. This is a WORD address.
-            word_addr += len_words;
+            // Saturating so a corrupt length is caught by the end of EEPROM check above, or by the
+            // overflow check if the size isn't known.
+            word_addr = word_addr.saturating_add(len_words);
         }
     }
 
@@ -125,7 +259,21 @@ where
     }
 
     /// Set the configured station alias for the device.
-    pub(crate) async fn set_station_alias(&self, new_alias: u16) -> Result<(), Error> {
+    ///
+    /// If `require_valid_existing_crc` is `true`, the checksum currently stored in the EEPROM is
+    /// verified first and nothing is written if it doesn't match, as recomputing it would hide
+    /// corruption elsewhere in the first 7 words.
+    pub(crate) async fn set_station_alias(
+        &self,
+        new_alias: u16,
+        require_valid_existing_crc: bool,
+    ) -> Result<(), Error> {
+        if require_valid_existing_crc && !self.checksum_valid().await? {
+            fmt::error!("Existing EEPROM checksum is invalid, refusing to set station alias");
+
+            return Err(Error::Eeprom(EepromError::InvalidChecksum));
+        }
+
         let new_checksum = {
             // Read first 14 bytes of EEPROM
             let mut reader = self.start_at(0x0000, 14);
@@ -137,7 +285,7 @@ where
 
             chunk[STATION_ALIAS_POSITION].copy_from_slice(&new_alias.to_le_bytes());
 
-            u16::from(STATION_ALIAS_CRC.checksum(&chunk))
+            sii_checksum(&chunk)
         };
 
         fmt::debug!(
@@ -160,6 +308,21 @@ where
         Ok(())
     }
 
+    /// Check the checksum stored in word 7 of the EEPROM against the first 7 words.
+    pub(crate) async fn checksum_valid(&self) -> Result<bool, Error> {
+        let mut reader = self.start_at(0x0000, CHECKSUM_POSITION.end as u16);
+
+        let mut buf = [0u8; CHECKSUM_POSITION.end];
+
+        reader.read_exact(&mut buf).await?;
+
+        match validate_sii_header(&buf) {
+            Ok(_) => Ok(true),
+            Err(EepromError::InvalidChecksum) => Ok(false),
+            Err(e) => Err(Error::Eeprom(e)),
+        }
+    }
+
     /// Get the device name.
     ///
     /// This is the `OrderIdx` field as described in ETG2010 Table 7.
@@ -183,7 +346,13 @@ where
     }
 
     /// Get the EEPROM size in bytes.
+    ///
+    /// The size is only read from the device once.
     pub(crate) async fn size(&self) -> Result<usize, Error> {
+        if let Some(size) = self.known_size() {
+            return Ok(size);
+        }
+
         let mut buf = u16::buffer();
 
         // ETG2010 page 7: 0x003e is the EEPROM address size register in kilobit minus 1 (u16).
@@ -191,9 +360,19 @@ where
 
         reader.read_exact(&mut buf).await?;
 
-        let len = (u16::from_le_bytes(buf) + 1) * 128;
+        // Computed as a `usize` as EEPROMs larger than 512 kbit would overflow a `u16` byte count
+        let kbit = usize::from(u16::from_le_bytes(buf)) + 1;
+
+        let size = kbit * 128;
+
+        self.size.borrow().store(size, Ordering::Relaxed);
 
-        Ok(usize::from(len))
+        Ok(size)
+    }
+
+    /// Get the EEPROM size in bytes if it has already been read.
+    fn known_size(&self) -> Option<usize> {
+        Some(self.size.borrow().load(Ordering::Relaxed)).filter(|size| *size > 0)
     }
 
     /// Get the long name of the device.
@@ -224,6 +403,34 @@ where
         Ok(DefaultMailbox::unpack_from_slice(&buf)?)
     }
 
+    /// Read the SII items needed to configure the SubDevice's mailboxes and FMMUs.
+    ///
+    /// The General category is passed in as it is also needed to find the device name, so has
+    /// usually already been read.
+    pub(crate) async fn config_summary(
+        &self,
+        general: &SiiGeneral,
+    ) -> Result<SiiConfigSummary, Error> {
+        let mailbox = self
+            .mailbox_config()
+            .await
+            .ignore_no_category()?
+            .unwrap_or_default();
+
+        let sync_managers = self.sync_managers().await?;
+        let fmmus = self.fmmus().await?;
+
+        Ok(SiiConfigSummary {
+            mailbox,
+            complete_access: general
+                .coe_details
+                .contains(CoeDetails::ENABLE_COMPLETE_ACCESS),
+            sync_managers,
+            fmmus,
+            complete: true,
+        })
+    }
+
     pub(crate) async fn general(&self) -> Result<SiiGeneral, Error> {
         let mut reader = self
             .category(CategoryType::General)
@@ -238,15 +445,7 @@ where
     }
 
     pub(crate) async fn identity(&self) -> Result<SubDeviceIdentity, Error> {
-        let mut reader = self.start_at(0x0008, SubDeviceIdentity::PACKED_LEN as u16);
-
-        fmt::trace!("Get identity");
-
-        let mut buf = SubDeviceIdentity::buffer();
-
-        reader.read_exact(&mut buf).await?;
-
-        Ok(SubDeviceIdentity::unpack_from_slice(&buf)?)
+        read_identity(self.provider.clone()).await
     }
 
     pub(crate) async fn sync_managers(&self) -> Result<heapless::Vec<SyncManager, 8>, Error> {
@@ -523,7 +722,7 @@ mod tests {
     use super::*;
     use crate::{
         eeprom::{
-            file_provider::EepromFile,
+            file_provider::{CountingProvider, EepromFile},
             types::{
                 CoeDetails, Flags, MailboxProtocols, PortStatus, PortStatuses, SyncManagerEnable,
                 SyncManagerType,
@@ -886,6 +1085,97 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn past_end_range() {
+        crate::test_logger();
+
+        // Size word at 0x3e is zero, i.e. a 1 kbit EEPROM
+        static SMALL: [u8; 128] = [0u8; 128];
+
+        let provider = CountingProvider::new(EepromFile::new(&SMALL));
+        let e = SubDeviceEeprom::new(provider.clone());
+
+        assert_eq!(e.size().await, Ok(128));
+
+        // Last 8 bytes
+        let mut reader = e.start_at_checked(0x003c, 8).await.expect("In range");
+        let mut buf = [0xffu8; 8];
+        reader.read_exact(&mut buf).await.expect("Read");
+        assert_eq!(buf, [0u8; 8]);
+
+        let clears = provider.clears();
+
+        assert!(matches!(
+            e.start_at_checked(0x003e, 8).await,
+            Err(Error::Eeprom(EepromError::PastEnd))
+        ));
+        assert!(matches!(
+            e.start_at_checked(u16::MAX, 2).await,
+            Err(Error::Eeprom(EepromError::PastEnd))
+        ));
+
+        // Each out of range access clears any latched errors
+        assert_eq!(provider.clears(), clears + 2);
+
+        let reads = provider.reads();
+
+        // Size is only read once
+        assert_eq!(e.size().await, Ok(128));
+        assert_eq!(provider.reads(), reads);
+
+        // First category header would be past the end, so the search stops instead of reading
+        assert!(matches!(e.category(CategoryType::General).await, Ok(None)));
+        assert_eq!(provider.reads(), reads);
+    }
+
+    #[tokio::test]
+    async fn past_end_category() {
+        crate::test_logger();
+
+        // 2 kbit EEPROM
+        let mut image = vec![0u8; 256];
+        image[0x7c..0x7e].copy_from_slice(&1u16.to_le_bytes());
+
+        // Strings category that fits, followed by a General category claiming 0x100 words
+        image[0x80..0x84].copy_from_slice(&[0x0a, 0x00, 0x02, 0x00]);
+        image[0x88..0x8c].copy_from_slice(&[0x1e, 0x00, 0x00, 0x01]);
+
+        let image: &'static [u8] = image.leak();
+
+        let provider = CountingProvider::new(EepromFile::new(image));
+        let e = SubDeviceEeprom::new(provider.clone());
+
+        // The size isn't read by a search, so only the two category headers are read
+        assert!(e.category(CategoryType::General).await.unwrap().is_some());
+        assert_eq!(provider.reads(), 2);
+
+        // Once the size is known, searches are checked against it
+        assert_eq!(e.size().await, Ok(256));
+        assert!(e.category(CategoryType::Strings).await.unwrap().is_some());
+
+        let clears = provider.clears();
+
+        assert!(matches!(
+            e.category(CategoryType::General).await,
+            Err(Error::Eeprom(EepromError::PastEnd))
+        ));
+        assert_eq!(provider.clears(), clears + 1);
+
+        // A shared size is stored the first time it's read, and used from then on
+        let size = AtomicUsize::new(0);
+        let provider = CountingProvider::new(EepromFile::new(image));
+
+        let e = SubDeviceEeprom::with_shared_size(provider.clone(), &size);
+        assert_eq!(e.size().await, Ok(256));
+        assert_eq!(size.load(Ordering::Relaxed), 256);
+
+        let reads = provider.reads();
+
+        let e = SubDeviceEeprom::with_shared_size(provider.clone(), &size);
+        assert_eq!(e.size().await, Ok(256));
+        assert_eq!(provider.reads(), reads);
+    }
+
     #[tokio::test]
     async fn get_fmmu_usage() {
         assert_eq!(
@@ -1103,13 +1393,69 @@ mod tests {
 
         assert_eq!(e.station_alias().await, Ok(0));
 
-        e.set_station_alias(0xabcd).await.expect("set alias");
+        e.set_station_alias(0xabcd, true).await.expect("set alias");
 
         // TODO: Current file test harness loses written data when calling `start_at` because it's
         // cloned, so we can't do a proper assertion. This would be nice to fix in the future. At
         // the moment the test in `eeprom::mod::write_station_alias` covers this case.
     }
 
+    #[tokio::test]
+    async fn set_alias_corrupted_checksum() {
+        crate::test_logger();
+
+        let corrupted = include_bytes!("../../dumps/eeprom/el2262.bin")
+            .to_vec()
+            .leak();
+
+        // Corrupt the reserved bytes after the station alias without updating the checksum
+        corrupted[STATION_ALIAS_POSITION.end] ^= 0x01;
+
+        let e = SubDeviceEeprom::new(EepromFile::new(corrupted));
+
+        assert_eq!(
+            e.set_station_alias(0xabcd, true).await,
+            Err(Error::Eeprom(EepromError::InvalidChecksum))
+        );
+
+        // Writing is still possible if the caller doesn't care about the existing checksum
+        assert_eq!(e.set_station_alias(0xabcd, false).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn single_word_access() {
+        crate::test_logger();
+
+        let provider = CountingProvider::new(EepromFile::new(include_bytes!(
+            "../../dumps/eeprom/el2262.bin"
+        )));
+        let e = SubDeviceEeprom::new(provider.clone());
+
+        // Vendor ID and product ID low word
+        assert_eq!(e.read_word(0x0008).await, Ok(0x0002));
+        assert_eq!(e.read_word(0x000a).await, Ok(0x3052));
+        // Near the end of the EEPROM. The file provider can't return a 2 byte chunk for the very
+        // last word.
+        assert_eq!(e.read_word(0x03fe).await, Ok(0xffff));
+
+        assert_eq!(
+            e.read_word(0x0400).await,
+            Err(Error::Eeprom(EepromError::PastEnd))
+        );
+
+        e.write_word(0x0004, 0xabcd).await.expect("write word");
+
+        assert_eq!(provider.writes(), [(0x0004, [0xcd, 0xab])]);
+
+        assert_eq!(
+            e.write_word(0x0400, 0xabcd).await,
+            Err(Error::Eeprom(EepromError::PastEnd))
+        );
+
+        // Nothing written past the end
+        assert_eq!(provider.writes().len(), 1);
+    }
+
     #[tokio::test]
     async fn get_size_bytes() {
         crate::test_logger();
@@ -1128,4 +1474,68 @@ mod tests {
 
         assert_eq!(e.size().await, Ok(2048));
     }
+
+    #[tokio::test]
+    async fn bundled_dump_sizes() {
+        crate::test_logger();
+
+        for (name, image, expected) in [
+            (
+                "akd",
+                &include_bytes!("../../dumps/eeprom/akd.hex")[..],
+                2048,
+            ),
+            (
+                "akd_null_strings",
+                &include_bytes!("../../dumps/eeprom/akd_null_strings.hex")[..],
+                2048,
+            ),
+            (
+                "ek1100",
+                &include_bytes!("../../dumps/eeprom/ek1100.hex")[..],
+                2048,
+            ),
+            (
+                "el2262",
+                &include_bytes!("../../dumps/eeprom/el2262.bin")[..],
+                2048,
+            ),
+            (
+                "hbm_clipx",
+                &include_bytes!("../../dumps/eeprom/hbm_clipx_eeprom_dump.bin")[..],
+                4096,
+            ),
+        ] {
+            let e = SubDeviceEeprom::new(EepromFile::new(image));
+
+            let size = e.size().await.expect(name);
+
+            assert_eq!(size, expected, "{}", name);
+
+            // Every dump must fit in the EEPROM it was read from
+            assert!(image.len() <= size, "{} dump larger than EEPROM", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn checksum() {
+        crate::test_logger();
+
+        let e = SubDeviceEeprom::new(EepromFile::new(include_bytes!(
+            "../../dumps/eeprom/el2889.hex"
+        )));
+
+        assert_eq!(e.checksum_valid().await, Ok(true));
+
+        let corrupted = include_bytes!("../../dumps/eeprom/el2262.bin")
+            .to_vec()
+            .leak();
+
+        // Flip a bit in the station alias without updating the checksum
+        corrupted[STATION_ALIAS_POSITION.start] ^= 0x01;
+
+        let e = SubDeviceEeprom::new(EepromFile::new(corrupted));
+
+        assert_eq!(e.checksum_valid().await, Ok(false));
+    }
 }
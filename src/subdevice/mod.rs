@@ -1,12 +1,14 @@
+pub(crate) mod al_status_history;
+mod config_snapshot;
 pub(crate) mod configuration;
 mod dc;
-mod eeprom;
+pub(crate) mod eeprom;
 pub mod pdi;
 pub mod ports;
 mod types;
 
 use crate::{
-    WrappedRead, WrappedWrite,
+    EepromErrorBehaviour, WrappedRead, WrappedWrite,
     al_control::AlControl,
     al_status_code::AlStatusCode,
     coe::{
@@ -14,23 +16,33 @@ use crate::{
         services::CoeServiceRequest,
     },
     command::Command,
+    dl_control::{DlControl, LoopControl, PortLoopState},
     dl_status::DlStatus,
-    eeprom::{device_provider::DeviceEeprom, types::SiiOwner},
-    error::{Error, IgnoreNoCategory, Item, MailboxError, PduError},
+    eeprom::{
+        device_provider::DeviceEeprom,
+        types::{MailboxProtocols, SiiControl, SiiOwner},
+    },
+    error::{DistributedClockError, Error, IgnoreNoCategory, Item, MailboxError, PduError},
+    fmmu::Fmmu,
     fmt,
     mailbox::{MailboxHeader, MailboxType},
     maindevice::MainDevice,
-    pdu_loop::ReceivedPdu,
-    register::{DcSupport, RegisterAddress, SupportFlags},
-    subdevice::{ports::Ports, types::SubDeviceConfig},
+    pdu_loop::HeldPdu,
+    register::{DcSupport, DlInformation, DpramLayout, EscType, RegisterAddress, SupportFlags},
+    subdevice::{
+        ports::Ports,
+        types::{SiiConfigSummary, SubDeviceConfig},
+    },
     subdevice_state::SubDeviceState,
+    sync_manager_channel::SyncManagerChannel,
     timer_factory::IntoTimeout,
 };
 use core::{
     any::type_name,
     fmt::{Debug, Write},
     ops::{Deref, DerefMut},
-    sync::atomic::{AtomicU8, Ordering},
+    sync::atomic::{AtomicU8, AtomicU32, AtomicUsize, Ordering},
+    time::Duration,
 };
 use embedded_io_async::{Read, Write as EioWrite};
 use ethercrab_wire::{
@@ -38,11 +50,16 @@ use ethercrab_wire::{
     EtherCrabWireWrite, EtherCrabWireWriteSized,
 };
 
-pub use self::pdi::SubDevicePdi;
+pub use self::config_snapshot::{DeviceConfigSnapshot, FmmuConfig, SyncManagerConfig};
+pub use self::pdi::{PdiView, SubDevicePdi};
 pub use self::types::IoRanges;
 pub use self::types::SubDeviceIdentity;
-use self::{eeprom::SubDeviceEeprom, types::Mailbox};
-pub use dc::DcSync;
+use self::{
+    al_status_history::{AL_STATUS_HISTORY_LEN, AlStatusHistory},
+    eeprom::SubDeviceEeprom,
+    types::Mailbox,
+};
+pub use dc::{DcControlLoopParams, DcSync, SyncCapabilities, SyncModes};
 
 /// SubDevice device metadata. See [`SubDeviceRef`] for richer behaviour.
 #[doc(alias = "Slave")]
@@ -58,6 +75,12 @@ pub struct SubDevice {
 
     pub(crate) config: SubDeviceConfig,
 
+    /// SII items read during discovery for use during configuration.
+    pub(crate) sii_summary: SiiConfigSummary,
+
+    /// EEPROM size in bytes, stored the first time it's read. Zero if it hasn't been read yet.
+    pub(crate) eeprom_size: AtomicUsize,
+
     pub(crate) identity: SubDeviceIdentity,
 
     // NOTE: Default length in SOEM is 40 bytes
@@ -88,6 +111,15 @@ pub struct SubDevice {
     /// The 1-7 cyclic counter used when working with mailbox requests.
     pub(crate) mailbox_counter: AtomicU8,
 
+    /// The SDO transfer currently in progress, if any. See `SdoTransfer`.
+    pub(crate) sdo_transfer: AtomicU32,
+
+    /// The number of mailbox transactions currently in progress. See `MailboxPermit`.
+    pub(crate) mailbox_transactions: AtomicU8,
+
+    /// AL status codes seen during the last state transition. See `AlStatusHistory`.
+    pub(crate) al_status_history: AlStatusHistory,
+
     /// DC config.
     pub(crate) dc_sync: DcSync,
 }
@@ -101,6 +133,8 @@ impl PartialEq for SubDevice {
         self.configured_address == other.configured_address
             && self.alias_address == other.alias_address
             && self.config == other.config
+            && self.sii_summary == other.sii_summary
+            && self.eeprom_size.load(Ordering::Relaxed) == other.eeprom_size.load(Ordering::Relaxed)
             && self.identity == other.identity
             && self.name == other.name
             && self.dc_support == other.dc_support
@@ -110,7 +144,7 @@ impl PartialEq for SubDevice {
             && self.parent_index == other.parent_index
             && self.propagation_delay == other.propagation_delay
             && self.dc_sync == other.dc_sync
-        // NOTE: No mailbox_counter
+        // NOTE: No mailbox_counter, sdo_transfer, mailbox_transactions or al_status_history
     }
 }
 
@@ -123,6 +157,8 @@ impl Clone for SubDevice {
             configured_address: self.configured_address,
             alias_address: self.alias_address,
             config: self.config.clone(),
+            sii_summary: self.sii_summary.clone(),
+            eeprom_size: AtomicUsize::new(self.eeprom_size.load(Ordering::Relaxed)),
             identity: self.identity,
             name: self.name.clone(),
             dc_support: self.dc_support,
@@ -133,10 +169,115 @@ impl Clone for SubDevice {
             propagation_delay: self.propagation_delay,
             dc_sync: self.dc_sync,
             mailbox_counter: AtomicU8::new(self.mailbox_counter.load(Ordering::Acquire)),
+            sdo_transfer: AtomicU32::new(self.sdo_transfer.load(Ordering::Acquire)),
+            mailbox_transactions: AtomicU8::new(self.mailbox_transactions.load(Ordering::Acquire)),
+            al_status_history: AlStatusHistory::default(),
+        }
+    }
+}
+
+/// A claim on one of a SubDevice's mailbox transaction slots, released when dropped.
+///
+/// Holding the permit in the future driving a transaction means the slot is also released if the
+/// future is cancelled.
+struct MailboxPermit<'a> {
+    transactions: &'a AtomicU8,
+}
+
+impl Drop for MailboxPermit<'_> {
+    fn drop(&mut self) {
+        self.transactions.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Exclusive use of a SubDevice's SDO server, released when dropped.
+///
+/// Dropping the guard without calling [`finish`](SdoTransferGuard::finish) leaves the transfer
+/// recorded as cancelled, so it is aborted before the next transfer starts.
+struct SdoTransferGuard<'a> {
+    transfer: &'a AtomicU32,
+    finished: bool,
+}
+
+impl SdoTransferGuard<'_> {
+    /// Mark the transfer as finished, whether it succeeded or not.
+    fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for SdoTransferGuard<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            self.transfer.store(SdoTransfer::NONE, Ordering::Release);
+        } else {
+            self.transfer
+                .fetch_and(!SdoTransfer::ACTIVE, Ordering::AcqRel);
         }
     }
 }
 
+/// A raw mailbox request with the counter in its mailbox header replaced when packed.
+struct CountedMailboxRequest<'a> {
+    request: &'a [u8],
+    counter: u8,
+}
+
+impl CountedMailboxRequest<'_> {
+    /// Length of the standard mailbox header, without any protocol specific header.
+    const HEADER_LEN: usize = 6;
+
+    /// Offset of the byte holding the mailbox type and counter in the mailbox header.
+    const COUNTER_BYTE: usize = 5;
+
+    /// Get the counter from a raw mailbox header.
+    fn counter(mailbox: &[u8]) -> Option<u8> {
+        mailbox.get(Self::COUNTER_BYTE).map(|b| (b >> 4) & 0x07)
+    }
+}
+
+impl EtherCrabWireWrite for CountedMailboxRequest<'_> {
+    fn pack_to_slice_unchecked<'buf>(&self, buf: &'buf mut [u8]) -> &'buf [u8] {
+        let buf = &mut buf[0..self.request.len()];
+
+        buf.copy_from_slice(self.request);
+
+        buf[Self::COUNTER_BYTE] = (buf[Self::COUNTER_BYTE] & !0x70) | ((self.counter & 0x07) << 4);
+
+        buf
+    }
+
+    fn packed_len(&self) -> usize {
+        self.request.len()
+    }
+}
+
+/// Tracks the SDO transfer in progress on a SubDevice, packed into a `u32` so it can be stored in
+/// an atomic.
+///
+/// A transfer is marked as started before its first request is sent, and cleared when it completes
+/// or fails. If the future driving the transfer is dropped part way through, the mark is left in
+/// place so the next transfer knows it must abort the old one first.
+///
+/// The SubDevice's SDO server only handles one transfer at a time, so the `ACTIVE` bit is held
+/// for as long as a transfer's future is alive, even if more than one mailbox transaction is
+/// allowed at once.
+struct SdoTransfer;
+
+impl SdoTransfer {
+    const NONE: u32 = 0;
+    const IN_PROGRESS: u32 = 1 << 24;
+    const ACTIVE: u32 = 1 << 25;
+
+    fn pack(index: u16, sub_index: u8) -> u32 {
+        Self::IN_PROGRESS | (u32::from(index) << 8) | u32::from(sub_index)
+    }
+
+    fn unpack(raw: u32) -> Option<(u16, u8)> {
+        (raw & Self::IN_PROGRESS > 0).then_some(((raw >> 8) as u16, raw as u8))
+    }
+}
+
 impl SubDevice {
     /// Create a SubDevice instance using the given configured address.
     ///
@@ -157,27 +298,49 @@ impl SubDevice {
 
         subdevice_ref.wait_for_state(SubDeviceState::Init).await?;
 
-        // Make sure master has access to SubDevice EEPROM
-        subdevice_ref.set_eeprom_mode(SiiOwner::Master).await?;
-
-        let eeprom = subdevice_ref.eeprom();
+        Self::read_identity(maindevice, index, configured_address).await
+    }
 
-        let identity = eeprom.identity().await?;
+    /// Read the SubDevice's name and other identifying information without waiting for it to
+    /// enter INIT.
+    ///
+    /// The only registers written are those needed to read the EEPROM. If the EEPROM can't be read,
+    /// [`MainDeviceConfig::eeprom_error`](crate::MainDeviceConfig::eeprom_error) decides whether to
+    /// return the error or continue with an unknown identity. Errors not caused by the EEPROM, e.g.
+    /// lost frames, are always returned.
+    pub(crate) async fn read_identity<'sto>(
+        maindevice: &'sto MainDevice<'sto>,
+        index: u16,
+        configured_address: u16,
+    ) -> Result<Self, Error> {
+        let subdevice_ref = SubDeviceRef::new(maindevice, configured_address, ());
 
-        let name = eeprom.device_name().await?.unwrap_or_else(|| {
-            let mut s = heapless::String::new();
+        let (identity, name, sii_summary, eeprom_size) = match Self::read_eeprom_identity(
+            maindevice,
+            configured_address,
+        )
+        .await
+        {
+            Ok(info) => info,
+            Err(e)
+                if maindevice.config.eeprom_error == EepromErrorBehaviour::Placeholder
+                    && Self::is_eeprom_failure(maindevice, &subdevice_ref, e).await =>
+            {
+                fmt::warn!(
+                    "Failed to read EEPROM of SubDevice {:#06x}, continuing with an unknown identity: {}",
+                    configured_address,
+                    e
+                );
 
-            fmt::unwrap!(
-                write!(
-                    s,
-                    "manu. {:#010x}, device {:#010x}, serial {:#010x}",
-                    identity.vendor_id, identity.product_id, identity.serial
+                (
+                    SubDeviceIdentity::default(),
+                    heapless::String::new(),
+                    SiiConfigSummary::default(),
+                    None,
                 )
-                .map_err(|_| ())
-            );
-
-            s
-        });
+            }
+            Err(e) => return Err(e),
+        };
 
         let flags = subdevice_ref
             .read(RegisterAddress::SupportFlags)
@@ -220,6 +383,8 @@ impl SubDevice {
             configured_address,
             alias_address,
             config: SubDeviceConfig::default(),
+            sii_summary,
+            eeprom_size: AtomicUsize::new(eeprom_size.unwrap_or(0)),
             index,
             parent_index: None,
             propagation_delay: 0,
@@ -231,9 +396,108 @@ impl SubDevice {
             dc_sync: DcSync::Disabled,
             // 0 is a reserved value, so we initialise the cycle at 1. The cycle repeats 1 - 7.
             mailbox_counter: AtomicU8::new(1),
+            sdo_transfer: AtomicU32::new(SdoTransfer::NONE),
+            mailbox_transactions: AtomicU8::new(0),
+            al_status_history: AlStatusHistory::default(),
         })
     }
 
+    /// Whether an error returned by [`read_eeprom_identity`](SubDevice::read_eeprom_identity) was
+    /// caused by the EEPROM itself, as opposed to e.g. a lost frame.
+    ///
+    /// An EEPROM that never finishes a read leaves the SII busy, which is reported as
+    /// [`Error::Timeout`] just like a lost frame. The SII control register is read again to tell
+    /// the two apart.
+    async fn is_eeprom_failure(
+        maindevice: &MainDevice<'_>,
+        subdevice_ref: &SubDeviceRef<'_, ()>,
+        error: Error,
+    ) -> bool {
+        match error {
+            Error::Eeprom(_) => true,
+            Error::Timeout => subdevice_ref
+                .read(RegisterAddress::SiiControl)
+                .receive::<SiiControl>(maindevice)
+                .await
+                .is_ok_and(|control| control.busy),
+            _ => false,
+        }
+    }
+
+    /// Read the SubDevice's identity, name, SII config summary and EEPROM size from its EEPROM.
+    async fn read_eeprom_identity(
+        maindevice: &MainDevice<'_>,
+        configured_address: u16,
+    ) -> Result<
+        (
+            SubDeviceIdentity,
+            heapless::String<64>,
+            SiiConfigSummary,
+            Option<usize>,
+        ),
+        Error,
+    > {
+        let subdevice_ref = SubDeviceRef::new(maindevice, configured_address, ());
+
+        // Make sure master has access to SubDevice EEPROM
+        subdevice_ref.set_eeprom_mode(SiiOwner::Master).await?;
+
+        let eeprom = SubDeviceEeprom::new(DeviceEeprom::new(maindevice, configured_address));
+
+        let identity = eeprom.identity().await?;
+
+        // Read once and stored on the SubDevice so every later category search is bounded by it,
+        // including the ones below. Searches are unbounded if it can't be read.
+        let eeprom_size = eeprom.size().await.ok();
+
+        // Read once here and reused for the SII config summary below
+        let general = eeprom.general().await.ignore_no_category()?;
+
+        let name = match &general {
+            Some(general) => eeprom
+                .find_string(general.order_string_idx)
+                .await
+                .ignore_no_category()?
+                .flatten(),
+            None => None,
+        };
+
+        let name = name.unwrap_or_else(|| {
+            let mut s = heapless::String::new();
+
+            fmt::unwrap!(
+                write!(
+                    s,
+                    "manu. {:#010x}, device {:#010x}, serial {:#010x}",
+                    identity.vendor_id, identity.product_id, identity.serial
+                )
+                .map_err(|_| ())
+            );
+
+            s
+        });
+
+        // Configuration will read the summary again if this fails, so errors aren't fatal here.
+        let sii_summary = if maindevice.config.cache_sii_config {
+            eeprom
+                .config_summary(&general.unwrap_or_default())
+                .await
+                .unwrap_or_else(|e| {
+                    fmt::warn!(
+                        "Failed to read SII config for SubDevice {:#06x}, will retry during configuration: {}",
+                        configured_address,
+                        e
+                    );
+
+                    SiiConfigSummary::default()
+                })
+        } else {
+            SiiConfigSummary::default()
+        };
+
+        Ok((identity, name, sii_summary, eeprom_size))
+    }
+
     /// Get the SubDevice's human readable short name.
     ///
     /// To get a longer, more descriptive name, use [`SubDevice::description`].
@@ -252,7 +516,7 @@ impl SubDevice {
         &self,
         maindevice: &MainDevice<'_>,
     ) -> Result<Option<heapless::String<128>>, Error> {
-        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, self);
 
         Ok(subdevice_ref
             .eeprom()
@@ -263,10 +527,14 @@ impl SubDevice {
     }
 
     /// Get the SubDevice's EEPROM size in bytes.
+    ///
+    /// The size stored during discovery is used if there is one, otherwise it is read from the
+    /// EEPROM and stored for next time.
     pub async fn eeprom_size(&self, maindevice: &MainDevice<'_>) -> Result<usize, Error> {
-        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
-
-        subdevice_ref.eeprom().size().await
+        SubDeviceRef::new(maindevice, self.configured_address, self)
+            .eeprom()
+            .size()
+            .await
     }
 
     /// Read raw bytes from the SubDevice's EEPROM, starting at the given **word** address.
@@ -276,17 +544,21 @@ impl SubDevice {
     ///
     /// To read individual typed values including fixed size chunks of `[u8; N]`, see
     /// [`eeprom_read`](SubDevice::eeprom_read).
+    ///
+    /// Returns [`EepromError::PastEnd`](crate::error::EepromError::PastEnd) if `buf` extends past
+    /// the end of the EEPROM.
     pub async fn eeprom_read_raw(
         &self,
         maindevice: &MainDevice<'_>,
         start_word: u16,
         buf: &mut [u8],
     ) -> Result<usize, Error> {
-        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, self);
 
         let mut reader = subdevice_ref
             .eeprom()
-            .start_at(start_word, buf.len() as u16);
+            .start_at_checked(start_word, buf.len() as u16)
+            .await?;
 
         reader.read(buf).await
     }
@@ -305,11 +577,12 @@ impl SubDevice {
     where
         T: EtherCrabWireReadSized,
     {
-        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, self);
 
         let mut reader = subdevice_ref
             .eeprom()
-            .start_at(start_word, T::PACKED_LEN as u16);
+            .start_at_checked(start_word, T::PACKED_LEN as u16)
+            .await?;
 
         let mut buf = T::buffer();
 
@@ -320,6 +593,42 @@ impl SubDevice {
         Ok(result)
     }
 
+    /// Read a single word from the SubDevice's SII EEPROM at the given **word** address.
+    ///
+    /// This is intended for low level tooling and debugging. To read larger values, see
+    /// [`eeprom_read`](SubDevice::eeprom_read).
+    pub async fn read_sii_word(
+        &self,
+        maindevice: &MainDevice<'_>,
+        word_addr: u16,
+    ) -> Result<u16, Error> {
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, self);
+
+        subdevice_ref.eeprom().read_word(word_addr).await
+    }
+
+    /// Write a single word to the SubDevice's SII EEPROM at the given **word** address.
+    ///
+    /// <div class="warning">
+    ///
+    /// **Warning:** The SII checksum is not updated, so writing to the first 8 words will likely
+    /// invalidate it. Writing to the EEPROM can break the SubDevice if done incorrectly.
+    ///
+    /// </div>
+    ///
+    /// This waits for the EEPROM to finish any access in progress before writing, and retries the
+    /// write if the ESC reports a command error.
+    pub async fn write_sii_word(
+        &self,
+        maindevice: &MainDevice<'_>,
+        word_addr: u16,
+        value: u16,
+    ) -> Result<(), Error> {
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, self);
+
+        subdevice_ref.eeprom().write_word(word_addr, value).await
+    }
+
     /// Write a value to the SubDevice's EEPROM at the given **word** address.
     ///
     /// <div class="warning">
@@ -331,6 +640,9 @@ impl SubDevice {
     ///
     /// **The given start address is in words NOT bytes. To address the EEPROM using a byte address,
     /// divide the given byte address by two.**
+    ///
+    /// Writes that would extend past the end of the EEPROM are rejected with
+    /// [`EepromError::PastEnd`](crate::error::EepromError::PastEnd) before anything is written.
     pub async fn eeprom_write_dangerously<T>(
         &self,
         maindevice: &MainDevice<'_>,
@@ -340,11 +652,12 @@ impl SubDevice {
     where
         T: EtherCrabWireWriteSized,
     {
-        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, self);
 
         let mut writer = subdevice_ref
             .eeprom()
-            .start_at(start_word, T::PACKED_LEN as u16);
+            .start_at_checked(start_word, T::PACKED_LEN as u16)
+            .await?;
 
         writer.write_all(value.pack().as_ref()).await?;
 
@@ -375,9 +688,39 @@ impl SubDevice {
         maindevice: &MainDevice<'_>,
         new_alias: u16,
     ) -> Result<(), Error> {
-        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, &*self);
+
+        subdevice_ref
+            .eeprom()
+            .set_station_alias(new_alias, false)
+            .await?;
+
+        self.alias_address = new_alias;
+
+        Ok(())
+    }
+
+    /// Like [`set_alias_address`](SubDevice::set_alias_address), but refuses to write the new
+    /// alias if the checksum currently stored in the EEPROM is invalid.
+    ///
+    /// Writing a new alias recomputes the checksum over the first 7 words of the EEPROM, which
+    /// would otherwise make a corrupted EEPROM look valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EepromError::InvalidChecksum`](crate::error::EepromError::InvalidChecksum) if the
+    /// existing checksum does not match.
+    pub async fn set_alias_address_checked(
+        &mut self,
+        maindevice: &MainDevice<'_>,
+        new_alias: u16,
+    ) -> Result<(), Error> {
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, &*self);
 
-        subdevice_ref.eeprom().set_station_alias(new_alias).await?;
+        subdevice_ref
+            .eeprom()
+            .set_station_alias(new_alias, true)
+            .await?;
 
         self.alias_address = new_alias;
 
@@ -392,11 +735,102 @@ impl SubDevice {
         self.propagation_delay
     }
 
+    /// Get the local time in nanoseconds at which this SubDevice received the frame used to latch
+    /// Distributed Clock receive times during initialisation.
+    ///
+    /// This can be used to correlate SubDevice local time with a host timestamp taken when that
+    /// frame was sent.
+    ///
+    /// Returns `0` if the SubDevice does not support DC or before
+    /// [`MainDevice::init`](crate::MainDevice::init) is called.
+    pub fn dc_receive_time(&self) -> u64 {
+        self.dc_receive_time
+    }
+
     /// Distributed Clock (DC) support.
     pub fn dc_support(&self) -> DcSupport {
         self.dc_support
     }
 
+    /// Mailbox protocols supported by the SubDevice, e.g. CoE or FoE.
+    ///
+    /// This is read from the standard mailbox protocol word (`0x001C`) of the SubDevice's EEPROM
+    /// during [`MainDevice::init`](crate::MainDevice::init). SubDevices without a mailbox return an
+    /// empty set.
+    pub fn mailbox_protocols(&self) -> MailboxProtocols {
+        self.config.mailbox.supported_protocols
+    }
+
+    /// Read the ESC chip type, number of implemented ports and DC capability of this SubDevice.
+    ///
+    /// Different ESCs have different capabilities, e.g. the LAN9252 only implements two ports. This
+    /// method reads the DL information registers from the SubDevice each time it is called.
+    pub async fn esc_type(&self, maindevice: &MainDevice<'_>) -> Result<EscType, Error> {
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
+
+        subdevice_ref
+            .read(RegisterAddress::Type)
+            .receive::<DlInformation>(maindevice)
+            .await
+            .map(|info| info.esc_type())
+    }
+
+    /// Write to multiple registers of this SubDevice, packing as many `FPWR` PDUs into each
+    /// EtherCAT frame as will fit.
+    ///
+    /// Each item in `writes` is a register address and the bytes to write to it. Writes are sent
+    /// in order, and fewer round trips are required than when writing each register separately,
+    /// which can be useful to reduce SubDevice bring-up time.
+    ///
+    /// # Errors
+    ///
+    /// Every write is expected to return a working counter of `1`. If any do not, an
+    /// [`Error::WorkingCounter`] is returned after the frame containing that write has been
+    /// received. Any writes in earlier frames will already have been sent.
+    ///
+    /// A [`PduError::TooLong`] error is returned if a single write is too large to fit in one
+    /// frame.
+    pub async fn write_batch(
+        &self,
+        maindevice: &MainDevice<'_>,
+        writes: &[(u16, &[u8])],
+    ) -> Result<(), Error> {
+        let mut writes = writes.iter().peekable();
+
+        while writes.peek().is_some() {
+            let mut frame = maindevice.pdu_loop.alloc_frame()?;
+
+            while let Some((register, data)) =
+                writes.next_if(|(_, data)| frame.can_push_pdu_payload(data.len()))
+            {
+                frame.push_pdu(
+                    Command::fpwr(self.configured_address, *register).into(),
+                    *data,
+                    None,
+                )?;
+            }
+
+            // Nothing fit into an empty frame, so the next write can never be sent.
+            if frame.is_empty() {
+                return Err(Error::Pdu(PduError::TooLong));
+            }
+
+            let frame = frame.mark_sendable(
+                &maindevice.pdu_loop,
+                maindevice.timeouts.pdu,
+                maindevice.config.retry_behaviour.retry_count(),
+            );
+
+            maindevice.pdu_loop.wake_sender();
+
+            for pdu in frame.await?.into_pdu_iter() {
+                pdu?.wkc(1)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn io_segments(&self) -> &IoRanges {
         &self.config.io
     }
@@ -477,6 +911,17 @@ where
     pub async fn set_alias_address(&mut self, new_alias: u16) -> Result<(), Error> {
         SubDevice::set_alias_address(&mut self.state, self.maindevice, new_alias).await
     }
+
+    /// Like [`set_alias_address`](SubDeviceRef::set_alias_address), but refuses to write the new
+    /// alias if the checksum currently stored in the EEPROM is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EepromError::InvalidChecksum`](crate::error::EepromError::InvalidChecksum) if the
+    /// existing checksum does not match.
+    pub async fn set_alias_address_checked(&mut self, new_alias: u16) -> Result<(), Error> {
+        SubDevice::set_alias_address_checked(&mut self.state, self.maindevice, new_alias).await
+    }
 }
 
 impl<S> Deref for SubDeviceRef<'_, S>
@@ -494,6 +939,15 @@ impl<'maindevice, S> SubDeviceRef<'maindevice, S>
 where
     S: Deref<Target = SubDevice>,
 {
+    /// Access the SubDevice's EEPROM, sharing the EEPROM size stored on the SubDevice so it's only
+    /// read once.
+    fn eeprom(&self) -> SubDeviceEeprom<DeviceEeprom<'_>, &AtomicUsize> {
+        SubDeviceEeprom::with_shared_size(
+            DeviceEeprom::new(self.maindevice, self.configured_address),
+            &self.state.eeprom_size,
+        )
+    }
+
     /// Get the long name of the SubDevice.
     ///
     /// Using the EK1100 as an example, the [`name`](crate::SubDevice::name) method will return
@@ -505,6 +959,31 @@ where
         SubDevice::description(&self.state, self.maindevice).await
     }
 
+    /// Get the SubDevice's EEPROM size in 16 bit words.
+    ///
+    /// This is read from the size word of the SII (ETG2010 Table 2), and can be used to bound reads
+    /// with [`eeprom_read_raw`](SubDevice::eeprom_read_raw), which also addresses the EEPROM in
+    /// words.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::IntegerTypeConversion`] if the EEPROM is larger than 1 Mbit, as its size in
+    /// words does not fit in a `u16`.
+    pub async fn eeprom_size_words(&self) -> Result<u16, Error> {
+        let bytes = SubDevice::eeprom_size(&self.state, self.maindevice).await?;
+
+        u16::try_from(bytes / 2).map_err(|_| Error::IntegerTypeConversion)
+    }
+
+    /// Get the SubDevice's EEPROM size in bytes if it has been read.
+    ///
+    /// The size is read during discovery. Returns `None` if it couldn't be read then, e.g. if the
+    /// EEPROM was not accessible, and hasn't been read successfully since. Use
+    /// [`eeprom_size`](SubDevice::eeprom_size) to try reading it from the SubDevice again.
+    pub fn eeprom_size_bytes(&self) -> Option<usize> {
+        Some(self.state.eeprom_size.load(Ordering::Relaxed)).filter(|size| *size > 0)
+    }
+
     /// INTERNAL: Read address from EEPROM.
     ///
     /// Useful for testing. Please don't rely on this as a public API item.
@@ -513,7 +992,7 @@ where
         &self,
         maindevice: &MainDevice<'_>,
     ) -> Result<u16, Error> {
-        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, ());
+        let subdevice_ref = SubDeviceRef::new(maindevice, self.configured_address, &*self.state);
 
         subdevice_ref.eeprom().station_alias().await
     }
@@ -522,6 +1001,21 @@ where
         self.state.dc_sync
     }
 
+    /// Get the AL status codes reported by this SubDevice during its most recent group state
+    /// transition, oldest first.
+    ///
+    /// A code is only recorded when it differs from the one seen in the previous poll, so a
+    /// SubDevice that errors then recovers will show the error followed by
+    /// [`AlStatusCode::NoError`]. The history is cleared at the start of every transition and holds
+    /// at most 8 codes, after which the oldest are dropped.
+    ///
+    /// Recording is disabled by default as it makes every state poll read a few more bytes per
+    /// SubDevice. Enable it with
+    /// [`MainDeviceConfig::al_status_history`](crate::MainDeviceConfig::al_status_history).
+    pub fn al_status_history(&self) -> heapless::Vec<AlStatusCode, AL_STATUS_HISTORY_LEN> {
+        self.state.al_status_history.get()
+    }
+
     /// Return the current cyclic mailbox counter value, from 0-7.
     ///
     /// Calling this method internally increments the counter, so subequent calls will produce a new
@@ -534,6 +1028,105 @@ where
         ))
     }
 
+    /// Wait until fewer than
+    /// [`MainDeviceConfig::mailbox_concurrency`](crate::MainDeviceConfig::mailbox_concurrency)
+    /// mailbox transactions are in progress on this SubDevice, then claim a slot.
+    async fn acquire_mailbox(&self) -> MailboxPermit<'_> {
+        let max = self.maindevice.config.mailbox_concurrency.max(1);
+
+        loop {
+            let claimed = self.state.mailbox_transactions.fetch_update(
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                |n| (n < max).then_some(n + 1),
+            );
+
+            if claimed.is_ok() {
+                break MailboxPermit {
+                    transactions: &self.state.mailbox_transactions,
+                };
+            }
+
+            // The transaction holding the mailbox may be polled by the same task as this one, so
+            // it must get a chance to run even if the loop delay is zero.
+            futures_lite::future::yield_now().await;
+
+            self.maindevice.timeouts.loop_tick().await;
+        }
+    }
+
+    /// Wait for any other SDO transfer on this SubDevice to finish, then mark this one as started.
+    ///
+    /// If a previous transfer never finished because its future was dropped, an SDO abort request is
+    /// sent for it first so the SubDevice's SDO server is ready for a new transfer.
+    async fn begin_sdo_transfer(
+        &self,
+        index: u16,
+        sub_index: u8,
+    ) -> Result<SdoTransferGuard<'_>, Error> {
+        let stale = loop {
+            let current = self.state.sdo_transfer.load(Ordering::Acquire);
+
+            if current & SdoTransfer::ACTIVE == 0
+                && self
+                    .state
+                    .sdo_transfer
+                    .compare_exchange(
+                        current,
+                        current | SdoTransfer::ACTIVE,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    )
+                    .is_ok()
+            {
+                break current;
+            }
+
+            futures_lite::future::yield_now().await;
+
+            self.maindevice.timeouts.loop_tick().await;
+        };
+
+        // If sending the abort fails, the cancelled transfer stays recorded for next time
+        let guard = SdoTransferGuard {
+            transfer: &self.state.sdo_transfer,
+            finished: false,
+        };
+
+        if let Some((stale_index, stale_sub_index)) = SdoTransfer::unpack(stale) {
+            fmt::debug!(
+                "SubDevice {:#06x} SDO {:#06x}:{} was cancelled, aborting",
+                self.configured_address,
+                stale_index,
+                stale_sub_index
+            );
+
+            // Clears any response to the cancelled request from the SubDevice OUT mailbox
+            let (_read_mailbox, write_mailbox) = self.coe_mailboxes().await?;
+
+            let request = coe::services::abort(
+                self.mailbox_counter(),
+                stale_index,
+                stale_sub_index,
+                CoeAbortCode::General,
+            );
+
+            // Abort requests are unconfirmed so there's no response to wait for
+            self.write(write_mailbox.address)
+                .with_len(write_mailbox.len)
+                .scrub()
+                .send(self.maindevice, &request.pack().as_ref())
+                .await?;
+        }
+
+        self.state.sdo_transfer.store(
+            SdoTransfer::ACTIVE | SdoTransfer::pack(index, sub_index),
+            Ordering::Release,
+        );
+
+        Ok(guard)
+    }
+
     /// Get CoE read/write mailboxes.
     async fn coe_mailboxes(&self) -> Result<(Mailbox, Mailbox), Error> {
         let write_mailbox = self
@@ -578,6 +1171,7 @@ where
 
                 self.read(read_mailbox.address)
                     .ignore_wkc()
+                    .scrub()
                     .receive_slice(self.maindevice, read_mailbox.len)
                     .await?;
             } else {
@@ -622,12 +1216,52 @@ where
         Ok((read_mailbox, write_mailbox))
     }
 
-    /// Wait for a mailbox response
-    async fn coe_response(&self, read_mailbox: &Mailbox) -> Result<ReceivedPdu, Error> {
-        let mailbox_read_sm = RegisterAddress::sync_manager_status(read_mailbox.sync_manager);
-
-        // Wait for SubDevice OUT mailbox to be ready
-        async {
+    /// Send a raw mailbox request with the given counter, then wait for the SubDevice's response.
+    ///
+    /// The read mailbox is polled every [`Timeouts::wait_loop_delay`](crate::Timeouts::wait_loop_delay)
+    /// until it holds a response or `timeout` expires. If `match_counter` is set, responses with a
+    /// different counter are read and discarded. Counter matching is best-effort: a response with a
+    /// counter of 0 is always accepted, as SubDevices that don't implement the counter leave it
+    /// unset.
+    async fn mailbox_exchange(
+        &self,
+        request: &[u8],
+        counter: u8,
+        match_counter: bool,
+        timeout: Duration,
+    ) -> Result<HeldPdu<'maindevice>, Error> {
+        if request.len() < CountedMailboxRequest::HEADER_LEN {
+            fmt::error!(
+                "Mailbox request of {} bytes is too short to hold a mailbox header",
+                request.len()
+            );
+
+            return Err(Error::Mailbox(MailboxError::RequestTooShort));
+        }
+
+        let (read_mailbox, write_mailbox) = self.coe_mailboxes().await?;
+
+        if request.len() > usize::from(write_mailbox.len) {
+            fmt::error!(
+                "Mailbox request of {} bytes does not fit in SubDevice {:#06x} write mailbox of {} bytes",
+                request.len(),
+                self.configured_address,
+                write_mailbox.len
+            );
+
+            return Err(Error::Pdu(PduError::TooLong));
+        }
+
+        // Send data to SubDevice IN mailbox
+        self.write(write_mailbox.address)
+            .with_len(write_mailbox.len)
+            .scrub()
+            .send(self.maindevice, CountedMailboxRequest { request, counter })
+            .await?;
+        let mailbox_read_sm = RegisterAddress::sync_manager_status(read_mailbox.sync_manager);
+
+        // Wait for SubDevice OUT mailbox to be ready
+        async {
             loop {
                 let sm_status = self
                     .read(mailbox_read_sm)
@@ -635,13 +1269,31 @@ where
                     .await?;
 
                 if sm_status.mailbox_full {
-                    break Ok(());
+                    // Read response from SubDevice OUT mailbox
+                    let response = self
+                        .read(read_mailbox.address)
+                        .scrub()
+                        .receive_slice(self.maindevice, read_mailbox.len)
+                        .await?;
+
+                    let response_counter = CountedMailboxRequest::counter(&response);
+
+                    if !match_counter || matches!(response_counter, Some(0)) || response_counter == Some(counter) {
+                        break Ok(response);
+                    }
+
+                    fmt::debug!(
+                        "SubDevice {:#06x} discarding mailbox response with counter {:?}, expected {}",
+                        self.configured_address,
+                        response_counter,
+                        counter
+                    );
                 }
 
                 self.maindevice.timeouts.loop_tick().await;
             }
         }
-        .timeout(self.maindevice.timeouts.mailbox_echo)
+        .timeout(timeout)
         .await
         .inspect_err(|&e| {
             fmt::error!(
@@ -649,17 +1301,7 @@ where
                 self.configured_address,
                 e
             );
-        })?;
-
-        // Read acknowledgement from SubDevice OUT mailbox
-        let response = self
-            .read(read_mailbox.address)
-            .receive_slice(self.maindevice, read_mailbox.len)
-            .await?;
-
-        // TODO: Retries. Refer to SOEM's `ecx_mbxreceive` for inspiration
-
-        Ok(response)
+        })
     }
 
     /// Send a mailbox request, wait for response mailbox to be ready, read response from mailbox
@@ -667,19 +1309,50 @@ where
     async fn send_coe_service<R>(
         &'maindevice self,
         request: R,
-    ) -> Result<(R, ReceivedPdu<'maindevice>), Error>
+    ) -> Result<(R, HeldPdu<'maindevice>), Error>
     where
         R: CoeServiceRequest + Debug,
     {
-        let (read_mailbox, write_mailbox) = self.coe_mailboxes().await?;
-
-        // Send data to SubDevice IN mailbox
-        self.write(write_mailbox.address)
-            .with_len(write_mailbox.len)
-            .send(self.maindevice, &request.pack().as_ref())
-            .await?;
-
-        let mut response = self.coe_response(&read_mailbox).await?;
+        let packed = request.pack();
+        let packed = packed.as_ref();
+
+        // The request already holds its counter, so the first attempt is sent unchanged
+        let mut counter = CountedMailboxRequest::counter(packed).unwrap_or_default();
+        let mut retries = self.maindevice.config.mailbox_retries;
+
+        let mut response = loop {
+            // A retry only accepts a response with its own counter, so a late response to an
+            // earlier attempt isn't mistaken for the response to this one.
+            let match_counter = retries != self.maindevice.config.mailbox_retries;
+
+            match self
+                .mailbox_exchange(
+                    packed,
+                    counter,
+                    match_counter,
+                    self.maindevice.timeouts.mailbox_echo,
+                )
+                .await
+            {
+                Ok(response) => break response,
+                Err(e) if e.is_transient() && retries > 0 => {
+                    retries -= 1;
+
+                    // The SubDevice ignores a request with the same counter as the last one it
+                    // accepted, so the retry must be sent with a new counter.
+                    counter = self.mailbox_counter();
+
+                    fmt::warn!(
+                        "SubDevice {:#06x} mailbox transaction failed: {}, retrying with counter {} ({} retries left)",
+                        self.configured_address,
+                        e,
+                        counter,
+                        retries
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        };
 
         /// A super generalised version of the various header shapes for responses, extracting only
         /// what we need in this method.
@@ -778,15 +1451,66 @@ where
         } else {
             let headers = R::unpack_from_slice(&response)?;
 
-            response.trim_front(HeadersRaw::PACKED_LEN);
+            // Segmented responses have a 1 byte SDO header, so data starts earlier than in other
+            // responses.
+            response.trim_front(R::PACKED_LEN);
 
             Ok((headers, response))
         }
     }
 
+    /// Send a raw mailbox request and wait for the SubDevice's response to it.
+    ///
+    /// `request` must start with the 6 byte mailbox header, followed by the protocol specific
+    /// payload, and must fit in the SubDevice's write mailbox. The counter in the request's header
+    /// is replaced with the next value of this SubDevice's mailbox counter.
+    ///
+    /// Once the request is sent, the read mailbox is polled every
+    /// [`Timeouts::wait_loop_delay`](crate::Timeouts::wait_loop_delay) until it holds a response
+    /// with the same counter. Responses with any other counter, e.g. left over from a cancelled
+    /// transaction, are read and discarded. A response with a counter of 0 is always accepted, as
+    /// SubDevices that don't implement the mailbox counter leave it unset. If no matching response
+    /// arrives within
+    /// [`Timeouts::mailbox_response`](crate::Timeouts::mailbox_response),
+    /// [`Error::Timeout`] is returned.
+    ///
+    /// Only [`MainDeviceConfig::mailbox_concurrency`](crate::MainDeviceConfig::mailbox_concurrency)
+    /// transactions, including SDO reads and writes, may be in progress on a SubDevice at once. Any
+    /// further calls wait for an in-progress transaction to complete before sending their request,
+    /// so a slow SubDevice is never sent a new request before it has answered the previous one.
+    ///
+    /// The returned buffer holds the whole read mailbox, starting with the response's mailbox
+    /// header. Use the header's length field to find the end of the response data.
+    ///
+    /// # Errors
+    ///
+    /// A request shorter than the mailbox header returns
+    /// [`MailboxError::RequestTooShort`](crate::error::MailboxError::RequestTooShort), and one
+    /// longer than the write mailbox returns
+    /// [`PduError::TooLong`](crate::error::PduError::TooLong).
+    pub async fn mailbox_transaction(
+        &self,
+        request: &[u8],
+    ) -> Result<impl Deref<Target = [u8]> + 'maindevice, Error> {
+        let _permit = self.acquire_mailbox().await;
+
+        let counter = self.mailbox_counter();
+
+        self.mailbox_exchange(
+            request,
+            counter,
+            true,
+            self.maindevice.timeouts.mailbox_response,
+        )
+        .await
+    }
+
     /// Write a value to the given SDO index (address) and sub-index.
     ///
     /// Note that this method currently only supports expedited SDO downloads (4 bytes maximum).
+    ///
+    /// This method may be cancelled by dropping the returned future. See
+    /// [`sdo_read`](SubDeviceRef::sdo_read) for details.
     pub async fn sdo_write<T>(
         &self,
         index: u16,
@@ -816,7 +1540,17 @@ where
 
         fmt::trace!("CoE download");
 
-        let (_response, _data) = self.send_coe_service(request).await?;
+        let _permit = self.acquire_mailbox().await;
+
+        let transfer = self
+            .begin_sdo_transfer(index, sub_index.sub_index())
+            .await?;
+
+        let result = self.send_coe_service(request).await;
+
+        transfer.finish();
+
+        let (_response, _data) = result?;
 
         // TODO: Validate reply?
 
@@ -835,7 +1569,7 @@ where
     /// # use ethercrab::{
     /// #     error::Error, MainDevice, MainDeviceConfig, PduStorage, Timeouts, std::ethercat_now
     /// # };
-    /// # static PDU_STORAGE: PduStorage<8, 32> = PduStorage::new();
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
     /// # let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
     /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
     /// # async {
@@ -894,7 +1628,7 @@ where
     /// # use ethercrab::{
     /// #     error::Error, MainDevice, MainDeviceConfig, PduStorage, Timeouts, std::ethercat_now
     /// # };
-    /// # static PDU_STORAGE: PduStorage<8, 32> = PduStorage::new();
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
     /// # let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
     /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
     /// # async {
@@ -960,7 +1694,17 @@ where
 
         fmt::trace!("CoE upload {:#06x} {:?}", index, sub_index);
 
-        let (headers, response) = self.send_coe_service(request).await?;
+        let _permit = self.acquire_mailbox().await;
+
+        let transfer = self
+            .begin_sdo_transfer(index, sub_index.sub_index())
+            .await?;
+
+        let result = self.send_coe_service(request).await;
+
+        transfer.finish();
+
+        let (headers, response) = result?;
         let data: &[u8] = &response;
 
         // Expedited transfers where the data is 4 bytes or less long, denoted in the SDO header
@@ -977,12 +1721,99 @@ where
     }
 
     /// Read a value from an SDO (Service Data Object) from the given index (address) and sub-index.
+    ///
+    /// # Cancellation
+    ///
+    /// Large objects are read using multiple segmented requests which can take a long time to
+    /// complete. The transfer can be cancelled at any point by dropping the returned future.
+    ///
+    /// If this happens, the next SDO read or write to the same SubDevice first clears any stale
+    /// response from the SubDevice's mailbox and sends an SDO abort request for the cancelled
+    /// transfer, so the new transfer starts from a clean state. This is best effort: the abort
+    /// request is not confirmed by the SubDevice.
+    ///
+    /// # Concurrency
+    ///
+    /// SDO transfers to the same SubDevice are serialised, so a second read or write started while
+    /// this one is in progress waits for it to finish before using the mailbox. See
+    /// [`MainDeviceConfig::mailbox_concurrency`](crate::MainDeviceConfig::mailbox_concurrency).
     pub async fn sdo_read<T>(&self, index: u16, sub_index: impl Into<SubIndex>) -> Result<T, Error>
     where
         T: EtherCrabWireReadSized,
     {
         let sub_index = sub_index.into();
 
+        let _permit = self.acquire_mailbox().await;
+
+        let transfer = self
+            .begin_sdo_transfer(index, sub_index.sub_index())
+            .await?;
+
+        let result = self.sdo_upload(index, sub_index).await;
+
+        transfer.finish();
+
+        result
+    }
+
+    /// Compare the revision number read from the EEPROM during initialisation with the one reported
+    /// by the CoE identity object (`0x1018:03`).
+    ///
+    /// Returns `Ok(Some((eeprom, coe)))` if the two differ, or `Ok(None)` if they match. A mismatch
+    /// usually means the SubDevice's firmware was updated without also reflashing its EEPROM, so
+    /// the revision EtherCrab uses to identify the SubDevice may not describe the running firmware.
+    ///
+    /// The SubDevice must support CoE and be in at least PRE-OP for the identity object to be read.
+    pub async fn revision_mismatch(&self) -> Result<Option<(u32, u32)>, Error> {
+        let eeprom = self.state.identity.revision;
+        let coe = self.sdo_read::<u32>(0x1018, 3).await?;
+
+        Ok((eeprom != coe).then_some((eeprom, coe)))
+    }
+
+    /// Read the synchronisation capabilities of the SubDevice from the CoE SyncManager parameter
+    /// objects `0x1C32` and `0x1C33`.
+    ///
+    /// This can be used to check that a SubDevice can keep up with a given cycle time before
+    /// configuring it, e.g. by comparing [`SyncCapabilities::min_cycle_time`] with the SYNC0
+    /// period. Object `0x1C33` is optional and is ignored if the SubDevice doesn't have it.
+    ///
+    /// The SubDevice must support CoE and be in at least PRE-OP for these objects to be read.
+    pub async fn sync_capabilities(&self) -> Result<SyncCapabilities, Error> {
+        let sync_modes = self.sdo_read::<SyncModes>(0x1c32, 4).await?;
+        let output_min_cycle_time = self.sdo_read::<u32>(0x1c32, 5).await?;
+        let output_calc_and_copy_time = self.sdo_read::<u32>(0x1c32, 6).await?;
+
+        let inputs = match self.sdo_read::<u32>(0x1c33, 5).await {
+            Ok(input_min_cycle_time) => {
+                Some((input_min_cycle_time, self.sdo_read::<u32>(0x1c33, 6).await?))
+            }
+            Err(Error::Mailbox(MailboxError::Aborted {
+                code: CoeAbortCode::NotFound,
+                ..
+            })) => None,
+            Err(e) => return Err(e),
+        };
+
+        let min_cycle_time = inputs.map_or(output_min_cycle_time, |(input_min_cycle_time, _)| {
+            output_min_cycle_time.max(input_min_cycle_time)
+        });
+
+        Ok(SyncCapabilities {
+            sync_modes,
+            min_cycle_time: Duration::from_nanos(min_cycle_time.into()),
+            output_calc_and_copy_time: Duration::from_nanos(output_calc_and_copy_time.into()),
+            input_calc_and_copy_time: inputs.map(|(_, input_calc_and_copy_time)| {
+                Duration::from_nanos(input_calc_and_copy_time.into())
+            }),
+        })
+    }
+
+    /// Upload an SDO, using segmented transfers if required.
+    async fn sdo_upload<T>(&self, index: u16, sub_index: SubIndex) -> Result<T, Error>
+    where
+        T: EtherCrabWireReadSized,
+    {
         let mut storage = T::buffer();
         let buf = storage.as_mut();
 
@@ -1129,117 +1960,1708 @@ impl<'maindevice, S> SubDeviceRef<'maindevice, S> {
         futures_lite::future::try_zip(self.state(), code).await
     }
 
-    fn eeprom(&self) -> SubDeviceEeprom<DeviceEeprom> {
-        SubDeviceEeprom::new(DeviceEeprom::new(self.maindevice, self.configured_address))
-    }
-
-    /// Read a register.
+    /// Read the SubDevice's Explicit Device ID, e.g. the value set on a rotary switch.
     ///
-    /// Note that while this method is marked safe, raw alterations to SubDevice config or behaviour can
-    /// break higher level interactions with EtherCrab.
-    pub async fn register_read<T>(&self, register: impl Into<u16>) -> Result<T, Error>
-    where
-        T: EtherCrabWireReadSized,
-    {
-        self.read(register.into()).receive(self.maindevice).await
-    }
-
-    /// Write a register.
+    /// The ID is requested by setting the ID request flag in the AL control register. Once the
+    /// SubDevice sets the ID loaded flag in its AL status register, the ID is read from the AL
+    /// status code register and the request is cleared again. The SubDevice's current state is not
+    /// changed.
     ///
-    /// Note that while this method is marked safe, raw alterations to SubDevice config or behaviour can
-    /// break higher level interactions with EtherCrab.
-    pub async fn register_write<T>(&self, register: impl Into<u16>, value: T) -> Result<T, Error>
-    where
-        T: EtherCrabWireReadWrite,
-    {
-        self.write(register.into())
-            .send_receive(self.maindevice, value)
-            .await
-    }
+    /// This can be used to match SubDevices by a physical switch setting instead of by their
+    /// position in the network, e.g. to identify hot connect groups.
+    ///
+    /// If the SubDevice doesn't support Explicit Device ID, it never sets the ID loaded flag and
+    /// [`Error::Timeout`] is returned after the
+    /// [`state_transition`](crate::Timeouts::state_transition) timeout.
+    pub async fn explicit_device_id(&self) -> Result<u16, Error> {
+        let status = self
+            .read(RegisterAddress::AlStatus)
+            .receive::<AlControl>(self.maindevice)
+            .await?;
 
-    pub(crate) async fn wait_for_state(&self, desired_state: SubDeviceState) -> Result<(), Error> {
-        async {
+        self.write(RegisterAddress::AlControl)
+            .send(
+                self.maindevice,
+                AlControl {
+                    id_request: true,
+                    ..AlControl::new(status.state)
+                },
+            )
+            .await?;
+
+        let id = async {
             loop {
                 let status = self
                     .read(RegisterAddress::AlStatus)
-                    .ignore_wkc()
                     .receive::<AlControl>(self.maindevice)
                     .await?;
 
-                if status.state == desired_state {
-                    break Ok(());
+                // The ID request bit in AL control is the ID loaded bit in AL status
+                if status.id_request {
+                    break self
+                        .read(RegisterAddress::AlStatusCode)
+                        .receive::<u16>(self.maindevice)
+                        .await;
                 }
 
                 self.maindevice.timeouts.loop_tick().await;
             }
         }
         .timeout(self.maindevice.timeouts.state_transition)
-        .await
+        .await;
+
+        // Clear the request even if the ID couldn't be read, so the SubDevice reports AL status
+        // codes again.
+        self.write(RegisterAddress::AlControl)
+            .send(self.maindevice, AlControl::new(status.state))
+            .await?;
+
+        id
     }
 
-    pub(crate) fn write(&self, register: impl Into<u16>) -> WrappedWrite {
-        Command::fpwr(self.configured_address, register.into())
+    /// Read the DL control register, containing frame forwarding and port loop settings.
+    pub async fn dl_control(&self) -> Result<DlControl, Error> {
+        self.read(RegisterAddress::DlControl)
+            .receive::<DlControl>(self.maindevice)
+            .await
     }
 
-    pub(crate) fn read(&self, register: impl Into<u16>) -> WrappedRead {
-        Command::fprd(self.configured_address, register.into())
+    /// Write the DL control register.
+    ///
+    /// This can be used to manually open or close port loops, for example to segment the network by
+    /// forcing a port closed. Note that changing port settings may cause SubDevices further down the
+    /// network to become unreachable.
+    ///
+    /// An [`Error::WorkingCounter`] is returned if the SubDevice doesn't acknowledge the write.
+    ///
+    /// # Examples
+    ///
+    /// Close port 1 of the first SubDevice in a group.
+    ///
+    /// ```rust,no_run
+    /// # use ethercrab::{
+    /// #     error::Error, MainDevice, MainDeviceConfig, PduStorage, Timeouts, std::ethercat_now,
+    /// #     LoopControl,
+    /// # };
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
+    /// # let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    /// # async {
+    /// # let group = maindevice
+    /// #     .init_single_group::<8, 8>(ethercat_now)
+    /// #     .await
+    /// #     .expect("Init");
+    /// let subdevice = group.subdevice(&maindevice, 0)?;
+    ///
+    /// let control = subdevice.dl_control().await?;
+    ///
+    /// subdevice
+    ///     .set_dl_control(control.with_loop_control(1, LoopControl::Closed))
+    ///     .await?;
+    /// # Ok::<(), ethercrab::error::Error>(())
+    /// # };
+    /// ```
+    pub async fn set_dl_control(&self, control: DlControl) -> Result<(), Error> {
+        self.write(RegisterAddress::DlControl)
+            .send_receive_slice(self.maindevice, control)
+            .await?;
+
+        Ok(())
     }
 
-    pub(crate) async fn request_subdevice_state_nowait(
-        &self,
-        desired_state: SubDeviceState,
-    ) -> Result<(), Error> {
-        fmt::debug!(
-            "Set state {} for SubDevice address {:#04x}",
-            desired_state,
-            self.configured_address
-        );
+    /// Read the loop state of each of this SubDevice's ports, indexed by port number.
+    ///
+    /// This combines the loop control setting from the DL control register with the current loop
+    /// state from the DL status register, e.g. to check that a port in a redundant ring has closed
+    /// after a cable break, or opened again once the link returned.
+    pub async fn loop_status(&self) -> Result<[PortLoopState; 4], Error> {
+        let status = self
+            .read(RegisterAddress::DlStatus)
+            .receive::<DlStatus>(self.maindevice);
 
-        // Send state request
-        let response = self
-            .write(RegisterAddress::AlControl)
-            .send_receive::<AlControl>(self.maindevice, AlControl::new(desired_state))
-            .await?;
+        let (control, status) = futures_lite::future::try_zip(self.dl_control(), status).await?;
 
-        if response.error {
-            let error = self
-                .read(RegisterAddress::AlStatus)
-                .receive::<AlStatusCode>(self.maindevice)
-                .await?;
+        Ok(PortLoopState::from_registers(&control, &status))
+    }
 
-            fmt::error!(
-                "Error occurred transitioning SubDevice {:#06x} to {:?}: {}",
-                self.configured_address,
-                desired_state,
-                error,
-            );
+    /// Enable or disable automatic loop recovery on this SubDevice's ports.
+    ///
+    /// When enabled, a port that closed because its link was lost is opened again as soon as the
+    /// link returns ([`LoopControl::Auto`]), so a redundant ring heals itself. When disabled, such a
+    /// port stays closed until it is opened explicitly ([`LoopControl::AutoClose`]).
+    ///
+    /// Ports that are forced [`Open`](LoopControl::Open) or [`Closed`](LoopControl::Closed) are
+    /// left unchanged.
+    pub async fn set_port_recovery(&self, enabled: bool) -> Result<(), Error> {
+        let control = self.dl_control().await?;
 
-            return Err(Error::StateTransition);
-        }
+        let recovery = if enabled {
+            LoopControl::Auto
+        } else {
+            LoopControl::AutoClose
+        };
 
-        Ok(())
+        let control = (0..4).fold(control, |control, port| match control.loop_control(port) {
+            Some(LoopControl::Auto | LoopControl::AutoClose) => {
+                control.with_loop_control(port, recovery)
+            }
+            _ => control,
+        });
+
+        self.set_dl_control(control).await
     }
 
-    pub(crate) async fn request_subdevice_state(
-        &self,
-        desired_state: SubDeviceState,
-    ) -> Result<(), Error> {
-        self.request_subdevice_state_nowait(desired_state).await?;
+    /// Read the Sync Manager and PDI watchdog expiry counters.
+    ///
+    /// The Sync Manager (process data) watchdog counter from register `0x0442` is returned in the
+    /// low byte, and the PDI watchdog counter from `0x0443` in the high byte. Each counter is
+    /// incremented by the ESC every time its watchdog expires, and saturates at 255.
+    ///
+    /// Polling these counters during normal operation gives an early warning that the process data
+    /// cycle is too slow or jittery for the configured watchdog timeouts, before the SubDevice drops
+    /// out of OP.
+    pub async fn watchdog_counter(&self) -> Result<u16, Error> {
+        self.read(RegisterAddress::SyncManagerWatchdogCounter)
+            .receive::<u16>(self.maindevice)
+            .await
+    }
 
-        self.wait_for_state(desired_state).await
+    /// Read how many times each port has lost its link, indexed by port number.
+    ///
+    /// A count that keeps climbing on one port while the others stay put points to an intermittent
+    /// cable or connector on that port.
+    pub async fn link_change_counts(&self) -> Result<[u16; 4], Error> {
+        self.read(RegisterAddress::LostLinkCounter)
+            .receive::<[u8; 4]>(self.maindevice)
+            .await
+            .map(|counts| counts.map(u16::from))
     }
 
-    pub(crate) async fn set_eeprom_mode(&self, mode: SiiOwner) -> Result<(), Error> {
-        // ETG1000.4 Table 48 – SubDevice information interface access
-        // A value of 2 sets owner to Master (not PDI) and cancels access
-        self.write(RegisterAddress::SiiConfig)
-            .send(self.maindevice, 2u16)
+    /// Read the location and size of the process data RAM in this SubDevice's ESC.
+    ///
+    /// Process data RAM always starts at `0x1000`, but its size depends on the ESC, e.g. 8 KiB on
+    /// an ET1100 or up to 60 KiB on an FPGA IP core. Sync Managers for mailboxes and process data
+    /// must be placed within this area.
+    pub async fn dpram_layout(&self) -> Result<DpramLayout, Error> {
+        self.read(RegisterAddress::Type)
+            .receive::<DlInformation>(self.maindevice)
+            .await
+            .map(|info| info.dpram_layout())
+    }
+
+    /// Measure the time taken for a single one byte read from this SubDevice to return.
+    ///
+    /// `now` should be the same clock passed to [`MainDevice::init`](crate::MainDevice::init),
+    /// returning the current time in nanoseconds. The measured time includes the time the frame
+    /// spends queued in the TX/RX task as well as on the network, so comparing it between
+    /// SubDevices on the same network can help find a slow SubDevice or link.
+    pub async fn ping(&self, now: impl Fn() -> u64) -> Result<Duration, Error> {
+        let start = now();
+
+        self.read(RegisterAddress::Type)
+            .receive::<u8>(self.maindevice)
             .await?;
 
-        self.write(RegisterAddress::SiiConfig)
-            .send(self.maindevice, mode)
+        Ok(Duration::from_nanos(now().saturating_sub(start)))
+    }
+
+    /// Read the Distributed Clock control loop parameters.
+    ///
+    /// The speed counter difference gives an indication of how much the SubDevice's local clock is
+    /// drifting relative to the DC system time, which is useful when tuning the control loop.
+    pub async fn dc_control_loop_params(&self) -> Result<DcControlLoopParams, Error> {
+        self.read(RegisterAddress::DcControlLoopParam1)
+            .receive::<DcControlLoopParams>(self.maindevice)
+            .await
+    }
+
+    /// Write the Distributed Clock control loop parameters.
+    ///
+    /// Control loop parameter 3 is written first, then parameter 1, which resets the control loop
+    /// so the new filter depths take effect. The
+    /// [`speed_counter_diff`](DcControlLoopParams::speed_counter_diff) field is read only and is
+    /// not written.
+    ///
+    /// All SubDevices are given [`DcControlLoopParams::default`] during initialisation, so this
+    /// method is only needed to tune the control loop of a particular SubDevice.
+    pub async fn configure_dc_control_loop(
+        &self,
+        params: DcControlLoopParams,
+    ) -> Result<(), Error> {
+        self.write(RegisterAddress::DcControlLoopParam3)
+            .send(self.maindevice, params.param3())
+            .await?;
+
+        self.write(RegisterAddress::DcControlLoopParam1)
+            .send(self.maindevice, params.param1())
             .await?;
 
         Ok(())
     }
+
+    /// Read the Distributed Clock system time offset (`0x0920`) in nanoseconds.
+    ///
+    /// This is the offset between the SubDevice's local time and the DC system time, written to
+    /// every DC capable SubDevice during initialisation. That write does not check the working
+    /// counter, so this method can be used to confirm the offset was accepted by the SubDevice.
+    pub async fn read_dc_offset(&self) -> Result<i64, Error> {
+        self.read(RegisterAddress::DcSystemTimeOffset)
+            .receive::<i64>(self.maindevice)
+            .await
+    }
+
+    /// Read the Distributed Clock system time transmission delay (`0x0928`) in nanoseconds.
+    ///
+    /// This is the propagation delay from the reference SubDevice, computed and written to every DC
+    /// capable SubDevice during initialisation without checking the working counter. Unlike
+    /// [`propagation_delay`](SubDevice::propagation_delay), which returns the computed value,
+    /// this method reads back what the SubDevice actually holds.
+    pub async fn transmission_delay(&self) -> Result<u32, Error> {
+        self.read(RegisterAddress::DcSystemTimeTransmissionDelay)
+            .receive::<u32>(self.maindevice)
+            .await
+    }
+
+    /// Set the SYNC0 start time of this SubDevice to a cycle boundary in the future.
+    ///
+    /// The current DC system time is read from the reference SubDevice, then rounded up to the next
+    /// multiple of `cycle_time` that is at least `margin` later. The result is written to the
+    /// SubDevice's SYNC0 start time register (`0x0990`) and returned in nanoseconds.
+    ///
+    /// `margin` must be long enough for the write to reach the SubDevice before the start time
+    /// passes, otherwise SYNC0 will not start until the 64 bit system time wraps.
+    ///
+    /// This should be called while the SubDevice's SYNC0 unit is not yet activated, i.e. before
+    /// writing `DcSyncActive`.
+    ///
+    /// # Errors
+    ///
+    /// Returns
+    /// [`Error::DistributedClock(DistributedClockError::NoReference)`](Error::DistributedClock) if
+    /// no DC reference SubDevice has been found during initialisation, or
+    /// [`Error::DistributedClock(DistributedClockError::InvalidCycleTime)`](Error::DistributedClock)
+    /// if `cycle_time` is zero.
+    pub async fn set_sync0_start_aligned(
+        &self,
+        cycle_time: Duration,
+        margin: Duration,
+    ) -> Result<u64, Error> {
+        let cycle_time = cycle_time.as_nanos() as u64;
+
+        if cycle_time == 0 {
+            return Err(Error::DistributedClock(
+                DistributedClockError::InvalidCycleTime,
+            ));
+        }
+
+        let reference = self
+            .maindevice
+            .dc_ref_address()
+            .ok_or(Error::DistributedClock(DistributedClockError::NoReference))?;
+
+        let reference_time = Command::fprd(reference, RegisterAddress::DcSystemTime.into())
+            .receive::<u64>(self.maindevice)
+            .await?;
+
+        let start_time =
+            crate::dc::next_cycle_boundary(reference_time, cycle_time, margin.as_nanos() as u64);
+
+        fmt::debug!(
+            "SubDevice {:#06x} SYNC0 start time {} ns (reference time {} ns)",
+            self.configured_address,
+            start_time,
+            reference_time
+        );
+
+        self.write(RegisterAddress::DcSyncStartTime)
+            .send(self.maindevice, start_time)
+            .await?;
+
+        Ok(start_time)
+    }
+
+    /// Read back the configuration of a single FMMU.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if `index` is 16 or greater.
+    pub async fn fmmu_config(&self, index: u8) -> Result<FmmuConfig, Error> {
+        if usize::from(index) >= config_snapshot::MAX_CHANNELS {
+            return Err(Error::NotFound {
+                item: Item::Fmmu,
+                index: Some(usize::from(index)),
+            });
+        }
+
+        self.read(RegisterAddress::fmmu(index))
+            .receive::<Fmmu>(self.maindevice)
+            .await
+            .map(|fmmu| FmmuConfig::new(index, fmmu))
+    }
+
+    /// Read back the configuration of a single Sync Manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if `index` is 16 or greater.
+    pub async fn sync_manager_config(&self, index: u8) -> Result<SyncManagerConfig, Error> {
+        if usize::from(index) >= config_snapshot::MAX_CHANNELS {
+            return Err(Error::NotFound {
+                item: Item::SyncManager,
+                index: Some(usize::from(index)),
+            });
+        }
+
+        self.read(RegisterAddress::sync_manager(index))
+            .receive::<SyncManagerChannel>(self.maindevice)
+            .await
+            .map(|sm| SyncManagerConfig::new(index, sm))
+    }
+
+    /// Read back all enabled FMMUs and Sync Managers.
+    ///
+    /// Only the channels the ESC reports as supported are read. Comparing the result against the
+    /// intended configuration after [`MainDevice::init`](crate::MainDevice::init) catches writes
+    /// that silently failed to apply.
+    pub async fn config_snapshot(&self) -> Result<DeviceConfigSnapshot, Error> {
+        let [fmmu_count, sm_count] = self
+            .read(RegisterAddress::FmmuCount)
+            .receive::<[u8; 2]>(self.maindevice)
+            .await?;
+
+        let mut snapshot = DeviceConfigSnapshot {
+            fmmus: heapless::Vec::new(),
+            sync_managers: heapless::Vec::new(),
+        };
+
+        for index in 0..fmmu_count.min(config_snapshot::MAX_CHANNELS as u8) {
+            let fmmu = self.fmmu_config(index).await?;
+
+            if fmmu.enable {
+                // Can't fail: at most `MAX_CHANNELS` are read
+                let _ = snapshot.fmmus.push(fmmu);
+            }
+        }
+
+        for index in 0..sm_count.min(config_snapshot::MAX_CHANNELS as u8) {
+            let sm = self.sync_manager_config(index).await?;
+
+            if sm.enable {
+                let _ = snapshot.sync_managers.push(sm);
+            }
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Read a register.
+    ///
+    /// Note that while this method is marked safe, raw alterations to SubDevice config or behaviour can
+    /// break higher level interactions with EtherCrab.
+    pub async fn register_read<T>(&self, register: impl Into<u16>) -> Result<T, Error>
+    where
+        T: EtherCrabWireReadSized,
+    {
+        self.read(register.into()).receive(self.maindevice).await
+    }
+
+    /// Write a register.
+    ///
+    /// Note that while this method is marked safe, raw alterations to SubDevice config or behaviour can
+    /// break higher level interactions with EtherCrab.
+    pub async fn register_write<T>(&self, register: impl Into<u16>, value: T) -> Result<T, Error>
+    where
+        T: EtherCrabWireReadWrite,
+    {
+        self.write(register.into())
+            .send_receive(self.maindevice, value)
+            .await
+    }
+
+    pub(crate) async fn wait_for_state(&self, desired_state: SubDeviceState) -> Result<(), Error> {
+        async {
+            loop {
+                let status = self
+                    .read(RegisterAddress::AlStatus)
+                    .ignore_wkc()
+                    .receive::<AlControl>(self.maindevice)
+                    .await?;
+
+                if status.state == desired_state {
+                    break Ok(());
+                }
+
+                self.maindevice.timeouts.loop_tick().await;
+            }
+        }
+        .timeout(self.maindevice.timeouts.state_transition)
+        .await
+    }
+
+    pub(crate) fn write(&self, register: impl Into<u16>) -> WrappedWrite {
+        Command::fpwr(self.configured_address, register.into())
+    }
+
+    pub(crate) fn read(&self, register: impl Into<u16>) -> WrappedRead {
+        Command::fprd(self.configured_address, register.into())
+    }
+
+    pub(crate) async fn request_subdevice_state_nowait(
+        &self,
+        desired_state: SubDeviceState,
+    ) -> Result<(), Error> {
+        fmt::debug!(
+            "Set state {} for SubDevice address {:#04x}",
+            desired_state,
+            self.configured_address
+        );
+
+        // Send state request
+        let response = self
+            .write(RegisterAddress::AlControl)
+            .send_receive::<AlControl>(self.maindevice, AlControl::new(desired_state))
+            .await?;
+
+        if response.error {
+            let error = self
+                .read(RegisterAddress::AlStatus)
+                .receive::<AlStatusCode>(self.maindevice)
+                .await?;
+
+            fmt::error!(
+                "Error occurred transitioning SubDevice {:#06x} to {:?}: {}",
+                self.configured_address,
+                desired_state,
+                error,
+            );
+
+            return Err(Error::StateTransition);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn request_subdevice_state(
+        &self,
+        desired_state: SubDeviceState,
+    ) -> Result<(), Error> {
+        self.request_subdevice_state_nowait(desired_state).await?;
+
+        self.wait_for_state(desired_state).await
+    }
+
+    pub(crate) async fn set_eeprom_mode(&self, mode: SiiOwner) -> Result<(), Error> {
+        // ETG1000.4 Table 48 – SubDevice information interface access
+        // A value of 2 sets owner to Master (not PDI) and cancels access
+        self.write(RegisterAddress::SiiConfig)
+            .send(self.maindevice, 2u16)
+            .await?;
+
+        self.write(RegisterAddress::SiiConfig)
+            .send(self.maindevice, mode)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        MainDeviceConfig, PduStorage, Timeouts,
+        command::{FPRD, FPWR},
+        fake_network::{FakeNetwork, FakePdu},
+    };
+    use core::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn write_batch_single_frame() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(64) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let subdevice = SubDevice {
+            configured_address: 0x1001,
+            ..SubDevice::default()
+        };
+
+        let writes: [(u16, &[u8]); 3] = [
+            (RegisterAddress::AlControl.into(), &[0x02, 0x00]),
+            (RegisterAddress::Fmmu0.into(), &[0xaa; 16]),
+            (RegisterAddress::Sm0.into(), &[0xbb; 8]),
+        ];
+
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        for (wkcs, expected) in [
+            ([1, 1, 1], Ok(())),
+            (
+                [1, 1, 0],
+                Err(Error::WorkingCounter {
+                    expected: 1,
+                    received: 0,
+                }),
+            ),
+        ] {
+            let mut fut = pin!(subdevice.write_batch(&maindevice, &writes));
+
+            assert!(fut.as_mut().poll(&mut ctx).is_pending());
+
+            let mut frame = network.next_frame().expect("need a frame");
+
+            // All writes should be in the one frame
+            assert!(network.next_frame().is_none());
+
+            frame.set_wkcs(&wkcs);
+
+            assert_eq!(network.reply(frame), crate::ReceiveAction::Processed);
+
+            assert_eq!(fut.poll(&mut ctx), Poll::Ready(expected));
+        }
+    }
+
+    #[test]
+    fn link_change_counts() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run(subdevice.link_change_counts(), |pdu| {
+                assert_eq!(pdu.command, FPRD);
+                assert_eq!(pdu.adp, 0x1001);
+                assert_eq!(pdu.ado, 0x0310);
+
+                // Port 1 is flapping, the others have each lost link once
+                pdu.data.copy_from_slice(&[1, 0xfa, 1, 1]);
+
+                1
+            });
+
+        assert_eq!(result, Ok([1, 250, 1, 1]));
+    }
+
+    #[test]
+    fn ping() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let clock = core::cell::Cell::new(1_000_000u64);
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let mut ping = |delay_ns: u64| {
+            network.run(subdevice.ping(|| clock.get()), |pdu| {
+                assert_eq!(pdu.command, FPRD);
+                assert_eq!(pdu.adp, 0x1001);
+                assert_eq!(pdu.ado, u16::from(RegisterAddress::Type));
+
+                // Response takes `delay_ns` to come back
+                clock.set(clock.get() + delay_ns);
+
+                1
+            })
+        };
+
+        assert_eq!(ping(25_000), Ok(Duration::from_micros(25)));
+        assert_eq!(ping(1_500_000), Ok(Duration::from_micros(1500)));
+    }
+
+    #[test]
+    fn explicit_device_id() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let mut registers = [0u8; 0x200];
+
+        // PRE-OP, no error
+        registers[0x0130] = 0x02;
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run(subdevice.explicit_device_id(), |pdu| {
+                assert_eq!(pdu.adp, 0x1001);
+
+                match pdu.command {
+                    FPRD => pdu.read_from(&registers),
+                    FPWR => {
+                        assert_eq!(pdu.ado, u16::from(RegisterAddress::AlControl));
+
+                        pdu.write_to(&mut registers);
+
+                        let id_request = registers[0x0120] & 0x20 != 0;
+
+                        // Requested state must not change
+                        assert_eq!(registers[0x0120] & 0x0f, 0x02);
+
+                        // Emulate the ESC loading the ID, e.g. a rotary switch set to 42, into the AL
+                        // status code register
+                        registers[0x0130] = 0x02 | if id_request { 0x20 } else { 0 };
+                        registers[0x0134..0x0136].copy_from_slice(
+                            &(if id_request { 0x002au16 } else { 0 }).to_le_bytes(),
+                        );
+                    }
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+
+                1
+            });
+
+        assert_eq!(result, Ok(0x002a));
+
+        // Request was cleared afterwards
+        assert_eq!(registers[0x0120], 0x02);
+        assert_eq!(registers[0x0130], 0x02);
+    }
+
+    #[test]
+    fn watchdog_counter() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let result = FakeNetwork::new(&mut tx, &mut rx).run(subdevice.watchdog_counter(), |pdu| {
+            assert_eq!(pdu.command, FPRD);
+            assert_eq!(pdu.adp, 0x1001);
+            assert_eq!(pdu.ado, 0x0442);
+
+            // Process data watchdog expired 3 times, PDI watchdog once
+            pdu.data.copy_from_slice(&[0x03, 0x01]);
+
+            1
+        });
+
+        assert_eq!(result, Ok(0x0103));
+    }
+
+    #[test]
+    fn sync0_start_zero_cycle_time() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, _rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        maindevice.set_dc_ref_address(0x1000);
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        assert_eq!(
+            pin!(subdevice.set_sync0_start_aligned(Duration::ZERO, Duration::from_micros(100)))
+                .poll(&mut ctx),
+            Poll::Ready(Err(Error::DistributedClock(
+                DistributedClockError::InvalidCycleTime
+            )))
+        );
+
+        // Nothing was sent
+        assert!(tx.next_sendable_frame().is_none());
+    }
+
+    #[test]
+    fn sync0_start_aligned() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        maindevice.set_dc_ref_address(0x1000);
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        const CYCLE: u64 = 1_000_000;
+        const REFERENCE_TIME: u64 = 1_234_567_890;
+
+        let mut written = None;
+
+        let result = FakeNetwork::new(&mut tx, &mut rx).run(
+            subdevice
+                .set_sync0_start_aligned(Duration::from_nanos(CYCLE), Duration::from_micros(100)),
+            |pdu| {
+                match (pdu.command, pdu.adp, pdu.ado) {
+                    (FPRD, 0x1000, 0x0910) => {
+                        pdu.data.copy_from_slice(&REFERENCE_TIME.to_le_bytes())
+                    }
+                    (FPWR, 0x1001, 0x0990) => {
+                        written = Some(u64::from_le_bytes(pdu.data[..].try_into().unwrap()))
+                    }
+                    other => panic!("unexpected PDU {:#06x?}", other),
+                }
+
+                1
+            },
+        );
+
+        // Next whole cycle at least 100 us after the reference time
+        assert_eq!(result, Ok(1_235_000_000));
+        assert_eq!(written, Some(1_235_000_000));
+        assert_eq!(written.unwrap() % CYCLE, 0);
+    }
+
+    #[test]
+    fn set_port_recovery() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        // Port 0 auto close, port 1 forced closed, ports 2 and 3 auto. EBUS low jitter and fast
+        // link down are both enabled.
+        let current = [0x01, 0b0000_1101, 0x4f, 0x00];
+
+        for (enabled, write_wkc, expected_ports, expected) in [
+            (true, 1, 0b0000_1100, Ok(())),
+            (false, 1, 0b0101_1101, Ok(())),
+            (
+                true,
+                0,
+                0b0000_1100,
+                Err(Error::WorkingCounter {
+                    expected: 1,
+                    received: 0,
+                }),
+            ),
+        ] {
+            let mut fut = pin!(subdevice.set_port_recovery(enabled));
+
+            assert!(fut.as_mut().poll(&mut ctx).is_pending());
+
+            network.exchange(|pdu| {
+                assert_eq!(pdu.command, FPRD);
+                assert_eq!(pdu.adp, 0x1001);
+                assert_eq!(pdu.ado, 0x0100);
+
+                pdu.data.copy_from_slice(&current);
+
+                1
+            });
+
+            assert!(fut.as_mut().poll(&mut ctx).is_pending());
+
+            network.exchange(|pdu| {
+                assert_eq!(pdu.command, FPWR);
+                assert_eq!(pdu.adp, 0x1001);
+                assert_eq!(pdu.ado, 0x0100);
+                // Only the loop control bits change
+                assert_eq!(pdu.data, &[0x01, expected_ports, 0x4f, 0x00]);
+
+                write_wkc
+            });
+
+            assert_eq!(fut.poll(&mut ctx), Poll::Ready(expected));
+        }
+    }
+
+    #[test]
+    fn read_dc_offset() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let mut registers = vec![0u8; 0x1000];
+
+        let offset = -1_234_567_890_123i64;
+
+        let result = FakeNetwork::new(&mut tx, &mut rx).run(
+            async {
+                subdevice
+                    .register_write(RegisterAddress::DcSystemTimeOffset, offset)
+                    .await?;
+
+                subdevice.read_dc_offset().await
+            },
+            |pdu| {
+                assert_eq!(pdu.ado, 0x0920);
+
+                match pdu.command {
+                    FPRD => pdu.read_from(&registers),
+                    FPWR => pdu.write_to(&mut registers),
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+
+                1
+            },
+        );
+
+        assert_eq!(result, Ok(offset));
+        assert_eq!(registers[0x0920..0x0928], offset.to_le_bytes());
+    }
+
+    #[test]
+    fn transmission_delay() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(16) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        let mut registers = vec![0u8; 0x1000];
+
+        registers[0x0928..0x092c].copy_from_slice(&1_234u32.to_le_bytes());
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run(subdevice.transmission_delay(), |pdu| {
+                assert_eq!(pdu.command, FPRD);
+                assert_eq!(pdu.adp, 0x1001);
+                assert_eq!(pdu.ado, 0x0928);
+
+                pdu.read_from(&registers);
+
+                1
+            });
+
+        assert_eq!(result, Ok(1_234));
+    }
+
+    #[test]
+    fn config_snapshot_matches_written() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = SubDeviceRef::new(&maindevice, 0x1001, ());
+
+        // ESC with 3 FMMUs and 4 SMs
+        let mut registers = vec![0u8; 0x1000];
+        registers[0x0004] = 3;
+        registers[0x0005] = 4;
+
+        let sm = SyncManagerChannel {
+            physical_start_address: 0x1100,
+            length_bytes: 4,
+            control: crate::sync_manager_channel::Control {
+                direction: crate::sync_manager_channel::Direction::MasterWrite,
+                watchdog_enable: true,
+                ..Default::default()
+            },
+            status: Default::default(),
+            enable: crate::sync_manager_channel::Enable {
+                enable: true,
+                ..Default::default()
+            },
+        };
+
+        let fmmu = Fmmu {
+            logical_start_address: 0x0000_0010,
+            length_bytes: 4,
+            logical_start_bit: 0,
+            logical_end_bit: 7,
+            physical_start_address: 0x1100,
+            physical_start_bit: 0,
+            read_enable: false,
+            write_enable: true,
+            enable: true,
+        };
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        // Answer each PDU from `registers`
+        let mut answer = |pdu: &mut FakePdu<'_>| {
+            assert_eq!(pdu.adp, 0x1001);
+
+            match pdu.command {
+                FPRD => pdu.read_from(&registers),
+                FPWR => pdu.write_to(&mut registers),
+                other => panic!("unexpected command {:#04x}", other),
+            }
+
+            1
+        };
+
+        // Configure SM2 and FMMU0. SM1 is written but left disabled.
+        network
+            .run(
+                async {
+                    subdevice
+                        .write(RegisterAddress::sync_manager(2))
+                        .send(&maindevice, sm)
+                        .await?;
+                    subdevice
+                        .write(RegisterAddress::sync_manager(1))
+                        .send(
+                            &maindevice,
+                            SyncManagerChannel {
+                                enable: Default::default(),
+                                ..sm
+                            },
+                        )
+                        .await?;
+                    subdevice
+                        .write(RegisterAddress::fmmu(0))
+                        .send(&maindevice, fmmu)
+                        .await
+                },
+                &mut answer,
+            )
+            .expect("configure");
+
+        let snapshot = network
+            .run(subdevice.config_snapshot(), &mut answer)
+            .expect("snapshot");
+
+        assert_eq!(snapshot.fmmus.as_slice(), &[FmmuConfig::new(0, fmmu)]);
+        assert_eq!(
+            snapshot.sync_managers.as_slice(),
+            &[SyncManagerConfig {
+                index: 2,
+                physical_start_address: 0x1100,
+                length_bytes: 4,
+                // MasterWrite, watchdog enabled
+                control: 0b0100_0100,
+                enable: true,
+            }]
+        );
+        assert_eq!(snapshot.sync_manager(1), None);
+        assert_eq!(snapshot.fmmu(0).map(|f| f.write_enable), Some(true));
+    }
+
+    const WRITE_MAILBOX: u16 = 0x1000;
+    const READ_MAILBOX: u16 = 0x1080;
+    const MAILBOX_LEN: u16 = 128;
+    /// SM1 status register.
+    const READ_MAILBOX_STATUS: u16 = 0x080d;
+    const MAILBOX_FULL: u8 = 0x08;
+
+    /// A SubDevice with CoE mailboxes on SM0 and SM1.
+    fn subdevice_with_mailboxes<'maindevice>(
+        maindevice: &'maindevice MainDevice<'maindevice>,
+    ) -> SubDeviceRef<'maindevice, Box<SubDevice>> {
+        let mut subdevice = SubDevice {
+            configured_address: 0x1001,
+            ..SubDevice::default()
+        };
+
+        subdevice.config.mailbox.write = Some(Mailbox {
+            address: WRITE_MAILBOX,
+            len: MAILBOX_LEN,
+            sync_manager: 0,
+        });
+        subdevice.config.mailbox.read = Some(Mailbox {
+            address: READ_MAILBOX,
+            len: MAILBOX_LEN,
+            sync_manager: 1,
+        });
+
+        SubDeviceRef::new(maindevice, 0x1001, Box::new(subdevice))
+    }
+
+    /// A SubDevice with CoE mailboxes on SM0 and SM1 and a single object which is too large for an
+    /// expedited upload.
+    struct FakeSdoServer {
+        registers: Vec<u8>,
+        /// Object dictionary entries, keyed by index and sub-index.
+        objects: HashMap<(u16, u8), Vec<u8>>,
+        /// Object being uploaded by a segmented upload.
+        upload: (u16, u8),
+        /// Offset of the next segment to send if a segmented upload is in progress.
+        segment: Option<usize>,
+        aborted: Vec<(u16, u8)>,
+    }
+
+    impl FakeSdoServer {
+        fn respond(&mut self, response: &[u8]) {
+            let start = usize::from(READ_MAILBOX);
+
+            self.registers[start..start + response.len()].copy_from_slice(response);
+            self.registers[usize::from(READ_MAILBOX_STATUS)] |= MAILBOX_FULL;
+        }
+
+        /// Answer a register read or write, returning the working counter.
+        fn answer(&mut self, pdu: &mut FakePdu<'_>) -> u16 {
+            match pdu.command {
+                FPRD => self.read(pdu.register(), pdu.data),
+                FPWR => self.write(pdu.register(), pdu.data),
+                other => panic!("unexpected command {:#04x}", other),
+            }
+
+            1
+        }
+
+        fn read(&mut self, address: usize, data: &mut [u8]) {
+            data.copy_from_slice(&self.registers[address..address + data.len()]);
+
+            if address == usize::from(READ_MAILBOX) {
+                self.registers[usize::from(READ_MAILBOX_STATUS)] &= !MAILBOX_FULL;
+            }
+        }
+
+        fn write(&mut self, address: usize, data: &[u8]) {
+            self.registers[address..address + data.len()].copy_from_slice(data);
+
+            if address != usize::from(WRITE_MAILBOX) {
+                return;
+            }
+
+            let index = u16::from_le_bytes([data[9], data[10]]);
+            let sub_index = data[11];
+
+            // Mailbox header followed by an SDO response CoE header
+            let mut response = vec![0x0a, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x30];
+
+            match data[8] >> 5 {
+                // A real SDO server would also reject this while waiting for the next segment
+                0x02 if self.segment.is_some() => {
+                    response.push(0x80);
+                    response.extend_from_slice(&index.to_le_bytes());
+                    response.push(sub_index);
+                    response
+                        .extend_from_slice(&u32::from(CoeAbortCode::InvalidCommand).to_le_bytes());
+
+                    self.respond(&response);
+                }
+                // Initiate upload of an object that doesn't exist
+                0x02 if !self.objects.contains_key(&(index, sub_index)) => {
+                    response.push(0x80);
+                    response.extend_from_slice(&index.to_le_bytes());
+                    response.push(sub_index);
+                    response.extend_from_slice(&u32::from(CoeAbortCode::NotFound).to_le_bytes());
+
+                    self.respond(&response);
+                }
+                // Initiate upload, expedited if the object fits in the response
+                0x02 => {
+                    let object = &self.objects[&(index, sub_index)];
+
+                    if object.len() <= 4 {
+                        let unused = 4 - object.len() as u8;
+
+                        response.push(0x43 | (unused << 2));
+                        response.extend_from_slice(&index.to_le_bytes());
+                        response.push(sub_index);
+                        response.extend_from_slice(object);
+                        response.resize(16, 0);
+                    } else {
+                        self.upload = (index, sub_index);
+                        self.segment = Some(0);
+
+                        response.push(0x41);
+                        response.extend_from_slice(&index.to_le_bytes());
+                        response.push(sub_index);
+                        response.extend_from_slice(&(object.len() as u32).to_le_bytes());
+                    }
+
+                    self.respond(&response);
+                }
+                // Upload segment
+                0x03 => {
+                    let offset = self.segment.expect("no upload in progress");
+                    let object = &self.objects[&self.upload];
+                    let chunk = &object[offset..(offset + 7).min(object.len())];
+                    let is_last = offset + chunk.len() == object.len();
+                    let toggle = data[8] & 0x10;
+                    let unused = 7 - chunk.len() as u8;
+
+                    response.push(0x60 | toggle | (unused << 1) | u8::from(is_last));
+                    response.extend_from_slice(chunk);
+                    response.resize(16, 0);
+
+                    self.segment = (!is_last).then_some(offset + chunk.len());
+
+                    self.respond(&response);
+                }
+                // Abort, which has no response
+                0x04 => {
+                    self.segment = None;
+                    self.aborted.push((index, sub_index));
+                }
+                other => panic!("unexpected SDO command {:#04x}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn sdo_upload_cancelled_mid_segment() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(128) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = subdevice_with_mailboxes(&maindevice);
+
+        let mut server = FakeSdoServer {
+            registers: vec![0u8; 0x2000],
+            objects: HashMap::from([((0x2000, 0), (0..20).collect())]),
+            upload: (0, 0),
+            segment: None,
+            aborted: Vec::new(),
+        };
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        // Drive an upload, answering each frame from the fake SubDevice. Returns `None` if `stop`
+        // returned true before the upload completed.
+        let mut run =
+            |mut fut: core::pin::Pin<&mut dyn Future<Output = Result<[u8; 20], Error>>>,
+             stop: fn(&FakeSdoServer) -> bool| {
+                loop {
+                    if let Some(result) = network.step(fut.as_mut(), |pdu| server.answer(pdu)) {
+                        break Some(result);
+                    }
+
+                    if stop(&server) {
+                        break None;
+                    }
+                }
+            };
+
+        // Drop the upload after the SubDevice has responded with the first segment, leaving the
+        // response unread in its OUT mailbox.
+        let cancelled = run(pin!(subdevice.sdo_read::<[u8; 20]>(0x2000, 0)), |server| {
+            server.segment == Some(7)
+        });
+
+        assert_eq!(cancelled, None);
+
+        let result = run(pin!(subdevice.sdo_read::<[u8; 20]>(0x2000, 0)), |_| false);
+
+        let expected: [u8; 20] = core::array::from_fn(|i| i as u8);
+
+        assert_eq!(result, Some(Ok(expected)));
+        assert_eq!(server.aborted, [(0x2000, 0)]);
+        assert_eq!(server.segment, None);
+    }
+
+    #[test]
+    fn concurrent_sdo_reads_serialised() {
+        crate::test_logger();
+
+        // SDOs must not interleave even if the mailbox allows more than one transaction at once
+        for mailbox_concurrency in [1, 2] {
+            concurrent_sdo_reads(mailbox_concurrency);
+        }
+    }
+
+    fn concurrent_sdo_reads(mailbox_concurrency: u8) {
+        let storage = PduStorage::<2, { PduStorage::element_size(128) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts::default(),
+            MainDeviceConfig {
+                mailbox_concurrency,
+                ..MainDeviceConfig::default()
+            },
+        );
+
+        let subdevice = subdevice_with_mailboxes(&maindevice);
+
+        let mut server = FakeSdoServer {
+            registers: vec![0u8; 0x2000],
+            objects: HashMap::from([((0x2000, 0), (0..20).collect())]),
+            upload: (0, 0),
+            segment: None,
+            aborted: Vec::new(),
+        };
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        // Both uploads need several segments, so would interleave if not serialised
+        let mut a = pin!(subdevice.sdo_read::<[u8; 20]>(0x2000, 0));
+        let mut b = pin!(subdevice.sdo_read::<[u8; 20]>(0x2000, 0));
+
+        let mut result_a = None;
+        let mut result_b = None;
+
+        // SDO command specifiers written to the SubDevice IN mailbox, in order
+        let mut requests = Vec::new();
+
+        while result_a.is_none() || result_b.is_none() {
+            if result_a.is_none() {
+                if let Poll::Ready(result) = a.as_mut().poll(&mut ctx) {
+                    result_a = Some(result);
+                }
+            }
+
+            if result_b.is_none() {
+                if let Poll::Ready(result) = b.as_mut().poll(&mut ctx) {
+                    result_b = Some(result);
+                }
+            }
+
+            while let Some(mut frame) = network.next_frame() {
+                frame.respond(|pdu| {
+                    if pdu.command == FPWR && pdu.ado == WRITE_MAILBOX {
+                        requests.push(pdu.data[8] >> 5);
+                    }
+
+                    server.answer(pdu)
+                });
+
+                assert_eq!(network.reply(frame), crate::ReceiveAction::Processed);
+            }
+        }
+
+        let expected: [u8; 20] = core::array::from_fn(|i| i as u8);
+
+        assert_eq!(result_a, Some(Ok(expected)));
+        assert_eq!(result_b, Some(Ok(expected)));
+        assert!(server.aborted.is_empty());
+
+        // Initiate upload, then 3 segments for the first read before the second read starts
+        assert_eq!(requests, [2, 3, 3, 3, 2, 3, 3, 3]);
+    }
+
+    #[test]
+    fn sync_capabilities() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(128) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = subdevice_with_mailboxes(&maindevice);
+
+        // An output only SubDevice supporting free run, SM synchronous and SYNC0 modes with a
+        // 125us minimum cycle time. It has no `0x1C33` object.
+        let mut server = FakeSdoServer {
+            registers: vec![0u8; 0x2000],
+            objects: HashMap::from([
+                ((0x1c32, 4), 0x0007u16.to_le_bytes().to_vec()),
+                ((0x1c32, 5), 125_000u32.to_le_bytes().to_vec()),
+                ((0x1c32, 6), 40_000u32.to_le_bytes().to_vec()),
+            ]),
+            upload: (0, 0),
+            segment: None,
+            aborted: Vec::new(),
+        };
+
+        let outputs_only = FakeNetwork::new(&mut tx, &mut rx)
+            .run(subdevice.sync_capabilities(), |pdu| server.answer(pdu));
+
+        assert_eq!(
+            outputs_only,
+            Ok(SyncCapabilities {
+                sync_modes: SyncModes {
+                    free_run: true,
+                    sm_synchronous: true,
+                    dc_sync0: true,
+                    dc_sync1: false,
+                    dc_subordinate: false,
+                },
+                min_cycle_time: Duration::from_micros(125),
+                output_calc_and_copy_time: Duration::from_micros(40),
+                input_calc_and_copy_time: None,
+            })
+        );
+
+        // Inputs need a longer cycle than outputs
+        server
+            .objects
+            .insert((0x1c33, 5), 250_000u32.to_le_bytes().to_vec());
+        server
+            .objects
+            .insert((0x1c33, 6), 15_000u32.to_le_bytes().to_vec());
+
+        let capabilities = FakeNetwork::new(&mut tx, &mut rx)
+            .run(subdevice.sync_capabilities(), |pdu| server.answer(pdu))
+            .expect("sync capabilities");
+
+        assert_eq!(capabilities.min_cycle_time, Duration::from_micros(250));
+        assert_eq!(
+            capabilities.input_calc_and_copy_time,
+            Some(Duration::from_micros(15))
+        );
+        assert!(server.aborted.is_empty());
+    }
+
+    #[test]
+    fn mailbox_transaction_delayed_response() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(128) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut subdevice = subdevice_with_mailboxes(&maindevice);
+
+        // Same as a SubDevice found during init
+        subdevice.mailbox_counter = AtomicU8::new(1);
+
+        let mut registers = vec![0u8; 0x2000];
+
+        // Mailbox header with a counter of 7, which should be replaced, then 4 bytes of payload
+        let request = [0x04, 0x00, 0x00, 0x00, 0x00, 0x73, 0x01, 0x02, 0x03, 0x04];
+
+        let mut sent_counter: Option<u8> = None;
+        let mut status_polls_after_request = 0;
+
+        let response = FakeNetwork::new(&mut tx, &mut rx)
+            .run(subdevice.mailbox_transaction(&request), |pdu| {
+                match pdu.command {
+                    FPRD => {
+                        if pdu.ado == READ_MAILBOX_STATUS {
+                            if let Some(counter) = sent_counter {
+                                status_polls_after_request += 1;
+
+                                let respond_with = match status_polls_after_request {
+                                    // Left over from an earlier, cancelled transaction
+                                    2 => Some(counter % 7 + 1),
+                                    5 => Some(counter),
+                                    _ => None,
+                                };
+
+                                if let Some(counter) = respond_with {
+                                    let start = usize::from(READ_MAILBOX);
+
+                                    registers[start..start + 10].copy_from_slice(&[
+                                        0x04,
+                                        0x00,
+                                        0x00,
+                                        0x00,
+                                        0x00,
+                                        0x03 | (counter << 4),
+                                        0xaa,
+                                        0xbb,
+                                        0xcc,
+                                        counter,
+                                    ]);
+                                    registers[usize::from(READ_MAILBOX_STATUS)] |= MAILBOX_FULL;
+                                }
+                            }
+                        }
+
+                        pdu.read_from(&registers);
+
+                        if pdu.ado == READ_MAILBOX {
+                            registers[usize::from(READ_MAILBOX_STATUS)] &= !MAILBOX_FULL;
+                        }
+                    }
+                    FPWR => {
+                        assert_eq!(pdu.ado, WRITE_MAILBOX);
+                        assert_eq!(pdu.data.len(), usize::from(MAILBOX_LEN));
+                        assert_eq!(pdu.data[6..10], request[6..10]);
+
+                        sent_counter = Some((pdu.data[5] >> 4) & 0x07);
+                    }
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+
+                1
+            })
+            .map(|response| response[0..10].to_vec());
+
+        let counter = sent_counter.expect("request sent");
+
+        // Counter is taken from the SubDevice, not the request
+        assert_eq!(counter, 1);
+        assert_eq!(
+            response,
+            Ok(vec![
+                0x04,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                0x03 | (counter << 4),
+                0xaa,
+                0xbb,
+                0xcc,
+                counter
+            ])
+        );
+        assert_eq!(status_polls_after_request, 5);
+    }
+
+    #[test]
+    fn mailbox_transaction_counter_zero() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(128) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut subdevice = subdevice_with_mailboxes(&maindevice);
+
+        subdevice.mailbox_counter = AtomicU8::new(1);
+
+        let mut registers = vec![0u8; 0x2000];
+
+        let request = [0x04, 0x00, 0x00, 0x00, 0x00, 0x03, 0x01, 0x02, 0x03, 0x04];
+
+        // A SubDevice that doesn't implement the mailbox counter always responds with 0
+        let response = FakeNetwork::new(&mut tx, &mut rx)
+            .run(subdevice.mailbox_transaction(&request), |pdu| {
+                match pdu.command {
+                    FPRD => {
+                        pdu.read_from(&registers);
+
+                        if pdu.ado == READ_MAILBOX {
+                            registers[usize::from(READ_MAILBOX_STATUS)] &= !MAILBOX_FULL;
+                        }
+                    }
+                    FPWR => {
+                        let start = usize::from(READ_MAILBOX);
+
+                        registers[start..start + 8]
+                            .copy_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x03, 0xaa, 0xbb]);
+                        registers[usize::from(READ_MAILBOX_STATUS)] |= MAILBOX_FULL;
+                    }
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+
+                1
+            })
+            .map(|response| response[0..8].to_vec());
+
+        assert_eq!(
+            response,
+            Ok(vec![0x02, 0x00, 0x00, 0x00, 0x00, 0x03, 0xaa, 0xbb])
+        );
+    }
+
+    #[test]
+    fn mailbox_transaction_short_request() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(128) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevice = subdevice_with_mailboxes(&maindevice);
+
+        let result = FakeNetwork::new(&mut tx, &mut rx)
+            .run(subdevice.mailbox_transaction(&[0x00, 0x00, 0x00]), |pdu| {
+                panic!("unexpected PDU {:?}", pdu.command)
+            })
+            .map(|response| response.to_vec());
+
+        assert_eq!(result, Err(Error::Mailbox(MailboxError::RequestTooShort)));
+    }
+
+    #[test]
+    fn sdo_write_mailbox_retries() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(128) }>::new();
+
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts {
+                mailbox_echo: Duration::from_millis(20),
+                ..Timeouts::default()
+            },
+            MainDeviceConfig {
+                mailbox_retries: 2,
+                ..MainDeviceConfig::default()
+            },
+        );
+
+        let mut subdevice = subdevice_with_mailboxes(&maindevice);
+
+        subdevice.mailbox_counter = AtomicU8::new(1);
+
+        let mut registers = vec![0u8; 0x2000];
+        let mut sent_counters = Vec::new();
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run(subdevice.sdo_write(0x1c12, 0, 0u8), |pdu| {
+                match pdu.command {
+                    FPRD => {
+                        pdu.read_from(&registers);
+
+                        if pdu.ado == READ_MAILBOX {
+                            registers[usize::from(READ_MAILBOX_STATUS)] &= !MAILBOX_FULL;
+                        }
+
+                        1
+                    }
+                    FPWR => {
+                        assert_eq!(pdu.ado, WRITE_MAILBOX);
+
+                        let counter = (pdu.data[5] >> 4) & 0x07;
+
+                        sent_counters.push(counter);
+
+                        match sent_counters.len() {
+                            // Mailbox is busy so the write isn't accepted
+                            1 => 0,
+                            // Request is accepted but the response is lost
+                            2 => 1,
+                            _ => {
+                                let start = usize::from(READ_MAILBOX);
+
+                                registers[start..start + 16].copy_from_slice(&[
+                                    0x0a,
+                                    0x00,
+                                    0x00,
+                                    0x00,
+                                    0x00,
+                                    0x03 | (counter << 4),
+                                    0x00,
+                                    0x30,
+                                    0x60,
+                                    0x12,
+                                    0x1c,
+                                    0x00,
+                                    0x00,
+                                    0x00,
+                                    0x00,
+                                    0x00,
+                                ]);
+                                registers[usize::from(READ_MAILBOX_STATUS)] |= MAILBOX_FULL;
+
+                                1
+                            }
+                        }
+                    }
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+            });
+
+        assert_eq!(result, Ok(()));
+        // Each retry is sent with a new counter
+        assert_eq!(sent_counters, [1, 2, 3]);
+    }
+
+    #[test]
+    fn revision_mismatch() {
+        crate::test_logger();
+
+        fn check(coe_revision: u32) -> Result<Option<(u32, u32)>, Error> {
+            let storage = PduStorage::<1, { PduStorage::element_size(128) }>::new();
+
+            let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+
+            let maindevice =
+                MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+            let mut subdevice = subdevice_with_mailboxes(&maindevice);
+
+            subdevice.identity.revision = 0x0011_0000;
+
+            let mut registers = vec![0u8; 0x2000];
+
+            FakeNetwork::new(&mut tx, &mut rx).run(subdevice.revision_mismatch(), |pdu| {
+                match pdu.command {
+                    FPRD => {
+                        pdu.read_from(&registers);
+
+                        if pdu.ado == READ_MAILBOX {
+                            registers[usize::from(READ_MAILBOX_STATUS)] &= !MAILBOX_FULL;
+                        }
+                    }
+                    FPWR => {
+                        assert_eq!(pdu.ado, WRITE_MAILBOX);
+                        // Upload request for 0x1018:03
+                        assert_eq!(pdu.data[8..12], [0x40, 0x18, 0x10, 0x03]);
+
+                        let start = usize::from(READ_MAILBOX);
+
+                        // Expedited upload response with 4 bytes of data
+                        registers[start..start + 12].copy_from_slice(&[
+                            0x0a, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x30, 0x43, 0x18, 0x10, 0x03,
+                        ]);
+                        registers[start + 12..start + 16]
+                            .copy_from_slice(&coe_revision.to_le_bytes());
+                        registers[usize::from(READ_MAILBOX_STATUS)] |= MAILBOX_FULL;
+                    }
+                    other => panic!("unexpected command {:#04x}", other),
+                }
+
+                1
+            })
+        }
+
+        assert_eq!(check(0x0011_0000), Ok(None));
+        assert_eq!(check(0x0012_0000), Ok(Some((0x0011_0000, 0x0012_0000))));
+    }
 }
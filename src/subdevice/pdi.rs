@@ -1,9 +1,10 @@
 use super::{IoRanges, SubDevice, SubDeviceRef};
-use crate::subdevice_group::MySyncUnsafeCell;
+use crate::{error::Error, subdevice_group::MySyncUnsafeCell};
 use core::{
     marker::PhantomData,
     ops::{Deref, DerefMut, Range},
 };
+use ethercrab_wire::{EtherCrabWireReadSized, EtherCrabWireWrite, WireError};
 
 pub struct PdiReadGuard<'a, const N: usize> {
     lock: spin::RwLockReadGuard<'a, MySyncUnsafeCell<[u8; N]>>,
@@ -21,6 +22,53 @@ impl<const N: usize> Deref for PdiReadGuard<'_, N> {
     }
 }
 
+/// Types that can be viewed in place in the Process Data Image (PDI) with
+/// [`SubDeviceRef::inputs_view`], without being copied or unpacked.
+///
+/// Implemented for `u8`, `i8` and arrays of `PdiView` types. To view a whole SubDevice's inputs at
+/// once, implement it for a `#[repr(C)]` or `#[repr(C, packed)]` struct whose layout matches the
+/// wire layout of the inputs.
+///
+/// Multi-byte fields such as `u16` in a packed struct are read in the target's native byte order,
+/// so only match the little-endian wire layout on little-endian targets. Use
+/// [`inputs`](SubDeviceRef::inputs) to decode big-endian or bit-packed data.
+///
+/// # Safety
+///
+/// Implementors must:
+///
+/// - have an alignment of `1`, which is checked at compile time by
+///   [`inputs_view`](SubDeviceRef::inputs_view),
+/// - be valid for any bit pattern, so must not contain e.g. `bool`, `char`, enums or references,
+/// - not contain any padding bytes or interior mutability.
+pub unsafe trait PdiView: Sized {}
+
+unsafe impl PdiView for u8 {}
+unsafe impl PdiView for i8 {}
+unsafe impl<T: PdiView, const N: usize> PdiView for [T; N] {}
+
+/// A typed view into part of the PDI, holding it locked for reading.
+pub struct PdiViewGuard<'a, const N: usize, T> {
+    lock: spin::RwLockReadGuard<'a, MySyncUnsafeCell<[u8; N]>>,
+    start: usize,
+    _lt: PhantomData<&'a T>,
+}
+
+impl<const N: usize, T: PdiView> Deref for PdiViewGuard<'_, N, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        let all = unsafe { &*self.lock.get() }.as_slice();
+
+        // Panics instead of reading past the end of the PDI if the range doesn't fit.
+        let bytes = &all[self.start..self.start + size_of::<T>()];
+
+        // SAFETY: `bytes` is exactly `size_of::<T>()` long, and `PdiView` guarantees `T` has an
+        // alignment of 1 and is valid for any bytes.
+        unsafe { &*bytes.as_ptr().cast::<T>() }
+    }
+}
+
 pub struct PdiIoRawReadGuard<'a, const N: usize> {
     lock: spin::RwLockReadGuard<'a, MySyncUnsafeCell<[u8; N]>>,
     ranges: IoRanges,
@@ -47,7 +95,19 @@ pub struct PdiIoRawWriteGuard<'a, const N: usize> {
     _lt: PhantomData<&'a ()>,
 }
 
-impl<const N: usize> PdiIoRawWriteGuard<'_, N> {
+impl<'a, const N: usize> PdiIoRawWriteGuard<'a, N> {
+    /// Lock `pdi` for writing, giving access to the inputs and outputs in `ranges`.
+    pub(crate) fn new(
+        pdi: &'a spin::rwlock::RwLock<MySyncUnsafeCell<[u8; N]>, crate::SpinStrategy>,
+        ranges: IoRanges,
+    ) -> Self {
+        Self {
+            lock: pdi.write(),
+            ranges,
+            _lt: PhantomData,
+        }
+    }
+
     pub fn inputs(&self) -> &[u8] {
         let all = unsafe { &*self.lock.get() }.as_slice();
 
@@ -124,7 +184,7 @@ impl<const MAX_PDI: usize> SubDeviceRef<'_, SubDevicePdi<'_, MAX_PDI>> {
     /// #     error::Error, std::tx_rx_task, MainDevice, MainDeviceConfig, PduStorage, Timeouts,
     /// # };
     /// # async fn case() {
-    /// # static PDU_STORAGE: PduStorage<8, 32> = PduStorage::new();
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
     /// # let (tx, rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
     /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
     /// let mut group = maindevice.init_single_group::<8, 8>(ethercrab::std::ethercat_now).await.expect("Init");
@@ -137,11 +197,7 @@ impl<const MAX_PDI: usize> SubDeviceRef<'_, SubDevicePdi<'_, MAX_PDI>> {
     /// # }
     /// ```
     pub fn io_raw_mut(&self) -> PdiIoRawWriteGuard<'_, MAX_PDI> {
-        PdiIoRawWriteGuard {
-            lock: self.state.pdi.write(),
-            ranges: self.state.config.io.clone(),
-            _lt: PhantomData,
-        }
+        PdiIoRawWriteGuard::new(self.state.pdi, self.state.config.io.clone())
     }
 
     /// Get a reference to both the inputs and outputs for this SubDevice in the Process Data Image
@@ -158,7 +214,7 @@ impl<const MAX_PDI: usize> SubDeviceRef<'_, SubDevicePdi<'_, MAX_PDI>> {
     /// #     error::Error, std::tx_rx_task, MainDevice, MainDeviceConfig, PduStorage, Timeouts,
     /// # };
     /// # async fn case() {
-    /// # static PDU_STORAGE: PduStorage<8, 32> = PduStorage::new();
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
     /// # let (tx, rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
     /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
     /// let mut group = maindevice.init_single_group::<8, 8>(ethercrab::std::ethercat_now).await.expect("Init");
@@ -210,6 +266,161 @@ impl<const MAX_PDI: usize> SubDeviceRef<'_, SubDevicePdi<'_, MAX_PDI>> {
             _lt: PhantomData,
         }
     }
+
+    /// Decode this SubDevice's inputs into the given type.
+    ///
+    /// Multi-byte values are little-endian on the wire. For devices that produce big-endian
+    /// values, use a struct deriving [`EtherCrabWireRead`](ethercrab_wire::EtherCrabWireRead) with
+    /// `#[wire(big_endian)]` on the relevant fields to convert them automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SubDevice's inputs are too short to hold `T`, or the input data
+    /// cannot be decoded.
+    pub fn inputs<T>(&self) -> Result<T, Error>
+    where
+        T: EtherCrabWireReadSized,
+    {
+        Ok(T::unpack_from_slice(&self.inputs_raw())?)
+    }
+
+    /// Get a typed view of this SubDevice's inputs in place in the Process Data Image (PDI).
+    ///
+    /// Unlike [`inputs`](SubDeviceRef::inputs), no data is copied or unpacked, so this is cheaper
+    /// to call every cycle for large inputs. `T` must have the same layout as the wire data. See
+    /// [`PdiView`] for the requirements.
+    ///
+    /// The PDI is locked for reading until the returned guard is dropped, so the guard must not be
+    /// held across a call to e.g. [`tx_rx`](crate::SubDeviceGroup::tx_rx).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SubDevice's inputs are too short to hold `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ethercrab::PdiView;
+    ///
+    /// /// Inputs of a two channel analog input module.
+    /// #[repr(C)]
+    /// struct AnalogInputs {
+    ///     status: [u8; 2],
+    ///     /// Little-endian channel values.
+    ///     values: [[u8; 2]; 2],
+    /// }
+    ///
+    /// // SAFETY: Only contains `u8` arrays.
+    /// unsafe impl PdiView for AnalogInputs {}
+    ///
+    /// # async fn case() {
+    /// # use ethercrab::{MainDevice, MainDeviceConfig, PduStorage, Timeouts};
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
+    /// # let (tx, rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    /// # let mut group = maindevice.init_single_group::<8, 8>(ethercrab::std::ethercat_now).await.expect("Init");
+    /// # let group = group.into_op(&maindevice).await.expect("Op");
+    /// let subdevice = group.subdevice(&maindevice, 0).expect("No device");
+    ///
+    /// let inputs = subdevice.inputs_view::<AnalogInputs>().expect("Inputs too short");
+    ///
+    /// let channel_1 = i16::from_le_bytes(inputs.values[0]);
+    /// # }
+    /// ```
+    pub fn inputs_view<T>(&self) -> Result<PdiViewGuard<'_, MAX_PDI, T>, Error>
+    where
+        T: PdiView,
+    {
+        const {
+            assert!(
+                align_of::<T>() == 1,
+                "PdiView types must have an alignment of 1"
+            )
+        };
+
+        let range = self.state.config.io.input.bytes.clone();
+
+        if range.len() < size_of::<T>() || range.start + size_of::<T>() > MAX_PDI {
+            return Err(WireError::ReadBufferTooShort.into());
+        }
+
+        Ok(PdiViewGuard {
+            lock: self.state.pdi.read(),
+            start: range.start,
+            _lt: PhantomData,
+        })
+    }
+
+    /// Decode this SubDevice's outputs into the given type.
+    ///
+    /// See [`inputs`](SubDeviceRef::inputs) for details on endianness.
+    pub fn outputs<T>(&self) -> Result<T, Error>
+    where
+        T: EtherCrabWireReadSized,
+    {
+        Ok(T::unpack_from_slice(&self.outputs_raw())?)
+    }
+
+    /// Encode a value into this SubDevice's outputs.
+    ///
+    /// See [`inputs`](SubDeviceRef::inputs) for details on endianness.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the SubDevice's outputs are too short to hold the packed value.
+    pub fn set_outputs<T>(&self, value: T) -> Result<(), Error>
+    where
+        T: EtherCrabWireWrite,
+    {
+        value.pack_to_slice(&mut self.outputs_raw_mut())?;
+
+        Ok(())
+    }
+
+    /// Read a single bit from this SubDevice's inputs.
+    ///
+    /// Bits are numbered from the least significant bit of the first input byte, so bit `9` is bit
+    /// `1` of the second byte. This matches the channel numbering of most digital input terminals,
+    /// e.g. channel 1 of an EL1008 is bit `0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bit` lies outside this SubDevice's inputs.
+    pub fn input_bit(&self, bit: usize) -> Result<bool, Error> {
+        let inputs = self.inputs_raw();
+
+        let byte = inputs
+            .get(bit / 8)
+            .ok_or(Error::Wire(WireError::ReadBufferTooShort))?;
+
+        Ok(byte & (1 << (bit % 8)) > 0)
+    }
+
+    /// Set or clear a single bit in this SubDevice's outputs.
+    ///
+    /// Bits are numbered the same way as [`input_bit`](SubDeviceRef::input_bit). All other output
+    /// bits are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bit` lies outside this SubDevice's outputs. The outputs are not changed.
+    pub fn set_output_bit(&self, bit: usize, value: bool) -> Result<(), Error> {
+        let mut outputs = self.outputs_raw_mut();
+
+        let byte = outputs
+            .get_mut(bit / 8)
+            .ok_or(Error::Wire(WireError::WriteBufferTooShort))?;
+
+        let mask = 1 << (bit % 8);
+
+        if value {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -255,4 +466,229 @@ mod tests {
             &[0xab, 0xab, 0xff, 0xab]
         );
     }
+
+    #[test]
+    fn typed_big_endian_io() {
+        #[derive(Debug, PartialEq, ethercrab_wire::EtherCrabWireReadWrite)]
+        #[wire(bytes = 3)]
+        struct AnalogIn {
+            #[wire(bytes = 2, big_endian)]
+            value: i16,
+            #[wire(bits = 1, post_skip = 7)]
+            overrange: bool,
+        }
+
+        #[derive(Debug, PartialEq, ethercrab_wire::EtherCrabWireReadWrite)]
+        #[wire(bytes = 2)]
+        struct AnalogOut {
+            #[wire(bytes = 2, big_endian)]
+            value: u16,
+        }
+
+        static PDU_STORAGE: PduStorage<8, 64> = PduStorage::new();
+        let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+        let mut sd = SubDevice::default();
+
+        sd.config.io = IoRanges {
+            input: PdiSegment { bytes: 0..3 },
+            output: PdiSegment { bytes: 3..5 },
+        };
+
+        let mut raw = [0u8; 8];
+
+        raw[0..3].copy_from_slice(&[0xfc, 0x18, 0x01]);
+
+        let pdi_storage = spin::rwlock::RwLock::new(MySyncUnsafeCell::new(raw));
+
+        let pdi = SubDevicePdi::new(&sd, &pdi_storage);
+
+        let sd_ref = SubDeviceRef::new(&maindevice, 0x1000, pdi);
+
+        assert_eq!(
+            sd_ref.inputs::<AnalogIn>(),
+            Ok(AnalogIn {
+                value: -1000,
+                overrange: true
+            })
+        );
+
+        sd_ref
+            .set_outputs(AnalogOut { value: 0x1234 })
+            .expect("Set outputs");
+
+        assert_eq!(&pdi_storage.write().get_mut()[3..5], &[0x12, 0x34]);
+        assert_eq!(
+            sd_ref.outputs::<AnalogOut>(),
+            Ok(AnalogOut { value: 0x1234 })
+        );
+
+        // Too long for the 2 byte outputs
+        assert!(sd_ref.set_outputs(0u32).is_err());
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn zero_copy_inputs_view() {
+        #[derive(Debug, Copy, Clone, PartialEq, ethercrab_wire::EtherCrabWireRead)]
+        #[repr(C, packed)]
+        #[wire(bytes = 7)]
+        struct Inputs {
+            #[wire(bytes = 1)]
+            status: u8,
+            #[wire(bytes = 2)]
+            value: i16,
+            #[wire(bytes = 4)]
+            counter: u32,
+        }
+
+        // SAFETY: Packed, so has an alignment of 1 and no padding, and only contains integers.
+        unsafe impl PdiView for Inputs {}
+
+        static PDU_STORAGE: PduStorage<8, 64> = PduStorage::new();
+        let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+        let mut sd = SubDevice::default();
+
+        sd.config.io = IoRanges {
+            input: PdiSegment { bytes: 1..8 },
+            output: PdiSegment { bytes: 8..10 },
+        };
+
+        let mut raw = [0u8; 16];
+
+        raw[1..8].copy_from_slice(&[0x01, 0x18, 0xfc, 0x78, 0x56, 0x34, 0x12]);
+
+        let pdi_storage = spin::rwlock::RwLock::new(MySyncUnsafeCell::new(raw));
+
+        let pdi = SubDevicePdi::new(&sd, &pdi_storage);
+
+        let sd_ref = SubDeviceRef::new(&maindevice, 0x1000, pdi);
+
+        let unpacked = sd_ref.inputs::<Inputs>().expect("Unpack");
+
+        {
+            let view = sd_ref.inputs_view::<Inputs>().expect("View");
+
+            assert_eq!(*view, unpacked);
+            assert_eq!({ view.value }, -1000);
+            assert_eq!({ view.counter }, 0x1234_5678);
+
+            // The view points into the PDI
+            assert_eq!(
+                core::ptr::from_ref(&*view).cast::<u8>(),
+                pdi_storage
+                    .read()
+                    .get()
+                    .cast::<u8>()
+                    .wrapping_add(1)
+                    .cast_const()
+            );
+        }
+
+        assert_eq!(*sd_ref.inputs_view::<[u8; 7]>().expect("View"), raw[1..8]);
+
+        // Longer than the 7 input bytes
+        assert!(sd_ref.inputs_view::<[u8; 8]>().is_err());
+
+        // An input range running past the end of the PDI is an error, not an out of bounds read
+        let mut sd = SubDevice::default();
+
+        sd.config.io.input = PdiSegment { bytes: 12..20 };
+
+        let pdi = SubDevicePdi::new(&sd, &pdi_storage);
+
+        let sd_ref = SubDeviceRef::new(&maindevice, 0x1000, pdi);
+
+        assert!(sd_ref.inputs_view::<[u8; 8]>().is_err());
+    }
+
+    #[test]
+    fn individual_bits() {
+        static PDU_STORAGE: PduStorage<8, 64> = PduStorage::new();
+        let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+        let mut sd = SubDevice::default();
+
+        sd.config.io = IoRanges {
+            input: PdiSegment { bytes: 1..3 },
+            output: PdiSegment { bytes: 3..5 },
+        };
+
+        let mut raw = [0u8; 8];
+
+        raw[1..3].copy_from_slice(&[0b1000_0001, 0b0000_0010]);
+        raw[3..5].copy_from_slice(&[0b0000_0000, 0b1111_1111]);
+
+        let pdi_storage = spin::rwlock::RwLock::new(MySyncUnsafeCell::new(raw));
+
+        let pdi = SubDevicePdi::new(&sd, &pdi_storage);
+
+        let sd_ref = SubDeviceRef::new(&maindevice, 0x1000, pdi);
+
+        let inputs = (0..16)
+            .map(|bit| sd_ref.input_bit(bit).expect("input bit"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            inputs,
+            [
+                true, false, false, false, false, false, false, true, //
+                false, true, false, false, false, false, false, false
+            ]
+        );
+
+        sd_ref.set_output_bit(0, true).expect("set bit");
+        sd_ref.set_output_bit(5, true).expect("set bit");
+        sd_ref.set_output_bit(9, false).expect("set bit");
+        // Setting an already set bit changes nothing
+        sd_ref.set_output_bit(15, true).expect("set bit");
+
+        assert_eq!(
+            &pdi_storage.write().get_mut()[3..5],
+            &[0b0010_0001, 0b1111_1101]
+        );
+
+        sd_ref.set_output_bit(5, false).expect("set bit");
+
+        assert_eq!(
+            &pdi_storage.write().get_mut()[1..5],
+            &[0b1000_0001, 0b0000_0010, 0b0000_0001, 0b1111_1101]
+        );
+    }
+
+    #[test]
+    fn bits_out_of_range() {
+        static PDU_STORAGE: PduStorage<8, 64> = PduStorage::new();
+        let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+        let mut sd = SubDevice::default();
+
+        sd.config.io = IoRanges {
+            input: PdiSegment { bytes: 0..1 },
+            output: PdiSegment { bytes: 1..2 },
+        };
+
+        let pdi_storage = spin::rwlock::RwLock::new(MySyncUnsafeCell::new([0u8; 8]));
+
+        let pdi = SubDevicePdi::new(&sd, &pdi_storage);
+
+        let sd_ref = SubDeviceRef::new(&maindevice, 0x1000, pdi);
+
+        assert_eq!(
+            sd_ref.input_bit(8),
+            Err(Error::Wire(WireError::ReadBufferTooShort))
+        );
+        assert_eq!(
+            sd_ref.set_output_bit(8, true),
+            Err(Error::Wire(WireError::WriteBufferTooShort))
+        );
+
+        // The next SubDevice's data is left alone
+        assert_eq!(pdi_storage.write().get_mut(), &[0u8; 8]);
+    }
 }
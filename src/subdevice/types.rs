@@ -1,5 +1,5 @@
 use crate::{
-    eeprom::types::{MailboxProtocols, SyncManagerType},
+    eeprom::types::{DefaultMailbox, FmmuUsage, MailboxProtocols, SyncManager, SyncManagerType},
     pdi::PdiSegment,
 };
 use core::fmt::{self, Debug};
@@ -7,6 +7,7 @@ use core::fmt::{self, Debug};
 /// SubDevice identity information (vendor ID, product ID, etc).
 #[derive(Default, Copy, Clone, PartialEq, ethercrab_wire::EtherCrabWireRead)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[wire(bytes = 16)]
 #[doc(alias = "SlaveIdentity")]
 pub struct SubDeviceIdentity {
@@ -47,6 +48,20 @@ impl Debug for SubDeviceIdentity {
     }
 }
 
+/// The parts of a SubDevice's SII that are needed to configure it, read once during discovery so
+/// that configuration doesn't have to search the EEPROM for them again.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SiiConfigSummary {
+    /// Default mailbox offsets, sizes and supported protocols.
+    pub(in crate::subdevice) mailbox: DefaultMailbox,
+    /// True if the General category enables CoE Complete Access.
+    pub(in crate::subdevice) complete_access: bool,
+    pub(in crate::subdevice) sync_managers: heapless::Vec<SyncManager, 8>,
+    pub(in crate::subdevice) fmmus: heapless::Vec<FmmuUsage, 16>,
+    /// False if the summary could not be fully read and must be read again from the EEPROM.
+    pub(in crate::subdevice) complete: bool,
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct SubDeviceConfig {
     pub io: IoRanges,
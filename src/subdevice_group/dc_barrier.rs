@@ -0,0 +1,272 @@
+use super::DcConfiguration;
+use crate::{
+    error::{DistributedClockError, Error},
+    timer_factory::{Clock, SystemClock},
+};
+use core::time::Duration;
+
+/// Phase-align the process data cycles of multiple tasks to the Distributed Clocks SYNC0 pulse.
+///
+/// When more than one [`SubDeviceGroup`](crate::SubDeviceGroup) is driven from its own task, each
+/// task can wait on a `DcBarrier` created from the same [`DcConfiguration`] before calling
+/// [`tx_rx_dc`](crate::SubDeviceGroup::tx_rx_dc). All tasks are then released at the same SYNC0
+/// boundary (plus the configured shift), regardless of when in the cycle they last ran.
+///
+/// A barrier can be created from a DC-configured group with
+/// [`SubDeviceGroup::dc_barrier`](crate::SubDeviceGroup::dc_barrier), or directly with
+/// [`DcBarrier::new`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use ethercrab::{
+/// #     error::Error, MainDevice, SubDeviceGroup,
+/// #     subdevice_group::{CycleInfo, HasDc, Op, TxRxResponse},
+/// # };
+/// async fn cycle(
+///     maindevice: &MainDevice<'_>,
+///     group: &SubDeviceGroup<1, 1, Op, HasDc>,
+/// ) -> Result<(), Error> {
+///     let barrier = group.dc_barrier()?;
+///
+///     loop {
+///         let TxRxResponse {
+///             extra: CycleInfo { dc_system_time, .. },
+///             ..
+///         } = group.tx_rx_dc(maindevice).await?;
+///
+///         // Process data here
+///
+///         barrier.wait(dc_system_time).await;
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DcBarrier {
+    /// SYNC0 period in nanoseconds.
+    sync0_period: u64,
+    /// Shift from the SYNC0 pulse in nanoseconds, always less than `sync0_period`.
+    sync0_shift: u64,
+}
+
+impl DcBarrier {
+    /// Create a barrier that releases at the SYNC0 period and shift of the given DC configuration.
+    ///
+    /// A shift longer than the SYNC0 period wraps around into the next cycle.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DistributedClockError::InvalidCycleTime`] if the configured `sync0_period` is
+    /// zero.
+    pub fn new(config: DcConfiguration) -> Result<Self, Error> {
+        Self::from_nanos(
+            config.sync0_period.as_nanos() as u64,
+            config.sync0_shift.as_nanos() as u64,
+        )
+    }
+
+    pub(crate) fn from_nanos(sync0_period: u64, sync0_shift: u64) -> Result<Self, Error> {
+        if sync0_period == 0 {
+            return Err(Error::DistributedClock(
+                DistributedClockError::InvalidCycleTime,
+            ));
+        }
+
+        Ok(Self {
+            sync0_period,
+            sync0_shift: sync0_shift % sync0_period,
+        })
+    }
+
+    /// Compute the DC system time in nanoseconds of the first boundary strictly after
+    /// `dc_system_time`.
+    ///
+    /// If `dc_system_time` lies exactly on a boundary, the boundary one full period later is
+    /// returned.
+    pub fn next_boundary(&self, dc_system_time: u64) -> u64 {
+        dc_system_time + self.time_to_next_boundary_nanos(dc_system_time)
+    }
+
+    /// Compute how long to wait from `dc_system_time` until the next boundary.
+    ///
+    /// The returned duration is always greater than zero and at most one SYNC0 period.
+    pub fn time_to_next_boundary(&self, dc_system_time: u64) -> Duration {
+        Duration::from_nanos(self.time_to_next_boundary_nanos(dc_system_time))
+    }
+
+    fn time_to_next_boundary_nanos(&self, dc_system_time: u64) -> u64 {
+        // Position within the shifted cycle. Adding one period before subtracting the shift keeps
+        // this from underflowing.
+        let phase = (dc_system_time % self.sync0_period + self.sync0_period - self.sync0_shift)
+            % self.sync0_period;
+
+        self.sync0_period - phase
+    }
+
+    /// Wait until the next boundary after `dc_system_time`, returning the DC system time in
+    /// nanoseconds the barrier released at.
+    ///
+    /// `dc_system_time` should be read as close as possible to calling this method, e.g. from
+    /// [`CycleInfo::dc_system_time`](crate::subdevice_group::CycleInfo::dc_system_time), as any
+    /// time spent between reading it and waiting delays the release by the same amount.
+    pub async fn wait(&self, dc_system_time: u64) -> u64 {
+        self.wait_with_clock(dc_system_time, &SystemClock).await
+    }
+
+    pub(crate) async fn wait_with_clock<C: Clock>(&self, dc_system_time: u64, clock: &C) -> u64 {
+        clock
+            .timer(self.time_to_next_boundary(dc_system_time))
+            .await;
+
+        self.next_boundary(dc_system_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer_factory::manual::ManualClock;
+    use core::{
+        future::Future,
+        task::{Context, Poll, Waker},
+    };
+
+    /// Run a task that waits on the barrier `cycles` times, starting at `start` on a fake clock
+    /// that advances by exactly the requested wait time plus `work` ns of processing per cycle.
+    fn run(barrier: &DcBarrier, start: u64, work: u64, cycles: usize) -> Vec<u64> {
+        let mut now = start;
+
+        (0..cycles)
+            .map(|_| {
+                now += work;
+                now += barrier.time_to_next_boundary(now).as_nanos() as u64;
+
+                now
+            })
+            .collect()
+    }
+
+    #[test]
+    fn releases_on_sync0_boundaries() {
+        let barrier = DcBarrier::new(DcConfiguration {
+            start_delay: Duration::ZERO,
+            sync0_period: Duration::from_micros(1000),
+            sync0_shift: Duration::from_micros(250),
+        })
+        .unwrap();
+
+        // Two tasks starting at different points in the cycle and taking different amounts of
+        // time to process their data.
+        let a = run(&barrier, 1_000_000_123, 10_000, 4);
+        let b = run(&barrier, 1_000_600_000, 300_000, 4);
+
+        assert_eq!(
+            a,
+            [1_000_250_000, 1_001_250_000, 1_002_250_000, 1_003_250_000]
+        );
+        assert_eq!(
+            b,
+            [1_001_250_000, 1_002_250_000, 1_003_250_000, 1_004_250_000]
+        );
+    }
+
+    #[test]
+    fn wait_releases_waiters_together() {
+        let barrier = DcBarrier::from_nanos(100_000_000, 25_000_000).unwrap();
+        let clock = ManualClock::default();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        // Half way through a cycle, so the shifted boundary is 75ms away
+        let dc_start = 1_000_050_000_000u64;
+        let dc_now = || dc_start + clock.now().as_nanos() as u64;
+        let boundary = dc_start + 75_000_000;
+
+        // Tasks that reach the barrier at different points in the same cycle
+        let mut waiters = Vec::new();
+
+        for _ in 0..3 {
+            let mut waiter = Box::pin(barrier.wait_with_clock(dc_now(), &clock));
+
+            assert_eq!(waiter.as_mut().poll(&mut cx), Poll::Pending);
+
+            waiters.push(waiter);
+
+            clock.advance(Duration::from_millis(20));
+        }
+
+        // 1ms before the boundary
+        clock.advance(Duration::from_millis(14));
+
+        for waiter in waiters.iter_mut() {
+            assert_eq!(waiter.as_mut().poll(&mut cx), Poll::Pending);
+        }
+
+        clock.advance(Duration::from_millis(1));
+
+        for waiter in waiters.iter_mut() {
+            assert_eq!(waiter.as_mut().poll(&mut cx), Poll::Ready(boundary));
+        }
+
+        // A task that arrives after the boundary has passed waits for the next one
+        clock.advance(Duration::from_millis(15));
+
+        let mut late = Box::pin(barrier.wait_with_clock(dc_now(), &clock));
+
+        assert_eq!(late.as_mut().poll(&mut cx), Poll::Pending);
+
+        clock.advance(Duration::from_millis(84));
+
+        assert_eq!(late.as_mut().poll(&mut cx), Poll::Pending);
+
+        clock.advance(Duration::from_millis(1));
+
+        assert_eq!(
+            late.as_mut().poll(&mut cx),
+            Poll::Ready(boundary + 100_000_000)
+        );
+        assert_eq!(dc_now(), boundary + 100_000_000);
+    }
+
+    #[test]
+    fn boundary_is_strictly_after() {
+        let barrier = DcBarrier::from_nanos(1000, 0).unwrap();
+
+        assert_eq!(barrier.next_boundary(0), 1000);
+        assert_eq!(barrier.next_boundary(999), 1000);
+        assert_eq!(barrier.next_boundary(1000), 2000);
+        assert_eq!(
+            barrier.time_to_next_boundary(1000),
+            Duration::from_nanos(1000)
+        );
+    }
+
+    #[test]
+    fn shift_before_first_cycle() {
+        let barrier = DcBarrier::from_nanos(1000, 100).unwrap();
+
+        assert_eq!(barrier.next_boundary(0), 100);
+        assert_eq!(barrier.next_boundary(100), 1100);
+    }
+
+    #[test]
+    fn shift_wraps() {
+        assert_eq!(
+            DcBarrier::from_nanos(1000, 1250).unwrap(),
+            DcBarrier::from_nanos(1000, 250).unwrap()
+        );
+    }
+
+    #[test]
+    fn zero_period() {
+        assert_eq!(
+            DcBarrier::new(DcConfiguration {
+                start_delay: Duration::ZERO,
+                sync0_period: Duration::ZERO,
+                sync0_shift: Duration::from_micros(250),
+            }),
+            Err(Error::DistributedClock(
+                DistributedClockError::InvalidCycleTime
+            ))
+        );
+    }
+}
@@ -2,6 +2,17 @@ use crate::{
     GroupId, MainDevice, SubDevice, SubDeviceGroup, SubDeviceRef, error::Error, fmt, pdi::PdiOffset,
 };
 
+mod private {
+    /// Passed to [`SubDeviceGroupHandle`](super::SubDeviceGroupHandle) methods that are only
+    /// for use inside EtherCrab.
+    ///
+    /// This type can't be named or constructed by applications, so they can't call those methods.
+    #[derive(Debug, Copy, Clone)]
+    pub struct Internal(pub(crate) ());
+}
+
+pub(crate) use self::private::Internal;
+
 /// A trait implemented only by [`SubDeviceGroup`] so multiple groups with different const params
 /// can be stored in a hashmap, `Vec`, etc.
 #[doc(hidden)]
@@ -15,13 +26,20 @@ pub trait SubDeviceGroupHandle: Sync {
 
     /// Get a reference to the group with const generic params erased.
     fn as_ref(&self) -> SubDeviceGroupRef<'_>;
+
+    /// Get the SubDevices in this group.
+    ///
+    /// Only callable inside EtherCrab, which makes sure the group isn't being modified at the
+    /// same time.
+    fn subdevices(&self, _: Internal) -> &[SubDevice];
 }
 
 #[sealed::sealed]
-impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S> SubDeviceGroupHandle
-    for SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, S>
+impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S, DC> SubDeviceGroupHandle
+    for SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, S, DC>
 where
     S: Sync,
+    DC: Sync,
 {
     fn id(&self) -> GroupId {
         self.id
@@ -45,6 +63,10 @@ where
             },
         }
     }
+
+    fn subdevices(&self, _: Internal) -> &[SubDevice] {
+        unsafe { &(*self.inner.get()).subdevices }
+    }
 }
 
 #[derive(Debug)]
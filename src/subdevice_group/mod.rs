@@ -3,8 +3,10 @@
 //! SubDevices can be divided into multiple groups to allow multiple tasks to run concurrently,
 //! potentially at different tick rates.
 
+mod dc_barrier;
 mod group_id;
 mod handle;
+mod pdi_cursor;
 mod tx_rx_response;
 
 use crate::{
@@ -16,25 +18,37 @@ use crate::{
     command::Command,
     error::{DistributedClockError, Error, Item},
     fmt,
+    frame_layout,
     // lending_lock::LendingLock,
-    pdi::PdiOffset,
+    pdi::{PdiOffset, extend_logical_range},
     pdu_loop::{CreatedFrame, ReceivedPdu},
     subdevice::{
-        IoRanges, SubDevice, SubDeviceRef, configuration::PdoDirection, pdi::SubDevicePdi,
+        IoRanges, SubDevice, SubDeviceRef,
+        configuration::PdoDirection,
+        pdi::{PdiIoRawWriteGuard, SubDevicePdi},
     },
-    timer_factory::IntoTimeout,
+    timer_factory::{IntoTimeout, with_deadline},
+};
+use core::{
+    cell::UnsafeCell,
+    marker::PhantomData,
+    ops::Range,
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+    time::Duration,
 };
-use core::{cell::UnsafeCell, marker::PhantomData, sync::atomic::AtomicUsize, time::Duration};
 use ethercrab_wire::{EtherCrabWireRead, EtherCrabWireSized};
 
+pub use self::dc_barrier::DcBarrier;
 pub use self::group_id::GroupId;
+pub(crate) use self::handle::Internal;
 pub use self::handle::SubDeviceGroupHandle;
+use self::pdi_cursor::PdiCursor;
 pub use self::tx_rx_response::TxRxResponse;
 
 static GROUP_ID: AtomicUsize = AtomicUsize::new(0);
 
 /// The size of a DC sync PDU.
-const DC_PDU_SIZE: usize = CreatedFrame::PDU_OVERHEAD_BYTES + u64::PACKED_LEN;
+const DC_PDU_SIZE: usize = frame_layout::PDU_OVERHEAD + u64::PACKED_LEN;
 
 // MSRV: Remove when core SyncUnsafeCell is stabilised
 #[derive(Debug)]
@@ -106,6 +120,8 @@ pub struct NoDc;
 pub struct HasDc {
     sync0_period: u64,
     sync0_shift: u64,
+    /// Offset of this group's SYNC0 pulses from whole multiples of `sync0_period`.
+    sync0_phase: u64,
     /// Configured address of the DC reference SubDevice.
     reference: u16,
 }
@@ -128,6 +144,13 @@ impl IsPreOp for PreOpPdi {}
 struct GroupInner<const MAX_SUBDEVICES: usize> {
     subdevices: heapless::Vec<SubDevice, MAX_SUBDEVICES>,
     pdi_start: PdiOffset,
+    lost_frame_policy: LostFramePolicy,
+    /// SYNC0 phase offset in nanoseconds.
+    dc_sync_phase: AtomicU32,
+    /// Requested offset of the outputs from the start of the group's logical address space.
+    output_offset: Option<u16>,
+    /// Logical start address of the outputs if they are not mapped directly after the inputs.
+    split_output_address: Option<u32>,
 }
 
 const CYCLIC_OP_ENABLE: u8 = 0b0000_0001;
@@ -150,6 +173,37 @@ pub struct DcConfiguration {
     pub sync0_shift: Duration,
 }
 
+/// What a group's input data should contain when a process data frame is lost.
+///
+/// A frame is considered lost when no response is received within the PDU timeout set in
+/// [`Timeouts::pdu`](crate::Timeouts::pdu), including any retries.
+///
+/// Set with [`SubDeviceGroup::set_lost_frame_policy`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LostFramePolicy {
+    /// Return [`Error::Timeout`] from the cycle, leaving the inputs from the last successful cycle
+    /// in place.
+    #[default]
+    Error,
+    /// Keep the inputs from the last successful cycle and return a working counter of zero.
+    HoldLast,
+    /// Set the inputs not received in the current cycle to zero and return a working counter of
+    /// zero.
+    ///
+    /// Inputs already received in earlier frames of the same cycle are kept, e.g. if only the
+    /// `LWR` frame of a group with [split](SubDeviceGroup::set_output_offset) inputs and outputs
+    /// is lost, no inputs are zeroed.
+    Zero,
+}
+
+/// Deadline information returned by [`SubDeviceGroup::tx_rx_deadline`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeadlineInfo {
+    /// `true` if the process data exchange was still in progress when the deadline passed.
+    pub overrun: bool,
+}
+
 /// Information useful to a process data cycle.
 #[derive(Debug, Copy, Clone)]
 pub struct CycleInfo {
@@ -186,6 +240,64 @@ pub struct SubDeviceGroup<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S =
     _state: PhantomData<S>,
 }
 
+impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
+    SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, Init, DC>
+{
+    /// Transition all SubDevices in the group from INIT to PRE-OP, configuring their mailboxes
+    /// on the way.
+    pub async fn into_pre_op(
+        mut self,
+        maindevice: &MainDevice<'_>,
+    ) -> Result<SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, PreOp, DC>, Error> {
+        for subdevice in self.inner.get_mut().subdevices.iter_mut() {
+            SubDeviceRef::new(maindevice, subdevice.configured_address(), subdevice)
+                .configure_mailboxes()
+                .await?;
+        }
+
+        fmt::debug!("--> Group reached state {}", SubDeviceState::PreOp);
+
+        Ok(SubDeviceGroup {
+            id: self.id,
+            pdi: self.pdi,
+            read_pdi_len: self.read_pdi_len,
+            pdi_len: self.pdi_len,
+            inner: self.inner,
+            dc_conf: self.dc_conf,
+            _state: PhantomData,
+        })
+    }
+
+    /// Bring the group up from INIT to OP, waiting for each state to be reached.
+    ///
+    /// This is the same as calling [`into_pre_op`](SubDeviceGroup::into_pre_op) then
+    /// [`bring_up`](SubDeviceGroup::bring_up) on the PRE-OP group.
+    pub async fn bring_up(
+        self,
+        maindevice: &MainDevice<'_>,
+    ) -> Result<SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, Op, DC>, Error> {
+        self.bring_up_with(maindevice, async |_| Ok(())).await
+    }
+
+    /// Bring the group up from INIT to OP, calling `configure` for each SubDevice in the group
+    /// once it reaches PRE-OP.
+    ///
+    /// See the PRE-OP group's [`bring_up_with`](SubDeviceGroup::bring_up_with) for details.
+    pub async fn bring_up_with<F>(
+        self,
+        maindevice: &MainDevice<'_>,
+        configure: F,
+    ) -> Result<SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, Op, DC>, Error>
+    where
+        F: AsyncFnMut(SubDeviceRef<'_, &SubDevice>) -> Result<(), Error>,
+    {
+        self.into_pre_op(maindevice)
+            .await?
+            .bring_up_with(maindevice, configure)
+            .await
+    }
+}
+
 impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
     SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, PreOp, DC>
 {
@@ -201,22 +313,55 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
             inner.pdi_start.start_address
         );
 
+        // Logical address ranges covered by the input and output FMMUs of all SubDevices in the
+        // group.
+        let mut inputs_logical = None;
+        let mut outputs_logical = None;
+
         // Configure master read PDI mappings in the first section of the PDI
         for subdevice in inner.subdevices.iter_mut() {
             // We're in PRE-OP at this point
-            pdi_position = SubDeviceRef::new(maindevice, subdevice.configured_address(), subdevice)
-                .configure_fmmus(
-                    pdi_position,
-                    inner.pdi_start.start_address,
-                    PdoDirection::MasterRead,
-                )
-                .await?;
+            let (next, logical) =
+                SubDeviceRef::new(maindevice, subdevice.configured_address(), subdevice)
+                    .configure_fmmus(
+                        pdi_position,
+                        inner.pdi_start.start_address,
+                        PdoDirection::MasterRead,
+                    )
+                    .await?;
+
+            pdi_position = next;
+
+            if let Some(logical) = logical {
+                inputs_logical = Some(extend_logical_range(inputs_logical, logical));
+            }
         }
 
         self.read_pdi_len = (pdi_position.start_address - inner.pdi_start.start_address) as usize;
 
         fmt::debug!("SubDevice mailboxes configured and init hooks called");
 
+        if let Some(offset) = inner.output_offset {
+            if usize::from(offset) < self.read_pdi_len {
+                fmt::error!(
+                    "Group output offset {} overlaps {} input bytes",
+                    offset,
+                    self.read_pdi_len
+                );
+
+                return Err(Error::OutputOffsetOverlap {
+                    offset,
+                    input_len: self.read_pdi_len,
+                });
+            }
+
+            pdi_position = inner.pdi_start.increment(offset);
+        }
+
+        // Outputs follow the inputs in the PDI buffer, even if they are mapped elsewhere in the
+        // logical address space.
+        let outputs_base = pdi_position.start_address - self.read_pdi_len as u32;
+
         // We configured all read PDI mappings as a contiguous block in the previous loop. Now we'll
         // configure the write mappings in a separate loop. This means we have IIIIOOOO instead of
         // IOIOIO.
@@ -226,30 +371,60 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
             let mut subdevice_config = SubDeviceRef::new(maindevice, addr, subdevice);
 
             // Still in PRE-OP
-            pdi_position = subdevice_config
-                .configure_fmmus(
-                    pdi_position,
-                    inner.pdi_start.start_address,
-                    PdoDirection::MasterWrite,
-                )
+            let (next, logical) = subdevice_config
+                .configure_fmmus(pdi_position, outputs_base, PdoDirection::MasterWrite)
                 .await?;
+
+            pdi_position = next;
+
+            if let Some(logical) = logical {
+                outputs_logical = Some(extend_logical_range(outputs_logical, logical));
+            }
         }
 
+        // If the output FMMUs don't start directly after the input FMMUs, a single LRW can't cover
+        // both, so inputs and outputs must be exchanged separately.
+        let inputs_end = inputs_logical.map_or(inner.pdi_start.start_address, |range| range.end);
+
+        inner.split_output_address = outputs_logical
+            .map(|range| range.start)
+            .filter(|outputs_start| *outputs_start != inputs_end);
+
         fmt::debug!("SubDevice FMMUs configured for group. Able to move to SAFE-OP");
 
-        self.pdi_len = (pdi_position.start_address - inner.pdi_start.start_address) as usize;
+        self.pdi_len = (pdi_position.start_address - outputs_base) as usize;
+
+        // Includes any gap between inputs and outputs
+        let logical_len = (pdi_position.start_address - inner.pdi_start.start_address) as usize;
 
         fmt::debug!(
-            "Group PDI length: start {:#010x}, {} total bytes ({} input bytes)",
+            "Group PDI length: start {:#010x}, {} total bytes ({} input bytes), split outputs at {:?}",
             inner.pdi_start.start_address,
             self.pdi_len,
-            self.read_pdi_len
+            self.read_pdi_len,
+            inner.split_output_address
         );
 
-        if self.pdi_len > MAX_PDI {
+        if logical_len > MAX_PDI {
+            fmt::error!(
+                "Group PDI requires {} bytes but MAX_PDI is {}",
+                logical_len,
+                MAX_PDI
+            );
+
+            for subdevice in inner.subdevices.iter() {
+                fmt::debug!(
+                    "--> SubDevice {:#06x} {}: {} input bytes, {} output bytes",
+                    subdevice.configured_address(),
+                    subdevice.name(),
+                    subdevice.config.io.input.len(),
+                    subdevice.config.io.output.len()
+                );
+            }
+
             return Err(Error::PdiTooLong {
-                max_length: MAX_PDI,
-                desired_length: self.pdi_len,
+                required: logical_len,
+                available: MAX_PDI,
             });
         }
 
@@ -289,6 +464,160 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
         self_.into_op(maindevice).await
     }
 
+    /// Bring the group up from PRE-OP to OP, waiting for each state to be reached.
+    ///
+    /// The group's PDI is configured in PRE-OP as with
+    /// [`into_pre_op_pdi`](SubDeviceGroup::into_pre_op_pdi), then the group is moved to SAFE-OP
+    /// and finally OP. To configure each SubDevice in PRE-OP before the PDI is configured, e.g.
+    /// with SDO writes, use [`bring_up_with`](SubDeviceGroup::bring_up_with).
+    pub async fn bring_up(
+        self,
+        maindevice: &MainDevice<'_>,
+    ) -> Result<SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, Op, DC>, Error> {
+        self.bring_up_with(maindevice, async |_| Ok(())).await
+    }
+
+    /// Bring the group up from PRE-OP to OP, calling `configure` for each SubDevice in the group
+    /// while still in PRE-OP.
+    ///
+    /// SubDevices are configured in group order, before the group's PDI is configured, so any
+    /// changes to PDO mappings made by `configure` are picked up. If `configure` returns an error,
+    /// the group is left in PRE-OP and the error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use ethercrab::{
+    /// #     error::Error, MainDevice, MainDeviceConfig, PduStorage, Timeouts, std::ethercat_now
+    /// # };
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
+    /// # let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    /// # async {
+    /// let group = maindevice
+    ///     .init_single_group::<8, 8>(ethercat_now)
+    ///     .await
+    ///     .expect("Init");
+    ///
+    /// let group = group
+    ///     .bring_up_with(&maindevice, async |subdevice| {
+    ///         if subdevice.name() == "EL3004" {
+    ///             subdevice.sdo_write(0x1c12, 0, 0u8).await?;
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    ///     .await?;
+    /// # Ok::<(), ethercrab::error::Error>(())
+    /// # };
+    /// ```
+    pub async fn bring_up_with<F>(
+        self,
+        maindevice: &MainDevice<'_>,
+        mut configure: F,
+    ) -> Result<SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, Op, DC>, Error>
+    where
+        F: AsyncFnMut(SubDeviceRef<'_, &SubDevice>) -> Result<(), Error>,
+    {
+        for subdevice in self.inner().subdevices.iter() {
+            configure(SubDeviceRef::new(
+                maindevice,
+                subdevice.configured_address(),
+                subdevice,
+            ))
+            .await?;
+        }
+
+        self.into_pre_op_pdi(maindevice)
+            .await?
+            .into_safe_op(maindevice)
+            .await?
+            .into_op(maindevice)
+            .await
+    }
+
+    /// Compute the PDI layout this group will be given when it leaves PRE-OP, without configuring
+    /// any FMMUs or SMs.
+    ///
+    /// Each item holds a SubDevice's configured address, followed by the byte ranges of its inputs
+    /// and outputs in the group PDI. Inputs for all SubDevices come first, followed by all outputs,
+    /// so the end of the last output range is the total PDI length required. This can be compared
+    /// against `MAX_PDI` or the expected process image before calling e.g.
+    /// [`into_op`](SubDeviceGroup::into_op).
+    ///
+    /// PDO assignments are read from each SubDevice's EEPROM or CoE object dictionary in the same
+    /// way as during configuration, so any changes to PDO mappings should be made before calling
+    /// this method.
+    pub async fn planned_pdi(
+        &self,
+        maindevice: &MainDevice<'_>,
+    ) -> Result<heapless::Vec<(u16, Range<usize>, Range<usize>), MAX_SUBDEVICES>, Error> {
+        let subdevices = &self.inner().subdevices;
+
+        let mut layout = heapless::Vec::<_, MAX_SUBDEVICES>::new();
+        let mut offset = 0;
+
+        for subdevice in subdevices.iter() {
+            let len = SubDeviceRef::new(maindevice, subdevice.configured_address(), subdevice)
+                .planned_pdi_len(PdoDirection::MasterRead)
+                .await?;
+
+            layout
+                .push((subdevice.configured_address(), offset..(offset + len), 0..0))
+                .map_err(|_| Error::Capacity(Item::SubDevice))?;
+
+            offset += len;
+        }
+
+        // Outputs are laid out after all inputs, same as `configure_fmmus`
+        for (subdevice, (_, _, outputs)) in subdevices.iter().zip(layout.iter_mut()) {
+            let len = SubDeviceRef::new(maindevice, subdevice.configured_address(), subdevice)
+                .planned_pdi_len(PdoDirection::MasterWrite)
+                .await?;
+
+            *outputs = offset..(offset + len);
+
+            offset += len;
+        }
+
+        fmt::debug!(
+            "Planned group PDI length: {} total bytes ({} input bytes)",
+            offset,
+            layout.last().map(|(_, inputs, _)| inputs.end).unwrap_or(0)
+        );
+
+        Ok(layout)
+    }
+
+    /// Map the group's outputs `offset` bytes from the start of the group's logical address
+    /// space, instead of directly after its inputs.
+    ///
+    /// If this leaves a gap between the inputs and outputs, process data is exchanged using
+    /// separate `LRD` and `LWR` PDUs for the inputs and outputs, each in its own frame, instead of a
+    /// single `LRW`. The layout of the group's PDI is not affected. The offset and output length
+    /// must fit within `MAX_PDI`.
+    ///
+    /// `None`, the default, maps the outputs directly after the inputs.
+    ///
+    /// Whether separate frames are needed is decided from the logical address ranges of the input
+    /// and output FMMUs once they are configured, not from this setting alone. An offset equal to
+    /// the group's input length, or one that leaves the group with no inputs or outputs, results in
+    /// a contiguous mapping and a single `LRW`.
+    ///
+    /// With a split mapping, the working counter returned by
+    /// [`tx_rx`](SubDeviceGroup::tx_rx) is the sum of the `LRD` and `LWR` working counters. Each
+    /// SubDevice increments these by one, instead of by one for a read and two for a write with an
+    /// `LRW`, so a SubDevice with both inputs and outputs adds 2 to the working counter instead of
+    /// 3.
+    ///
+    /// This must be called before the group's FMMUs are configured, e.g. by
+    /// [`into_safe_op`](SubDeviceGroup::into_safe_op). Configuration returns
+    /// [`Error::OutputOffsetOverlap`] if the offset is smaller than the length of the group's
+    /// inputs.
+    pub fn set_output_offset(&mut self, offset: Option<u16>) {
+        self.inner.get_mut().output_offset = offset;
+    }
+
     /// Configure FMMUs, but leave the group in [`PreOp`] state.
     ///
     /// This method is used to obtain access to the group's PDI and related functionality. All SDO
@@ -344,6 +673,18 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
             .map(|sd| SubDeviceRef::new(maindevice, sd.configured_address, sd))
     }
 
+    /// Get an iterator over all SubDevices in this group, along with each SubDevice's position in
+    /// the group.
+    ///
+    /// The position can be passed to [`subdevice`](SubDeviceGroup::subdevice) to retrieve the same
+    /// SubDevice again, and is stable across state transitions of this group.
+    pub fn iter_indexed<'group, 'maindevice>(
+        &'group self,
+        maindevice: &'maindevice MainDevice<'maindevice>,
+    ) -> impl Iterator<Item = (usize, SubDeviceRef<'maindevice, &'group SubDevice>)> {
+        self.iter(maindevice).enumerate()
+    }
+
     /// Get a mutable iterator over all SubDevices in this group
     pub fn iter_mut<'group, 'maindevice>(
         &'group mut self,
@@ -362,6 +703,40 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S, DC>
 where
     S: IsPreOp,
 {
+    /// Offset this group's SYNC0 pulses from those of other groups.
+    ///
+    /// By default, the first SYNC0 pulse of every group lies on a whole multiple of the group's
+    /// [`sync0_period`](DcConfiguration::sync0_period), so groups with related cycle times pulse at
+    /// the same instant and their process data frames contend for the network. Setting a different
+    /// phase for each group staggers their cycles.
+    ///
+    /// The offset is added to the SYNC0 start time of every DC SubDevice in the group by
+    /// [`configure_dc_sync`](SubDeviceGroup::configure_dc_sync), so this must be called before it.
+    /// The [`CycleInfo`] returned by [`tx_rx_dc`](SubDeviceGroup::tx_rx_dc) and the
+    /// [`DcBarrier`] returned by [`dc_barrier`](SubDeviceGroup::dc_barrier) take the offset into
+    /// account.
+    ///
+    /// The phase is a position within a single cycle, so an offset of a whole
+    /// [`sync0_period`](DcConfiguration::sync0_period) or more is reduced modulo the period when
+    /// [`configure_dc_sync`](SubDeviceGroup::configure_dc_sync) is called. For example, an offset
+    /// of 1.25 ms with a 1 ms period gives a phase of 250 us. Offsets longer than `u32::MAX`
+    /// nanoseconds, the longest SYNC0 period a SubDevice can hold, are clamped to that value.
+    ///
+    /// Defaults to zero.
+    pub fn set_dc_sync_phase(&self, offset: Duration) {
+        let nanos = u32::try_from(offset.as_nanos()).unwrap_or_else(|_| {
+            fmt::warn!(
+                "DC sync phase of {} ns is too long, using {} ns",
+                offset.as_nanos(),
+                u32::MAX
+            );
+
+            u32::MAX
+        });
+
+        self.inner().dc_sync_phase.store(nanos, Ordering::Relaxed);
+    }
+
     /// Configure Distributed Clock SYNC0 for all SubDevices in this group.
     ///
     /// # Errors
@@ -388,6 +763,25 @@ where
             sync0_shift,
         } = dc_conf;
 
+        let sync0_phase = {
+            let phase = u64::from(self.inner().dc_sync_phase.load(Ordering::Relaxed));
+            let period = sync0_period.as_nanos() as u64;
+
+            // A zero period has no cycle to be in phase with, so the phase is left as it is
+            let reduced = phase.checked_rem(period).unwrap_or(phase);
+
+            if reduced != phase {
+                fmt::warn!(
+                    "DC sync phase of {} ns is not less than SYNC0 period of {} ns, using {} ns",
+                    phase,
+                    period,
+                    reduced
+                );
+            }
+
+            reduced
+        };
+
         // Coerce generics into concrete `PreOp` type as we don't need the PDI to configure the DC.
         let self_ = SubDeviceGroup {
             id: self.id,
@@ -437,8 +831,10 @@ where
 
             let first_pulse_delay = start_delay.as_nanos() as u64;
 
-            // Round first pulse time to a whole number of cycles
-            let start_time = (device_time + first_pulse_delay) / sync0_period * sync0_period;
+            // Round first pulse time to a whole number of cycles, then move it to this group's
+            // phase within the cycle
+            let start_time =
+                (device_time + first_pulse_delay) / sync0_period * sync0_period + sync0_phase;
 
             fmt::debug!("--> Computed DC sync start time: {}", start_time);
 
@@ -479,6 +875,7 @@ where
             dc_conf: HasDc {
                 sync0_period: sync0_period.as_nanos() as u64,
                 sync0_shift: sync0_shift.as_nanos() as u64,
+                sync0_phase,
                 reference,
             },
             _state: PhantomData,
@@ -583,6 +980,29 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
             _state: PhantomData,
         })
     }
+
+    /// Read the SubDevice group's inputs without writing any outputs.
+    ///
+    /// This sends an `LRD` over the input portion of the PDI only, so outputs set with e.g.
+    /// [`SubDeviceRef::outputs_raw_mut`](crate::SubDeviceRef::outputs_raw_mut) are never sent to
+    /// the network. Inputs are valid in SAFE-OP and SubDevices hold their outputs in a safe state,
+    /// so this can be used to observe a machine live during commissioning without any chance of
+    /// energising an output.
+    ///
+    /// Each SubDevice with inputs increments the returned working counter by 1, so the expected
+    /// value is the number of SubDevices in the group with inputs, not the value returned by
+    /// [`tx_rx`](SubDeviceGroup::tx_rx).
+    ///
+    /// # Errors
+    ///
+    /// This method will return with an error if the PDU could not be sent over the network, or the
+    /// response times out. A timeout is handled according to the group's [`LostFramePolicy`].
+    pub async fn tx_rx_inputs_only<'sto>(
+        &self,
+        maindevice: &'sto MainDevice<'sto>,
+    ) -> Result<TxRxResponse<MAX_SUBDEVICES>, Error> {
+        self.tx_rx_pdi(maindevice, true).await
+    }
 }
 
 impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
@@ -595,12 +1015,65 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, DC>
     ) -> Result<SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, SafeOp, DC>, Error> {
         self.transition_to(maindevice, SubDeviceState::SafeOp).await
     }
+
+    /// Check that every SubDevice in the group is still in OP.
+    ///
+    /// A SubDevice can lag behind the rest of the group or drop back to a lower state, e.g. after
+    /// a watchdog timeout, in which case it no longer exchanges process data and
+    /// [`tx_rx`](SubDeviceGroup::tx_rx) will carry on without it. This method reads the state of
+    /// every SubDevice in the group to detect this.
+    ///
+    /// # Errors
+    ///
+    /// If any SubDevice is not in OP, every such SubDevice is logged and an
+    /// [`Error::StateMismatch`] is returned holding the number of SubDevices not in OP and the
+    /// address and state of the first one.
+    ///
+    /// If a SubDevice does not respond to its state read, e.g. because it has been disconnected,
+    /// [`Error::WorkingCounter`] is returned.
+    pub async fn verify_op(&self, maindevice: &MainDevice<'_>) -> Result<(), Error> {
+        let mut count = 0u16;
+        let mut first = None;
+
+        subdevice_states(maindevice, &self.inner().subdevices, |subdevice, status| {
+            if status.state != SubDeviceState::Op {
+                fmt::error!(
+                    "--> SubDevice {:#06x} {} is in {}, expected {}",
+                    subdevice.configured_address(),
+                    subdevice.name(),
+                    status.state,
+                    SubDeviceState::Op
+                );
+
+                count += 1;
+                first.get_or_insert((subdevice.configured_address(), status.state));
+            }
+        })
+        .await?;
+
+        match first {
+            Some((configured_address, actual)) => Err(Error::StateMismatch {
+                expected: SubDeviceState::Op,
+                count,
+                configured_address,
+                actual,
+            }),
+            None => Ok(()),
+        }
+    }
 }
 
 impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S> Default
     for SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, S>
 {
     fn default() -> Self {
+        const {
+            assert!(
+                MAX_SUBDEVICES == 0 || MAX_PDI > 0,
+                "MAX_PDI must be greater than zero for a group that can hold SubDevices"
+            )
+        };
+
         Self {
             id: GroupId(GROUP_ID.fetch_add(1, core::sync::atomic::Ordering::Relaxed)),
             pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([0u8; MAX_PDI])),
@@ -620,6 +1093,17 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S, DC>
         unsafe { &*self.inner.get() }
     }
 
+    /// Create a cursor to send the group's PDI, or only its inputs if `inputs_only` is `true`.
+    fn pdi_cursor(&self, inputs_only: bool) -> PdiCursor {
+        PdiCursor::new(
+            self.inner().pdi_start.start_address,
+            self.read_pdi_len,
+            self.pdi_len,
+            self.inner().split_output_address,
+            inputs_only,
+        )
+    }
+
     /// Get the number of SubDevices in this group.
     pub fn len(&self) -> usize {
         self.inner().subdevices.len()
@@ -630,61 +1114,27 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S, DC>
         self.inner().subdevices.is_empty()
     }
 
-    /// Check if all SubDevices in the group are the given desired state.
-    async fn is_state(
-        &self,
-        maindevice: &MainDevice<'_>,
-        desired_state: SubDeviceState,
-    ) -> Result<bool, Error> {
-        fmt::trace!("Check group state");
-
-        let mut subdevices = self.inner().subdevices.iter();
-
-        let mut total_checks = 0;
-
-        // Send as many frames as required to check statuses of all subdevices
-        loop {
-            let mut frame = maindevice.pdu_loop.alloc_frame()?;
-
-            let (rest, num_in_this_frame) = push_state_checks(subdevices, &mut frame)?;
-
-            subdevices = rest;
-
-            // Nothing to send, we've checked all SDs
-            if num_in_this_frame == 0 {
-                fmt::trace!("--> No more state checks, pushed {}", total_checks);
-
-                break;
-            }
-
-            total_checks += num_in_this_frame;
-
-            let frame = frame.mark_sendable(
-                &maindevice.pdu_loop,
-                maindevice.timeouts.pdu,
-                maindevice.config.retry_behaviour.retry_count(),
-            );
-
-            maindevice.pdu_loop.wake_sender();
-
-            let received = frame.await?;
-
-            for pdu in received.into_pdu_iter() {
-                let pdu = pdu?;
-
-                let result = AlControl::unpack_from_slice(&pdu)?;
-
-                // Return from this fn as soon as the first undesired state is found
-                if result.state != desired_state {
-                    return Ok(false);
-                }
-            }
-        }
+    /// Get the number of bytes of the Process Data Image used by this group, including both
+    /// inputs and outputs.
+    ///
+    /// This is zero until the group's PDI has been configured, e.g. by
+    /// [`into_op`](SubDeviceGroup::into_op). Compare against the `MAX_PDI` const parameter to
+    /// find how much headroom is left, or how far `MAX_PDI` can be reduced to save memory.
+    pub fn pdi_used(&self) -> usize {
+        self.pdi_len
+    }
 
-        // Just sanity checking myself
-        debug_assert_eq!(total_checks, self.len());
+    /// Get the policy applied to this group's inputs when a process data frame is lost.
+    pub fn lost_frame_policy(&self) -> LostFramePolicy {
+        self.inner().lost_frame_policy
+    }
 
-        Ok(true)
+    /// Set what the group's inputs should contain when a process data frame sent by
+    /// [`tx_rx`](SubDeviceGroup::tx_rx) is lost.
+    ///
+    /// Defaults to [`LostFramePolicy::Error`].
+    pub fn set_lost_frame_policy(&mut self, policy: LostFramePolicy) {
+        self.inner.get_mut().lost_frame_policy = policy;
     }
 
     /// Wait for all SubDevices in this group to transition to the given state.
@@ -693,17 +1143,7 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S, DC>
         maindevice: &MainDevice<'_>,
         desired_state: SubDeviceState,
     ) -> Result<(), Error> {
-        async {
-            loop {
-                if self.is_state(maindevice, desired_state).await? {
-                    break Ok(());
-                }
-
-                maindevice.timeouts.loop_tick().await;
-            }
-        }
-        .timeout(maindevice.timeouts.state_transition)
-        .await
+        wait_for_subdevices_state(maindevice, &self.inner().subdevices, desired_state).await
     }
 
     /// Transition to a new state.
@@ -738,38 +1178,194 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S, DC>
     }
 }
 
-fn push_state_checks<'group, 'sto, I>(
-    mut subdevices: I,
-    frame: &mut CreatedFrame<'sto>,
-) -> Result<(I, usize), Error>
-where
-    I: Iterator<Item = &'group SubDevice>,
-{
-    let mut num_in_this_frame = 0;
+/// Check if all of the given SubDevices are in the desired state.
+async fn subdevices_in_state(
+    maindevice: &MainDevice<'_>,
+    subdevices: &[SubDevice],
+    desired_state: SubDeviceState,
+) -> Result<bool, Error> {
+    fmt::trace!("Check group state");
+
+    let record_history = maindevice.config.al_status_history;
+
+    // AL status code is at 0x0134, so read the AL status register, 2 reserved bytes, and the code
+    // in one go if we need to record it.
+    let read_len = if record_history {
+        AlControl::PACKED_LEN + 4
+    } else {
+        AlControl::PACKED_LEN
+    };
 
-    while frame.can_push_pdu_payload(AlControl::PACKED_LEN) {
-        let Some(sd) = subdevices.next() else {
-            break;
-        };
+    let mut remaining = subdevices.iter();
 
-        // A too-long error here should be unreachable as we check if the payload can be
-        // pushed in the loop condition.
-        frame.push_pdu(
-            Command::fprd(sd.configured_address(), RegisterAddress::AlStatus.into()).into(),
-            (),
-            Some(AlControl::PACKED_LEN as u16),
-        )?;
+    let mut total_checks = 0;
 
-        num_in_this_frame += 1;
+    // Send as many frames as required to check statuses of all subdevices
+    loop {
+        let mut frame = maindevice.pdu_loop.alloc_frame()?;
+
+        let in_this_frame = remaining.clone();
+
+        let (rest, num_in_this_frame) = push_state_checks(remaining, &mut frame, read_len)?;
+
+        remaining = rest;
+
+        // Nothing to send, we've checked all SDs
+        if num_in_this_frame == 0 {
+            fmt::trace!("--> No more state checks, pushed {}", total_checks);
 
-        // A status check datagram is 14 bytes, meaning we can fit at most just over 100
-        // checks per normal EtherCAT frame. This leaves spare PDU indices available for
-        // other purposes, however if the user is using jumbo frames or something, we should
-        // always leave some indices free for e.g. other threads.
-        if num_in_this_frame > 128 {
             break;
         }
-    }
+
+        total_checks += num_in_this_frame;
+
+        let frame = frame.mark_sendable(
+            &maindevice.pdu_loop,
+            maindevice.timeouts.pdu,
+            maindevice.config.retry_behaviour.retry_count(),
+        );
+
+        maindevice.pdu_loop.wake_sender();
+
+        let received = frame.await?;
+
+        let mut all_in_state = true;
+
+        for (pdu, subdevice) in received.into_pdu_iter().zip(in_this_frame) {
+            let pdu = pdu?;
+
+            let result = AlControl::unpack_from_slice(&pdu)?;
+
+            if record_history {
+                // The status code is only meaningful while the error flag is set
+                let code = match pdu.get(4..6) {
+                    Some(&[lo, hi]) if result.error => u16::from_le_bytes([lo, hi]),
+                    _ => 0,
+                };
+
+                subdevice.al_status_history.observe(code);
+            }
+
+            if result.state != desired_state {
+                all_in_state = false;
+
+                // Every status in the frame must be recorded, otherwise stop at the first
+                // undesired state.
+                if !record_history {
+                    break;
+                }
+            }
+        }
+
+        if !all_in_state {
+            return Ok(false);
+        }
+    }
+
+    // Just sanity checking myself
+    debug_assert_eq!(total_checks, subdevices.len());
+
+    Ok(true)
+}
+
+/// Read the AL status of every given SubDevice, calling `f` with each one.
+async fn subdevice_states(
+    maindevice: &MainDevice<'_>,
+    subdevices: &[SubDevice],
+    mut f: impl FnMut(&SubDevice, AlControl),
+) -> Result<(), Error> {
+    let mut remaining = subdevices.iter();
+
+    loop {
+        let mut frame = maindevice.pdu_loop.alloc_frame()?;
+
+        let in_this_frame = remaining.clone();
+
+        let (rest, num_in_this_frame) =
+            push_state_checks(remaining, &mut frame, AlControl::PACKED_LEN)?;
+
+        remaining = rest;
+
+        if num_in_this_frame == 0 {
+            break Ok(());
+        }
+
+        let frame = frame.mark_sendable(
+            &maindevice.pdu_loop,
+            maindevice.timeouts.pdu,
+            maindevice.config.retry_behaviour.retry_count(),
+        );
+
+        maindevice.pdu_loop.wake_sender();
+
+        let received = frame.await?;
+
+        for (pdu, subdevice) in received.into_pdu_iter().zip(in_this_frame) {
+            let pdu = pdu?.wkc(1)?;
+
+            f(subdevice, AlControl::unpack_from_slice(&pdu)?);
+        }
+    }
+}
+
+/// Wait for all of the given SubDevices to transition to the desired state.
+pub(crate) async fn wait_for_subdevices_state(
+    maindevice: &MainDevice<'_>,
+    subdevices: &[SubDevice],
+    desired_state: SubDeviceState,
+) -> Result<(), Error> {
+    if maindevice.config.al_status_history {
+        for subdevice in subdevices {
+            subdevice.al_status_history.clear();
+        }
+    }
+
+    async {
+        loop {
+            if subdevices_in_state(maindevice, subdevices, desired_state).await? {
+                break Ok(());
+            }
+
+            maindevice.timeouts.loop_tick().await;
+        }
+    }
+    .timeout(maindevice.timeouts.state_transition)
+    .await
+}
+
+fn push_state_checks<'group, 'sto, I>(
+    mut subdevices: I,
+    frame: &mut CreatedFrame<'sto>,
+    read_len: usize,
+) -> Result<(I, usize), Error>
+where
+    I: Iterator<Item = &'group SubDevice>,
+{
+    let mut num_in_this_frame = 0;
+
+    while frame.can_push_pdu_payload(read_len) {
+        let Some(sd) = subdevices.next() else {
+            break;
+        };
+
+        // A too-long error here should be unreachable as we check if the payload can be
+        // pushed in the loop condition.
+        frame.push_pdu(
+            Command::fprd(sd.configured_address(), RegisterAddress::AlStatus.into()).into(),
+            (),
+            Some(read_len as u16),
+        )?;
+
+        num_in_this_frame += 1;
+
+        // A status check datagram is 14 bytes, meaning we can fit at most just over 100
+        // checks per normal EtherCAT frame. This leaves spare PDU indices available for
+        // other purposes, however if the user is using jumbo frames or something, we should
+        // always leave some indices free for e.g. other threads.
+        if num_in_this_frame > 128 {
+            break;
+        }
+    }
 
     fmt::trace!(
         "--> Pushed {} status checks into frame {}",
@@ -821,6 +1417,55 @@ where
         ))
     }
 
+    /// Call `f` with the inputs and outputs of the SubDevice with the given configured address.
+    ///
+    /// Unlike [`subdevice`](SubDeviceGroup::subdevice) or [`iter`](SubDeviceGroup::iter), no
+    /// borrow of the SubDevice is held once `f` returns, so reads and writes to different
+    /// SubDevices can be interleaved with other uses of the group. The group's PDI is locked while
+    /// `f` runs, so `f` should return quickly and must not access the PDI of this group again.
+    ///
+    /// Returns `None` if no SubDevice in this group has the given configured address.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use ethercrab::{MainDevice, MainDeviceConfig, PduStorage, Timeouts, std::ethercat_now};
+    /// # static PDU_STORAGE: PduStorage<8, { PduStorage::element_size(4) }> = PduStorage::new();
+    /// # let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+    /// # let maindevice = MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+    /// # async {
+    /// let group = maindevice
+    ///     .init_single_group::<8, 8>(ethercat_now)
+    ///     .await
+    ///     .expect("Init")
+    ///     .into_op(&maindevice)
+    ///     .await
+    ///     .expect("PRE-OP -> OP");
+    ///
+    /// // Copy the first input byte of one SubDevice to the first output byte of another
+    /// let input = group.with_device(0x1001, |io| io.inputs()[0]);
+    ///
+    /// if let Some(input) = input {
+    ///     group.with_device(0x1002, |io| io.outputs()[0] = input);
+    /// }
+    /// # };
+    /// ```
+    pub fn with_device<R>(
+        &self,
+        configured_address: u16,
+        f: impl FnOnce(&mut PdiIoRawWriteGuard<'_, MAX_PDI>) -> R,
+    ) -> Option<R> {
+        let subdevice = self
+            .inner()
+            .subdevices
+            .iter()
+            .find(|sd| sd.configured_address() == configured_address)?;
+
+        let mut io = PdiIoRawWriteGuard::new(&self.pdi, subdevice.io_segments().clone());
+
+        Some(f(&mut io))
+    }
+
     /// Get an iterator over all SubDevices in this group.
     pub fn iter<'group, 'maindevice>(
         &'group self,
@@ -838,10 +1483,28 @@ where
         })
     }
 
+    /// Get an iterator over all SubDevices in this group, along with each SubDevice's position in
+    /// the group.
+    ///
+    /// The position can be passed to [`subdevice`](SubDeviceGroup::subdevice) to retrieve the same
+    /// SubDevice again, and is stable across state transitions of this group.
+    pub fn iter_indexed<'group, 'maindevice>(
+        &'group self,
+        maindevice: &'maindevice MainDevice<'maindevice>,
+    ) -> impl Iterator<Item = (usize, SubDeviceRef<'group, SubDevicePdi<'group, MAX_PDI>>)>
+    where
+        'maindevice: 'group,
+    {
+        self.iter(maindevice).enumerate()
+    }
+
     /// Drive the SubDevice group's inputs and outputs.
     ///
     /// A `SubDeviceGroup` will not process any inputs or outputs unless this method is called
     /// periodically. It will send an `LRW` to update SubDevice outputs and read SubDevice inputs.
+    /// If the group's outputs were mapped apart from its inputs with
+    /// [`set_output_offset`](SubDeviceGroup::set_output_offset), separate `LRD` and `LWR` frames
+    /// are sent instead, which changes the expected working counter.
     ///
     /// This method returns a [`TxRxResponse`] containing the working counter and a list of all
     /// SubDevice states on success.
@@ -850,20 +1513,73 @@ where
     ///
     /// This method will return with an error if the PDU could not be sent over the network, or the
     /// response times out.
+    ///
+    /// A response timeout is handled according to the group's [`LostFramePolicy`]. If the policy
+    /// is not [`LostFramePolicy::Error`], the cycle is ended early and a working counter of zero is
+    /// returned instead of an error.
     pub async fn tx_rx<'sto>(
         &self,
         maindevice: &'sto MainDevice<'sto>,
     ) -> Result<TxRxResponse<MAX_SUBDEVICES>, Error> {
+        self.tx_rx_pdi(maindevice, false).await
+    }
+
+    /// Like [`tx_rx`](SubDeviceGroup::tx_rx), but also report whether the exchange took longer than
+    /// `deadline`.
+    ///
+    /// The deadline is measured from when this method is first polled. Passing it does not cancel
+    /// the exchange: the response is still waited for and processed as normal, and
+    /// [`DeadlineInfo::overrun`] is set in the returned [`TxRxResponse`]. Realtime loops can use
+    /// this to detect cycles where the network response was too slow, e.g. by passing the time
+    /// remaining in the current cycle.
+    ///
+    /// # Errors
+    ///
+    /// This method returns the same errors as [`tx_rx`](SubDeviceGroup::tx_rx).
+    pub async fn tx_rx_deadline<'sto>(
+        &self,
+        maindevice: &'sto MainDevice<'sto>,
+        deadline: Duration,
+    ) -> Result<TxRxResponse<MAX_SUBDEVICES, DeadlineInfo>, Error> {
+        let (response, overrun) = with_deadline(self.tx_rx(maindevice), deadline).await;
+
+        if overrun {
+            fmt::trace!("Group TX/RX overran its deadline");
+        }
+
+        response.map(|response| TxRxResponse {
+            working_counter: response.working_counter,
+            subdevice_states: response.subdevice_states,
+            extra: DeadlineInfo { overrun },
+        })
+    }
+
+    /// Send the group's PDI and process the response.
+    ///
+    /// If `inputs_only` is `true`, only the input portion of the PDI is read with `LRD`, otherwise
+    /// the whole PDI is exchanged with `LRW`, or with `LRD` and `LWR` if the outputs are not mapped
+    /// directly after the inputs.
+    async fn tx_rx_pdi<'sto>(
+        &self,
+        maindevice: &'sto MainDevice<'sto>,
+        inputs_only: bool,
+    ) -> Result<TxRxResponse<MAX_SUBDEVICES>, Error> {
+        let pdi_len = if inputs_only {
+            self.read_pdi_len
+        } else {
+            self.pdi_len
+        };
+
         fmt::trace!(
             "Group TX/RX, start address {:#010x}, data len {}, of which read bytes: {}",
             self.inner().pdi_start.start_address,
-            self.pdi_len,
+            pdi_len,
             self.read_pdi_len
         );
 
         let mut pdi_lock = self.pdi.write();
 
-        let mut total_bytes_sent = 0;
+        let mut cursor = self.pdi_cursor(inputs_only);
         let mut lrw_wkc_sum = 0;
 
         let mut subdevices = self.inner().subdevices.iter();
@@ -871,28 +1587,24 @@ where
         let mut subdevice_states = heapless::Vec::<_, MAX_SUBDEVICES>::new();
 
         loop {
-            let chunk_len = self.pdi_len.saturating_sub(total_bytes_sent);
+            let remaining = cursor.remaining();
 
-            if chunk_len == 0 && total_checks >= self.len() {
+            if remaining.is_none() && total_checks >= self.len() {
                 break;
             }
 
-            let chunk_start = total_bytes_sent.min(self.pdi_len);
-            let chunk = pdi_lock.get_mut()[chunk_start..(chunk_start + chunk_len)].as_ref();
-
             let mut frame = maindevice.pdu_loop.alloc_frame()?;
 
-            // Start offset in the EtherCAT address space
-            let pushed_chunk = if !chunk.is_empty() {
-                let start_addr = self.inner().pdi_start.start_address + total_bytes_sent as u32;
-
-                frame.push_pdu_slice_rest(Command::lrw(start_addr).into(), chunk)?
-            } else {
-                None
+            let pushed_chunk = match &remaining {
+                Some((command, chunk)) => {
+                    frame.push_pdu_slice_rest(*command, &pdi_lock.get_mut()[chunk.clone()], None)?
+                }
+                None => None,
             };
 
             // If there's space left, push as many state checks as we can into the frame
-            let (rest, num_checks_in_this_frame) = push_state_checks(subdevices, &mut frame)?;
+            let (rest, num_checks_in_this_frame) =
+                push_state_checks(subdevices, &mut frame, AlControl::PACKED_LEN)?;
             subdevices = rest;
             total_checks += num_checks_in_this_frame;
 
@@ -908,20 +1620,34 @@ where
 
             maindevice.pdu_loop.wake_sender();
 
-            let received = frame.await?;
+            let received = match frame.await {
+                Ok(received) => received,
+                Err(Error::Timeout) if self.lost_frame_policy() != LostFramePolicy::Error => {
+                    self.handle_lost_frame(&mut pdi_lock, remaining.as_ref());
+
+                    return Ok(TxRxResponse {
+                        working_counter: 0,
+                        subdevice_states,
+                        extra: (),
+                    });
+                }
+                Err(e) => return Err(e),
+            };
 
             let mut pdus = received.into_pdu_iter();
 
             // If we pushed a non-zero amount of PDI bytes, process the response
-            if let Some((bytes_in_this_chunk, _pdu_handle)) = pushed_chunk {
+            if let (Some((bytes_in_this_chunk, _pdu_handle)), Some((_, chunk))) =
+                (pushed_chunk, &remaining)
+            {
                 let wkc = self.process_received_pdi_chunk(
-                    total_bytes_sent,
+                    chunk.start,
                     bytes_in_this_chunk,
                     &pdus.next().ok_or(Error::Internal)??,
                     &mut pdi_lock,
                 )?;
 
-                total_bytes_sent += bytes_in_this_chunk;
+                cursor.advance(bytes_in_this_chunk);
                 lrw_wkc_sum += wkc;
             }
 
@@ -957,6 +1683,10 @@ where
     ///
     /// This method will return with an error if the PDU could not be sent over the network, or the
     /// response times out.
+    ///
+    /// A response timeout is handled according to the group's [`LostFramePolicy`]. If the policy
+    /// is not [`LostFramePolicy::Error`], the cycle is ended early and a working counter of zero is
+    /// returned instead of an error. The system time is `None` if the frame carrying it was lost.
     pub async fn tx_rx_sync_system_time<'sto>(
         &self,
         maindevice: &'sto MainDevice<'sto>,
@@ -971,7 +1701,7 @@ where
         );
 
         if let Some(dc_ref) = maindevice.dc_ref_address() {
-            let mut total_bytes_sent = 0;
+            let mut cursor = self.pdi_cursor(false);
             let mut time = 0;
             let mut lrw_wkc_sum = 0;
             let mut time_read = false;
@@ -998,16 +1728,15 @@ where
                     None
                 };
 
-                let chunk_start = total_bytes_sent.min(self.pdi_len);
-                let chunk_len = self.pdi_len.saturating_sub(total_bytes_sent);
-                let chunk = pdi_lock.get_mut()[chunk_start..(chunk_start + chunk_len)].as_ref();
+                let remaining = cursor.remaining();
 
-                let pushed_chunk = if !chunk.is_empty() {
-                    let start_addr = self.inner().pdi_start.start_address + total_bytes_sent as u32;
-
-                    frame.push_pdu_slice_rest(Command::lrw(start_addr).into(), chunk)?
-                } else {
-                    None
+                let pushed_chunk = match &remaining {
+                    Some((command, chunk)) => frame.push_pdu_slice_rest(
+                        *command,
+                        &pdi_lock.get_mut()[chunk.clone()],
+                        None,
+                    )?,
+                    None => None,
                 };
 
                 if let Some((bytes_in_this_chunk, _)) = pushed_chunk {
@@ -1015,7 +1744,8 @@ where
                 }
 
                 // If there's space left, push as many state checks as we can into the frame
-                let (rest, num_checks_in_this_frame) = push_state_checks(subdevices, &mut frame)?;
+                let (rest, num_checks_in_this_frame) =
+                    push_state_checks(subdevices, &mut frame, AlControl::PACKED_LEN)?;
                 subdevices = rest;
                 total_checks += num_checks_in_this_frame;
 
@@ -1035,7 +1765,19 @@ where
 
                 maindevice.pdu_loop.wake_sender();
 
-                let received = frame.await?;
+                let received = match frame.await {
+                    Ok(received) => received,
+                    Err(Error::Timeout) if self.lost_frame_policy() != LostFramePolicy::Error => {
+                        self.handle_lost_frame(&mut pdi_lock, remaining.as_ref());
+
+                        break Ok(TxRxResponse {
+                            working_counter: 0,
+                            subdevice_states,
+                            extra: time_read.then_some(time),
+                        });
+                    }
+                    Err(e) => return Err(e),
+                };
 
                 let mut pdus = received.into_pdu_iter();
 
@@ -1049,15 +1791,17 @@ where
                 }
 
                 // If we pushed a non-zero amount of PDI bytes, process the response
-                if let Some((bytes_in_this_chunk, _pdu_handle)) = pushed_chunk {
+                if let (Some((bytes_in_this_chunk, _pdu_handle)), Some((_, chunk))) =
+                    (pushed_chunk, &remaining)
+                {
                     let wkc = self.process_received_pdi_chunk(
-                        total_bytes_sent,
+                        chunk.start,
                         bytes_in_this_chunk,
                         &pdus.next().ok_or(Error::Internal)??,
                         &mut pdi_lock,
                     )?;
 
-                    total_bytes_sent += bytes_in_this_chunk;
+                    cursor.advance(bytes_in_this_chunk);
                     lrw_wkc_sum += wkc;
                 }
 
@@ -1072,7 +1816,7 @@ where
 
                 // NOTE: Not using a while loop as we want to always send the DC sync PDU even if
                 // the PDI is empty.
-                if chunk_len == 0 && total_checks >= self.len() {
+                if remaining.is_none() && total_checks >= self.len() {
                     break Ok(TxRxResponse {
                         working_counter: lrw_wkc_sum,
                         subdevice_states,
@@ -1089,9 +1833,42 @@ where
         }
     }
 
+    /// Apply the group's [`LostFramePolicy`] to the input section of the PDI.
+    ///
+    /// `lost` is the command and PDI range of the unsent part of the cursor when the frame was
+    /// lost, or `None` if the lost frame held no PDI data. Only inputs from the start of this range
+    /// on were not received this cycle.
+    fn handle_lost_frame(
+        &self,
+        pdi_lock: &mut spin::rwlock::RwLockWriteGuard<
+            '_,
+            MySyncUnsafeCell<[u8; MAX_PDI]>,
+            crate::SpinStrategy,
+        >,
+        lost: Option<&(Command, Range<usize>)>,
+    ) {
+        let policy = self.lost_frame_policy();
+
+        fmt::warn!(
+            "Group process data frame lost, applying policy {:?}",
+            policy
+        );
+
+        match policy {
+            LostFramePolicy::Error | LostFramePolicy::HoldLast => (),
+            LostFramePolicy::Zero => {
+                let lost_start = lost
+                    .map_or(self.read_pdi_len, |(_, chunk)| chunk.start)
+                    .min(self.read_pdi_len);
+
+                pdi_lock.get_mut()[lost_start..self.read_pdi_len].fill(0)
+            }
+        }
+    }
+
     fn process_received_pdi_chunk(
         &self,
-        total_bytes_sent: usize,
+        chunk_start: usize,
         bytes_in_this_chunk: usize,
         data: &ReceivedPdu<'_>,
         pdi_lock: &mut spin::rwlock::RwLockWriteGuard<
@@ -1102,8 +1879,8 @@ where
     ) -> Result<u16, Error> {
         let wkc = data.working_counter;
 
-        let rx_range = total_bytes_sent.min(self.read_pdi_len)
-            ..(total_bytes_sent + bytes_in_this_chunk).min(self.read_pdi_len);
+        let rx_range = chunk_start.min(self.read_pdi_len)
+            ..(chunk_start + bytes_in_this_chunk).min(self.read_pdi_len);
 
         let inputs_chunk = &mut pdi_lock.get_mut()[rx_range];
 
@@ -1119,6 +1896,23 @@ impl<const MAX_SUBDEVICES: usize, const MAX_PDI: usize, S>
 where
     S: HasPdi,
 {
+    /// Create a [`DcBarrier`] that releases at this group's SYNC0 period and shift.
+    ///
+    /// Tasks driving groups configured with the same [`DcConfiguration`] can wait on their
+    /// barriers to phase-align their process data cycles. Any phase set with
+    /// [`set_dc_sync_phase`](SubDeviceGroup::set_dc_sync_phase) is added to the shift.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DistributedClockError::InvalidCycleTime`] if the group was configured with a zero
+    /// `sync0_period`.
+    pub fn dc_barrier(&self) -> Result<DcBarrier, Error> {
+        DcBarrier::from_nanos(
+            self.dc_conf.sync0_period,
+            self.dc_conf.sync0_shift + self.dc_conf.sync0_phase,
+        )
+    }
+
     /// Drive the SubDevice group's inputs and outputs, synchronise EtherCAT system time with
     /// `FRMW`, and return cycle timing and SubDevice state information.
     ///
@@ -1134,6 +1928,12 @@ where
     /// This method will return with an error if the PDU could not be sent over the network, or the
     /// response times out.
     ///
+    /// A response timeout is handled according to the group's [`LostFramePolicy`]. If the policy
+    /// is not [`LostFramePolicy::Error`], the cycle is ended early and a working counter of zero is
+    /// returned instead of an error. If the frame carrying the DC system time was lost,
+    /// [`CycleInfo::dc_system_time`] is zero and [`CycleInfo::next_cycle_wait`] is one whole
+    /// SYNC0 period.
+    ///
     /// # Examples
     ///
     /// This example sends process data at 2.5ms offset into a 5ms cycle.
@@ -1240,7 +2040,7 @@ where
 
         let mut pdi_lock = self.pdi.write();
 
-        let mut total_bytes_sent = 0;
+        let mut cursor = self.pdi_cursor(false);
         let mut time = 0;
         let mut lrw_wkc_sum = 0;
         let mut time_read = false;
@@ -1268,20 +2068,18 @@ where
                 None
             };
 
-            let chunk_start = total_bytes_sent.min(self.pdi_len);
-            let chunk_len = self.pdi_len.saturating_sub(total_bytes_sent);
-            let chunk = pdi_lock.get_mut()[chunk_start..(chunk_start + chunk_len)].as_ref();
-
-            let pushed_chunk = if !chunk.is_empty() {
-                let start_addr = self.inner().pdi_start.start_address + total_bytes_sent as u32;
+            let remaining = cursor.remaining();
 
-                frame.push_pdu_slice_rest(Command::lrw(start_addr).into(), chunk)?
-            } else {
-                None
+            let pushed_chunk = match &remaining {
+                Some((command, chunk)) => {
+                    frame.push_pdu_slice_rest(*command, &pdi_lock.get_mut()[chunk.clone()], None)?
+                }
+                None => None,
             };
 
             // If there's space left, push as many state checks as we can into the frame
-            let (rest, num_checks_in_this_frame) = push_state_checks(subdevices, &mut frame)?;
+            let (rest, num_checks_in_this_frame) =
+                push_state_checks(subdevices, &mut frame, AlControl::PACKED_LEN)?;
             subdevices = rest;
             total_checks += num_checks_in_this_frame;
 
@@ -1297,7 +2095,17 @@ where
 
             maindevice.pdu_loop.wake_sender();
 
-            let received = frame.await?;
+            let received = match frame.await {
+                Ok(received) => received,
+                Err(Error::Timeout) if self.lost_frame_policy() != LostFramePolicy::Error => {
+                    self.handle_lost_frame(&mut pdi_lock, remaining.as_ref());
+
+                    lrw_wkc_sum = 0;
+
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
 
             let mut pdus = received.into_pdu_iter();
 
@@ -1310,15 +2118,17 @@ where
             }
 
             // If we pushed a non-zero amount of PDI bytes, process the response
-            if let Some((bytes_in_this_chunk, _pdu_handle)) = pushed_chunk {
+            if let (Some((bytes_in_this_chunk, _pdu_handle)), Some((_, chunk))) =
+                (pushed_chunk, &remaining)
+            {
                 let wkc = self.process_received_pdi_chunk(
-                    total_bytes_sent,
+                    chunk.start,
                     bytes_in_this_chunk,
                     &pdus.next().ok_or(Error::Internal)??,
                     &mut pdi_lock,
                 )?;
 
-                total_bytes_sent += bytes_in_this_chunk;
+                cursor.advance(bytes_in_this_chunk);
                 lrw_wkc_sum += wkc;
             }
 
@@ -1335,14 +2145,31 @@ where
             // PDI is empty.
             // This condition will exit the loop if the whole PDI has been sent as well as all
             // SubDevice status check PDUs.
-            if chunk_len == 0 && total_checks >= self.len() {
+            if remaining.is_none() && total_checks >= self.len() {
                 break;
             }
         }
 
+        // The frame carrying the DC system time was lost, so there's nothing to synchronise to.
+        // Waiting a whole period keeps the application cycling at roughly the right rate.
+        if !time_read {
+            return Ok(TxRxResponse {
+                working_counter: 0,
+                subdevice_states,
+                extra: CycleInfo {
+                    dc_system_time: 0,
+                    cycle_start_offset: Duration::ZERO,
+                    next_cycle_wait: Duration::from_nanos(self.dc_conf.sync0_period),
+                },
+            });
+        }
+
         // Nanoseconds from the start of the cycle. This works because the first SYNC0 pulse
-        // time is rounded to a whole number of `sync0_period`-length cycles.
-        let cycle_start_offset = time % self.dc_conf.sync0_period;
+        // time is rounded to a whole number of `sync0_period`-length cycles, plus the group's
+        // phase. Adding one period before subtracting the phase keeps this from underflowing.
+        let period = self.dc_conf.sync0_period;
+        let cycle_start_offset =
+            (time % period + period - self.dc_conf.sync0_phase % period) % period;
 
         let time_to_next_iter =
             (self.dc_conf.sync0_period - cycle_start_offset) + self.dc_conf.sync0_shift;
@@ -1363,11 +2190,19 @@ where
 mod tests {
     use super::*;
     use crate::{
-        MainDeviceConfig, PduStorage, Timeouts,
-        ethernet::{EthernetAddress, EthernetFrame},
+        DcSupport, MainDeviceConfig, PduLoop, PduRx, PduStorage, PduTx, Timeouts,
+        al_status_code::AlStatusCode,
+        command::{FPRD, FPWR, LRD, LRW, LWR},
+        fake_network::{FakeEsc, FakeNetwork, SentFrame},
+        pdi::PdiSegment,
         pdu_loop::ReceivedFrame,
+        timer_factory::manual::ManualClock,
+    };
+    use core::{
+        pin::pin,
+        sync::atomic::{AtomicBool, AtomicU8, Ordering},
+        task::{Context, Poll, Waker},
     };
-    use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
     use std::{sync::Arc, thread};
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 3)]
@@ -1423,11 +2258,7 @@ mod tests {
                 thread::sleep(Duration::from_millis(1));
 
                 // Munge fake sent frame into a fake received frame
-                let ethernet_frame = {
-                    let mut frame = EthernetFrame::new_checked(ethernet_frame).unwrap();
-                    frame.set_src_addr(EthernetAddress([0x12, 0x10, 0x10, 0x10, 0x10, 0x10]));
-                    frame.into_inner()
-                };
+                let ethernet_frame = SentFrame::new(ethernet_frame).into_reply();
 
                 while rx.receive_frame(&ethernet_frame).is_err() {}
 
@@ -1457,6 +2288,10 @@ mod tests {
             inner: MySyncUnsafeCell::new(GroupInner {
                 subdevices: heapless::Vec::new(),
                 pdi_start: PdiOffset::default(),
+                lost_frame_policy: LostFramePolicy::Error,
+                dc_sync_phase: AtomicU32::new(0),
+                output_offset: None,
+                split_output_address: None,
             }),
             dc_conf: NoDc,
             _state: PhantomData,
@@ -1480,17 +2315,36 @@ mod tests {
         rx_handle.join().unwrap();
     }
 
+    /// Allocate a frame with exactly `len` bytes of space left for PDUs.
+    ///
+    /// Frame buffers are at least 64 bytes long with the `software-fcs` feature enabled, so the
+    /// space not under test is taken up by a NOP PDU. Storage elements must be at least
+    /// [`PduStorage::element_size(len)`](PduStorage::element_size) bytes long to fit it.
+    fn alloc_frame_with_space<'sto>(pdu_loop: &PduLoop<'sto>, len: usize) -> CreatedFrame<'sto> {
+        let mut frame = pdu_loop.alloc_frame().expect("No frame");
+
+        let filler = vec![0u8; pdu_loop.max_frame_data() - len];
+
+        frame
+            .push_pdu(Command::Nop, filler.as_slice(), None)
+            .expect("Filler PDU");
+
+        frame
+    }
+
     #[test]
     fn multi_state_checks_single_frame() {
+        const PDUS_LEN: usize = AlControl::PACKED_LEN + frame_layout::PDU_OVERHEAD;
+
         const MAX_FRAMES: usize = 1;
-        const MAX_PDU_DATA: usize = PduStorage::element_size(AlControl::PACKED_LEN);
+        const MAX_PDU_DATA: usize = PduStorage::element_size(PDUS_LEN);
         static PDU_STORAGE: PduStorage<MAX_FRAMES, MAX_PDU_DATA> = PduStorage::new();
 
         crate::test_logger();
 
         let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
 
-        let mut frame = pdu_loop.alloc_frame().expect("No frame");
+        let mut frame = alloc_frame_with_space(&pdu_loop, PDUS_LEN);
 
         assert!(
             frame.can_push_pdu_payload(AlControl::PACKED_LEN),
@@ -1501,14 +2355,14 @@ mod tests {
             "test requires the frame to fit exactly one status check PDU"
         );
 
-        let single_sd = vec![SubDevice {
+        let single_sd = [SubDevice {
             ..SubDevice::default()
         }];
 
         let subdevices = single_sd.iter();
 
-        let (rest, num_pushed) =
-            push_state_checks(subdevices, &mut frame).expect("Could not push status check");
+        let (rest, num_pushed) = push_state_checks(subdevices, &mut frame, AlControl::PACKED_LEN)
+            .expect("Could not push status check");
 
         assert_eq!(rest.count(), 0);
         assert_eq!(num_pushed, single_sd.len());
@@ -1521,20 +2375,20 @@ mod tests {
         // 1 byte left. AlControl takes 2 bytes.
         const SPACE_LEFT: usize = 1;
 
+        const PDUS_LEN: usize = (AlControl::PACKED_LEN + frame_layout::PDU_OVERHEAD) * 2
+            + (SPACE_LEFT + frame_layout::PDU_OVERHEAD);
+
         const MAX_FRAMES: usize = 1;
-        const MAX_PDU_DATA: usize = (AlControl::PACKED_LEN + CreatedFrame::PDU_OVERHEAD_BYTES) * 2
-            + (SPACE_LEFT + CreatedFrame::PDU_OVERHEAD_BYTES)
-            // Ethernet and EtherCAT frame headers
-            + 16;
+        const MAX_PDU_DATA: usize = PduStorage::element_size(PDUS_LEN);
         static PDU_STORAGE: PduStorage<MAX_FRAMES, MAX_PDU_DATA> = PduStorage::new();
 
         crate::test_logger();
 
         let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
 
-        let mut frame = pdu_loop.alloc_frame().expect("No frame");
+        let mut frame = alloc_frame_with_space(&pdu_loop, PDUS_LEN);
 
-        let sds = vec![
+        let sds = [
             SubDevice {
                 ..SubDevice::default()
             },
@@ -1548,8 +2402,8 @@ mod tests {
 
         let subdevices = sds.iter();
 
-        let (rest, num_pushed) =
-            push_state_checks(subdevices, &mut frame).expect("Could not push status check");
+        let (rest, num_pushed) = push_state_checks(subdevices, &mut frame, AlControl::PACKED_LEN)
+            .expect("Could not push status check");
 
         assert_eq!(num_pushed, 2, "frame should hold two SD status checks");
         assert_eq!(rest.count(), 1, "frame can only hold two SD status checks");
@@ -1561,66 +2415,1348 @@ mod tests {
         );
     }
 
-    // This records the behaviour of a DC setup of the following 16 SubDevices:
-    //
-    // - EK1100
-    // - EL2828
-    // - EL2889
-    // - EL2004
-    // - EL1004
-    // - EL1018
-    // - EL1008
-    // - EL1004
-    // - EL2004
-    // - EL2008
-    // - EL1008
-    // - EL2008
-    // - EL2008
-    // - EL2522
-    // - EL1258
-    // - EL9505
-    #[test]
-    fn large_group_frame_split() {
-        const MAX_SUBDEVICES: usize = 32;
-        const MAX_PDU_DATA: usize = PduStorage::element_size(256);
-        const MAX_FRAMES: usize = 32;
-        const MAX_PDI: usize = 512;
-        static PDU_STORAGE: PduStorage<MAX_FRAMES, MAX_PDU_DATA> = PduStorage::new();
+    /// A fake ESC in PRE-OP at the given configured address.
+    fn pre_op_esc(configured_address: u16, eeprom: &'static [u8]) -> FakeEsc {
+        FakeEsc::new(eeprom)
+            .with_configured_address(configured_address)
+            .with_register(RegisterAddress::AlStatus, &[0x02, 0x00])
+    }
 
-        crate::test_logger();
+    /// Configure the PDI of a group containing an EL2828 (1 output byte) and an EL2889 (2 output
+    /// bytes), returning the result and the number of PDI bytes used.
+    fn configure_pdi<const MAX_PDI: usize>() -> (Result<(), Error>, usize) {
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut escs = [
+            pre_op_esc(0x1000, include_bytes!("../../dumps/eeprom/el2828.hex")),
+            pre_op_esc(0x1001, include_bytes!("../../dumps/eeprom/el2889.hex")),
+        ];
 
-        let (mock_net_tx, mock_net_rx) = std::sync::mpsc::sync_channel::<Vec<u8>>(16);
+        let mut group = SubDeviceGroup::<2, MAX_PDI, PreOp, NoDc>::default();
+
+        for esc in escs.iter() {
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
 
-        let (mut tx, mut rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+        let result = run_with_escs(
+            group.configure_fmmus(&maindevice),
+            &mut tx,
+            &mut rx,
+            &mut escs,
+        );
 
-        let maindevice = Arc::new(MainDevice::new(
-            pdu_loop,
-            Timeouts::default(),
-            MainDeviceConfig::default(),
-        ));
+        (result, group.pdi_used())
+    }
 
-        let stop = Arc::new(AtomicBool::new(false));
+    /// Poll a future to completion, answering each frame it sends from the given fake ESCs.
+    fn run_with_escs<'sto, T>(
+        fut: impl Future<Output = T>,
+        tx: &mut PduTx<'sto>,
+        rx: &mut PduRx<'sto>,
+        escs: &mut [FakeEsc],
+    ) -> T {
+        FakeNetwork::new(tx, rx).run(fut, |pdu| FakeEsc::process(escs, pdu))
+    }
 
-        let stop1 = stop.clone();
+    #[test]
+    fn bring_up_with_from_pre_op() {
+        crate::test_logger();
 
-        let tx_handle = thread::spawn(move || {
-            fmt::info!("Spawn TX task");
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
 
-            while !stop1.load(Ordering::Relaxed) {
-                while let Some(frame) = tx.next_sendable_frame() {
-                    fmt::info!("Sendable frame");
+        let mut escs = [
+            pre_op_esc(0x1000, include_bytes!("../../dumps/eeprom/el2828.hex")),
+            pre_op_esc(0x1001, include_bytes!("../../dumps/eeprom/el2889.hex")),
+        ];
 
-                    frame
-                        .send_blocking(|bytes| {
-                            mock_net_tx.send(bytes.to_vec()).unwrap();
+        let mut group = SubDeviceGroup::<2, 8, PreOp, NoDc>::default();
+
+        for esc in escs.iter() {
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
 
-                            Ok(bytes.len())
-                        })
-                        .unwrap();
+        let mut configured = Vec::new();
 
-                    thread::yield_now();
-                }
-            }
+        let group = run_with_escs(
+            group.bring_up_with(&maindevice, async |subdevice| {
+                configured.push(subdevice.configured_address());
+
+                Ok(())
+            }),
+            &mut tx,
+            &mut rx,
+            &mut escs,
+        )
+        .expect("bring up");
+
+        assert_eq!(configured, [0x1000, 0x1001]);
+
+        for esc in escs.iter() {
+            // SAFE-OP, OP
+            assert_eq!(esc.requested_states, [0x04, 0x08]);
+        }
+
+        assert_eq!(group.pdi_used(), 3);
+    }
+
+    #[test]
+    fn bring_up_with_configure_error() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut escs = [
+            pre_op_esc(0x1000, include_bytes!("../../dumps/eeprom/el2828.hex")),
+            pre_op_esc(0x1001, include_bytes!("../../dumps/eeprom/el2889.hex")),
+        ];
+
+        let mut group = SubDeviceGroup::<2, 8, PreOp, NoDc>::default();
+
+        for esc in escs.iter() {
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
+
+        let result = run_with_escs(
+            group.bring_up_with(&maindevice, async |subdevice| {
+                if subdevice.configured_address() == 0x1001 {
+                    return Err(Error::Internal);
+                }
+
+                Ok(())
+            }),
+            &mut tx,
+            &mut rx,
+            &mut escs,
+        );
+
+        assert_eq!(result.map(|_| ()), Err(Error::Internal));
+
+        // Group is left in PRE-OP with no PDI configured
+        for esc in escs.iter() {
+            assert!(esc.requested_states.is_empty());
+            assert!(
+                esc.registers[0x0600..0x0700].iter().all(|b| *b == 0),
+                "FMMUs written"
+            );
+        }
+    }
+
+    #[test]
+    fn planned_pdi_matches_configured() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut escs = [
+            pre_op_esc(0x1000, include_bytes!("../../dumps/eeprom/el2828.hex")),
+            pre_op_esc(0x1001, include_bytes!("../../dumps/eeprom/el2889.hex")),
+        ];
+
+        let mut group = SubDeviceGroup::<2, 8, PreOp, NoDc>::default();
+
+        for esc in escs.iter() {
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
+
+        let planned = run_with_escs(group.planned_pdi(&maindevice), &mut tx, &mut rx, &mut escs)
+            .expect("planned PDI");
+
+        assert_eq!(
+            planned.as_slice(),
+            &[(0x1000, 0..0, 0..1), (0x1001, 0..0, 1..3)]
+        );
+
+        // Planning must not configure anything
+        for esc in escs.iter() {
+            assert!(
+                esc.registers[0x0600..0x0700].iter().all(|b| *b == 0),
+                "FMMUs written"
+            );
+            assert!(
+                esc.registers[0x0800..0x0880].iter().all(|b| *b == 0),
+                "SMs written"
+            );
+        }
+
+        run_with_escs(
+            group.configure_fmmus(&maindevice),
+            &mut tx,
+            &mut rx,
+            &mut escs,
+        )
+        .expect("configure FMMUs");
+
+        let configured = group
+            .inner()
+            .subdevices
+            .iter()
+            .map(|sd| {
+                (
+                    sd.configured_address(),
+                    sd.config.io.input.bytes.clone(),
+                    sd.config.io.output.bytes.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(planned.as_slice(), configured.as_slice());
+        assert_eq!(
+            planned.last().map(|(_, _, outputs)| outputs.end),
+            Some(group.pdi_used())
+        );
+    }
+
+    #[test]
+    fn bring_up_from_init() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut escs = [
+            pre_op_esc(0x1000, include_bytes!("../../dumps/eeprom/el2828.hex")),
+            pre_op_esc(0x1001, include_bytes!("../../dumps/eeprom/el2889.hex")),
+        ];
+
+        let mut group = SubDeviceGroup::<2, 8, Init, NoDc>::default();
+
+        for esc in escs.iter_mut() {
+            // INIT
+            esc.registers[0x0130] = 0x01;
+
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
+
+        let mut configured = Vec::new();
+
+        let group = run_with_escs(
+            group.bring_up_with(&maindevice, async |subdevice| {
+                configured.push((subdevice.configured_address(), subdevice.state().await?));
+
+                Ok(())
+            }),
+            &mut tx,
+            &mut rx,
+            &mut escs,
+        )
+        .expect("bring up");
+
+        assert_eq!(
+            configured,
+            [
+                (0x1000, SubDeviceState::PreOp),
+                (0x1001, SubDeviceState::PreOp)
+            ]
+        );
+
+        for esc in escs.iter() {
+            // PRE-OP, SAFE-OP, OP
+            assert_eq!(esc.requested_states, [0x02, 0x04, 0x08]);
+            assert!(
+                esc.registers[0x0600..0x0700].iter().any(|b| *b != 0),
+                "FMMUs not configured"
+            );
+        }
+
+        assert_eq!(group.pdi_used(), 3);
+    }
+
+    #[test]
+    fn pdi_exact_fit() {
+        crate::test_logger();
+
+        assert_eq!(configure_pdi::<3>(), (Ok(()), 3));
+    }
+
+    #[test]
+    fn pdi_too_long() {
+        crate::test_logger();
+
+        assert_eq!(
+            configure_pdi::<2>(),
+            (
+                Err(Error::PdiTooLong {
+                    required: 3,
+                    available: 2
+                }),
+                3
+            )
+        );
+    }
+
+    #[test]
+    fn output_offset_splits_mapping() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut escs = [
+            pre_op_esc(0x1000, include_bytes!("../../dumps/eeprom/el2828.hex")),
+            pre_op_esc(0x1001, include_bytes!("../../dumps/eeprom/el2889.hex")),
+        ];
+
+        let mut group = SubDeviceGroup::<2, 8, PreOp, NoDc>::default();
+
+        for esc in escs.iter() {
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
+
+        group.set_output_offset(Some(4));
+
+        run_with_escs(
+            group.configure_fmmus(&maindevice),
+            &mut tx,
+            &mut rx,
+            &mut escs,
+        )
+        .expect("configure");
+
+        assert_eq!(group.inner().split_output_address, Some(4));
+
+        // PDI layout is unchanged
+        assert_eq!(group.pdi_used(), 3);
+        assert_eq!(
+            group
+                .inner()
+                .subdevices
+                .iter()
+                .map(|sd| sd.config.io.output.bytes.clone())
+                .collect::<Vec<_>>(),
+            [0..1, 1..3]
+        );
+
+        // Logical start address of each enabled FMMU
+        let logical_starts = escs
+            .iter()
+            .map(|esc| {
+                esc.registers[0x0600..0x0700]
+                    .chunks(16)
+                    .filter(|fmmu| fmmu[12] & 0x01 != 0)
+                    .map(|fmmu| u32::from_le_bytes(fmmu[0..4].try_into().unwrap()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // The EL2889 maps each of its two output bytes with its own FMMU
+        assert_eq!(logical_starts, [vec![4], vec![5, 6]]);
+
+        // Outputs must still fit in the group's logical address space
+        let mut group = SubDeviceGroup::<2, 8, PreOp, NoDc>::default();
+
+        for esc in escs.iter() {
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
+
+        group.set_output_offset(Some(6));
+
+        assert_eq!(
+            run_with_escs(
+                group.configure_fmmus(&maindevice),
+                &mut tx,
+                &mut rx,
+                &mut escs,
+            ),
+            Err(Error::PdiTooLong {
+                required: 9,
+                available: 8
+            })
+        );
+
+        // The group has no inputs, so an offset of zero maps the outputs where they would be
+        // anyway and the FMMUs are contiguous.
+        let mut group = SubDeviceGroup::<2, 8, PreOp, NoDc>::default();
+
+        for esc in escs.iter_mut() {
+            esc.registers[0x0600..0x0700].fill(0);
+
+            group
+                .inner
+                .get_mut()
+                .subdevices
+                .push(SubDevice {
+                    configured_address: esc.configured_address(),
+                    ..SubDevice::default()
+                })
+                .unwrap();
+        }
+
+        group.set_output_offset(Some(0));
+
+        run_with_escs(
+            group.configure_fmmus(&maindevice),
+            &mut tx,
+            &mut rx,
+            &mut escs,
+        )
+        .expect("configure");
+
+        assert_eq!(group.inner().split_output_address, None);
+    }
+
+    #[test]
+    fn output_offset_overlapping_inputs() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let mut escs = [pre_op_esc(
+            0x1000,
+            include_bytes!("../../dumps/eeprom/akd.hex"),
+        )];
+
+        let mut group = SubDeviceGroup::<1, 64, PreOp, NoDc>::default();
+
+        group
+            .inner
+            .get_mut()
+            .subdevices
+            .push(SubDevice {
+                configured_address: 0x1000,
+                ..SubDevice::default()
+            })
+            .unwrap();
+
+        // The AKD maps 6 input bytes
+        group.set_output_offset(Some(1));
+
+        assert_eq!(
+            run_with_escs(
+                group.configure_fmmus(&maindevice),
+                &mut tx,
+                &mut rx,
+                &mut escs,
+            ),
+            Err(Error::OutputOffsetOverlap {
+                offset: 1,
+                input_len: 6
+            })
+        );
+    }
+
+    #[test]
+    fn with_device_writes_outputs() {
+        crate::test_logger();
+
+        const MAX_PDI: usize = 8;
+
+        let subdevice = |configured_address, input: Range<usize>, output: Range<usize>| {
+            let mut sd = SubDevice {
+                configured_address,
+                ..SubDevice::default()
+            };
+
+            sd.config.io = IoRanges {
+                input: PdiSegment { bytes: input },
+                output: PdiSegment { bytes: output },
+            };
+
+            sd
+        };
+
+        let subdevices = heapless::Vec::from_slice(&[
+            subdevice(0x1001, 0..2, 4..6),
+            subdevice(0x1002, 2..4, 6..8),
+        ])
+        .unwrap();
+
+        let group: SubDeviceGroup<2, MAX_PDI, Op, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([
+                0x01, 0x02, 0x03, 0x04, 0, 0, 0, 0,
+            ])),
+            read_pdi_len: 4,
+            pdi_len: MAX_PDI,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                subdevices,
+                ..GroupInner::default()
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        let inputs = group.with_device(0x1002, |io| {
+            io.outputs().copy_from_slice(&[0xaa, 0xbb]);
+
+            [io.inputs()[0], io.inputs()[1]]
+        });
+
+        assert_eq!(inputs, Some([0x03, 0x04]));
+
+        // Unknown address
+        assert_eq!(group.with_device(0x1003, |_io| ()), None);
+
+        // Only the second SubDevice's outputs were changed
+        assert_eq!(
+            group.pdi.write().get_mut(),
+            &[0x01, 0x02, 0x03, 0x04, 0, 0, 0xaa, 0xbb]
+        );
+    }
+
+    #[test]
+    fn split_io_separate_frames() {
+        crate::test_logger();
+
+        const MAX_PDI: usize = 8;
+        const OUTPUTS_ADDRESS: u32 = 0x40;
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevices = heapless::Vec::from_slice(&[SubDevice {
+            configured_address: 0x1001,
+            ..SubDevice::default()
+        }])
+        .unwrap();
+
+        // 4 bytes of inputs followed by 4 bytes of outputs, mapped apart from the inputs
+        let mut pdi = [0u8; MAX_PDI];
+        pdi[4..].copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+
+        let group: SubDeviceGroup<1, MAX_PDI, Op, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new(pdi)),
+            read_pdi_len: 4,
+            pdi_len: MAX_PDI,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                subdevices,
+                split_output_address: Some(OUTPUTS_ADDRESS),
+                ..GroupInner::default()
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        // Commands, logical address and data length of the PDUs in each frame
+        let mut frames = Vec::new();
+        let mut written_outputs = Vec::new();
+
+        let response = FakeNetwork::new(&mut tx, &mut rx)
+            .run_frames(group.tx_rx(&maindevice), |frame| {
+                let mut pdus = Vec::new();
+
+                frame.respond(|pdu| {
+                    pdus.push((pdu.command, pdu.logical_address(), pdu.data.len()));
+
+                    match pdu.command {
+                        LRD => pdu.data.copy_from_slice(&[0xaa, 0xbb, 0xcc, 0xdd]),
+                        LWR => written_outputs.extend_from_slice(pdu.data),
+                        // AL status is OP
+                        FPRD => pdu.data.copy_from_slice(&[0x08, 0x00]),
+                        other => panic!("unexpected command {:#04x}", other),
+                    }
+
+                    1
+                });
+
+                frames.push(pdus);
+
+                true
+            })
+            .expect("cycle");
+
+        // Inputs and outputs are sent in separate frames to their own logical addresses
+        assert_eq!(
+            frames,
+            [
+                vec![(LRD, 0, 4), (FPRD, 0x0130_1001, 2)],
+                vec![(LWR, OUTPUTS_ADDRESS, 4)]
+            ]
+        );
+        // One each from the LRD and LWR, instead of 3 from an LRW
+        assert_eq!(response.working_counter, 2);
+        assert_eq!(response.subdevice_states, [SubDeviceState::Op]);
+        assert_eq!(written_outputs, [0x11, 0x22, 0x33, 0x44]);
+
+        let mut pdi = group.pdi.write();
+
+        assert_eq!(pdi.get_mut()[0..4], [0xaa, 0xbb, 0xcc, 0xdd]);
+        assert_eq!(pdi.get_mut()[4..], [0x11, 0x22, 0x33, 0x44]);
+    }
+
+    #[test]
+    fn split_io_lost_write_frame() {
+        crate::test_logger();
+
+        let clock = ManualClock::default();
+        let _guard = clock.drive_pdu_timeouts();
+
+        const MAX_PDI: usize = 8;
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts {
+                pdu: Duration::from_millis(10),
+                ..Timeouts::default()
+            },
+            MainDeviceConfig::default(),
+        );
+
+        let mut group: SubDeviceGroup<1, MAX_PDI, PreOpPdi, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([0u8; MAX_PDI])),
+            read_pdi_len: 4,
+            pdi_len: MAX_PDI,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                split_output_address: Some(0x40),
+                ..GroupInner::default()
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        group.set_lost_frame_policy(LostFramePolicy::Zero);
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let mut fut = pin!(group.tx_rx(&maindevice));
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        let response = loop {
+            if let Poll::Ready(result) = fut.as_mut().poll(&mut ctx) {
+                break result;
+            }
+
+            let Some(mut frame) = network.next_frame() else {
+                // Nothing more to send, so the lost frame's PDU timeout can expire
+                clock.advance(Duration::from_millis(10));
+
+                continue;
+            };
+
+            let mut inputs = false;
+
+            frame.respond(|pdu| {
+                inputs |= pdu.command == LRD;
+
+                pdu.data.copy_from_slice(&[0xaa, 0xbb, 0xcc, 0xdd]);
+
+                1
+            });
+
+            // Only the LWR frame is lost
+            if inputs {
+                network.reply(frame);
+            }
+        };
+
+        assert_eq!(response.map(|response| response.working_counter), Ok(0));
+
+        // Inputs were received before the outputs frame was lost, so are not zeroed
+        let mut pdi = group.pdi.write();
+
+        assert_eq!(pdi.get_mut()[0..4], [0xaa, 0xbb, 0xcc, 0xdd]);
+    }
+
+    /// Run two cycles of a group with 4 input and 4 output bytes. The first cycle's frame is
+    /// answered with known inputs, and the second cycle's frame is lost.
+    ///
+    /// Returns the working counter of the second cycle and the group's inputs afterwards.
+    fn lost_frame_cycle(policy: LostFramePolicy) -> (Result<u16, Error>, [u8; 4]) {
+        const MAX_PDI: usize = 8;
+
+        let clock = ManualClock::default();
+        let _guard = clock.drive_pdu_timeouts();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(MAX_PDI) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts {
+                pdu: Duration::from_millis(10),
+                ..Timeouts::default()
+            },
+            MainDeviceConfig::default(),
+        );
+
+        let mut group: SubDeviceGroup<1, MAX_PDI, PreOpPdi, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([0u8; MAX_PDI])),
+            read_pdi_len: 4,
+            pdi_len: MAX_PDI,
+            inner: MySyncUnsafeCell::new(GroupInner::default()),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        group.set_lost_frame_policy(policy);
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let mut result = Ok(0);
+
+        for respond in [true, false] {
+            result = lost_frame_run(&mut network, &clock, group.tx_rx(&maindevice), respond)
+                .map(|response| response.working_counter);
+        }
+
+        let mut pdi = group.pdi.write();
+        let inputs = pdi.get_mut()[0..4].try_into().unwrap();
+
+        (result, inputs)
+    }
+
+    /// Poll `fut` to completion, answering every frame it sends with `0xaa, 0xbb, 0xcc, 0xdd` in
+    /// the first four bytes of every PDU if `respond` is `true`, or advancing `clock` past the PDU
+    /// timeout if not.
+    fn lost_frame_run<F: Future>(
+        network: &mut FakeNetwork,
+        clock: &ManualClock,
+        fut: F,
+        respond: bool,
+    ) -> F::Output {
+        let mut fut = pin!(fut);
+
+        let mut ctx = Context::from_waker(Waker::noop());
+
+        loop {
+            if let Poll::Ready(result) = fut.as_mut().poll(&mut ctx) {
+                break result;
+            }
+
+            let Some(mut frame) = network.next_frame() else {
+                // Nothing more to send, so the lost frame's PDU timeout can expire
+                clock.advance(Duration::from_millis(10));
+
+                continue;
+            };
+
+            if !respond {
+                continue;
+            }
+
+            // Inputs and DC time, outputs left as sent
+            frame.respond(|pdu| {
+                pdu.data[0..4].copy_from_slice(&[0xaa, 0xbb, 0xcc, 0xdd]);
+
+                1
+            });
+
+            network.reply(frame);
+        }
+    }
+
+    #[test]
+    fn lost_frame_policy() {
+        crate::test_logger();
+
+        assert_eq!(
+            lost_frame_cycle(LostFramePolicy::Error),
+            (Err(Error::Timeout), [0xaa, 0xbb, 0xcc, 0xdd])
+        );
+        assert_eq!(
+            lost_frame_cycle(LostFramePolicy::HoldLast),
+            (Ok(0), [0xaa, 0xbb, 0xcc, 0xdd])
+        );
+        assert_eq!(lost_frame_cycle(LostFramePolicy::Zero), (Ok(0), [0; 4]));
+    }
+
+    #[test]
+    fn lost_frame_policy_dc() {
+        crate::test_logger();
+
+        const MAX_PDI: usize = 8;
+
+        let clock = ManualClock::default();
+        let _guard = clock.drive_pdu_timeouts();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(MAX_PDI + 8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts {
+                pdu: Duration::from_millis(10),
+                ..Timeouts::default()
+            },
+            MainDeviceConfig::default(),
+        );
+
+        maindevice.set_dc_ref_address(0x1000);
+
+        let mut group: SubDeviceGroup<1, MAX_PDI, Op, HasDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([0u8; MAX_PDI])),
+            read_pdi_len: 4,
+            pdi_len: MAX_PDI,
+            inner: MySyncUnsafeCell::new(GroupInner::default()),
+            dc_conf: HasDc {
+                sync0_period: 100_000,
+                sync0_shift: 0,
+                sync0_phase: 0,
+                reference: 0x1000,
+            },
+            _state: PhantomData,
+        };
+
+        group.set_lost_frame_policy(LostFramePolicy::Zero);
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        let response =
+            lost_frame_run(&mut network, &clock, group.tx_rx_dc(&maindevice), true).unwrap();
+
+        assert_eq!(response.working_counter, 1);
+        assert_eq!(response.extra.dc_system_time, 0xddccbbaa);
+        assert_eq!(group.pdi.write().get_mut()[0..4], [0xaa, 0xbb, 0xcc, 0xdd]);
+
+        let response =
+            lost_frame_run(&mut network, &clock, group.tx_rx_dc(&maindevice), false).unwrap();
+
+        assert_eq!(response.working_counter, 0);
+        assert_eq!(response.extra.dc_system_time, 0);
+        assert_eq!(
+            response.extra.next_cycle_wait,
+            Duration::from_nanos(100_000)
+        );
+        assert_eq!(group.pdi.write().get_mut()[0..4], [0; 4]);
+
+        lost_frame_run(
+            &mut network,
+            &clock,
+            group.tx_rx_sync_system_time(&maindevice),
+            true,
+        )
+        .unwrap();
+
+        let response = lost_frame_run(
+            &mut network,
+            &clock,
+            group.tx_rx_sync_system_time(&maindevice),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(response.working_counter, 0);
+        assert_eq!(response.extra, None);
+        assert_eq!(group.pdi.write().get_mut()[0..4], [0; 4]);
+    }
+
+    #[test]
+    fn inputs_only_never_writes_outputs() {
+        crate::test_logger();
+
+        const MAX_PDI: usize = 8;
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevices = heapless::Vec::from_slice(&[SubDevice {
+            configured_address: 0x1001,
+            ..SubDevice::default()
+        }])
+        .unwrap();
+
+        // 4 bytes of inputs followed by 4 bytes of outputs that must never be sent
+        let mut pdi = [0u8; MAX_PDI];
+        pdi[4..].fill(0xff);
+
+        let group: SubDeviceGroup<1, MAX_PDI, SafeOp, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new(pdi)),
+            read_pdi_len: 4,
+            pdi_len: MAX_PDI,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                subdevices,
+                ..GroupInner::default()
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        for inputs in [[0x01, 0x02, 0x03, 0x04], [0xaa, 0xbb, 0xcc, 0xdd]] {
+            let mut commands = Vec::new();
+
+            let response = network
+                .run(group.tx_rx_inputs_only(&maindevice), |pdu| {
+                    commands.push((pdu.command, pdu.data.len()));
+
+                    match pdu.command {
+                        LRD => pdu.data.copy_from_slice(&inputs),
+                        // AL status is SAFE-OP
+                        FPRD => pdu.data.copy_from_slice(&[0x04, 0x00]),
+                        other => panic!("unexpected command {:#04x}", other),
+                    }
+
+                    1
+                })
+                .expect("cycle");
+
+            // Only the inputs are read, along with the state check
+            assert_eq!(commands, [(LRD, 4), (FPRD, 2)]);
+            assert_eq!(response.working_counter, 1);
+            assert_eq!(response.subdevice_states, [SubDeviceState::SafeOp]);
+
+            let mut pdi = group.pdi.write();
+
+            assert_eq!(pdi.get_mut()[0..4], inputs);
+            assert_eq!(pdi.get_mut()[4..], [0xff; 4]);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn tx_rx_deadline_overrun() {
+        crate::test_logger();
+
+        const MAX_PDI: usize = 4;
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let group: SubDeviceGroup<1, MAX_PDI, Op, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([0u8; MAX_PDI])),
+            read_pdi_len: 2,
+            pdi_len: MAX_PDI,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                subdevices: heapless::Vec::from_slice(&[SubDevice {
+                    configured_address: 0x1001,
+                    ..SubDevice::default()
+                }])
+                .unwrap(),
+                ..GroupInner::default()
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        // Response delay, deadline, expected overrun
+        let cases = [
+            (Duration::ZERO, Duration::from_secs(1), false),
+            (Duration::from_millis(10), Duration::from_millis(1), true),
+        ];
+
+        for (delay, deadline, expected) in cases {
+            let response = network
+                .run_frames(group.tx_rx_deadline(&maindevice, deadline), |frame| {
+                    frame.respond(|pdu| match pdu.command {
+                        LRW => 3,
+                        // AL status is OP
+                        FPRD => {
+                            pdu.data.copy_from_slice(&[0x08, 0x00]);
+
+                            1
+                        }
+                        other => panic!("unexpected command {:#04x}", other),
+                    });
+
+                    thread::sleep(delay);
+
+                    true
+                })
+                .expect("cycle");
+
+            // The late response is still processed
+            assert_eq!(response.working_counter, 3);
+            assert_eq!(response.subdevice_states, [SubDeviceState::Op]);
+            assert_eq!(response.extra, DeadlineInfo { overrun: expected });
+        }
+    }
+
+    // This records the behaviour of a DC setup of the following 16 SubDevices:
+    //
+    // - EK1100
+    // - EL2828
+    // - EL2889
+    // - EL2004
+    // - EL1004
+    // - EL1018
+    // - EL1008
+    // - EL1004
+    // - EL2004
+    // - EL2008
+    // - EL1008
+    // - EL2008
+    // - EL2008
+    // - EL2522
+    // - EL1258
+    // - EL9505
+    #[test]
+    fn al_status_history_recorded() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(32) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice = MainDevice::new(
+            pdu_loop,
+            Timeouts::default(),
+            MainDeviceConfig {
+                al_status_history: true,
+                ..MainDeviceConfig::default()
+            },
+        );
+
+        let subdevices = [SubDevice {
+            configured_address: 0x1001,
+            ..SubDevice::default()
+        }];
+
+        let mut network = FakeNetwork::new(&mut tx, &mut rx);
+
+        // AL status and AL status code seen by each poll. The SubDevice reports an error while in
+        // PRE-OP, then recovers and reaches SAFE-OP.
+        let polls = [
+            (0x02u16, 0x0000u16, false),
+            (0x12, 0x001d, false),
+            (0x12, 0x001d, false),
+            (0x04, 0x0000, true),
+        ];
+
+        for (status, code, expected) in polls {
+            let result = network
+                .run(
+                    subdevices_in_state(&maindevice, &subdevices, SubDeviceState::SafeOp),
+                    |pdu| {
+                        assert_eq!(pdu.command, FPRD);
+                        assert_eq!(pdu.ado, 0x0130);
+                        // AL status, 2 reserved bytes and AL status code
+                        assert_eq!(pdu.data.len(), 6);
+
+                        pdu.data[0..2].copy_from_slice(&status.to_le_bytes());
+                        pdu.data[4..6].copy_from_slice(&code.to_le_bytes());
+
+                        1
+                    },
+                )
+                .expect("state check");
+
+            assert_eq!(result, expected);
+        }
+
+        assert_eq!(
+            subdevices[0].al_status_history.get().as_slice(),
+            &[
+                AlStatusCode::InvalidOutputConfiguration,
+                AlStatusCode::NoError
+            ]
+        );
+    }
+
+    #[test]
+    fn dc_sync_phase_offsets_start_time() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(8) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        maindevice.set_dc_ref_address(0x1000);
+
+        let dc_subdevice = |configured_address| SubDevice {
+            configured_address,
+            dc_support: DcSupport::Bits64,
+            dc_sync: DcSync::Sync0,
+            ..SubDevice::default()
+        };
+
+        for phase in [Duration::from_micros(250), Duration::from_micros(1250)] {
+            let group: SubDeviceGroup<2, 0, PreOp, NoDc> = SubDeviceGroup {
+                id: GroupId(0),
+                pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([])),
+                read_pdi_len: 0,
+                pdi_len: 0,
+                inner: MySyncUnsafeCell::new(GroupInner {
+                    subdevices: heapless::Vec::from_slice(&[
+                        dc_subdevice(0x1000),
+                        dc_subdevice(0x1001),
+                    ])
+                    .unwrap(),
+                    ..GroupInner::default()
+                }),
+                dc_conf: NoDc,
+                _state: PhantomData,
+            };
+
+            group.set_dc_sync_phase(phase);
+
+            // System time read from each SubDevice
+            let device_time = 10_123_456u64;
+
+            let mut start_times = Vec::new();
+
+            let group = FakeNetwork::new(&mut tx, &mut rx)
+                .run(
+                    group.configure_dc_sync(
+                        &maindevice,
+                        DcConfiguration {
+                            start_delay: Duration::from_millis(100),
+                            sync0_period: Duration::from_millis(1),
+                            sync0_shift: Duration::ZERO,
+                        },
+                    ),
+                    |pdu| {
+                        match (pdu.command, pdu.ado) {
+                            (FPRD, 0x0910) => pdu.data.copy_from_slice(&device_time.to_le_bytes()),
+                            (FPWR, 0x0990) => start_times
+                                .push(u64::from_le_bytes(pdu.data[0..8].try_into().unwrap())),
+                            _ => (),
+                        }
+
+                        1
+                    },
+                )
+                .expect("configure DC");
+
+            // 10.123456 ms plus 100 ms start delay, rounded down to 110 ms, plus 250 us phase. A phase
+            // longer than the 1 ms period is reduced to within one cycle.
+            assert_eq!(start_times, [110_250_000, 110_250_000]);
+            assert_eq!(group.dc_conf.sync0_phase, 250_000);
+        }
+    }
+
+    #[test]
+    fn verify_op_reports_stragglers() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(64) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevices = [0x1001, 0x1002, 0x1003].map(|configured_address| SubDevice {
+            configured_address,
+            ..SubDevice::default()
+        });
+
+        let group: SubDeviceGroup<3, 0, Op, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([])),
+            read_pdi_len: 0,
+            pdi_len: 0,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                subdevices: heapless::Vec::from_slice(&subdevices).unwrap(),
+                ..GroupInner::default()
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        let result =
+            FakeNetwork::new(&mut tx, &mut rx).run_frames(group.verify_op(&maindevice), |frame| {
+                // All three status checks are in the one frame
+                assert_eq!(frame.pdu_count(), 3);
+
+                frame.respond(|pdu| {
+                    assert_eq!(pdu.command, FPRD);
+                    assert_eq!(pdu.data.len(), AlControl::PACKED_LEN);
+
+                    // Middle SubDevice has dropped back to SAFE-OP
+                    let state: u16 = if pdu.adp == 0x1002 { 0x04 } else { 0x08 };
+
+                    pdu.data.copy_from_slice(&state.to_le_bytes());
+
+                    1
+                });
+
+                true
+            });
+
+        assert_eq!(
+            result,
+            Err(Error::StateMismatch {
+                expected: SubDeviceState::Op,
+                count: 1,
+                configured_address: 0x1002,
+                actual: SubDeviceState::SafeOp,
+            })
+        );
+    }
+
+    #[test]
+    fn verify_op_missing_subdevice() {
+        crate::test_logger();
+
+        let storage = PduStorage::<1, { PduStorage::element_size(64) }>::new();
+        let (mut tx, mut rx, pdu_loop) = storage.try_split().unwrap();
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        let subdevices = [0x1001, 0x1002].map(|configured_address| SubDevice {
+            configured_address,
+            ..SubDevice::default()
+        });
+
+        let group: SubDeviceGroup<2, 0, Op, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([])),
+            read_pdi_len: 0,
+            pdi_len: 0,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                subdevices: heapless::Vec::from_slice(&subdevices).unwrap(),
+                ..GroupInner::default()
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        let result = FakeNetwork::new(&mut tx, &mut rx).run(group.verify_op(&maindevice), |pdu| {
+            // Second SubDevice has dropped off the network, so its zeroed status isn't read as INIT
+            if pdu.adp == 0x1002 {
+                return 0;
+            }
+
+            pdu.data.copy_from_slice(&0x08u16.to_le_bytes());
+
+            1
+        });
+
+        assert_eq!(
+            result,
+            Err(Error::WorkingCounter {
+                expected: 1,
+                received: 0
+            })
+        );
+    }
+
+    // This records the behaviour of a DC setup of the following 16 SubDevices:
+    //
+    // - EK1100
+    // - EL2828
+    // - EL2889
+    // - EL2004
+    // - EL1004
+    // - EL1018
+    // - EL1008
+    // - EL1004
+    // - EL2004
+    // - EL2008
+    // - EL1008
+    // - EL2008
+    // - EL2008
+    // - EL2522
+    // - EL1258
+    // - EL9505
+    #[test]
+    fn large_group_frame_split() {
+        const MAX_SUBDEVICES: usize = 32;
+        const MAX_PDU_DATA: usize = PduStorage::element_size(256);
+        const MAX_FRAMES: usize = 32;
+        const MAX_PDI: usize = 512;
+        static PDU_STORAGE: PduStorage<MAX_FRAMES, MAX_PDU_DATA> = PduStorage::new();
+
+        crate::test_logger();
+
+        let (mock_net_tx, mock_net_rx) = std::sync::mpsc::sync_channel::<Vec<u8>>(16);
+
+        let (mut tx, mut rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+
+        let maindevice = Arc::new(MainDevice::new(
+            pdu_loop,
+            Timeouts::default(),
+            MainDeviceConfig::default(),
+        ));
+
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stop1 = stop.clone();
+
+        let tx_handle = thread::spawn(move || {
+            fmt::info!("Spawn TX task");
+
+            while !stop1.load(Ordering::Relaxed) {
+                while let Some(frame) = tx.next_sendable_frame() {
+                    fmt::info!("Sendable frame");
+
+                    frame
+                        .send_blocking(|bytes| {
+                            mock_net_tx.send(bytes.to_vec()).unwrap();
+
+                            Ok(bytes.len())
+                        })
+                        .unwrap();
+
+                    thread::yield_now();
+                }
+            }
         });
 
         let stop1 = stop.clone();
@@ -1632,11 +3768,7 @@ mod tests {
                 fmt::info!("RX task received packet");
 
                 // Munge fake sent frame into a fake received frame
-                let ethernet_frame = {
-                    let mut frame = EthernetFrame::new_checked(ethernet_frame).unwrap();
-                    frame.set_src_addr(EthernetAddress([0x12, 0x10, 0x10, 0x10, 0x10, 0x10]));
-                    frame.into_inner()
-                };
+                let ethernet_frame = SentFrame::new(ethernet_frame).into_reply();
 
                 while rx.receive_frame(&ethernet_frame).is_err() {}
 
@@ -1686,10 +3818,15 @@ mod tests {
             inner: MySyncUnsafeCell::new(GroupInner {
                 subdevices,
                 pdi_start: PdiOffset { start_address: 0 },
+                lost_frame_policy: LostFramePolicy::Error,
+                dc_sync_phase: AtomicU32::new(0),
+                output_offset: None,
+                split_output_address: None,
             }),
             dc_conf: HasDc {
                 sync0_period: 100_000,
                 sync0_shift: 0,
+                sync0_phase: 0,
                 reference: 0,
             },
             _state: PhantomData::<Op>,
@@ -1767,4 +3904,104 @@ mod tests {
         // 4th frame should be empty as we only sent 3
         assert_eq!(b.into_pdu_iter().count(), 0);
     }
+
+    #[test]
+    fn indexed_access() {
+        const MAX_SUBDEVICES: usize = 4;
+        const MAX_PDI: usize = 8;
+        const MAX_PDU_DATA: usize = PduStorage::element_size(8);
+        static PDU_STORAGE: PduStorage<1, MAX_PDU_DATA> = PduStorage::new();
+
+        crate::test_logger();
+
+        let (_tx, _rx, pdu_loop) = PDU_STORAGE.try_split().expect("can only split once");
+
+        let maindevice =
+            MainDevice::new(pdu_loop, Timeouts::default(), MainDeviceConfig::default());
+
+        fn sd(addr: u16) -> SubDevice {
+            SubDevice {
+                configured_address: addr,
+                ..SubDevice::default()
+            }
+        }
+
+        let subdevices =
+            heapless::Vec::<_, MAX_SUBDEVICES>::from_slice(&[sd(0x1000), sd(0x1001), sd(0x1002)])
+                .unwrap();
+
+        let group: SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, PreOp, NoDc> = SubDeviceGroup {
+            id: GroupId(0),
+            pdi: spin::rwlock::RwLock::new(MySyncUnsafeCell::new([0u8; MAX_PDI])),
+            read_pdi_len: 0,
+            pdi_len: 0,
+            inner: MySyncUnsafeCell::new(GroupInner {
+                subdevices,
+                pdi_start: PdiOffset::default(),
+                lost_frame_policy: LostFramePolicy::Error,
+                dc_sync_phase: AtomicU32::new(0),
+                output_offset: None,
+                split_output_address: None,
+            }),
+            dc_conf: NoDc,
+            _state: PhantomData,
+        };
+
+        assert_eq!(group.len(), 3);
+        assert!(!group.is_empty());
+
+        let pre_op_addresses = group
+            .iter_indexed(&maindevice)
+            .map(|(idx, sd)| (idx, sd.configured_address()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            pre_op_addresses,
+            vec![(0, 0x1000), (1, 0x1001), (2, 0x1002)]
+        );
+
+        for (idx, addr) in pre_op_addresses.iter() {
+            assert_eq!(
+                group
+                    .subdevice(&maindevice, *idx)
+                    .map(|sd| sd.configured_address()),
+                Ok(*addr)
+            );
+        }
+
+        assert_eq!(
+            group.subdevice(&maindevice, 3).map(|_| ()),
+            Err(Error::NotFound {
+                item: Item::SubDevice,
+                index: Some(3)
+            })
+        );
+
+        // Mimic a state transition
+        let group: SubDeviceGroup<MAX_SUBDEVICES, MAX_PDI, Op, NoDc> = SubDeviceGroup {
+            id: group.id,
+            pdi: group.pdi,
+            read_pdi_len: group.read_pdi_len,
+            pdi_len: group.pdi_len,
+            inner: group.inner,
+            dc_conf: group.dc_conf,
+            _state: PhantomData,
+        };
+
+        let op_addresses = group
+            .iter_indexed(&maindevice)
+            .map(|(idx, sd)| (idx, sd.configured_address()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(op_addresses, pre_op_addresses);
+
+        assert_eq!(
+            group
+                .subdevice(&maindevice, 2)
+                .map(|sd| sd.configured_address()),
+            Ok(0x1002)
+        );
+
+        assert!(group.subdevice(&maindevice, 3).is_err());
+    }
 }
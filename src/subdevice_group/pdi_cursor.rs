@@ -0,0 +1,160 @@
+use crate::command::Command;
+use core::ops::Range;
+
+/// The PDU command used to exchange a [`LogicalSegment`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SegmentKind {
+    /// Inputs and outputs in one `LRW`.
+    ReadWrite,
+    /// Inputs only, using `LRD`.
+    Read,
+    /// Outputs only, using `LWR`.
+    Write,
+}
+
+/// A part of a group's PDI that is contiguous in the EtherCAT logical address space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LogicalSegment {
+    /// Logical address of the first byte of the segment.
+    start_address: u32,
+    /// Range of the group PDI held by this segment.
+    pdi: Range<usize>,
+    kind: SegmentKind,
+}
+
+/// Tracks progress through a group's PDI while it is sent in one or more frames.
+///
+/// The PDI is split into one segment if the inputs and outputs are contiguous in the logical address
+/// space, or two if they are not. Chunks from different segments are never returned together, so
+/// each segment is sent in its own frame(s).
+#[derive(Debug)]
+pub(crate) struct PdiCursor {
+    segments: heapless::Vec<LogicalSegment, 2>,
+    /// Index of the segment currently being sent.
+    current: usize,
+    /// Number of bytes of the current segment already sent.
+    sent: usize,
+}
+
+impl PdiCursor {
+    /// Create a cursor over a group PDI with `read_pdi_len` input bytes followed by outputs up to
+    /// `pdi_len`.
+    ///
+    /// If `split_output_address` is `Some`, the outputs are mapped to that logical address instead
+    /// of directly after the inputs. If `inputs_only` is `true`, only the inputs are sent.
+    pub(crate) fn new(
+        start_address: u32,
+        read_pdi_len: usize,
+        pdi_len: usize,
+        split_output_address: Option<u32>,
+        inputs_only: bool,
+    ) -> Self {
+        let mut segments = heapless::Vec::new();
+
+        let mut push = |start_address, pdi: Range<usize>, kind| {
+            if !pdi.is_empty() {
+                // Capacity is 2 and at most 2 segments are ever pushed
+                let _ = segments.push(LogicalSegment {
+                    start_address,
+                    pdi,
+                    kind,
+                });
+            }
+        };
+
+        match (inputs_only, split_output_address) {
+            (true, _) => push(start_address, 0..read_pdi_len, SegmentKind::Read),
+            (false, None) => push(start_address, 0..pdi_len, SegmentKind::ReadWrite),
+            (false, Some(output_address)) => {
+                push(start_address, 0..read_pdi_len, SegmentKind::Read);
+                push(output_address, read_pdi_len..pdi_len, SegmentKind::Write);
+            }
+        }
+
+        Self {
+            segments,
+            current: 0,
+            sent: 0,
+        }
+    }
+
+    /// The command and PDI range of the unsent part of the current segment, or `None` if the whole
+    /// PDI has been sent.
+    pub(crate) fn remaining(&self) -> Option<(Command, Range<usize>)> {
+        let segment = self.segments.get(self.current)?;
+
+        let address = segment.start_address + self.sent as u32;
+
+        let command = match segment.kind {
+            SegmentKind::ReadWrite => Command::lrw(address).into(),
+            SegmentKind::Read => Command::lrd(address).into(),
+            SegmentKind::Write => Command::lwr(address).into(),
+        };
+
+        Some((command, (segment.pdi.start + self.sent)..segment.pdi.end))
+    }
+
+    /// Mark `bytes` of the current segment as sent, moving on to the next segment once the current
+    /// one is complete.
+    pub(crate) fn advance(&mut self, bytes: usize) {
+        self.sent += bytes;
+
+        if self
+            .segments
+            .get(self.current)
+            .is_some_and(|segment| self.sent >= segment.pdi.len())
+        {
+            self.current += 1;
+            self.sent = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contiguous() {
+        let mut cursor = PdiCursor::new(0x100, 4, 10, None, false);
+
+        assert_eq!(
+            cursor.remaining(),
+            Some((Command::lrw(0x100).into(), 0..10))
+        );
+
+        cursor.advance(6);
+
+        assert_eq!(
+            cursor.remaining(),
+            Some((Command::lrw(0x106).into(), 6..10))
+        );
+
+        cursor.advance(4);
+
+        assert_eq!(cursor.remaining(), None);
+    }
+
+    #[test]
+    fn split() {
+        let mut cursor = PdiCursor::new(0x100, 4, 10, Some(0x180), false);
+
+        assert_eq!(cursor.remaining(), Some((Command::lrd(0x100).into(), 0..4)));
+
+        cursor.advance(4);
+
+        assert_eq!(
+            cursor.remaining(),
+            Some((Command::lwr(0x180).into(), 4..10))
+        );
+
+        cursor.advance(6);
+
+        assert_eq!(cursor.remaining(), None);
+
+        // Outputs are never sent when only reading inputs
+        let cursor = PdiCursor::new(0x100, 4, 10, Some(0x180), true);
+
+        assert_eq!(cursor.remaining(), Some((Command::lrd(0x100).into(), 0..4)));
+    }
+}
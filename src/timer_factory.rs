@@ -1,4 +1,4 @@
-use crate::error::Error;
+use crate::error::{Error, TimeoutField, TimeoutsError};
 use core::{future::Future, pin::Pin, task::Poll, time::Duration};
 
 #[cfg(not(feature = "std"))]
@@ -25,6 +25,178 @@ pub(crate) fn timer(_duration: Duration) -> Timer {
     core::future::pending()
 }
 
+/// A source of timers.
+///
+/// Futures with non-trivial timeout handling take a `Clock` so tests can drive time by hand instead
+/// of waiting on real timers.
+pub(crate) trait Clock {
+    /// The timer future returned by [`timer`](Clock::timer).
+    type Timer: Future + Unpin;
+
+    /// Create a timer that completes once `duration` has elapsed.
+    fn timer(&self, duration: Duration) -> Self::Timer;
+}
+
+/// The executor's timer, as returned by [`timer`].
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    type Timer = Timer;
+
+    fn timer(&self, duration: Duration) -> Self::Timer {
+        timer(duration)
+    }
+}
+
+/// The clock PDU response timeouts are measured with.
+///
+/// In tests, this can be replaced per thread with
+/// [`ManualClock::drive_pdu_timeouts`](manual::ManualClock::drive_pdu_timeouts).
+#[cfg(not(test))]
+pub(crate) type PduClock = SystemClock;
+#[cfg(test)]
+pub(crate) type PduClock = manual::PduClock;
+
+#[cfg(test)]
+pub(crate) mod manual {
+    use super::{Clock, Timer};
+    use core::{
+        cell::RefCell,
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll, Waker},
+        time::Duration,
+    };
+    use std::sync::{Arc, Mutex};
+
+    std::thread_local! {
+        static PDU_CLOCK: RefCell<Option<ManualClock>> = const { RefCell::new(None) };
+    }
+
+    #[derive(Debug, Default)]
+    struct State {
+        now: Duration,
+        wakers: Vec<Waker>,
+    }
+
+    /// A clock that only moves forward when [`advance`](ManualClock::advance) is called.
+    #[derive(Debug, Clone, Default)]
+    pub(crate) struct ManualClock {
+        state: Arc<Mutex<State>>,
+    }
+
+    impl ManualClock {
+        /// Time elapsed since the clock was created.
+        pub(crate) fn now(&self) -> Duration {
+            self.state.lock().unwrap().now
+        }
+
+        /// Measure the response timeouts of PDUs sent from the current thread with this clock,
+        /// until the returned guard is dropped.
+        pub(crate) fn drive_pdu_timeouts(&self) -> PduClockGuard {
+            PDU_CLOCK.set(Some(self.clone()));
+
+            PduClockGuard
+        }
+
+        /// Move the clock forward, waking every timer that is waiting on it.
+        pub(crate) fn advance(&self, by: Duration) {
+            let wakers = {
+                let mut state = self.state.lock().unwrap();
+
+                state.now += by;
+
+                core::mem::take(&mut state.wakers)
+            };
+
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+
+    impl Clock for ManualClock {
+        type Timer = ManualTimer;
+
+        fn timer(&self, duration: Duration) -> Self::Timer {
+            ManualTimer {
+                deadline: self.state.lock().unwrap().now + duration,
+                state: Arc::clone(&self.state),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct ManualTimer {
+        state: Arc<Mutex<State>>,
+        deadline: Duration,
+    }
+
+    impl Future for ManualTimer {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut state = self.state.lock().unwrap();
+
+            if state.now >= self.deadline {
+                return Poll::Ready(());
+            }
+
+            state.wakers.push(cx.waker().clone());
+
+            Poll::Pending
+        }
+    }
+
+    /// Restores the system clock for PDU timeouts when dropped.
+    #[must_use]
+    pub(crate) struct PduClockGuard;
+
+    impl Drop for PduClockGuard {
+        fn drop(&mut self) {
+            PDU_CLOCK.set(None);
+        }
+    }
+
+    /// The system clock, or the [`ManualClock`] driving PDU timeouts on the thread the clock was
+    /// created on.
+    #[derive(Debug, Clone)]
+    pub(crate) struct PduClock(Option<ManualClock>);
+
+    impl Default for PduClock {
+        fn default() -> Self {
+            Self(PDU_CLOCK.with_borrow(Clone::clone))
+        }
+    }
+
+    impl Clock for PduClock {
+        type Timer = PduTimer;
+
+        fn timer(&self, duration: Duration) -> Self::Timer {
+            match &self.0 {
+                Some(clock) => PduTimer::Manual(clock.timer(duration)),
+                None => PduTimer::System(super::timer(duration)),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) enum PduTimer {
+        System(Timer),
+        Manual(ManualTimer),
+    }
+
+    impl Future for PduTimer {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            match self.get_mut() {
+                PduTimer::System(timer) => Pin::new(timer).poll(cx).map(|_| ()),
+                PduTimer::Manual(timer) => Pin::new(timer).poll(cx),
+            }
+        }
+    }
+}
+
 pub(crate) trait IntoTimeout<O> {
     fn timeout(self, timeout: Duration) -> TimeoutFuture<impl Future<Output = Result<O, Error>>>;
 }
@@ -78,6 +250,53 @@ where
     }
 }
 
+/// Drive a future to completion, recording whether it was still running after `deadline`.
+///
+/// Unlike [`IntoTimeout::timeout`], the future is not cancelled when the deadline passes.
+pub(crate) fn with_deadline<F: Future>(f: F, deadline: Duration) -> DeadlineFuture<F> {
+    DeadlineFuture {
+        f,
+        timer: timer(deadline),
+        overrun: false,
+        #[cfg(miri)]
+        duration: deadline,
+    }
+}
+
+pub(crate) struct DeadlineFuture<F> {
+    f: F,
+    timer: Timer,
+    overrun: bool,
+    #[cfg(miri)]
+    duration: Duration,
+}
+
+impl<F> Future for DeadlineFuture<F>
+where
+    F: Future,
+{
+    /// The inner future's output, and whether the deadline was overrun.
+    type Output = (F::Output, bool);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let timer = unsafe { Pin::new_unchecked(&mut this.timer) };
+        let f = unsafe { Pin::new_unchecked(&mut this.f) };
+
+        #[cfg(miri)]
+        if this.duration == Duration::ZERO {
+            this.overrun = true;
+        }
+
+        // Don't poll the timer again once it has fired
+        if !this.overrun && timer.poll(cx).is_ready() {
+            this.overrun = true;
+        }
+
+        f.poll(cx).map(|output| (output, this.overrun))
+    }
+}
+
 /// Timeout configuration for the EtherCrab master.
 #[derive(Copy, Clone, Debug)]
 pub struct Timeouts {
@@ -108,6 +327,62 @@ pub struct Timeouts {
 }
 
 impl Timeouts {
+    /// Create a builder for a validated set of timeouts, starting from the defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use core::time::Duration;
+    /// use ethercrab::Timeouts;
+    ///
+    /// let timeouts = Timeouts::builder()
+    ///     .wait_loop_delay(Duration::from_millis(2))
+    ///     .mailbox_response(Duration::from_millis(1000))
+    ///     .build()
+    ///     .expect("valid timeouts");
+    ///
+    /// // A zero PDU timeout would make every PDU fail immediately
+    /// assert!(Timeouts::builder().pdu(Duration::ZERO).build().is_err());
+    /// ```
+    pub fn builder() -> TimeoutsBuilder {
+        TimeoutsBuilder {
+            timeouts: Self::default(),
+        }
+    }
+
+    /// Check that these timeouts make sense.
+    ///
+    /// All timeouts except [`wait_loop_delay`](Timeouts::wait_loop_delay) must be non-zero, and the
+    /// PDU timeout must not be longer than the state transition or mailbox response timeouts as
+    /// these are made up of one or more PDU round trips.
+    pub fn validate(&self) -> Result<(), Error> {
+        let non_zero = [
+            (TimeoutField::StateTransition, self.state_transition),
+            (TimeoutField::Pdu, self.pdu),
+            (TimeoutField::Eeprom, self.eeprom),
+            (TimeoutField::MailboxEcho, self.mailbox_echo),
+            (TimeoutField::MailboxResponse, self.mailbox_response),
+        ];
+
+        if let Some((field, _)) = non_zero.iter().find(|(_, timeout)| timeout.is_zero()) {
+            return Err(Error::InvalidTimeouts(TimeoutsError::Zero(*field)));
+        }
+
+        let longer_than_pdu = [
+            (TimeoutField::StateTransition, self.state_transition),
+            (TimeoutField::MailboxResponse, self.mailbox_response),
+        ];
+
+        if let Some((field, _)) = longer_than_pdu
+            .iter()
+            .find(|(_, timeout)| self.pdu > *timeout)
+        {
+            return Err(Error::InvalidTimeouts(TimeoutsError::PduExceeds(*field)));
+        }
+
+        Ok(())
+    }
+
     pub(crate) async fn loop_tick(&self) {
         #[cfg(not(miri))]
         timer(self.wait_loop_delay).await;
@@ -128,3 +403,153 @@ impl Default for Timeouts {
         }
     }
 }
+
+/// A builder for [`Timeouts`], created with [`Timeouts::builder`].
+///
+/// Any timeout not set keeps its default value.
+#[derive(Copy, Clone, Debug)]
+pub struct TimeoutsBuilder {
+    timeouts: Timeouts,
+}
+
+impl TimeoutsBuilder {
+    /// Set [`Timeouts::state_transition`].
+    pub fn state_transition(self, timeout: Duration) -> Self {
+        Self {
+            timeouts: Timeouts {
+                state_transition: timeout,
+                ..self.timeouts
+            },
+        }
+    }
+
+    /// Set [`Timeouts::pdu`].
+    pub fn pdu(self, timeout: Duration) -> Self {
+        Self {
+            timeouts: Timeouts {
+                pdu: timeout,
+                ..self.timeouts
+            },
+        }
+    }
+
+    /// Set [`Timeouts::eeprom`].
+    pub fn eeprom(self, timeout: Duration) -> Self {
+        Self {
+            timeouts: Timeouts {
+                eeprom: timeout,
+                ..self.timeouts
+            },
+        }
+    }
+
+    /// Set [`Timeouts::wait_loop_delay`].
+    pub fn wait_loop_delay(self, delay: Duration) -> Self {
+        Self {
+            timeouts: Timeouts {
+                wait_loop_delay: delay,
+                ..self.timeouts
+            },
+        }
+    }
+
+    /// Set [`Timeouts::mailbox_echo`].
+    pub fn mailbox_echo(self, timeout: Duration) -> Self {
+        Self {
+            timeouts: Timeouts {
+                mailbox_echo: timeout,
+                ..self.timeouts
+            },
+        }
+    }
+
+    /// Set [`Timeouts::mailbox_response`].
+    pub fn mailbox_response(self, timeout: Duration) -> Self {
+        Self {
+            timeouts: Timeouts {
+                mailbox_response: timeout,
+                ..self.timeouts
+            },
+        }
+    }
+
+    /// Validate and return the timeouts.
+    ///
+    /// See [`Timeouts::validate`] for the checks performed.
+    pub fn build(self) -> Result<Timeouts, Error> {
+        self.timeouts.validate()?;
+
+        Ok(self.timeouts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_valid() {
+        assert_eq!(Timeouts::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn builder_valid() {
+        let timeouts = Timeouts::builder()
+            .state_transition(Duration::from_secs(10))
+            .pdu(Duration::from_millis(50))
+            .wait_loop_delay(Duration::ZERO)
+            .mailbox_response(Duration::from_millis(50))
+            .build()
+            .expect("valid");
+
+        assert_eq!(timeouts.state_transition, Duration::from_secs(10));
+        assert_eq!(timeouts.pdu, Duration::from_millis(50));
+        assert_eq!(timeouts.mailbox_response, Duration::from_millis(50));
+        // Untouched values keep their defaults
+        assert_eq!(timeouts.eeprom, Timeouts::default().eeprom);
+        assert_eq!(timeouts.mailbox_echo, Timeouts::default().mailbox_echo);
+    }
+
+    #[test]
+    fn builder_invalid() {
+        let cases = [
+            (
+                Timeouts::builder().pdu(Duration::ZERO),
+                TimeoutsError::Zero(TimeoutField::Pdu),
+            ),
+            (
+                Timeouts::builder().state_transition(Duration::ZERO),
+                TimeoutsError::Zero(TimeoutField::StateTransition),
+            ),
+            (
+                Timeouts::builder().eeprom(Duration::ZERO),
+                TimeoutsError::Zero(TimeoutField::Eeprom),
+            ),
+            (
+                Timeouts::builder().mailbox_echo(Duration::ZERO),
+                TimeoutsError::Zero(TimeoutField::MailboxEcho),
+            ),
+            (
+                Timeouts::builder()
+                    .pdu(Duration::from_secs(2))
+                    .state_transition(Duration::from_secs(1)),
+                TimeoutsError::PduExceeds(TimeoutField::StateTransition),
+            ),
+            (
+                Timeouts::builder()
+                    .pdu(Duration::from_millis(200))
+                    .mailbox_response(Duration::from_millis(100)),
+                TimeoutsError::PduExceeds(TimeoutField::MailboxResponse),
+            ),
+        ];
+
+        for (builder, expected) in cases {
+            assert_eq!(
+                builder.build().map(|_| ()),
+                Err(Error::InvalidTimeouts(expected)),
+                "{:?}",
+                builder
+            );
+        }
+    }
+}
@@ -240,12 +240,12 @@ pub fn dummy_tx_rx_task(
             log::debug!("Grouped {} blocks", packet_number);
         }
 
-        if src_addr.as_bytes() == &MAINDEVICE_ADDR {
+        if src_addr.as_bytes() == MAINDEVICE_ADDR {
             pdu_sends
                 .entry(preamble)
                 .or_insert(VecDeque::new())
                 .push_back((raw, packet_number));
-        } else if src_addr.as_bytes() == &REPLY_ADDR {
+        } else if src_addr.as_bytes() == REPLY_ADDR {
             pdu_responses
                 .entry(preamble)
                 .or_insert(VecDeque::new())